@@ -7,12 +7,27 @@ pub enum TableScore {
     Exact(i32),
 }
 
+impl TableScore {
+    /// Apply a function to the score, keeping the kind of bound.
+    pub fn map(self, f: impl FnOnce(i32) -> i32) -> Self {
+        match self {
+            TableScore::Upper(x) => TableScore::Upper(f(x)),
+            TableScore::Lower(x) => TableScore::Lower(f(x)),
+            TableScore::Exact(x) => TableScore::Exact(f(x)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TableValue {
     pub hash: u64,
     pub depth: u8,
     pub r#move: Move,
     pub score: TableScore,
+    /// Whether the score depends on the moves leading to the position, because the search
+    /// below it hit a repetition or the fifty-move rule. Only the move of such an entry can be
+    /// reused.
+    pub path_dependent: bool,
 }
 
 pub struct HashTable {
@@ -21,18 +36,18 @@ pub struct HashTable {
 }
 
 impl HashTable {
+    const EMPTY: TableValue = TableValue {
+        hash: 0,
+        depth: 0,
+        r#move: Move::INVALID,
+        score: TableScore::Upper(i32::MAX),
+        path_dependent: false,
+    };
+
     pub fn new(size: usize) -> Self {
         let size = size.next_power_of_two() >> 1;
         let bitmap = size as u64 - 1;
-        let values = vec![
-            TableValue {
-                hash: 0,
-                depth: 0,
-                r#move: Move::INVALID,
-                score: TableScore::Upper(i32::MAX)
-            };
-            size
-        ];
+        let values = vec![Self::EMPTY; size];
 
         HashTable {
             values: values.into_boxed_slice(),
@@ -49,6 +64,18 @@ impl HashTable {
         None
     }
 
+    /// Remove all entries from the table.
+    pub fn clear(&mut self) {
+        self.values.fill(Self::EMPTY);
+    }
+
+    /// Returns the permille of the table in use, sampled from the first entries.
+    pub fn hashfull(&self) -> u16 {
+        let sample = self.values.len().min(1000);
+        let used = self.values[..sample].iter().filter(|x| x.hash != 0).count();
+        (used * 1000 / sample) as u16
+    }
+
     #[inline]
     pub fn set(&mut self, v: TableValue) {
         self.values[(self.bitmap & v.hash) as usize] = v;
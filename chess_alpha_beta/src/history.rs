@@ -0,0 +1,118 @@
+use chess_core::{Move, Player};
+
+/// Scores of quiet moves indexed by player, from and to square, increased when a quiet move
+/// causes a cutoff.
+///
+/// The table is kept between searches of the same game and only aged when a new search starts.
+pub struct HistoryTable {
+    values: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl HistoryTable {
+    /// Maximum value of an entry, the table is aged when an entry would exceed it.
+    pub const MAX: i32 = 1 << 16;
+
+    pub fn new() -> Self {
+        HistoryTable {
+            values: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    fn player_index(player: Player) -> usize {
+        match player {
+            Player::White => 0,
+            Player::Black => 1,
+        }
+    }
+
+    pub fn get(&self, player: Player, m: Move) -> i32 {
+        self.values[Self::player_index(player)][m.from().get() as usize][m.to().get() as usize]
+    }
+
+    /// Reward a move which caused a cutoff at the given depth.
+    pub fn add(&mut self, player: Player, m: Move, depth: u8) {
+        let bonus = depth as i32 * depth as i32;
+        if self.get(player, m) + bonus > Self::MAX {
+            self.age();
+        }
+        self.values[Self::player_index(player)][m.from().get() as usize][m.to().get() as usize] +=
+            bonus;
+    }
+
+    /// Halve all entries, so newer cutoffs weigh more than older ones.
+    pub fn age(&mut self) {
+        self.values
+            .iter_mut()
+            .flat_map(|x| x.iter_mut())
+            .flat_map(|x| x.iter_mut())
+            .for_each(|x| *x /= 2);
+    }
+
+    pub fn clear(&mut self) {
+        self.values
+            .iter_mut()
+            .flat_map(|x| x.iter_mut())
+            .for_each(|x| *x = [0; 64]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values
+            .iter()
+            .flat_map(|x| x.iter())
+            .all(|x| x.iter().all(|x| *x == 0))
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The quiet move which last caused a cutoff in reply to a move, indexed by the from and to
+/// square of the move replied to.
+///
+/// Like the history table it is kept between searches of the same game.
+pub struct CounterMoveTable {
+    moves: Box<[[Move; 64]; 64]>,
+}
+
+impl CounterMoveTable {
+    pub fn new() -> Self {
+        CounterMoveTable {
+            moves: Box::new([[Move::INVALID; 64]; 64]),
+        }
+    }
+
+    /// Returns the counter move of the previous move, if there is one.
+    pub fn get(&self, prev: Move) -> Option<Move> {
+        if prev == Move::INVALID {
+            return None;
+        }
+        let m = self.moves[prev.from().get() as usize][prev.to().get() as usize];
+        (m != Move::INVALID).then_some(m)
+    }
+
+    /// Store the quiet move which caused a cutoff in reply to the previous move.
+    pub fn set(&mut self, prev: Move, m: Move) {
+        if prev != Move::INVALID {
+            self.moves[prev.from().get() as usize][prev.to().get() as usize] = m;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.moves.iter_mut().for_each(|x| *x = [Move::INVALID; 64]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves
+            .iter()
+            .all(|x| x.iter().all(|x| *x == Move::INVALID))
+    }
+}
+
+impl Default for CounterMoveTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,53 +1,105 @@
 #![allow(dead_code)]
 
 use chess_core::{
-    board::{Board as BaseBoard, EndChain, HashChain},
-    engine::{Engine, EngineControl, EngineLimit, OptionKind, OptionValue},
+    board::{Board as BaseBoard, EvalChain, HashChain},
+    engine::{Engine, EngineControl, EngineLimit, Info, OptionKind, OptionValue, Score, Version},
     gen::MoveGenerator,
-    Move,
+    polyglot::Book,
+    repetition::GameHistory,
+    tablebase::{Syzygy, TablebaseProber},
+    Move, Piece, Player,
 };
 
 mod eval;
+pub use eval::EvalTrace;
 mod hash;
+pub use hash::{HashTable, TableScore, TableValue};
+mod history;
+pub use history::{CounterMoveTable, HistoryTable};
+pub mod negamax;
 mod search;
+pub use search::{to_wdl, Line};
 mod sort;
-use search::Line;
+mod stats;
+pub use stats::{IterationStats, SearchStats};
+pub mod test_util;
+pub mod tree;
+use tree::{SearchTree, TreeRecorder};
 
 use std::{
     cell::Cell,
-    collections::HashMap,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
-type Board = BaseBoard<HashChain<EndChain>>;
+type Board = BaseBoard<HashChain<EvalChain>>;
+
+/// Maximum amount of plies the `DumpTree` option records.
+const MAX_TREE_PLIES: i32 = 16;
+/// Half-width of the aspiration window above which the search falls back to a full window.
+const MAX_ASPIRATION_WINDOW: i32 = eval::QUEEN_VALUE;
 
 pub struct TimeLimit {
     start: Instant,
     limit: Duration,
-    nodes_searched: u64,
+    /// Node count at the last read of the clock.
+    nodes_searched: Cell<u64>,
+    /// Time since the start at the last read of the clock.
+    last_elapsed: Cell<Duration>,
+    /// Amount of nodes to search before reading the clock again.
+    interval: Cell<u64>,
+    clock_reads: Cell<u64>,
     exceeded: Cell<bool>,
 }
 
 impl TimeLimit {
-    const WAIT_NODES: u64 = 10_000;
+    /// Amount of nodes searched before the first read of the clock.
+    const WAIT_NODES: u64 = 1_000;
+    pub const MIN_INTERVAL: u64 = 64;
+    pub const MAX_INTERVAL: u64 = 100_000;
+    /// Targeted time between two reads of the clock.
+    const CHECK_INTERVAL: Duration = Duration::from_millis(1);
 
     pub fn limit(limit: Duration) -> Self {
         TimeLimit {
             start: Instant::now(),
             limit,
-            nodes_searched: 0,
+            nodes_searched: Cell::new(0),
+            last_elapsed: Cell::new(Duration::from_secs(0)),
+            interval: Cell::new(Self::WAIT_NODES),
+            clock_reads: Cell::new(0),
             exceeded: Cell::new(false),
         }
     }
 
+    /// Returns how often the clock was read.
+    pub fn clock_reads(&self) -> u64 {
+        self.clock_reads.get()
+    }
+
     fn check_time(&self, nodes: u64) -> bool {
-        if nodes > self.nodes_searched + Self::WAIT_NODES {
-            let res = self.start.elapsed() > self.limit;
-            self.exceeded.set(res);
-            res
-        } else {
-            false
+        let last_nodes = self.nodes_searched.get();
+        if nodes < last_nodes + self.interval.get() {
+            return false;
         }
+
+        let elapsed = self.start.elapsed();
+        self.clock_reads.set(self.clock_reads.get() + 1);
+
+        // Adjust the interval to the measured node rate so the clock is read about once every
+        // CHECK_INTERVAL, growing at most twice as large at a time.
+        let delta_nanos = (elapsed - self.last_elapsed.get()).as_nanos().max(1);
+        let target = (nodes - last_nodes) as u128 * Self::CHECK_INTERVAL.as_nanos() / delta_nanos;
+        let interval = (target as u64)
+            .min(self.interval.get() * 2)
+            .clamp(Self::MIN_INTERVAL, Self::MAX_INTERVAL);
+        self.interval.set(interval);
+        self.nodes_searched.set(nodes);
+        self.last_elapsed.set(elapsed);
+
+        let res = elapsed > self.limit;
+        self.exceeded.set(res);
+        res
     }
 
     #[inline]
@@ -58,38 +110,404 @@ impl TimeLimit {
 
 pub struct AlphaBeta<C> {
     contempt: i32,
+    /// The side to move at the root of the search, the side which avoids draws with positive
+    /// contempt.
+    root_player: Player,
+    /// The engine is used for analysis instead of playing a game, draws score zero and the
+    /// opening book is not used.
+    analyse_mode: bool,
+    /// Print the terms of the evaluation of the root position before searching.
+    trace_eval: bool,
+    /// Extend the search by a ply in positions where the side to move is in check.
+    check_extension: bool,
+    /// Report the chances of winning, drawing and losing along with the score.
+    show_wdl: bool,
+    /// Report the statistics of every search after it finished.
+    show_stats: bool,
+    /// Statistics of the current search.
+    stats: SearchStats,
+    /// Path of the Polyglot book.
+    book_file: Option<PathBuf>,
+    /// The opening book probed before searching, loaded from the file on the first search
+    /// after it is set.
+    book: Option<Book>,
+    /// The directories of the Syzygy tablebases, loaded on the first search after they are set.
+    tablebase_path: Option<String>,
+    /// The tablebases probed during the search and at the root, if any.
+    tablebase: Option<Box<dyn TablebaseProber>>,
+    /// Copy of the position which is probed in the tablebases.
+    tablebase_board: BaseBoard,
+    /// Positions of the current search which were found in the tablebases.
+    tablebase_hits: u64,
+    /// Amount of plies of the search to record in the tree dump, 0 to not record the tree.
+    tree_plies: u8,
+    /// Maximum amount of nodes in the tree dump.
+    tree_max_nodes: usize,
+    /// The file the tree of the last completed iteration is written to.
+    tree_file: PathBuf,
+    /// Records the tree of the iteration in progress, if enabled.
+    tree: Option<TreeRecorder>,
+    /// The tree of the last completed iteration.
+    last_tree: Option<SearchTree>,
     board: Board,
+    /// The positions played in the game since the last irreversible move and those on the
+    /// current search line, used for detecting repetitions.
+    history: GameHistory,
     table: hash::HashTable,
+    /// Scores of quiet moves which caused cutoffs, kept between searches of the same game.
+    butterfly: HistoryTable,
+    /// Quiet moves which caused cutoffs in reply to a move, kept between searches of the same
+    /// game.
+    counter_moves: CounterMoveTable,
+    /// Cutoffs by quiet moves in the current search, and how many of them were by the counter
+    /// move.
+    quiet_cutoffs: u64,
+    counter_move_cutoffs: u64,
+    /// Half-width of the window around the score of the previous iteration that the next
+    /// iteration is searched with, 0 to always search with a full window.
+    aspiration_window: i32,
+    /// Amount of times an iteration of the current search was searched again after the score
+    /// fell outside of the aspiration window.
+    researches: u64,
     gen: MoveGenerator,
     pv: Line,
     nodes: u64,
     table_hit: u64,
+    /// Lookups of the transposition table in the current search.
+    table_probes: u64,
+    /// When the current search started.
+    search_start: Instant,
+    /// When the search statistics were last reported.
+    last_report: Instant,
+    /// Node count at which to check whether to report the search statistics again.
+    next_report: u64,
     depth: u8,
+    /// Deepest ply reached in the current search.
+    seldepth: u8,
+    /// Score of the last completed iteration, from the perspective of the side to move.
+    score: i32,
+    /// Depth of the last completed iteration.
+    completed_depth: u8,
     control: C,
     limits: EngineLimit,
     time_limit: Option<TimeLimit>,
+    /// Whether the time limit applies. It doesn't until the first iteration completed, so
+    /// the search always has a searched move to play.
+    time_limit_applies: bool,
+    /// Time reserved on every move for the delay between the engine and the clock.
+    move_overhead: Duration,
 }
 
 impl<C: EngineControl> AlphaBeta<C> {
+    const DEFAULT_TREE_NODES: usize = 100_000;
+    const DEFAULT_TREE_FILE: &'static str = "alpha_beta.dot";
+    const DEFAULT_ASPIRATION_WINDOW: i32 = eval::PAWN_VALUE / 4;
+    const DEFAULT_CONTEMPT: i32 = 100;
+    const DEFAULT_MOVE_OVERHEAD_MS: i32 = 30;
+    /// The choices of the `Style` option with the contempt they set, from seeking draws
+    /// against stronger opponents to avoiding them against weaker ones.
+    const STYLES: [(&'static str, i32); 3] = [("solid", 0), ("normal", 100), ("aggressive", 250)];
+
+    /// The start position with the chains keeping the hash and the piece square values.
+    fn start_board() -> Board {
+        Board::start_position(HashChain::with(EvalChain::new(Self::psqt_tables())))
+    }
+
     pub fn new() -> Self {
+        let board = Self::start_board();
+        let mut history = GameHistory::new();
+        history.push(board.chain.hash, true);
         AlphaBeta {
-            contempt: 100,
-            board: Board::start_position(HashChain::new()),
+            contempt: Self::DEFAULT_CONTEMPT,
+            root_player: board.state.player,
+            analyse_mode: false,
+            trace_eval: false,
+            check_extension: true,
+            show_wdl: false,
+            show_stats: false,
+            stats: SearchStats::default(),
+            book_file: None,
+            book: None,
+            tablebase_path: None,
+            tablebase: None,
+            tablebase_board: BaseBoard::empty(),
+            tablebase_hits: 0,
+            tree_plies: 0,
+            tree_max_nodes: Self::DEFAULT_TREE_NODES,
+            tree_file: PathBuf::from(Self::DEFAULT_TREE_FILE),
+            tree: None,
+            last_tree: None,
+            history,
+            board,
             table: hash::HashTable::new(16 * 1024),
+            butterfly: HistoryTable::new(),
+            counter_moves: CounterMoveTable::new(),
+            quiet_cutoffs: 0,
+            counter_move_cutoffs: 0,
+            aspiration_window: Self::DEFAULT_ASPIRATION_WINDOW,
+            researches: 0,
             gen: MoveGenerator::new(),
             pv: Line::new(),
             nodes: 0,
             table_hit: 0,
+            table_probes: 0,
+            search_start: Instant::now(),
+            last_report: Instant::now(),
+            next_report: 0,
             depth: 0,
+            seldepth: 0,
+            score: 0,
+            completed_depth: 0,
             control: C::default(),
             limits: EngineLimit::none(),
             time_limit: None,
+            time_limit_applies: false,
+            move_overhead: Duration::from_millis(Self::DEFAULT_MOVE_OVERHEAD_MS as u64),
+        }
+    }
+
+    /// Returns the move of the opening book in the current position, if any, loading the book
+    /// first if needed.
+    fn probe_book(&mut self) -> Option<Move> {
+        if self.analyse_mode {
+            return None;
+        }
+        if self.book.is_none() {
+            let book_file = self.book_file.as_ref()?;
+            match Book::open(book_file) {
+                Ok(x) => self.book = Some(x),
+                Err(e) => {
+                    self.control
+                        .info(Info::String(format!("failed to load book: {:#}", e)));
+                    // Don't try again on every move.
+                    self.book_file = None;
+                    return None;
+                }
+            }
+        }
+        let m = self.book.as_ref()?.probe(&self.board, &self.gen)?;
+        match self.limits.search_moves.as_ref() {
+            Some(x) if !x.contains(&m) => None,
+            _ => Some(m),
+        }
+    }
+
+    /// Load the tablebases from the directories of the `SyzygyPath` option, if they changed.
+    fn load_tablebase(&mut self) {
+        let Some(path) = self.tablebase_path.take() else {
+            return;
+        };
+        match Syzygy::open(&path) {
+            Ok(x) => {
+                self.control.info(Info::Debug(format!(
+                    "found {} tablebases of up to {} pieces",
+                    x.tables().len(),
+                    x.max_pieces()
+                )));
+                self.tablebase = Some(Box::new(x));
+            }
+            Err(e) => self
+                .control
+                .info(Info::String(format!("failed to load tablebases: {:#}", e))),
+        }
+    }
+
+    /// Returns the move the tablebases give in the current position, if any.
+    fn probe_tablebase_root(&mut self) -> Option<Move> {
+        if self.analyse_mode {
+            return None;
+        }
+        let m = self.probe_tablebase(|prober, board| prober.probe_root(board))?;
+        match self.limits.search_moves.as_ref() {
+            Some(x) if !x.contains(&m) => None,
+            _ => {
+                self.control
+                    .info(Info::Debug(format!("tablebase move {}", m.to_uci())));
+                Some(m)
+            }
+        }
+    }
+}
+
+impl<C> AlphaBeta<C> {
+    /// Set the tablebases probed during the search and at the root, replacing those of the
+    /// `SyzygyPath` option.
+    pub fn set_tablebase(&mut self, prober: Option<Box<dyn TablebaseProber>>) {
+        self.tablebase = prober;
+        self.tablebase_path = None;
+    }
+
+    /// Returns the amount of positions of the last search which were found in the tablebases.
+    pub fn tablebase_hits(&self) -> u64 {
+        self.tablebase_hits
+    }
+
+    /// Probe the current position in the tablebases with `f`, if it can be in them.
+    ///
+    /// Tablebases don't know about castling, and their results assume the half-move clock was
+    /// just reset.
+    fn probe_tablebase<T>(
+        &mut self,
+        f: impl FnOnce(&dyn TablebaseProber, &BaseBoard) -> Option<T>,
+    ) -> Option<T> {
+        let prober = self.tablebase.as_deref()?;
+        let state = self.board.state;
+        if state.castle != 0 || state.move_clock != 0 || !self.board.pocket.is_empty() {
+            return None;
+        }
+        let pieces: u32 = Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .map(|p| self.board.pieces[p].count() as u32)
+            .sum();
+        if pieces > prober.max_pieces() {
+            return None;
         }
+        self.tablebase_board.copy_position(&self.board);
+        f(prober, &self.tablebase_board)
+    }
+}
+
+/// The result of a search, returned by [`AlphaBeta::analyze`].
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    /// Score from the perspective of the side to move.
+    pub score: i32,
+    pub best_move: Option<Move>,
+    pub pv: Vec<Move>,
+    /// Depth of the last completed iteration.
+    pub depth: u8,
+    pub nodes: u64,
+    /// The statistics of the search, if the `ShowStats` option is enabled.
+    pub stats: Option<SearchStats>,
+}
+
+impl Analysis {
+    /// Returns whether the score is a forced mate for either side.
+    pub fn is_mate(&self) -> bool {
+        search::mate_plies(self.score).is_some()
+    }
+
+    /// Returns the moves until mate, negative when the side to move is getting mated.
+    pub fn mate_in(&self) -> Option<i32> {
+        match search::to_score(self.score) {
+            Score::Mate(x) => Some(x),
+            Score::Cp(_) => None,
+        }
+    }
+}
+
+impl<C: EngineControl> AlphaBeta<C> {
+    /// Search the given position on the current thread and return the result instead of
+    /// reporting it through the engine control.
+    pub fn analyze(&mut self, board: &BaseBoard, limit: EngineLimit) -> Analysis {
+        self.set_board(board.clone());
+        self.control = C::default();
+        self.time_limit = limit.time.map(TimeLimit::limit);
+        self.limits = limit;
+        let best_move = self.go_search();
+        Analysis {
+            score: self.score,
+            best_move,
+            pv: self.current_pv(),
+            depth: self.completed_depth,
+            nodes: self.nodes,
+            stats: self.show_stats.then(|| self.stats.clone()),
+        }
+    }
+}
+
+impl<C> AlphaBeta<C> {
+    /// Returns how long a search with the limit may run given the time left on the clock, with
+    /// the move overhead taken off so the move arrives before the clock runs out.
+    ///
+    /// The budget is never less than zero, a search without time still completes its first
+    /// iteration.
+    pub fn time_budget(
+        &self,
+        time_left: Option<Duration>,
+        limit: &EngineLimit,
+    ) -> Option<Duration> {
+        limit
+            .search_time(time_left)
+            .map(|x| x.saturating_sub(self.move_overhead))
+    }
+
+    /// Add the current position to the history, after making a move.
+    fn push_history(&mut self) {
+        let irreversible = self.board.state.move_clock == 0;
+        self.history.push(self.board.chain.hash, irreversible);
+    }
+
+    /// Returns the score of a draw in the current position, from the perspective of the side
+    /// to move.
+    ///
+    /// A draw is worse than equal for the side to move at the root and better for its opponent.
+    /// The contempt is scaled down with the game phase, so draws are avoided in the middle game
+    /// but accepted once the pieces are traded off. Without pieces besides kings and pawns a
+    /// draw scores zero, as does every draw in analyse mode.
+    #[inline]
+    pub fn draw_score(&self) -> i32 {
+        if self.analyse_mode {
+            return 0;
+        }
+        let phase = self.board.phase().min(Board::MAX_PHASE);
+        let scaled = self.contempt * phase / Board::MAX_PHASE;
+        if self.board.state.player == self.root_player {
+            -scaled
+        } else {
+            scaled
+        }
+    }
+
+    /// Returns the score of the last completed iteration of the last search, from the
+    /// perspective of the side to move.
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// Returns the recorded tree of the last completed iteration, if recording is enabled with
+    /// the `DumpTree` option.
+    pub fn search_tree(&self) -> Option<&SearchTree> {
+        self.last_tree.as_ref()
+    }
+
+    /// Returns the transposition table, kept between searches of the same game.
+    pub fn hash_table(&self) -> &HashTable {
+        &self.table
+    }
+
+    /// Returns the history scores of quiet moves.
+    pub fn history_table(&self) -> &HistoryTable {
+        &self.butterfly
+    }
+
+    /// Returns the counter moves of quiet moves.
+    pub fn counter_move_table(&self) -> &CounterMoveTable {
+        &self.counter_moves
+    }
+
+    /// Returns the amount of cutoffs by quiet moves in the last search and how many of them
+    /// were by the counter move.
+    pub fn counter_move_stats(&self) -> (u64, u64) {
+        (self.quiet_cutoffs, self.counter_move_cutoffs)
+    }
+
+    /// Returns the statistics of the last search, collected whether or not the `ShowStats`
+    /// option is enabled.
+    pub fn search_stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Returns how often an iteration of the last search was searched again because the score
+    /// fell outside of the aspiration window.
+    pub fn researches(&self) -> u64 {
+        self.researches
     }
 }
 
 impl<C: EngineControl> Engine<C> for AlphaBeta<C> {
     const NAME: &'static str = "AlphaBeta 2";
+    const VERSION: Option<Version> = Some(Version::new(0, 1, 0));
 
     fn go(
         &mut self,
@@ -98,46 +516,118 @@ impl<C: EngineControl> Engine<C> for AlphaBeta<C> {
         limit: chess_core::engine::EngineLimit,
     ) -> Option<Move> {
         self.control = control;
+        self.time_limit = self.time_budget(time_left, &limit).map(TimeLimit::limit);
         self.limits = limit;
 
-        let time_limit = match (self.limits.time, time_left) {
-            (None, None) => None,
-            (Some(x), None) => Some(x),
-            (None, Some(x)) => Some(x / 30),
-            (Some(a), Some(b)) => Some(a.min(b / 30)),
-        };
-
-        self.time_limit = time_limit.map(TimeLimit::limit);
-
+        if let Some(m) = self.probe_book() {
+            return Some(m);
+        }
+        self.load_tablebase();
+        if let Some(m) = self.probe_tablebase_root() {
+            return Some(m);
+        }
         self.go_search()
     }
 
     fn make_move(&mut self, m: Move) {
         self.board.make_move(m);
+        self.root_player = self.board.state.player;
+        // Played positions before an irreversible move can't repeat anymore.
+        if self.board.state.move_clock == 0 {
+            self.history.clear();
+        }
+        self.push_history();
     }
 
-    fn options(&self) -> HashMap<String, OptionKind> {
-        [
+    fn options(&self) -> Vec<(String, OptionKind)> {
+        vec![
+            (
+                "Hash".to_string(),
+                OptionKind::Spin {
+                    default: 16,
+                    min: Some(1),
+                    max: Some(1024 * 4),
+                },
+            ),
             (
                 "contempt".to_string(),
                 OptionKind::Spin {
-                    default: 100,
-                    max: Some(900),
+                    default: Self::DEFAULT_CONTEMPT,
                     min: Some(-100),
+                    max: Some(900),
                 },
             ),
             (
-                "Hash".to_string(),
+                "Style".to_string(),
+                OptionKind::Combo {
+                    default: "normal".to_string(),
+                    choices: Self::STYLES
+                        .iter()
+                        .map(|(name, _)| name.to_string())
+                        .collect(),
+                },
+            ),
+            (
+                "UCI_AnalyseMode".to_string(),
+                OptionKind::Check { default: false },
+            ),
+            (
+                "EvalTrace".to_string(),
+                OptionKind::Check { default: false },
+            ),
+            (
+                "CheckExtension".to_string(),
+                OptionKind::Check { default: true },
+            ),
+            (
+                "UCI_ShowWDL".to_string(),
+                OptionKind::Check { default: false },
+            ),
+            (
+                "ShowStats".to_string(),
+                OptionKind::Check { default: false },
+            ),
+            (
+                "DumpTree".to_string(),
                 OptionKind::Spin {
-                    default: 16,
+                    default: 0,
+                    min: Some(0),
+                    max: Some(MAX_TREE_PLIES),
+                },
+            ),
+            (
+                "DumpTreeNodes".to_string(),
+                OptionKind::Spin {
+                    default: Self::DEFAULT_TREE_NODES as i32,
                     min: Some(1),
-                    max: Some(1024 * 4),
+                    max: Some(10_000_000),
+                },
+            ),
+            (
+                "DumpTreeFile".to_string(),
+                OptionKind::String {
+                    default: Self::DEFAULT_TREE_FILE.to_string(),
+                },
+            ),
+            ("BookFile".to_string(), OptionKind::string()),
+            ("SyzygyPath".to_string(), OptionKind::string()),
+            (
+                "AspirationWindow".to_string(),
+                OptionKind::Spin {
+                    default: Self::DEFAULT_ASPIRATION_WINDOW,
+                    min: Some(0),
+                    max: Some(MAX_ASPIRATION_WINDOW),
+                },
+            ),
+            (
+                "Move Overhead".to_string(),
+                OptionKind::Spin {
+                    default: Self::DEFAULT_MOVE_OVERHEAD_MS,
+                    min: Some(0),
+                    max: Some(5000),
                 },
             ),
         ]
-        .iter()
-        .cloned()
-        .collect()
     }
 
     fn set_option(&mut self, name: String, value: OptionValue) {
@@ -149,20 +639,109 @@ impl<C: EngineControl> Engine<C> for AlphaBeta<C> {
                     }
                 }
             }
+            "Move Overhead" => {
+                if let OptionValue::Spin(x) = value {
+                    self.move_overhead = Duration::from_millis(x.max(0) as u64);
+                }
+            }
+            "AspirationWindow" => {
+                if let OptionValue::Spin(x) = value {
+                    self.aspiration_window = x.clamp(0, MAX_ASPIRATION_WINDOW);
+                }
+            }
             "contempt" => {
                 if let OptionValue::Spin(x) = value {
                     self.contempt = x;
                 }
             }
+            "Style" => {
+                if let OptionValue::Combo(x) = value {
+                    if let Some((_, contempt)) = Self::STYLES.get(x) {
+                        self.contempt = *contempt;
+                    }
+                }
+            }
+            "EvalTrace" => {
+                if let OptionValue::Check(x) = value {
+                    self.trace_eval = x;
+                }
+            }
+            "UCI_AnalyseMode" => {
+                if let OptionValue::Check(x) = value {
+                    if x != self.analyse_mode {
+                        // Draws in the table were scored with the contempt of the other mode.
+                        self.table.clear();
+                    }
+                    self.analyse_mode = x;
+                }
+            }
+            "CheckExtension" => {
+                if let OptionValue::Check(x) = value {
+                    self.check_extension = x;
+                }
+            }
+            "UCI_ShowWDL" => {
+                if let OptionValue::Check(x) = value {
+                    self.show_wdl = x;
+                }
+            }
+            "ShowStats" => {
+                if let OptionValue::Check(x) = value {
+                    self.show_stats = x;
+                }
+            }
+            "DumpTree" => {
+                if let OptionValue::Spin(x) = value {
+                    self.tree_plies = x.clamp(0, MAX_TREE_PLIES) as u8;
+                }
+            }
+            "DumpTreeNodes" => {
+                if let OptionValue::Spin(x) = value {
+                    if x > 0 {
+                        self.tree_max_nodes = x as usize;
+                    }
+                }
+            }
+            "DumpTreeFile" => {
+                if let OptionValue::String(x) = value {
+                    self.tree_file = if x.is_empty() || x == "<empty>" {
+                        PathBuf::from(Self::DEFAULT_TREE_FILE)
+                    } else {
+                        PathBuf::from(x)
+                    };
+                }
+            }
+            "BookFile" => {
+                if let OptionValue::String(x) = value {
+                    // An empty path, as `<empty>` in the UCI protocol, disables the book.
+                    self.book_file = (!x.is_empty() && x != "<empty>").then(|| PathBuf::from(x));
+                    self.book = None;
+                }
+            }
+            "SyzygyPath" => {
+                if let OptionValue::String(x) = value {
+                    // An empty path, as `<empty>` in the UCI protocol, disables the tablebases.
+                    self.tablebase = None;
+                    self.tablebase_path = (!x.is_empty() && x != "<empty>").then_some(x);
+                }
+            }
             _ => {}
         }
     }
 
     fn new_game(&mut self) {
-        self.board = Board::start_position(HashChain::new());
+        self.board = Self::start_board();
+        self.table.clear();
+        self.butterfly.clear();
+        self.counter_moves.clear();
+        self.history.clear();
+        self.history.push(self.board.chain.hash, true);
     }
 
     fn set_board(&mut self, board: BaseBoard) {
         self.board.copy_position(&board);
+        self.root_player = self.board.state.player;
+        self.history.clear();
+        self.history.push(self.board.chain.hash, true);
     }
 }
@@ -0,0 +1,439 @@
+//! The negamax search with alpha-beta pruning, independent of the engine.
+//!
+//! The search is driven through a [`SearchContext`], which supplies the board, the
+//! transposition table, the evaluation and when to stop. Everything else an engine keeps track
+//! of during a search, like move ordering, statistics or the recorded tree, goes through the
+//! provided methods of the trait, which do nothing by default. So an alternative evaluation or
+//! stop condition only needs a new context instead of a copy of the search.
+//!
+//! Windows are given from the perspective of the side to move, with `alpha` the score the side
+//! to move is already guaranteed and `beta` the score the opponent is already guaranteed.
+
+use crate::{
+    hash::{HashTable, TableScore, TableValue},
+    history::HistoryTable,
+    search::{Line, CHECKMATE_SCORE, TABLEBASE_WIN_SCORE},
+    sort::MoveSorter,
+    tree::Cutoff,
+};
+use chess_core::{
+    board::{Board, HashChain, MoveChain, UnmakeMove},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, PositionInfo, MAX_MOVES},
+    tablebase::Wdl,
+    Move, Player,
+};
+
+/// A bound beyond every score, a search with a window of `-INIT_BOUND` to `INIT_BOUND` is a
+/// search with a full window.
+pub const INIT_BOUND: i32 = 2_000_000;
+/// Score returned once the search is stopped.
+pub(crate) const INVALID_SCORE: i32 = 2_121_212;
+/// Half-move clock from which scores in the table are no longer trusted, as the fifty-move rule
+/// may draw lines which the stored score was searched without.
+const FIFTY_MOVE_HORIZON: u8 = 80;
+
+/// What the search needs from the engine running it.
+pub trait SearchContext {
+    /// The chain of the board below the hash.
+    type Chain: MoveChain;
+
+    /// The position being searched, moves are made and unmade on it during the search.
+    fn board(&self) -> &Board<HashChain<Self::Chain>>;
+
+    fn board_mut(&mut self) -> &mut Board<HashChain<Self::Chain>>;
+
+    fn move_gen(&self) -> &MoveGenerator;
+
+    fn table(&self) -> &HashTable;
+
+    fn table_mut(&mut self) -> &mut HashTable;
+
+    /// Statically evaluate the current position from the perspective of white.
+    fn eval(&mut self, info: &PositionInfo) -> i32;
+
+    /// Returns whether the search should stop. Once it returns true the search returns as fast
+    /// as possible, with invalid scores.
+    fn should_stop(&self) -> bool;
+
+    /// Returns the score of a draw in the current position, from the perspective of the side
+    /// to move.
+    fn draw_score(&self) -> i32 {
+        0
+    }
+
+    /// Returns how often the current position occurred before, the second repetition scores as
+    /// a draw.
+    fn repetitions(&self) -> usize {
+        0
+    }
+
+    /// Make a move on the board, when the search enters a child.
+    fn push_move(&mut self, m: Move) -> UnmakeMove {
+        self.board_mut().make_move(m)
+    }
+
+    /// Unmake the move made by [`push_move`](Self::push_move).
+    fn pop_move(&mut self, undo: UnmakeMove) {
+        self.board_mut().unmake_move(undo)
+    }
+
+    /// Whether to extend the search by a ply in positions where the side to move is in check.
+    fn check_extension(&self) -> bool {
+        true
+    }
+
+    /// Called for every position searched, with its distance to the root.
+    fn visit(&mut self, _: u8) {}
+
+    /// Called for every lookup of the table, with whether it returned a score which could be
+    /// used.
+    fn table_probed(&mut self, _: bool) {}
+
+    /// Returns the move of the principal variation of the previous search at the given ply,
+    /// which is searched first.
+    fn pv_move(&self, _: u8) -> Option<Move> {
+        None
+    }
+
+    /// Returns the history scores quiet moves are ordered by.
+    fn move_history(&self) -> Option<&HistoryTable> {
+        None
+    }
+
+    /// Returns the counter move to the previous move, which is searched first of the quiet
+    /// moves.
+    fn counter_move(&self, _: Move) -> Option<Move> {
+        None
+    }
+
+    /// Called when a quiet move caused a cutoff, given the remaining depth and the move which
+    /// lead to the position.
+    fn quiet_cutoff(&mut self, _: Move, _: u8, _: Move) {}
+
+    /// Called before searching the position after the move, with the remaining depth and the
+    /// window of the child.
+    fn enter_child(&mut self, _: Move, _: u8, _: i32, _: i32) {}
+
+    /// Called after searching the position entered last, with its score.
+    fn leave_child(&mut self, _: i32) {}
+
+    /// Called when the child left last is the new best move of the position at the given ply,
+    /// with its move and the line after it.
+    fn new_best(&mut self, _: u8, _: Move, _: &Line) {}
+
+    /// Called when the current position returns early.
+    fn cutoff(&mut self, _: Cutoff) {}
+
+    /// Returns the outcome of the current position according to the tablebases, if it is in
+    /// them. Positions below the root are probed before they are searched.
+    fn probe_wdl(&mut self) -> Option<Wdl> {
+        None
+    }
+}
+
+/// The result of searching the moves at the root.
+#[derive(Clone, Copy, Debug)]
+pub struct RootResult {
+    /// The best move, [`Move::INVALID`] if no move scored above alpha.
+    pub best_move: Move,
+    /// Score of the best move, or alpha if no move scored above it.
+    pub score: i32,
+    /// Whether the score depends on the moves leading to the root, because the search hit a
+    /// repetition or the fifty-move rule.
+    pub path_dependent: bool,
+}
+
+/// Search the given moves of the current position to the given depth.
+///
+/// The search stops after the move during which [`SearchContext::should_stop`] became true, the
+/// result is then only valid for the moves searched before.
+pub fn search_root<S: SearchContext>(
+    ctx: &mut S,
+    moves: &InlineBuffer<MAX_MOVES>,
+    depth: u8,
+    mut alpha: i32,
+    beta: i32,
+) -> RootResult {
+    let mut buffer = *moves;
+    let mut line = Line::new();
+    let mut best_move = Move::INVALID;
+    let mut path_dependent = false;
+
+    let mut sort = MoveSorter::new(&mut buffer, None, ctx.pv_move(0), None);
+    while let Some(m) = sort.next_move(ctx.board(), ctx.move_history()) {
+        let undo = ctx.push_move(m);
+        ctx.enter_child(m, depth - 1, -beta, -alpha);
+        let (child, draws) = search(ctx, depth - 1, 1, 0, -beta, -alpha, m, &mut line);
+        let value = -child;
+        path_dependent |= draws;
+        ctx.pop_move(undo);
+        ctx.leave_child(-value);
+        if ctx.should_stop() {
+            break;
+        }
+        if value > alpha {
+            alpha = value;
+            best_move = m;
+            ctx.new_best(0, m, &line);
+        }
+    }
+
+    RootResult {
+        best_move,
+        score: alpha,
+        path_dependent,
+    }
+}
+
+/// Search the current position to the given depth.
+///
+/// `ply` is the distance to the root and `extensions` the amount of plies this line has already
+/// been extended by. The total extension of a line is capped to half the depth of the root.
+/// `prev` is the move which lead to the position.
+///
+/// Returns the score and whether it depends on the moves leading to the position.
+#[allow(clippy::too_many_arguments)]
+fn search<S: SearchContext>(
+    ctx: &mut S,
+    mut depth: u8,
+    ply: u8,
+    mut extensions: u8,
+    mut alpha: i32,
+    mut beta: i32,
+    prev: Move,
+    pv_line: &mut Line,
+) -> (i32, bool) {
+    if ctx.should_stop() {
+        return (-INVALID_SCORE, false);
+    }
+
+    let repetitions = ctx.repetitions();
+    if repetitions >= 2 {
+        ctx.cutoff(Cutoff::Repetition);
+        return (ctx.draw_score(), true);
+    }
+    ctx.visit(ply);
+
+    // Every ply of the line either used up a ply of depth or was an extension.
+    let root_depth = ply + depth - extensions;
+
+    // Mate distance pruning, no line from here can do better than mating on the next ply
+    // or worse than being mated right now.
+    alpha = alpha.max(ply as i32 - CHECKMATE_SCORE);
+    beta = beta.min(CHECKMATE_SCORE - ply as i32 - 1);
+    if alpha >= beta {
+        ctx.cutoff(Cutoff::MateDistance);
+        return (alpha, false);
+    }
+
+    // Extend the search by a ply when the side to move is in check, so forcing sequences
+    // of checks are not cut off before they end in mate.
+    let info = ctx.move_gen().gen_info(ctx.board());
+    let in_check = ctx.move_gen().checked_king(ctx.board(), &info);
+    if in_check && ctx.check_extension() && extensions < root_depth / 2 {
+        depth += 1;
+        extensions += 1;
+    }
+
+    // Scores from the table were searched without the history of this line, they can't be
+    // trusted when the line already repeats the position or nears the fifty-move rule.
+    let trust_table = repetitions == 0 && ctx.board().state.move_clock <= FIFTY_MOVE_HORIZON;
+
+    let mut hash_move = None;
+    let mut table_hit = false;
+    let mut table_score = None;
+    if let Some(hash) = ctx.table().get(ctx.board().chain.hash) {
+        if hash.depth >= depth {
+            // A key collision can return the move of a different position.
+            hash_move =
+                Some(hash.r#move).filter(|m| ctx.move_gen().is_pseudo_legal(*m, ctx.board()));
+            if trust_table && !hash.path_dependent {
+                table_hit = true;
+                table_score = Some(hash.score.read_table(ply));
+            }
+        }
+    }
+    ctx.table_probed(table_hit);
+    match table_score {
+        Some(TableScore::Exact(x)) => {
+            ctx.cutoff(Cutoff::Table);
+            return (x, false);
+        }
+        Some(TableScore::Upper(x)) => {
+            alpha = alpha.max(x);
+            if alpha >= beta {
+                ctx.cutoff(Cutoff::Table);
+                return (x, false);
+            }
+        }
+        Some(TableScore::Lower(x)) => {
+            beta = beta.min(x);
+            if alpha >= beta {
+                ctx.cutoff(Cutoff::Table);
+                return (x, false);
+            }
+        }
+        None => {}
+    }
+
+    if let Some(wdl) = ctx.probe_wdl() {
+        let draw = ctx.draw_score();
+        let score = match wdl {
+            Wdl::Win => TABLEBASE_WIN_SCORE - ply as i32,
+            Wdl::CursedWin => draw + 1,
+            Wdl::Draw => draw,
+            Wdl::BlessedLoss => draw - 1,
+            Wdl::Loss => ply as i32 - TABLEBASE_WIN_SCORE,
+        };
+        ctx.cutoff(Cutoff::Tablebase);
+        return (score, false);
+    }
+
+    if depth == 0 {
+        ctx.cutoff(Cutoff::Quiesce);
+        let q = quiesce(ctx, alpha, beta, ply);
+        assert_ne!(q.abs(), INIT_BOUND);
+        return (q, false);
+    }
+
+    let mut buffer = InlineBuffer::<MAX_MOVES>::new();
+    ctx.move_gen()
+        .gen_moves_info::<gen_type::All, _, _>(ctx.board(), &info, &mut buffer);
+
+    if ctx.move_gen().drawn(ctx.board(), &info) {
+        ctx.cutoff(Cutoff::GameEnd);
+        return (ctx.draw_score(), ctx.board().is_fifty_move_draw());
+    }
+
+    if buffer.len() == 0 {
+        ctx.cutoff(Cutoff::GameEnd);
+        if in_check {
+            return (ply as i32 - CHECKMATE_SCORE, false);
+        } else {
+            return (ctx.draw_score(), false);
+        }
+    }
+
+    // Also extend lines where the side to move has only a single reply, unless already
+    // extended for being in check.
+    let (next_depth, next_extensions) =
+        if !in_check && buffer.len() == 1 && extensions < root_depth / 2 {
+            (depth, extensions + 1)
+        } else {
+            (depth - 1, extensions)
+        };
+
+    let mut value = -INIT_BOUND;
+    let mut path_dependent = false;
+
+    let mut new_line = Line::new();
+
+    let pv_move = ctx.pv_move(ply);
+    let counter_move = ctx.counter_move(prev);
+    let mut sort = MoveSorter::new(&mut buffer, hash_move, pv_move, counter_move);
+
+    let mut best_move = Move::INVALID;
+    // The bound of the score is decided by the window it was searched with, not by the alpha
+    // raised by the moves.
+    let alpha_orig = alpha;
+
+    while let Some(m) = sort.next_move(ctx.board(), ctx.move_history()) {
+        let undo = ctx.push_move(m);
+        ctx.enter_child(m, next_depth, -beta, -alpha);
+        let (child, draws) = search(
+            ctx,
+            next_depth,
+            ply + 1,
+            next_extensions,
+            -beta,
+            -alpha,
+            m,
+            &mut new_line,
+        );
+        let child = -child;
+        value = value.max(child);
+        path_dependent |= draws;
+        ctx.pop_move(undo);
+        ctx.leave_child(-child);
+        if value > alpha {
+            best_move = m;
+            alpha = value;
+            pv_line.apply(m, &new_line);
+            ctx.new_best(ply, m, &new_line);
+        }
+        if alpha >= beta {
+            if !m.is_capture(ctx.board()) {
+                ctx.quiet_cutoff(m, depth, prev);
+            }
+            ctx.cutoff(Cutoff::Beta);
+            break;
+        }
+    }
+
+    // The moves after the search was stopped returned invalid scores, which must not end up
+    // in the table.
+    if ctx.should_stop() {
+        return (value, path_dependent);
+    }
+
+    let score = if value <= alpha_orig {
+        TableScore::Lower(value)
+    } else if value >= beta {
+        TableScore::Upper(value)
+    } else {
+        TableScore::Exact(value)
+    };
+    let score = score.into_table(ply);
+
+    let hash = ctx.board().chain.hash;
+    ctx.table_mut().set(TableValue {
+        hash,
+        depth,
+        r#move: best_move,
+        score,
+        path_dependent: repetitions > 0 || path_dependent,
+    });
+    (value, path_dependent)
+}
+
+/// Search only captures until the position is quiet.
+///
+/// When the side to move is in check it can't stand pat, so all evasions are searched
+/// instead.
+fn quiesce<S: SearchContext>(ctx: &mut S, mut alpha: i32, beta: i32, ply: u8) -> i32 {
+    ctx.visit(ply);
+    let info = ctx.move_gen().gen_info(ctx.board());
+    let mut buffer = InlineBuffer::<MAX_MOVES>::new();
+    if ctx.move_gen().checked_king(ctx.board(), &info) {
+        ctx.move_gen()
+            .gen_moves_info::<gen_type::All, _, _>(ctx.board(), &info, &mut buffer);
+        if buffer.len() == 0 {
+            return ply as i32 - CHECKMATE_SCORE;
+        }
+    } else {
+        let color = match ctx.board().state.player {
+            Player::White => 1,
+            Player::Black => -1,
+        };
+        let value = color * ctx.eval(&info);
+        if value >= beta {
+            return beta;
+        }
+        alpha = alpha.max(value);
+        ctx.move_gen()
+            .gen_moves_info::<gen_type::Captures, _, _>(ctx.board(), &info, &mut buffer);
+    }
+    let mut sort = MoveSorter::new(&mut buffer, None, None, None);
+
+    while let Some(m) = sort.next_move(ctx.board(), ctx.move_history()) {
+        let undo = ctx.board_mut().make_move(m);
+        let value = -quiesce(ctx, -beta, -alpha, ply.saturating_add(1));
+        ctx.board_mut().unmake_move(undo);
+
+        if value >= beta {
+            return beta;
+        }
+        alpha = alpha.max(value);
+    }
+    alpha
+}
@@ -1,15 +1,24 @@
-use crate::{eval, sort::MoveSorter};
+use crate::{
+    negamax::{self, SearchContext, INIT_BOUND},
+    tree::{Cutoff, TreeRecorder},
+};
 
 use super::{
-    hash::{TableScore, TableValue},
-    AlphaBeta,
+    hash::{HashTable, TableScore, TableValue},
+    AlphaBeta, Board, HistoryTable, SearchStats, MAX_ASPIRATION_WINDOW,
 };
 use chess_core::{
-    engine::{EngineControl, Info},
-    gen::{gen_type, InlineBuffer, MoveList},
-    Move, Player,
+    board::{Board as BaseBoard, EvalChain, HashChain, MoveChain, UnmakeMove},
+    engine::{EngineControl, Info, Score, Wdl},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, PositionInfo, MAX_MOVES},
+    tablebase, Move,
+};
+use std::{
+    fs,
+    mem::MaybeUninit,
+    ptr,
+    time::{Duration, Instant},
 };
-use std::{mem::MaybeUninit, ptr};
 
 #[derive(Debug)]
 pub struct Line {
@@ -31,7 +40,7 @@ impl Line {
     }
 
     #[inline]
-    fn apply(&mut self, m: Move, other: &Line) {
+    pub(crate) fn apply(&mut self, m: Move, other: &Line) {
         self.v[0] = MaybeUninit::new(m);
         unsafe {
             ptr::copy_nonoverlapping(&other.v[0], &mut self.v[1] as *mut _, other.len);
@@ -40,7 +49,7 @@ impl Line {
     }
 
     #[inline]
-    pub fn get_pv(&mut self) -> &[Move] {
+    pub fn get_pv(&self) -> &[Move] {
         unsafe { &*(&self.v[0..self.len] as *const [MaybeUninit<Move>] as *const [Move]) }
     }
 
@@ -53,248 +62,609 @@ impl Line {
         }
     }
 }
+
+impl Default for Line {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Score of being checkmated at the root, mates further away score closer to zero by one per
+/// ply.
 pub const CHECKMATE_SCORE: i32 = 1_000_000;
-const INIT_BOUND: i32 = 2_000_000;
-const INVALID_SCORE: i32 = 2_121_212;
+/// Scores at least this far from zero are mates.
+pub const MATE_THRESHOLD: i32 = CHECKMATE_SCORE - u8::MAX as i32;
+/// Score of a position the tablebases say is won, above every evaluation but below the mates.
+/// Wins closer to the root score higher by one per ply.
+pub const TABLEBASE_WIN_SCORE: i32 = 20_000;
 const MAX_DEPTH: u8 = 99;
+/// Amount of nodes between checks whether the search statistics should be reported.
+const REPORT_NODES: u64 = 4096;
+/// Minimum time between two reports of the search statistics.
+const REPORT_INTERVAL: Duration = Duration::from_millis(500);
 
 impl<C: EngineControl> AlphaBeta<C> {
+    /// Returns whether the search should stop.
+    ///
+    /// The time limit applies while searching for a mate and once the first iteration
+    /// completed. Before that a budget smaller than the first iteration would leave the search
+    /// without a searched move.
     pub fn should_stop(&self) -> bool {
         let nodes = self.nodes;
         self.control.should_stop()
-            || self.limits.nodes.map(|x| x > nodes).unwrap_or(false)
-            || self
-                .time_limit
-                .as_ref()
-                .map(|x| x.should_stop(nodes))
-                .unwrap_or(false)
+            || self.limits.nodes.is_some_and(|x| nodes >= x)
+            || self.time_limit_applies
+                && self
+                    .time_limit
+                    .as_ref()
+                    .map(|x| x.should_stop(nodes))
+                    .unwrap_or(false)
+    }
+
+    /// Report the search statistics if enough time has passed since the last report.
+    fn report_progress(&mut self) {
+        if self.nodes < self.next_report {
+            return;
+        }
+        self.next_report = self.nodes + REPORT_NODES;
+
+        let now = Instant::now();
+        if now.duration_since(self.last_report) < REPORT_INTERVAL {
+            return;
+        }
+        self.last_report = now;
+
+        let time = now.duration_since(self.search_start);
+        self.control.info(Info::Progress {
+            nodes: self.nodes as usize,
+            nps: self.nodes as f32 / time.as_secs_f32(),
+            time,
+            hashfull: self.table.hashfull(),
+            tbhits: self.tablebase_hits as usize,
+        });
     }
 
     pub fn go_search(&mut self) -> Option<Move> {
         self.nodes = 0;
         self.table_hit = 0;
+        self.tablebase_hits = 0;
+        self.table_probes = 0;
+        self.stats = SearchStats::default();
+        self.quiet_cutoffs = 0;
+        self.counter_move_cutoffs = 0;
+        self.researches = 0;
+        self.search_start = Instant::now();
+        self.last_report = self.search_start;
+        self.next_report = REPORT_NODES;
+        self.butterfly.age();
+        self.score = 0;
+        self.completed_depth = 0;
+        self.time_limit_applies = false;
+        self.seldepth = 0;
+        self.tree =
+            (self.tree_plies > 0).then(|| TreeRecorder::new(self.tree_plies, self.tree_max_nodes));
+        self.last_tree = None;
+
+        if self.trace_eval {
+            let trace = self.eval_trace();
+            for (name, value) in [
+                ("material", trace.material),
+                ("piece_square", trace.piece_square),
+                ("king", trace.king),
+                ("total", trace.total()),
+            ]
+            .iter()
+            {
+                self.control
+                    .info(Info::String(format!("eval {} {}", name, value)));
+            }
+        }
 
-        let mut moves = InlineBuffer::<256>::new();
+        let mut moves = InlineBuffer::<MAX_MOVES>::new();
         self.gen
             .gen_moves::<gen_type::All, _, _>(&self.board, &mut moves);
 
+        if let Some(search_moves) = self.limits.search_moves.as_ref() {
+            let mut filtered = InlineBuffer::<MAX_MOVES>::new();
+            for m in moves.iter().filter(|m| search_moves.contains(m)) {
+                filtered.push(m);
+            }
+            if filtered.len() > 0 {
+                moves = filtered;
+            }
+        }
+
         if moves.len() == 0 {
             return None;
         }
 
-        let color = match self.board.state.player {
-            Player::White => 1,
-            Player::Black => -1,
-        };
-
         self.pv.clear();
 
+        // A search for a mate doesn't look further than the last move of the mate.
+        let mate_bound = self
+            .limits
+            .mate
+            .map(|x| x.saturating_mul(2).saturating_sub(1).min(MAX_DEPTH as u32) as u8)
+            .filter(|x| *x > 0);
+        let max_depth = match (self.limits.depth, mate_bound) {
+            (Some(a), Some(b)) => Some(a.min(b as u32)),
+            (a, b) => a.or(b.map(u32::from)),
+        };
+        if let Some(plies) = mate_bound {
+            // The clock applies to the search for a mate from its first depth. When it runs out
+            // the first iteration below still completes for a move to play.
+            self.time_limit_applies = true;
+            if let Some(m) = self.search_mate(&moves, plies) {
+                self.finish_search();
+                return Some(m);
+            }
+            // Without a mate the best move within the bound is searched as usual.
+            if !self.should_stop() {
+                self.control
+                    .info(Info::Debug(format!("no mate within {} plies", plies)));
+            }
+            self.time_limit_applies = false;
+        }
+
         self.depth = 1;
 
         let mut best_move_total = Move::INVALID;
 
+        // The first iteration is searched with a full window.
         let mut lower = INIT_BOUND;
         let mut upper = -INIT_BOUND;
+        let mut window = 0;
 
-        let mut hit_bound = false;
+        let mut root_entry = None;
 
         'depth_loop: while self.depth <= MAX_DEPTH {
             let mut best_move = Move::INVALID;
-            let mut line = Line::new();
+            let mut path_dependent = false;
 
             loop {
-                let mut buffer = moves;
-
                 let pref_upper = upper;
+                if let Some(tree) = self.tree.as_mut() {
+                    tree.start(self.depth, upper, lower);
+                }
 
-                let mut sort = MoveSorter::new(&mut buffer, None, self.pv.get(0));
-
-                while let Some(m) = sort.next_move(&self.board) {
-                    let undo = self.board.make_move(m);
-                    let value = -self.search(self.depth - 1, -upper, -lower, -color, &mut line);
-                    self.board.unmake_move(undo);
-                    if value > upper {
-                        self.pv.apply(m, &line);
-                        upper = value;
-                        best_move = m;
-                    }
+                let res = negamax::search_root(self, &moves, self.depth, upper, lower);
+                path_dependent |= res.path_dependent;
+                if res.best_move != Move::INVALID {
+                    upper = res.score;
+                    best_move = res.best_move;
                 }
 
                 if self.should_stop() {
+                    self.finish_iteration(false);
                     break 'depth_loop;
                 }
 
-                if !hit_bound && upper == lower || upper == pref_upper {
+                let fail_low = upper == pref_upper;
+                let fail_high = upper >= lower;
+                if window == 0 || !(fail_low || fail_high) {
+                    break;
+                }
+
+                // Search again with a window twice as wide on the side which failed, or with a
+                // full window once the window gets too wide.
+                self.researches += 1;
+                if fail_low {
+                    self.stats.fail_low();
+                } else {
+                    self.stats.fail_high();
+                }
+                window *= 2;
+                if window > MAX_ASPIRATION_WINDOW {
+                    window = 0;
                     lower = INIT_BOUND;
                     upper = -INIT_BOUND;
-                    hit_bound = true;
-                    self.control.info(Info::Debug("RETRY".to_string()));
+                } else if fail_low {
+                    upper = pref_upper - window;
                 } else {
-                    break;
+                    lower = upper + window;
+                    upper = pref_upper;
                 }
+                self.control.info(Info::Debug(format!(
+                    "aspiration {} at depth {}, searching again with [{}, {}]",
+                    if fail_low { "fail low" } else { "fail high" },
+                    self.depth,
+                    upper,
+                    lower
+                )));
             }
 
             best_move_total = best_move;
+            if let Some(tree) = self.tree.as_mut() {
+                self.last_tree = Some(tree.finish(upper));
+            }
 
-            self.control.info(Info::Depth(self.depth as u16));
-            self.control.info(Info::BestMove {
-                mov: best_move_total,
-                value: color * upper,
-            });
-            self.control.info(Info::Nodes(self.nodes as usize));
-            self.control.info(Info::TransHit(self.table_hit as usize));
-            self.control.info(Info::Pv(self.pv.get_pv().to_vec()));
-            self.control.info(Info::Round);
-
-            if self.should_stop()
-                || self
-                    .limits
-                    .depth
-                    .map(|x| x >= self.depth as u32)
-                    .unwrap_or(false)
-            {
+            let entry = TableValue {
+                hash: self.board.chain.hash,
+                depth: self.depth,
+                r#move: best_move_total,
+                score: TableScore::Exact(upper),
+                path_dependent,
+            };
+            self.table.set(entry);
+            root_entry = Some(entry);
+
+            self.complete_iteration(upper);
+            self.finish_iteration(true);
+
+            if self.should_stop() || max_depth.is_some_and(|x| self.depth as u32 >= x) {
                 break;
             }
 
-            if upper.abs() == CHECKMATE_SCORE {
-                break;
+            // Stop at a mate once all shorter mates have been searched as well.
+            if let Some(plies) = mate_plies(upper) {
+                if plies <= self.depth as i32 {
+                    break;
+                }
             }
 
-            lower = upper + eval::PAWN_VALUE / 4;
-            upper -= eval::PAWN_VALUE / 4;
-            hit_bound = false;
+            window = self.aspiration_window;
+            if window == 0 {
+                lower = INIT_BOUND;
+                upper = -INIT_BOUND;
+            } else {
+                lower = upper + window;
+                upper -= window;
+            }
 
             self.depth += 1;
         }
 
+        // Positions of an unfinished iteration can replace the entry of the root, which the
+        // principal variation is read from.
+        if let Some(entry) = root_entry {
+            self.table.set(entry);
+        }
+
+        self.finish_search();
+
         if best_move_total != Move::INVALID {
             Some(best_move_total)
         } else {
-            None
+            // Stopped before the first iteration completed, any legal move is better than none.
+            Some(moves.get(0))
         }
     }
 
-    fn search(
-        &mut self,
-        depth: u8,
-        mut lower: i32,
-        mut upper: i32,
-        color: i32,
-        pv_line: &mut Line,
-    ) -> i32 {
-        if self.should_stop() {
-            return -INVALID_SCORE;
+    /// Report the statistics of the search once it finished.
+    fn finish_search(&mut self) {
+        self.write_tree();
+        self.control.info(Info::Debug(format!(
+            "counter move cutoffs {} of {} quiet cutoffs",
+            self.counter_move_cutoffs, self.quiet_cutoffs
+        )));
+        self.control.info(Info::Debug(format!(
+            "aspiration re-searches {}",
+            self.researches
+        )));
+
+        self.stats.table_probes = self.table_probes;
+        self.stats.table_hits = self.table_hit;
+        if self.show_stats {
+            for line in self.stats.lines() {
+                self.control.info(Info::String(line));
+            }
         }
+    }
 
-        let mut hash_move = None;
-        if let Some(hash) = self.table.get(self.board.chain.hash) {
-            if hash.depth >= depth {
-                self.table_hit += 1;
-                hash_move = Some(hash.r#move);
-                match hash.score {
-                    TableScore::Exact(x) => return x,
-                    TableScore::Upper(x) => {
-                        upper = upper.max(x);
-                        if upper >= lower {
-                            return x;
-                        }
-                    }
-                    TableScore::Lower(x) => {
-                        lower = lower.max(x);
-                        if upper >= lower {
-                            return x;
-                        }
-                    }
-                }
+    /// Add the statistics of the iteration at the current depth.
+    fn finish_iteration(&mut self, completed: bool) {
+        let time = self.search_start.elapsed();
+        self.stats
+            .finish_iteration(self.depth, self.nodes, time, completed);
+    }
+
+    /// Search only for a mate within the given amount of plies.
+    ///
+    /// The root is searched with a window above every score but the mates within the bound, so
+    /// lines without one are cut off quickly. Returns the first move of the shortest mate found,
+    /// or `None` if there is none within the bound or the search was stopped.
+    fn search_mate(&mut self, moves: &InlineBuffer<MAX_MOVES>, plies: u8) -> Option<Move> {
+        let lower = CHECKMATE_SCORE;
+        let upper = CHECKMATE_SCORE - plies as i32 - 1;
+        for depth in 1..=plies {
+            self.depth = depth;
+            let res = negamax::search_root(self, moves, depth, upper, lower);
+            if self.should_stop() {
+                self.finish_iteration(false);
+                return None;
             }
-        }
 
-        if depth == 0 {
-            let q = self.quiesce(lower, upper, color);
-            assert_ne!(q.abs(), INIT_BOUND);
-            return q;
+            self.finish_iteration(true);
+            if res.best_move != Move::INVALID {
+                self.table.set(TableValue {
+                    hash: self.board.chain.hash,
+                    depth,
+                    r#move: res.best_move,
+                    score: TableScore::Exact(res.score),
+                    path_dependent: false,
+                });
+                self.complete_iteration(res.score);
+                return Some(res.best_move);
+            }
         }
+        None
+    }
 
-        let mut buffer = InlineBuffer::<128>::new();
-        let pos_info = self
-            .gen
-            .gen_moves::<gen_type::All, _, _>(&self.board, &mut buffer);
+    /// Report a completed iteration of the search with the score of the root.
+    fn complete_iteration(&mut self, score: i32) {
+        self.score = score;
+        self.completed_depth = self.depth;
+        self.time_limit_applies = true;
+        let time = self.search_start.elapsed();
+        self.control.info(Info::Stats {
+            depth: self.depth as u16,
+            seldepth: self.seldepth as u16,
+            nodes: self.nodes as usize,
+            nps: self.nodes as f32 / time.as_secs_f32(),
+            time,
+            hashfull: self.table.hashfull(),
+            score: to_score(score),
+            wdl: self.show_wdl.then(|| to_wdl(score)),
+            pv: self.current_pv(),
+        });
+        self.control.info(Info::Round);
+    }
 
-        if self.gen.drawn(&self.board, &pos_info) {
-            return -self.contempt;
+    /// Write the tree of the last completed iteration to the dump file, if it was recorded.
+    fn write_tree(&mut self) {
+        let Some(tree) = self.last_tree.as_ref() else {
+            return;
+        };
+        let res = fs::File::create(&self.tree_file).and_then(|mut f| tree.write_dot(&mut f));
+        if let Err(e) = res {
+            self.control.info(Info::String(format!(
+                "failed to write search tree to `{}`: {}",
+                self.tree_file.display(),
+                e
+            )));
         }
+    }
 
-        if buffer.len() == 0 {
-            if self.gen.checked_king(&self.board, &pos_info) {
-                return -CHECKMATE_SCORE;
-            } else {
-                return -self.contempt;
+    /// Returns the principal variation of the last completed iteration.
+    pub(crate) fn current_pv(&self) -> Vec<Move> {
+        let pv = self.pv_from_tt(&self.board);
+        if pv.len() < self.pv.get_pv().len() {
+            self.pv.get_pv().to_vec()
+        } else {
+            pv
+        }
+    }
+
+    /// Reconstruct the principal variation by following the best moves stored in the
+    /// transposition table, starting from the given board.
+    ///
+    /// Stops at the first position without a table entry, on a repetition or when the
+    /// line reaches the current search depth.
+    pub fn pv_from_tt<N: MoveChain + Clone>(&self, board: &BaseBoard<HashChain<N>>) -> Vec<Move> {
+        let mut board = board.clone();
+        let mut pv = Vec::new();
+        let mut seen = vec![board.chain.hash];
+
+        while pv.len() < self.depth as usize {
+            let m = match self.table.get(board.chain.hash) {
+                Some(x) if x.r#move != Move::INVALID => x.r#move,
+                _ => break,
+            };
+
+            let mut moves = InlineBuffer::<MAX_MOVES>::new();
+            self.gen
+                .gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if !moves.iter().any(|x| x == m) {
+                break;
+            }
+
+            board.make_move(m);
+            pv.push(m);
+
+            if seen.contains(&board.chain.hash) {
+                break;
             }
+            seen.push(board.chain.hash);
         }
+        pv
+    }
+}
 
-        let mut value = -INIT_BOUND;
+impl<C: EngineControl> SearchContext for AlphaBeta<C> {
+    type Chain = EvalChain;
 
-        let mut new_line = Line::new();
+    fn board(&self) -> &Board {
+        &self.board
+    }
 
-        let pv_move = self.pv.get(self.depth - depth);
-        let mut sort = MoveSorter::new(&mut buffer, hash_move, pv_move);
+    fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
 
-        let mut best_move = Move::INVALID;
+    fn move_gen(&self) -> &MoveGenerator {
+        &self.gen
+    }
 
-        while let Some(m) = sort.next_move(&self.board) {
-            let undo = self.board.make_move(m);
-            value = value.max(-self.search(depth - 1, -upper, -lower, -color, &mut new_line));
-            self.board.unmake_move(undo);
-            if value > upper {
-                best_move = m;
-                upper = value;
-                pv_line.apply(m, &new_line);
-            }
-            if upper >= lower {
-                break;
-            }
+    fn table(&self) -> &HashTable {
+        &self.table
+    }
+
+    fn table_mut(&mut self) -> &mut HashTable {
+        &mut self.table
+    }
+
+    #[inline]
+    fn eval(&mut self, info: &PositionInfo) -> i32 {
+        self.eval_board(info)
+    }
+
+    #[inline]
+    fn should_stop(&self) -> bool {
+        AlphaBeta::should_stop(self)
+    }
+
+    #[inline]
+    fn draw_score(&self) -> i32 {
+        AlphaBeta::draw_score(self)
+    }
+
+    /// Counts the repetitions in the game as well as on the current search line.
+    fn repetitions(&self) -> usize {
+        // The current position is on the history as well.
+        (self.history.repetitions(self.board.chain.hash) as usize).saturating_sub(1)
+    }
+
+    fn push_move(&mut self, m: Move) -> UnmakeMove {
+        let undo = self.board.make_move(m);
+        self.push_history();
+        undo
+    }
+
+    fn pop_move(&mut self, undo: UnmakeMove) {
+        self.history.pop();
+        self.board.unmake_move(undo);
+    }
+
+    fn check_extension(&self) -> bool {
+        self.check_extension
+    }
+
+    fn visit(&mut self, ply: u8) {
+        self.report_progress();
+        self.seldepth = self.seldepth.max(ply);
+    }
+
+    fn table_probed(&mut self, hit: bool) {
+        self.table_probes += 1;
+        if hit {
+            self.table_hit += 1;
         }
+    }
 
-        let score = if value <= upper {
-            TableScore::Lower(value)
-        } else if value >= lower {
-            TableScore::Upper(value)
-        } else {
-            TableScore::Exact(value)
-        };
+    fn pv_move(&self, ply: u8) -> Option<Move> {
+        self.pv.get(ply)
+    }
 
-        self.table.set(TableValue {
-            hash: self.board.chain.hash,
-            depth,
-            r#move: best_move,
-            score,
-        });
-        value
+    fn move_history(&self) -> Option<&HistoryTable> {
+        Some(&self.butterfly)
+    }
+
+    fn counter_move(&self, prev: Move) -> Option<Move> {
+        self.counter_moves.get(prev)
     }
 
-    fn quiesce(&mut self, lower: i32, mut upper: i32, color: i32) -> i32 {
-        let info = self.gen.gen_info(&self.board);
-        let value = color * self.eval_board(&info);
-        if value >= lower {
-            return lower;
+    fn quiet_cutoff(&mut self, m: Move, depth: u8, prev: Move) {
+        if self.counter_moves.get(prev) == Some(m) {
+            self.counter_move_cutoffs += 1;
         }
-        upper = upper.max(value);
+        self.quiet_cutoffs += 1;
+        self.butterfly.add(self.board.state.player, m, depth);
+        self.counter_moves.set(prev, m);
+    }
 
-        let mut buffer = InlineBuffer::<128>::new();
-        self.gen
-            .gen_moves_info::<gen_type::Captures, _, _>(&self.board, &info, &mut buffer);
-        let mut sort = MoveSorter::new(&mut buffer, None, None);
+    fn enter_child(&mut self, m: Move, depth: u8, alpha: i32, beta: i32) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.enter(m, depth, alpha, beta);
+        }
+    }
+
+    fn leave_child(&mut self, score: i32) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.leave(score);
+        }
+    }
 
-        while let Some(m) = sort.next_move(&self.board) {
-            let undo = self.board.make_move(m);
-            let value = -self.quiesce(-upper, -lower, -color);
-            self.board.unmake_move(undo);
+    fn new_best(&mut self, ply: u8, m: Move, line: &Line) {
+        if ply == 0 {
+            self.pv.apply(m, line);
+        }
+        if let Some(tree) = self.tree.as_mut() {
+            tree.best();
+        }
+    }
 
-            if value >= lower {
-                return lower;
-            }
-            upper = upper.max(value);
+    fn cutoff(&mut self, cutoff: Cutoff) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.cutoff(cutoff);
         }
-        upper
+    }
+
+    fn probe_wdl(&mut self) -> Option<tablebase::Wdl> {
+        let wdl = self.probe_tablebase(|prober, board| prober.probe_wdl(board))?;
+        self.tablebase_hits += 1;
+        Some(wdl)
+    }
+}
+
+/// Returns the distance in plies to the mate for a mate score.
+pub fn mate_plies(score: i32) -> Option<i32> {
+    if (MATE_THRESHOLD..=CHECKMATE_SCORE).contains(&score.abs()) {
+        Some(CHECKMATE_SCORE - score.abs())
+    } else {
+        None
+    }
+}
+
+/// Convert a score from the perspective of the side to move into a score to report.
+pub fn to_score(score: i32) -> Score {
+    match mate_plies(score) {
+        // Mating on an odd ply is mating with our own move.
+        Some(plies) if score > 0 => Score::Mate((plies + 1) / 2),
+        Some(plies) => Score::Mate(-plies / 2),
+        None => Score::Cp(score),
+    }
+}
+
+/// Score in centipawns at which winning and drawing are equally likely.
+const WDL_MIDPOINT: f32 = 200.0;
+/// How fast the chances change with the score, in centipawns.
+const WDL_SCALE: f32 = 100.0;
+
+/// Convert a score from the perspective of the side to move into the chances of winning,
+/// drawing and losing, using a logistic model of the outcome of games.
+pub fn to_wdl(score: i32) -> Wdl {
+    if mate_plies(score).is_some() {
+        return if score > 0 {
+            Wdl {
+                win: 1000,
+                draw: 0,
+                loss: 0,
+            }
+        } else {
+            Wdl {
+                win: 0,
+                draw: 0,
+                loss: 1000,
+            }
+        };
+    }
+    let chance = |x: f32| 1.0 / (1.0 + ((WDL_MIDPOINT - x) / WDL_SCALE).exp());
+    let win = (chance(score as f32) * 1000.0).round() as u16;
+    let loss = (chance(-score as f32) * 1000.0).round() as u16;
+    Wdl {
+        win,
+        draw: 1000u16.saturating_sub(win + loss),
+        loss,
+    }
+}
+
+impl TableScore {
+    /// Mate scores are relative to the root of the search. The table stores them relative to
+    /// the position itself, so they stay correct when the position is reached at another ply.
+    pub(crate) fn into_table(self, ply: u8) -> Self {
+        self.map(|x| match mate_plies(x) {
+            Some(_) if x > 0 => x + ply as i32,
+            Some(_) => x - ply as i32,
+            None => x,
+        })
+    }
+
+    /// Convert a score from the table back into one relative to the root.
+    pub(crate) fn read_table(self, ply: u8) -> Self {
+        self.map(|x| match mate_plies(x) {
+            Some(_) if x > 0 => x - ply as i32,
+            Some(_) => x + ply as i32,
+            None => x,
+        })
     }
 }
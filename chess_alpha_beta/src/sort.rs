@@ -1,7 +1,7 @@
-use crate::eval;
+use crate::{eval, history::HistoryTable};
 
-use super::Board;
 use chess_core::{
+    board::{Board, MoveChain},
     gen::{InlineBuffer, MoveList},
     Move,
 };
@@ -10,50 +10,42 @@ pub struct MoveSorter<'a, const SIZE: usize> {
     moves: &'a mut InlineBuffer<SIZE>,
     hash_move: Option<Move>,
     pv_move: Option<Move>,
+    counter_move: Option<Move>,
     sort_count: u8,
 }
 
 impl<'a, const SIZE: usize> MoveSorter<'a, SIZE> {
     const LIMIT_SORT: u8 = 20;
 
-    const PIECE_VALUE: [i32; 12] = [
-        0,
-        eval::QUEEN_VALUE,
-        eval::BISHOP_VALUE,
-        eval::KNIGHT_VALUE,
-        eval::ROOK_VALUE,
-        eval::PAWN_VALUE,
-        0,
-        eval::QUEEN_VALUE,
-        eval::BISHOP_VALUE,
-        eval::KNIGHT_VALUE,
-        eval::ROOK_VALUE,
-        eval::PAWN_VALUE,
-    ];
-
     pub fn new(
         moves: &'a mut InlineBuffer<SIZE>,
         hash_move: Option<Move>,
         pv_move: Option<Move>,
+        counter_move: Option<Move>,
     ) -> Self {
         Self {
             moves,
             hash_move,
             pv_move,
+            counter_move,
             sort_count: 0,
         }
     }
 
-    pub fn next_move(&mut self, board: &Board) -> Option<Move> {
+    pub fn next_move<M: MoveChain>(
+        &mut self,
+        board: &Board<M>,
+        history: Option<&HistoryTable>,
+    ) -> Option<Move> {
         if self.moves.len() == 0 {
             return None;
         }
 
         if self.sort_count < Self::LIMIT_SORT {
-            let mut best = self.score_move(self.moves.get(0), board);
+            let mut best = self.score_move(self.moves.get(0), board, history);
             let mut sorted = true;
             for i in 1..self.moves.len() {
-                let score = self.score_move(self.moves.get(i), board);
+                let score = self.score_move(self.moves.get(i), board, history);
                 if score > best {
                     best = score;
                 } else {
@@ -68,7 +60,12 @@ impl<'a, const SIZE: usize> MoveSorter<'a, SIZE> {
         self.moves.pop()
     }
 
-    fn score_move(&self, m: Move, board: &Board) -> i32 {
+    fn score_move<M: MoveChain>(
+        &self,
+        m: Move,
+        board: &Board<M>,
+        history: Option<&HistoryTable>,
+    ) -> i32 {
         if Some(m) == self.pv_move {
             return 5000;
         }
@@ -77,10 +74,16 @@ impl<'a, const SIZE: usize> MoveSorter<'a, SIZE> {
             return 4000;
         }
 
-        let from = board.on(m.from()).unwrap();
-
         if let Some(to) = board.on(m.to()) {
-            Self::PIECE_VALUE[to as usize] - Self::PIECE_VALUE[from as usize] + eval::QUEEN_VALUE
+            // Drops land on empty squares, so a capture always has a piece on its from square.
+            let from = board.on(m.from()).unwrap();
+            eval::PIECE_VALUE[to as usize] - eval::PIECE_VALUE[from as usize] + eval::QUEEN_VALUE
+        } else if Some(m) == self.counter_move {
+            // The counter move goes first of the quiet moves.
+            99
+        } else if let Some(history) = history {
+            // Quiet moves are ordered by history, always below captures.
+            history.get(board.state.player, m) * 98 / HistoryTable::MAX
         } else {
             0
         }
@@ -0,0 +1,117 @@
+//! Statistics of a search for tuning, collected over the iterations of iterative deepening.
+
+use std::time::Duration;
+
+/// The statistics of a single iteration of the search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IterationStats {
+    pub depth: u8,
+    /// Nodes searched in this iteration alone.
+    pub nodes: u64,
+    /// Time spent on this iteration alone.
+    pub time: Duration,
+    /// Times the score at the root fell outside of the aspiration window.
+    pub fail_highs: u32,
+    pub fail_lows: u32,
+    /// Whether the iteration finished, the last iteration is cut short when the search is
+    /// stopped.
+    pub completed: bool,
+}
+
+/// The statistics of a search, reported after the search when the `ShowStats` option is
+/// enabled.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    pub iterations: Vec<IterationStats>,
+    /// Lookups of the transposition table and how many of them returned a usable score.
+    pub table_probes: u64,
+    pub table_hits: u64,
+    fail_highs: u32,
+    fail_lows: u32,
+}
+
+impl SearchStats {
+    /// Returns the total amount of nodes of all iterations.
+    pub fn nodes(&self) -> u64 {
+        self.iterations.iter().map(|x| x.nodes).sum()
+    }
+
+    /// Returns the total time of all iterations.
+    pub fn time(&self) -> Duration {
+        self.iterations.iter().map(|x| x.time).sum()
+    }
+
+    /// Returns the effective branching factor of the iteration at the index, the amount of
+    /// nodes it searched divided by those of the previous iteration.
+    pub fn branching_factor(&self, index: usize) -> Option<f32> {
+        let prev = self.iterations.get(index.checked_sub(1)?)?;
+        let cur = self.iterations.get(index)?;
+        (prev.nodes > 0).then(|| cur.nodes as f32 / prev.nodes as f32)
+    }
+
+    /// Returns the fraction of the lookups of the transposition table which returned a usable
+    /// score.
+    pub fn table_hit_rate(&self) -> f32 {
+        if self.table_probes == 0 {
+            0.0
+        } else {
+            self.table_hits as f32 / self.table_probes as f32
+        }
+    }
+
+    pub(crate) fn fail_high(&mut self) {
+        self.fail_highs += 1;
+    }
+
+    pub(crate) fn fail_low(&mut self) {
+        self.fail_lows += 1;
+    }
+
+    /// Finish the current iteration, given the nodes and time of the search so far.
+    pub(crate) fn finish_iteration(
+        &mut self,
+        depth: u8,
+        nodes: u64,
+        time: Duration,
+        completed: bool,
+    ) {
+        let iteration = IterationStats {
+            depth,
+            nodes: nodes - self.nodes(),
+            time: time.saturating_sub(self.time()),
+            fail_highs: std::mem::take(&mut self.fail_highs),
+            fail_lows: std::mem::take(&mut self.fail_lows),
+            completed,
+        };
+        self.iterations.push(iteration);
+    }
+
+    /// Returns a line of text for every iteration followed by a summary.
+    pub fn lines(&self) -> Vec<String> {
+        let mut res = Vec::new();
+        for (i, x) in self.iterations.iter().enumerate() {
+            let ebf = self
+                .branching_factor(i)
+                .map_or("-".to_string(), |x| format!("{:.2}", x));
+            res.push(format!(
+                "stats depth {} nodes {} time {} ebf {} fail high {} fail low {}{}",
+                x.depth,
+                x.nodes,
+                x.time.as_millis(),
+                ebf,
+                x.fail_highs,
+                x.fail_lows,
+                if x.completed { "" } else { " incomplete" }
+            ));
+        }
+        res.push(format!(
+            "stats total nodes {} time {} table hits {} of {} ({:.1}%)",
+            self.nodes(),
+            self.time().as_millis(),
+            self.table_hits,
+            self.table_probes,
+            self.table_hit_rate() * 100.0
+        ));
+        res
+    }
+}
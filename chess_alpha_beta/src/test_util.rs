@@ -0,0 +1,49 @@
+//! Helpers for testing the evaluation.
+//!
+//! Authors of new evaluation terms can run [`assert_eval_symmetric`] over a set of positions to
+//! catch terms which favour one of the colors.
+
+use crate::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineControl},
+};
+
+/// Statically evaluates positions from the perspective of white.
+pub trait Evaluator {
+    fn evaluate(&mut self, board: &Board<EndChain>) -> i32;
+}
+
+impl<F: FnMut(&Board<EndChain>) -> i32> Evaluator for F {
+    fn evaluate(&mut self, board: &Board<EndChain>) -> i32 {
+        self(board)
+    }
+}
+
+impl<C: EngineControl> Evaluator for AlphaBeta<C> {
+    fn evaluate(&mut self, board: &Board<EndChain>) -> i32 {
+        self.set_board(board.clone());
+        self.eval_trace().total()
+    }
+}
+
+/// Assert that the evaluator scores the position of the FEN as the exact negative of the
+/// position with the board mirrored and the colors swapped.
+///
+/// # Panics
+///
+/// Panics if the FEN is invalid or the scores are not each others negative.
+pub fn assert_eval_symmetric(evaluator: &mut impl Evaluator, fen: &str) {
+    let board =
+        Board::from_fen(fen, EndChain).unwrap_or_else(|e| panic!("invalid fen `{}`: {}", fen, e));
+    let mirrored = board.mirror();
+    let score = evaluator.evaluate(&board);
+    let mirrored_score = evaluator.evaluate(&mirrored);
+    assert_eq!(
+        score,
+        -mirrored_score,
+        "evaluation of {} is not symmetric with {}",
+        fen,
+        mirrored.to_fen()
+    );
+}
@@ -0,0 +1,232 @@
+//! Recording the nodes of a search for debugging, written as a graphviz dot file.
+//!
+//! Only the first plies of the last completed iteration are recorded, up to a maximum amount of
+//! nodes, so deep searches don't produce huge files.
+
+use chess_core::Move;
+use std::{fmt, io};
+
+/// Why a node returned without searching all of its moves.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Cutoff {
+    /// A move scored at least beta.
+    Beta,
+    /// The score was taken from the transposition table.
+    Table,
+    /// No line from the node could score inside the window.
+    MateDistance,
+    /// The position repeated on the search line.
+    Repetition,
+    /// The position is drawn by the rules or has no legal moves.
+    GameEnd,
+    /// The node was scored by the quiescence search.
+    Quiesce,
+    /// The outcome of the position was found in the tablebases.
+    Tablebase,
+}
+
+impl fmt::Display for Cutoff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Cutoff::Beta => "beta cutoff",
+            Cutoff::Table => "table cutoff",
+            Cutoff::MateDistance => "mate distance",
+            Cutoff::Repetition => "repetition",
+            Cutoff::GameEnd => "game end",
+            Cutoff::Quiesce => "quiesce",
+            Cutoff::Tablebase => "tablebase",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A node of a recorded search.
+#[derive(Clone, Debug)]
+pub struct TreeNode {
+    /// The move leading to the node, `None` for the root.
+    pub mov: Option<Move>,
+    /// Remaining depth of the search at the node.
+    pub depth: u8,
+    /// The window the node was searched with, from the perspective of the side to move.
+    pub alpha: i32,
+    pub beta: i32,
+    /// The returned score, from the perspective of the side to move.
+    pub score: Option<i32>,
+    pub cutoff: Option<Cutoff>,
+    /// The children in the order their moves were searched.
+    pub children: Vec<usize>,
+    /// The child which raised alpha last, the next move on the best line.
+    pub best: Option<usize>,
+}
+
+/// The recorded nodes of a search.
+#[derive(Clone, Debug, Default)]
+pub struct SearchTree {
+    nodes: Vec<TreeNode>,
+    /// Whether nodes were left out because the tree reached the maximum amount of nodes.
+    truncated: bool,
+}
+
+impl SearchTree {
+    pub fn root(&self) -> &TreeNode {
+        &self.nodes[0]
+    }
+
+    pub fn node(&self, index: usize) -> &TreeNode {
+        &self.nodes[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the moves of the best line from the root, as far as it was recorded.
+    pub fn best_path(&self) -> Vec<Move> {
+        let mut res = Vec::new();
+        let mut node = self.root();
+        while let Some(best) = node.best {
+            node = &self.nodes[best];
+            res.extend(node.mov);
+        }
+        res
+    }
+
+    /// Write the tree as a graphviz dot file, with the best line in red.
+    pub fn write_dot(&self, f: &mut impl io::Write) -> io::Result<()> {
+        writeln!(f, "digraph alpha_beta {{")?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let name = node.mov.map_or("root".to_string(), |x| x.to_uci());
+            let score = node.score.map_or("-".to_string(), |x| x.to_string());
+            let cutoff = node.cutoff.map_or(String::new(), |x| format!("|{}", x));
+            writeln!(
+                f,
+                "{} [shape=record, label=\"{{ {}|depth {}|[{}, {}]|{}{} }}\"];",
+                i, name, node.depth, node.alpha, node.beta, score, cutoff
+            )?;
+            for c in node.children.iter() {
+                if node.best == Some(*c) {
+                    writeln!(f, "{} -> {} [color=red];", i, c)?;
+                } else {
+                    writeln!(f, "{} -> {};", i, c)?;
+                }
+            }
+        }
+        if self.truncated {
+            writeln!(
+                f,
+                "truncated [shape=plaintext, label=\"node limit reached\"];"
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// Records the nodes of a search while it runs.
+pub(crate) struct TreeRecorder {
+    /// Amount of plies from the root to record.
+    plies: u8,
+    max_nodes: usize,
+    tree: SearchTree,
+    /// Indices of the nodes on the current line, `None` for nodes which are not recorded.
+    stack: Vec<Option<usize>>,
+    /// The node which was left last.
+    last: Option<usize>,
+}
+
+impl TreeRecorder {
+    pub fn new(plies: u8, max_nodes: usize) -> Self {
+        TreeRecorder {
+            plies,
+            max_nodes: max_nodes.max(1),
+            tree: SearchTree::default(),
+            stack: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// Start recording a new pass over the root.
+    pub fn start(&mut self, depth: u8, alpha: i32, beta: i32) {
+        self.tree = SearchTree::default();
+        self.stack.clear();
+        self.last = None;
+        self.tree.nodes.push(TreeNode {
+            mov: None,
+            depth,
+            alpha,
+            beta,
+            score: None,
+            cutoff: None,
+            children: Vec::new(),
+            best: None,
+        });
+        self.stack.push(Some(0));
+    }
+
+    /// Enter the node reached by the move.
+    pub fn enter(&mut self, mov: Move, depth: u8, alpha: i32, beta: i32) {
+        let parent = self.stack.last().copied().flatten();
+        let recorded = parent.filter(|_| self.stack.len() <= self.plies as usize);
+        let index = match recorded {
+            Some(_) if self.tree.nodes.len() >= self.max_nodes => {
+                self.tree.truncated = true;
+                None
+            }
+            Some(parent) => {
+                let index = self.tree.nodes.len();
+                self.tree.nodes.push(TreeNode {
+                    mov: Some(mov),
+                    depth,
+                    alpha,
+                    beta,
+                    score: None,
+                    cutoff: None,
+                    children: Vec::new(),
+                    best: None,
+                });
+                self.tree.nodes[parent].children.push(index);
+                Some(index)
+            }
+            None => None,
+        };
+        self.stack.push(index);
+    }
+
+    /// Leave the current node with the score it returned.
+    pub fn leave(&mut self, score: i32) {
+        self.last = self.stack.pop().flatten();
+        if let Some(index) = self.last {
+            self.tree.nodes[index].score = Some(score);
+        }
+    }
+
+    /// Note why the current node returned early.
+    pub fn cutoff(&mut self, cutoff: Cutoff) {
+        if let Some(Some(index)) = self.stack.last() {
+            self.tree.nodes[*index].cutoff = Some(cutoff);
+        }
+    }
+
+    /// Mark the node which was left last as the best child of the current node.
+    pub fn best(&mut self) {
+        if let Some(Some(index)) = self.stack.last() {
+            self.tree.nodes[*index].best = self.last;
+        }
+    }
+
+    /// Finish the pass over the root with its score and return the recorded tree.
+    pub fn finish(&mut self, score: i32) -> SearchTree {
+        if let Some(root) = self.tree.nodes.first_mut() {
+            root.score = Some(score);
+        }
+        self.stack.clear();
+        std::mem::take(&mut self.tree)
+    }
+}
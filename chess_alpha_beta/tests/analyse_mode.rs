@@ -0,0 +1,43 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+};
+
+/// White is a bishop up behind locked pawns, every move reaches the fifty-move rule.
+const FIFTY_MOVES: &str = "7k/8/3p4/2pPp3/2P1P3/8/8/5B1K w - - 99 60";
+
+fn score(board: &Board, contempt: i32, analyse_mode: bool) -> i32 {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(contempt));
+    engine.set_option(
+        "UCI_AnalyseMode".to_string(),
+        OptionValue::Check(analyse_mode),
+    );
+    engine.analyze(board, EngineLimit::depth(3)).score
+}
+
+#[test]
+fn analyse_mode_scores_draws_as_zero() {
+    let board = Board::from_fen(FIFTY_MOVES, EndChain).unwrap();
+    let contempt = 480;
+    // The contempt scales with the material, here only a bishop.
+    let scaled = contempt * board.phase() / Board::<EndChain>::MAX_PHASE;
+    assert_eq!(scaled, 20);
+
+    // White is to move at the root, so the draw costs white the contempt even though it is
+    // reached with black to move.
+    let playing = score(&board, contempt, false);
+    let analysing = score(&board, contempt, true);
+    assert_eq!(analysing, 0);
+    assert_eq!(analysing - playing, scaled);
+}
+
+#[test]
+fn option_is_declared() {
+    let engine = AlphaBeta::<NoControl>::new();
+    assert!(engine
+        .options()
+        .iter()
+        .any(|(name, _)| name == "UCI_AnalyseMode"));
+}
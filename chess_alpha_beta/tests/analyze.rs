@@ -0,0 +1,33 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{EngineLimit, NoControl},
+    Square,
+};
+
+#[test]
+fn analyze_mate_in_one() {
+    // Back rank mate with Re8.
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::depth(3));
+
+    let best = analysis.best_move.unwrap();
+    assert_eq!(best.from(), Square::from_name("e1").unwrap());
+    assert_eq!(best.to(), Square::from_name("e8").unwrap());
+    assert!(analysis.is_mate());
+    assert!(analysis.score > 0);
+    assert_eq!(analysis.pv.first(), Some(&best));
+    assert!(analysis.depth >= 1);
+    assert!(analysis.nodes > 0);
+}
+
+#[test]
+fn analyze_with_drops() {
+    // The moves from the pocket are ordered along with the moves on the board.
+    let board = Board::from_fen("2k5/8/8/8/8/8/8/4K3[Qq] w - - 0 1", EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::depth(3));
+    assert!(analysis.best_move.is_some());
+    assert_eq!(analysis.depth, 3);
+}
@@ -0,0 +1,55 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+};
+
+fn analyze(fen: &str, window: i32, depth: u32) -> (i32, u64, AlphaBeta<NoControl>) {
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    Engine::<NoControl>::set_option(
+        &mut engine,
+        "AspirationWindow".to_string(),
+        OptionValue::Spin(window),
+    );
+    let analysis = engine.analyze(&board, EngineLimit::depth(depth));
+    (analysis.score, engine.researches(), engine)
+}
+
+#[test]
+fn research_finds_same_score() {
+    // With a window of a single centipawn nearly every change in score between iterations
+    // needs a re-search, which must end at the score of a search with a full window.
+    let positions = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp2ppp/8/3pp3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3",
+        "rnbqkb1r/pp1p1ppp/2p5/4P3/2B5/8/PPP1NnPP/RNBQK2R w KQkq - 0 6",
+    ];
+    for fen in positions {
+        for depth in 1..=4 {
+            let (full, researches, _) = analyze(fen, 0, depth);
+            assert_eq!(researches, 0);
+            let (narrow, _, _) = analyze(fen, 1, depth);
+            assert_eq!(narrow, full, "{} depth {}", fen, depth);
+        }
+        let (_, researches, _) = analyze(fen, 1, 5);
+        assert!(researches > 0, "{}", fen);
+    }
+}
+
+#[test]
+fn researches_counted_per_search() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let (_, _, mut engine) = analyze(fen, 1, 5);
+    assert!(engine.researches() > 0);
+
+    // A window wider than any change in score never needs a re-search.
+    Engine::<NoControl>::set_option(
+        &mut engine,
+        "AspirationWindow".to_string(),
+        OptionValue::Spin(975),
+    );
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1", EndChain).unwrap();
+    engine.analyze(&board, EngineLimit::depth(4));
+    assert_eq!(engine.researches(), 0);
+}
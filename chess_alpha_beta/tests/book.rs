@@ -0,0 +1,126 @@
+mod common;
+
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, OptionValue},
+    gen::MoveGenerator,
+    polyglot::{Book, BookEntry},
+    Move,
+};
+use common::SearchLog;
+use std::path::{Path, PathBuf};
+
+/// Write a book with 1.e4 and 1.d4 from the start position and castling king side in a rook
+/// endgame, returns the path of the book.
+fn write_fixture(name: &str) -> PathBuf {
+    // The published Polyglot key of the start position.
+    let start = 0x463b96181691fc9c;
+    let castle = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1", EndChain)
+        .unwrap()
+        .polyglot_key();
+    let entry = |key, mov, weight| BookEntry {
+        key,
+        mov,
+        weight,
+        learn: 0,
+    };
+    // Moves are the destination and the origin square, 6 bits each.
+    let book = Book::new(vec![
+        entry(start, 27 | (11 << 6), 10),
+        entry(start, 28 | (12 << 6), 20),
+        // Castling is written as the king taking the rook, e1h1.
+        entry(castle, 7 | (4 << 6), 1),
+    ]);
+
+    let dir = std::env::temp_dir().join(format!(
+        "chess_alpha_beta_book_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let book_path = dir.join("book.bin");
+    std::fs::write(&book_path, book.to_bytes()).unwrap();
+    book_path
+}
+
+fn engine(book: &Path) -> AlphaBeta<SearchLog> {
+    let mut engine = AlphaBeta::new();
+    engine.set_option(
+        "BookFile".to_string(),
+        OptionValue::String(book.display().to_string()),
+    );
+    engine
+}
+
+#[test]
+fn book_round_trip() {
+    let gen = MoveGenerator::new();
+    let book_path = write_fixture("round_trip");
+    let book = Book::open(&book_path).unwrap();
+    assert_eq!(book.len(), 3);
+
+    let board = Board::start_position(EndChain);
+    let entries = book.entries(board.polyglot_key());
+    assert_eq!(entries.len(), 2);
+    let e4 = Move::from_uci_on_board("e2e4", &board, &gen).unwrap();
+    assert_eq!(book.probe(&board, &gen), Some(e4));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1", EndChain).unwrap();
+    let m = book.probe(&board, &gen).unwrap();
+    assert!(m.is_castle());
+    assert_eq!(m.to_uci(), "e1g1");
+
+    assert!(Book::from_bytes(&[0; 17]).is_err());
+}
+
+#[test]
+fn book_move_without_search() {
+    let mut engine = engine(&write_fixture("hit"));
+    let control = SearchLog::default();
+    let m = engine
+        .go(control.clone(), None, EngineLimit::depth(4))
+        .unwrap();
+    assert_eq!(m.to_uci(), "e2e4");
+    assert_eq!(control.0.lock().unwrap().0, 0);
+}
+
+#[test]
+fn search_outside_book() {
+    let mut engine = engine(&write_fixture("miss"));
+    engine.set_board(
+        Board::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            EndChain,
+        )
+        .unwrap(),
+    );
+    let control = SearchLog::default();
+    assert!(engine
+        .go(control.clone(), None, EngineLimit::depth(3))
+        .is_some());
+    assert!(control.0.lock().unwrap().0 > 0);
+}
+
+#[test]
+fn missing_book_falls_back_to_search() {
+    let mut engine = engine(&PathBuf::from("/nonexistent/book.bin"));
+    let control = SearchLog::default();
+    assert!(engine
+        .go(control.clone(), None, EngineLimit::depth(2))
+        .is_some());
+    let log = control.0.lock().unwrap();
+    assert!(log.0 > 0);
+    assert!(log.1.iter().any(|x| x.starts_with("failed to load book")));
+}
+
+#[test]
+fn analyse_mode_ignores_book() {
+    let mut engine = engine(&write_fixture("analyse"));
+    engine.set_option("UCI_AnalyseMode".to_string(), OptionValue::Check(true));
+    let control = SearchLog::default();
+    assert!(engine
+        .go(control.clone(), None, EngineLimit::depth(2))
+        .is_some());
+    assert!(control.0.lock().unwrap().0 > 0);
+}
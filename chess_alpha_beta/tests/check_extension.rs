@@ -0,0 +1,107 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    Square,
+};
+
+// Ladder mate: Ra7+ leaves three king moves, after each of which Rb8 mates. The mating move is
+// quiet, so at a nominal depth of two it is only seen when the check extends the search.
+const LADDER: &str = "8/4k3/1R6/8/8/8/8/R6K w - - 0 1";
+
+fn analyze(check_extension: bool) -> chess_alpha_beta::Analysis {
+    let board = Board::from_fen(LADDER, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option(
+        "CheckExtension".to_string(),
+        OptionValue::Check(check_extension),
+    );
+    engine.analyze(&board, EngineLimit::depth(2))
+}
+
+#[test]
+fn check_extension_finds_mate() {
+    let analysis = analyze(true);
+    assert!(analysis.is_mate(), "score {}", analysis.score);
+    assert!(analysis.score > 0);
+    let best = analysis.best_move.unwrap();
+    assert_eq!(best.from(), Square::from_name("a1").unwrap());
+    assert_eq!(best.to(), Square::from_name("a7").unwrap());
+}
+
+#[test]
+fn mate_missed_without_check_extension() {
+    let analysis = analyze(false);
+    assert!(!analysis.is_mate(), "score {}", analysis.score);
+}
+
+#[test]
+fn quiescence_detects_mate_while_in_check() {
+    // Back rank mate in one, found at depth one as the mated side can't stand pat in the
+    // quiescence search.
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1", EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("CheckExtension".to_string(), OptionValue::Check(false));
+    let analysis = engine.analyze(&board, EngineLimit::depth(1));
+    assert!(analysis.is_mate(), "score {}", analysis.score);
+    assert_eq!(
+        analysis.best_move.unwrap().to(),
+        Square::from_name("e8").unwrap()
+    );
+}
+
+#[test]
+fn single_reply_extension_finds_mate() {
+    // After the quiet Qa6 black can only play Kg8, and Qc8 mates. The mate is only seen at a
+    // nominal depth of two because the single reply extends the search.
+    let board = Board::from_fen("7k/8/6K1/8/8/4P3/4Q3/8 w - - 0 1", EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("CheckExtension".to_string(), OptionValue::Check(false));
+    let analysis = engine.analyze(&board, EngineLimit::depth(2));
+    assert!(analysis.is_mate(), "score {}", analysis.score);
+    assert!(analysis.score > 0);
+}
+
+#[test]
+fn sacrificing_check_is_extended() {
+    // Morphy's Opera game: Qb8+ gives up the queen, Nxb8 is forced and Rd8 mates. The check
+    // loses material, it is extended all the same.
+    let board = Board::from_fen(
+        "4kb1r/p2n1ppp/4q3/4p1B1/4P3/1Q6/PPP2PPP/2KR4 w k - 1 16",
+        EndChain,
+    )
+    .unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::depth(2));
+    assert!(analysis.is_mate(), "score {}", analysis.score);
+    assert_eq!(
+        analysis.best_move.unwrap().to(),
+        Square::from_name("b8").unwrap()
+    );
+}
+
+#[test]
+fn check_with_many_replies_is_extended() {
+    // Rb2+ leaves the king five squares, each answered by a quiet queen mate on a1, g1 or d4.
+    let board = Board::from_fen("1R6/Q7/6pp/3K4/8/8/3k4/8 w - - 0 1", EndChain).unwrap();
+    for check_extension in [true, false] {
+        let mut engine = AlphaBeta::<NoControl>::new();
+        engine.set_option(
+            "CheckExtension".to_string(),
+            OptionValue::Check(check_extension),
+        );
+        let analysis = engine.analyze(&board, EngineLimit::depth(2));
+        assert_eq!(
+            analysis.is_mate(),
+            check_extension,
+            "score {}",
+            analysis.score
+        );
+        if check_extension {
+            assert_eq!(
+                analysis.best_move.unwrap().to(),
+                Square::from_name("b2").unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use chess_core::engine::{EngineControl, Info};
+use std::sync::{Arc, Mutex};
+
+/// Control which collects the messages of the engine.
+#[derive(Clone, Default)]
+pub struct MessageLog(pub Arc<Mutex<Vec<String>>>);
+
+impl EngineControl for MessageLog {
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    fn info(&self, info: Info) {
+        if let Info::String(x) = info {
+            self.0.lock().unwrap().push(x);
+        }
+    }
+}
+
+/// Control which counts the reported search iterations and collects its messages.
+#[derive(Clone, Default)]
+pub struct SearchLog(pub Arc<Mutex<(usize, Vec<String>)>>);
+
+impl EngineControl for SearchLog {
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    fn info(&self, info: Info) {
+        let mut log = self.0.lock().unwrap();
+        match info {
+            Info::Stats { .. } => log.0 += 1,
+            Info::String(x) => log.1.push(x),
+            _ => {}
+        }
+    }
+}
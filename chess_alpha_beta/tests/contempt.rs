@@ -0,0 +1,53 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, NoControl, OptionValue},
+};
+
+fn draw_score(fen: &str, contempt: i32) -> i32 {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(contempt));
+    engine.set_board(Board::from_fen(fen, EndChain).unwrap());
+    engine.draw_score()
+}
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const PAWNS: &str = "4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1";
+// Two rooks and a bishop each, less than half of the phase of the start position.
+const HALF: &str = "r1b1k2r/pppp4/8/8/8/8/4PPPP/2R1KB1R b - - 0 1";
+
+#[test]
+fn contempt_scales_with_material() {
+    assert_eq!(draw_score(START, 100), -100);
+    assert_eq!(draw_score(PAWNS, 100), 0);
+    let half = draw_score(HALF, 100);
+    assert_eq!(half, -100 * 10 / 24);
+}
+
+#[test]
+fn without_contempt() {
+    assert_eq!(draw_score(START, 0), 0);
+    assert_eq!(draw_score(PAWNS, 0), 0);
+}
+
+#[test]
+fn negative_contempt() {
+    // Seeking draws is scaled down in the same way.
+    assert_eq!(draw_score(START, -100), 100);
+    assert_eq!(draw_score(PAWNS, -100), 0);
+}
+
+#[test]
+fn style_sets_contempt() {
+    let draw_score = |style: usize| {
+        let mut engine = AlphaBeta::<NoControl>::new();
+        engine.set_option("contempt".to_string(), OptionValue::Spin(50));
+        engine.set_option("Style".to_string(), OptionValue::Combo(style));
+        engine.set_board(Board::from_fen(START, EndChain).unwrap());
+        engine.draw_score()
+    };
+    // solid, normal and aggressive.
+    assert_eq!(draw_score(0), 0);
+    assert_eq!(draw_score(1), -100);
+    assert_eq!(draw_score(2), -250);
+}
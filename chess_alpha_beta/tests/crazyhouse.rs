@@ -0,0 +1,32 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain, HashChain},
+    engine::{EngineLimit, NoControl},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, MAX_MOVES},
+};
+
+// Both sides can drop every piece, which gives more moves than standard chess ever has in the
+// first and more than fit the buffers sized for it in the second.
+const POSITIONS: [&str; 2] = [
+    "r3k2r/8/8/8/8/8/8/R3K2R[QRBNPqrbnp] w KQkq - 0 1",
+    "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R[QRBNPqrbnp] w KQkq - 0 1",
+];
+
+#[test]
+fn search_with_full_pockets() {
+    let gen = MoveGenerator::new();
+    for fen in POSITIONS {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        let mut moves = InlineBuffer::<MAX_MOVES>::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+        assert!(moves.len() > 128, "{} has {} moves", fen, moves.len());
+
+        let mut engine = AlphaBeta::<NoControl>::new();
+        let analysis = engine.analyze(&board, EngineLimit::depth(2));
+        let best = analysis.best_move.unwrap();
+        assert!(moves.iter().any(|m| m == best), "{}", fen);
+
+        let pv = engine.pv_from_tt(&Board::from_fen(fen, HashChain::new()).unwrap());
+        assert_eq!(pv.first(), Some(&best), "{}", fen);
+    }
+}
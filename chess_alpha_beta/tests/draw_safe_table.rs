@@ -0,0 +1,105 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain, HashChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::MoveGenerator,
+    Move,
+};
+
+/// White is a bishop up, but the pawns are locked and the bishop can never attack them, so
+/// neither side can capture or move a pawn.
+const FORTRESS: &str = "7k/8/3p4/2pPp3/2P1P3/8/8/5B1K w - - {} 60";
+
+fn fortress(move_clock: u8) -> Board {
+    Board::from_fen(&FORTRESS.replace("{}", &move_clock.to_string()), EndChain).unwrap()
+}
+
+/// Returns the hash the position is stored under in the transposition table.
+fn table_hash(board: &Board) -> u64 {
+    let mut hashed = Board::start_position(HashChain::new());
+    hashed.copy_position(board);
+    hashed.chain.hash
+}
+
+#[test]
+fn fifty_move_draw_not_hidden_by_table() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+
+    // Far from the fifty-move rule white is simply winning on material.
+    let analysis = engine.analyze(&fortress(0), EngineLimit::depth(3));
+    assert!(analysis.score > 200, "score {}", analysis.score);
+
+    // The same positions are now drawn within the search, the scores stored in the table by
+    // the previous search must not be reused.
+    let analysis = engine.analyze(&fortress(98), EngineLimit::depth(3));
+    assert_eq!(analysis.score, 0);
+
+    // And the drawn scores must not leak back into a search far from the rule.
+    let analysis = engine.analyze(&fortress(0), EngineLimit::depth(3));
+    assert!(analysis.score > 200, "score {}", analysis.score);
+}
+
+#[test]
+fn repetition_draw_not_stored() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+
+    // Shuffle the bishop and kings back to the start position twice, every line of the search
+    // can now repeat it a third time.
+    let gen = MoveGenerator::new();
+    let mut board = fortress(0);
+    engine.set_board(board.clone());
+    for name in [
+        "f1e2", "h8g8", "e2f1", "g8h8", "f1e2", "h8g8", "e2f1", "g8h8",
+    ] {
+        let m = Move::from_uci_on_board(name, &board, &gen).unwrap();
+        board.make_move(m);
+        engine.make_move(m);
+    }
+    engine.go(NoControl, None, EngineLimit::depth(3));
+
+    // The lines of the search reach the root a third time, its score is only valid after these
+    // moves.
+    let hash = table_hash(&board);
+    let entry = engine.hash_table().get(hash).unwrap();
+    assert!(entry.path_dependent);
+
+    // Without the played moves the position is winning again.
+    let analysis = engine.analyze(&fortress(0), EngineLimit::depth(3));
+    assert!(analysis.score > 200, "score {}", analysis.score);
+}
+
+#[test]
+fn scores_without_draws_are_stored() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.analyze(&fortress(0), EngineLimit::depth(3));
+
+    // No line of the search is long enough to repeat a position.
+    let hash = table_hash(&fortress(0));
+    let entry = engine.hash_table().get(hash).unwrap();
+    assert!(!entry.path_dependent);
+    assert_eq!(entry.depth, 3);
+}
+
+#[test]
+fn table_cuts_research() {
+    let board = Board::from_fen(
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+        EndChain,
+    )
+    .unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let first = engine.analyze(&board, EngineLimit::depth(5));
+
+    // The children of the root were stored with their bounds by the first search, so the
+    // same search again is cut off by the table right below the root.
+    let second = engine.analyze(&board, EngineLimit::depth(5));
+    assert_eq!(first.best_move, second.best_move);
+    assert!(
+        second.nodes * 4 < first.nodes,
+        "{} nodes after {}",
+        second.nodes,
+        first.nodes
+    );
+}
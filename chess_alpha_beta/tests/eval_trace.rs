@@ -0,0 +1,77 @@
+mod common;
+
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, OptionValue},
+    gen::{gen_type, MoveGenerator},
+};
+use common::MessageLog;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn trace_terms_sum_to_total() {
+    let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+    let mut engine = AlphaBeta::<MessageLog>::new();
+    engine.set_option("EvalTrace".to_string(), OptionValue::Check(true));
+    engine.set_board(Board::from_fen(fen, EndChain).unwrap());
+
+    let log = MessageLog::default();
+    engine.go(log.clone(), None, EngineLimit::depth(1));
+
+    let terms: Vec<(String, i32)> = log
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|x| {
+            let mut parts = x.split_whitespace();
+            if parts.next() != Some("eval") {
+                return None;
+            }
+            let name = parts.next()?.to_string();
+            let value = parts.next()?.parse().ok()?;
+            Some((name, value))
+        })
+        .collect();
+
+    let total = terms
+        .iter()
+        .find(|x| x.0 == "total")
+        .expect("no total printed")
+        .1;
+    let sum: i32 = terms.iter().filter(|x| x.0 != "total").map(|x| x.1).sum();
+    assert!(terms.len() > 2);
+    assert_eq!(sum, total);
+}
+
+#[test]
+fn incremental_eval_matches_trace() {
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..10 {
+        let mut engine = AlphaBeta::<MessageLog>::new();
+        let mut board = Board::start_position(EndChain);
+        for ply in 0..150 {
+            assert_eq!(
+                engine.eval_incremental(),
+                engine.eval_trace().total(),
+                "{}",
+                board.to_fen()
+            );
+            // A short search makes and undoes moves on the board of the engine.
+            if ply % 30 == 0 {
+                engine.go(MessageLog::default(), None, EngineLimit::depth(2));
+                assert_eq!(engine.eval_incremental(), engine.eval_trace().total());
+            }
+            let mut moves = Vec::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if moves.is_empty() {
+                break;
+            }
+            let m = moves[rng.gen_range(0..moves.len())];
+            board.make_move(m);
+            engine.make_move(m);
+        }
+    }
+}
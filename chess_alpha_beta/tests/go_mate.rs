@@ -0,0 +1,114 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{EngineLimit, NoControl},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+};
+use std::time::{Duration, Instant};
+
+// Légal's mate: 1. Nf6+ gxf6 2. Bxf7#.
+const MATE_IN_2: &str = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+// Black to move mates in three, starting with 1... Qg6+.
+const MATE_IN_3: &str = "2r3k1/p4p2/3Rp2p/1p2P1pK/8/1P4P1/P3Q2P/1q6 b - - 0 1";
+// Rook ladder, the shortest mate takes four moves.
+const LADDER: &str = "8/8/8/4k3/8/8/R7/1R5K w - - 0 1";
+
+/// Search for a mate in the given amount of moves and check that the found move forces it,
+/// by searching again after every reply of the opponent.
+fn assert_forced_mate(engine: &mut AlphaBeta<NoControl>, board: &Board, moves: i32) {
+    let analysis = engine.analyze(board, EngineLimit::mate(moves as u32));
+    let mate_in = analysis.mate_in().unwrap();
+    assert!(
+        (1..=moves).contains(&mate_in),
+        "mate in {} in position {}",
+        mate_in,
+        board.to_fen()
+    );
+    assert!((analysis.depth as i32) < moves * 2);
+
+    let mut board = board.clone();
+    board.make_move(analysis.best_move.unwrap());
+
+    let gen = MoveGenerator::new();
+    let mut replies = InlineBuffer::<128>::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut replies);
+    if replies.len() == 0 {
+        assert!(gen.checked_king(&board, &gen.gen_info(&board)));
+        return;
+    }
+    assert!(mate_in > 1, "no mate after {}", board.to_fen());
+    for reply in replies.iter() {
+        let mut board = board.clone();
+        board.make_move(reply);
+        assert_forced_mate(engine, &board, mate_in - 1);
+    }
+}
+
+#[test]
+fn mate_in_2() {
+    let board = Board::from_fen(MATE_IN_2, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::mate(2));
+    assert_eq!(analysis.mate_in(), Some(2));
+    assert_eq!(analysis.best_move.unwrap().to_uci(), "d5f6");
+    assert_forced_mate(&mut engine, &board, 2);
+}
+
+#[test]
+fn mate_in_3() {
+    let board = Board::from_fen(MATE_IN_3, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::mate(3));
+    assert_eq!(analysis.mate_in(), Some(3));
+    assert_eq!(analysis.best_move.unwrap().to_uci(), "b1g6");
+    assert_forced_mate(&mut engine, &board, 3);
+}
+
+#[test]
+fn shorter_mate_within_bound() {
+    let board = Board::from_fen(MATE_IN_2, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::mate(4));
+    assert_eq!(analysis.mate_in(), Some(2));
+}
+
+#[test]
+fn no_mate_within_bound() {
+    // Without a mate the best move of a search up to the bound is returned.
+    let board = Board::from_fen(LADDER, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::mate(2));
+    assert_eq!(analysis.mate_in(), None);
+    assert!(analysis.best_move.is_some());
+    assert!(analysis.depth <= 3);
+    assert!(analysis.score > 0);
+}
+
+#[test]
+fn mate_search_respects_time_limit() {
+    // Kiwipete, without a mate within five moves.
+    let board = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let time = Duration::from_millis(100);
+    let limit = EngineLimit {
+        mate: Some(5),
+        time: Some(time),
+        ..EngineLimit::none()
+    };
+    let start = Instant::now();
+    let analysis = engine.analyze(&board, limit);
+    let elapsed = start.elapsed();
+
+    assert!(analysis.best_move.is_some());
+    assert_eq!(analysis.mate_in(), None);
+    // Leaves room for the first iteration after the clock ran out, slow in debug builds.
+    assert!(
+        elapsed < time + Duration::from_millis(400),
+        "search took {:?}",
+        elapsed
+    );
+}
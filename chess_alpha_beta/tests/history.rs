@@ -0,0 +1,44 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl},
+};
+
+#[test]
+fn history_kept_until_new_game() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    assert!(engine.history_table().is_empty());
+
+    engine.set_board(Board::start_position(EndChain));
+    engine.go(NoControl, None, EngineLimit::depth(5));
+    assert!(!engine.history_table().is_empty());
+
+    // Setting up the next position of the same game keeps the history.
+    engine.set_board(
+        Board::from_fen(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            EndChain,
+        )
+        .unwrap(),
+    );
+    assert!(!engine.history_table().is_empty());
+
+    engine.new_game();
+    assert!(engine.history_table().is_empty());
+}
+
+#[test]
+fn counter_moves_cause_cutoffs() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    assert!(engine.counter_move_table().is_empty());
+
+    engine.set_board(Board::start_position(EndChain));
+    engine.go(NoControl, None, EngineLimit::depth(6));
+    assert!(!engine.counter_move_table().is_empty());
+    let (quiet, counter) = engine.counter_move_stats();
+    assert!(counter > 0);
+    assert!(counter <= quiet);
+
+    engine.new_game();
+    assert!(engine.counter_move_table().is_empty());
+}
@@ -0,0 +1,46 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{EngineLimit, NoControl, Score},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+};
+
+// Rook ladder, mate in four: the king has to be driven up the board twice.
+const LADDER: &str = "8/4k3/8/8/8/8/R7/1R5K w - - 0 1";
+
+#[test]
+fn mate_distance_counts_down() {
+    let gen = MoveGenerator::new();
+    let mut board = Board::from_fen(LADDER, EndChain).unwrap();
+    // The same engine is used for both sides, so later searches find the mate scores stored in
+    // the transposition table by earlier ones.
+    let mut engine = AlphaBeta::<NoControl>::new();
+
+    let mut expected = vec![
+        Some(4),
+        Some(-3),
+        Some(3),
+        Some(-2),
+        Some(2),
+        Some(-1),
+        Some(1),
+    ];
+    expected.reverse();
+    while let Some(mate_in) = expected.pop() {
+        let analysis = engine.analyze(&board, EngineLimit::depth(6));
+        assert_eq!(analysis.mate_in(), mate_in, "position {}", board.to_fen());
+        board.make_move(analysis.best_move.unwrap());
+    }
+
+    let mut moves = InlineBuffer::<128>::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+    assert_eq!(moves.len(), 0);
+    assert!(gen.checked_king(&board, &gen.gen_info(&board)));
+}
+
+#[test]
+fn score_formats_as_uci() {
+    assert_eq!(Score::Mate(3).to_string(), "mate 3");
+    assert_eq!(Score::Mate(-2).to_string(), "mate -2");
+    assert_eq!(Score::Cp(-40).to_string(), "cp -40");
+}
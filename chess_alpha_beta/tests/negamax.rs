@@ -0,0 +1,223 @@
+use chess_alpha_beta::{
+    negamax::{self, SearchContext, INIT_BOUND},
+    test_util::Evaluator,
+    AlphaBeta, HashTable,
+};
+use chess_core::{
+    board::{Board, EndChain, HashChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::{gen_type, InlineBuffer, MoveGenerator, PositionInfo, MAX_MOVES},
+    material_eval, Move, Player,
+};
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1",
+    "8/8/4k3/3p4/3K4/4P3/8/8 b - - 0 1",
+];
+
+/// A context with the bare minimum, evaluating with the engine's evaluation from scratch.
+struct Plain {
+    board: Board<HashChain>,
+    scratch: Board<EndChain>,
+    gen: MoveGenerator,
+    table: HashTable,
+    evaluator: AlphaBeta<NoControl>,
+}
+
+impl Plain {
+    fn new(fen: &str) -> Self {
+        Plain {
+            board: Board::from_fen(fen, HashChain::new()).unwrap(),
+            scratch: Board::from_fen(fen, EndChain).unwrap(),
+            gen: MoveGenerator::new(),
+            table: HashTable::new(16 * 1024),
+            evaluator: AlphaBeta::new(),
+        }
+    }
+
+    fn root_moves(&self) -> InlineBuffer<MAX_MOVES> {
+        let mut moves = InlineBuffer::new();
+        self.gen
+            .gen_moves::<gen_type::All, _, _>(&self.board, &mut moves);
+        moves
+    }
+}
+
+impl SearchContext for Plain {
+    type Chain = EndChain;
+
+    fn board(&self) -> &Board<HashChain> {
+        &self.board
+    }
+
+    fn board_mut(&mut self) -> &mut Board<HashChain> {
+        &mut self.board
+    }
+
+    fn move_gen(&self) -> &MoveGenerator {
+        &self.gen
+    }
+
+    fn table(&self) -> &HashTable {
+        &self.table
+    }
+
+    fn table_mut(&mut self) -> &mut HashTable {
+        &mut self.table
+    }
+
+    fn eval(&mut self, _: &PositionInfo) -> i32 {
+        self.scratch.copy_position(&self.board);
+        self.evaluator.evaluate(&self.scratch)
+    }
+
+    fn should_stop(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn reproduces_engine() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+    engine.set_option("AspirationWindow".to_string(), OptionValue::Spin(0));
+
+    for fen in POSITIONS.iter() {
+        for depth in 1..=4 {
+            engine.new_game();
+            let board = Board::from_fen(fen, EndChain).unwrap();
+            let analysis = engine.analyze(&board, EngineLimit::depth(depth));
+
+            let mut ctx = Plain::new(fen);
+            let moves = ctx.root_moves();
+            let mut res = None;
+            for d in 1..=depth as u8 {
+                res = Some(negamax::search_root(
+                    &mut ctx,
+                    &moves,
+                    d,
+                    -INIT_BOUND,
+                    INIT_BOUND,
+                ));
+            }
+            let res = res.unwrap();
+
+            assert_eq!(
+                analysis.score, res.score,
+                "score of {} at depth {}",
+                fen, depth
+            );
+            assert_eq!(
+                analysis.best_move,
+                Some(res.best_move),
+                "best move of {} at depth {}",
+                fen,
+                depth
+            );
+        }
+    }
+}
+
+/// A context counting only material, which stops after a fixed amount of evaluations.
+struct Material {
+    board: Board<HashChain>,
+    gen: MoveGenerator,
+    table: HashTable,
+    evals: u64,
+    max_evals: u64,
+}
+
+impl SearchContext for Material {
+    type Chain = EndChain;
+
+    fn board(&self) -> &Board<HashChain> {
+        &self.board
+    }
+
+    fn board_mut(&mut self) -> &mut Board<HashChain> {
+        &mut self.board
+    }
+
+    fn move_gen(&self) -> &MoveGenerator {
+        &self.gen
+    }
+
+    fn table(&self) -> &HashTable {
+        &self.table
+    }
+
+    fn table_mut(&mut self) -> &mut HashTable {
+        &mut self.table
+    }
+
+    fn eval(&mut self, _: &PositionInfo) -> i32 {
+        self.evals += 1;
+        match self.board.state.player {
+            Player::White => material_eval(&self.board),
+            Player::Black => -material_eval(&self.board),
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.evals >= self.max_evals
+    }
+}
+
+#[test]
+fn alternate_context() {
+    // White can take the undefended queen.
+    let fen = "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1";
+    let mut ctx = Material {
+        board: Board::from_fen(fen, HashChain::new()).unwrap(),
+        gen: MoveGenerator::new(),
+        table: HashTable::new(1024),
+        evals: 0,
+        max_evals: u64::MAX,
+    };
+    let mut moves = InlineBuffer::new();
+    ctx.gen
+        .gen_moves::<gen_type::All, _, _>(&ctx.board, &mut moves);
+
+    let res = negamax::search_root(&mut ctx, &moves, 2, -INIT_BOUND, INIT_BOUND);
+    assert_eq!(res.best_move.to_uci(), "d2d5");
+    assert_eq!(res.score, 500);
+
+    // Stopping early leaves the search with the moves searched so far.
+    ctx.evals = 0;
+    ctx.max_evals = 1;
+    ctx.table = HashTable::new(1024);
+    let res = negamax::search_root(&mut ctx, &moves, 2, -INIT_BOUND, INIT_BOUND);
+    assert!(ctx.evals <= 2);
+    assert_eq!(res.best_move, Move::INVALID);
+}
+
+#[test]
+fn table_cuts_research() {
+    let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+    let mut ctx = Material {
+        board: Board::from_fen(fen, HashChain::new()).unwrap(),
+        gen: MoveGenerator::new(),
+        table: HashTable::new(16 * 1024),
+        evals: 0,
+        max_evals: u64::MAX,
+    };
+    let mut moves = InlineBuffer::new();
+    ctx.gen
+        .gen_moves::<gen_type::All, _, _>(&ctx.board, &mut moves);
+
+    let first = negamax::search_root(&mut ctx, &moves, 4, -INIT_BOUND, INIT_BOUND);
+    let first_evals = ctx.evals;
+
+    // The exact scores of the children of the root are now in the table.
+    ctx.evals = 0;
+    let second = negamax::search_root(&mut ctx, &moves, 4, -INIT_BOUND, INIT_BOUND);
+    assert_eq!(first.score, second.score);
+    assert!(
+        ctx.evals * 4 < first_evals,
+        "{} evaluations after {}",
+        ctx.evals,
+        first_evals
+    );
+}
@@ -0,0 +1,53 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineControl, EngineLimit, Info},
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Control which collects the node counts, times and tablebase hits of the progress reports.
+#[derive(Clone, Default)]
+struct ProgressLog(Arc<Mutex<Vec<(usize, Duration, usize)>>>);
+
+impl EngineControl for ProgressLog {
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    fn info(&self, info: Info) {
+        if let Info::Progress {
+            nodes,
+            time,
+            tbhits,
+            ..
+        } = info
+        {
+            self.0.lock().unwrap().push((nodes, time, tbhits));
+        }
+    }
+}
+
+#[test]
+fn reports_progress_during_search() {
+    let mut engine = AlphaBeta::<ProgressLog>::new();
+    engine.set_board(Board::start_position(EndChain));
+
+    let log = ProgressLog::default();
+    engine.go(
+        log.clone(),
+        None,
+        EngineLimit::time(Duration::from_millis(2500)),
+    );
+
+    let reports = log.0.lock().unwrap();
+    assert!(reports.len() >= 3, "only {} reports", reports.len());
+    for w in reports.windows(2) {
+        assert!(w[0].0 < w[1].0, "node count not increasing: {:?}", *reports);
+        assert!(w[1].1 - w[0].1 >= Duration::from_millis(500));
+    }
+    // Without a tablebase nothing is probed, transposition table hits don't count.
+    assert!(reports.iter().all(|x| x.2 == 0), "{:?}", *reports);
+}
@@ -0,0 +1,26 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain, HashChain},
+    engine::{Engine, EngineLimit, NoControl},
+};
+use std::time::Duration;
+
+const FEN: &str = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+
+#[test]
+fn tt_pv_starts_with_best_move() {
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_board(Board::from_fen(FEN, EndChain).unwrap());
+    let best = engine
+        .go(
+            NoControl,
+            None,
+            EngineLimit::time(Duration::from_millis(200)),
+        )
+        .unwrap();
+
+    let board = Board::from_fen(FEN, HashChain::new()).unwrap();
+    let pv = engine.pv_from_tt(&board);
+    assert!(!pv.is_empty());
+    assert_eq!(pv[0], best);
+}
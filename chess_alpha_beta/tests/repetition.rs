@@ -0,0 +1,78 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    Move, Square,
+};
+
+fn find_move(board: &Board, from: &str, to: &str) -> Move {
+    let gen = MoveGenerator::new();
+    let mut moves = InlineBuffer::<128>::new();
+    gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+    let from = Square::from_name(from).unwrap();
+    let to = Square::from_name(to).unwrap();
+    moves
+        .iter()
+        .find(|m| m.from() == from && m.to() == to)
+        .unwrap()
+}
+
+#[test]
+fn sees_threefold_with_played_moves() {
+    // Black is a queen down but can repeat the start position a third time with Nb8.
+    let mut board = Board::from_fen("1n4k1/5ppp/8/8/8/8/5PPP/3Q2K1 w - - 0 1", EndChain).unwrap();
+
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+    engine.set_board(board.clone());
+
+    let played = [
+        ("d1", "d2"),
+        ("b8", "c6"),
+        ("d2", "d1"),
+        ("c6", "b8"),
+        ("d1", "d2"),
+        ("b8", "c6"),
+        ("d2", "d1"),
+    ];
+    for (from, to) in played.iter() {
+        let m = find_move(&board, from, to);
+        board.make_move(m);
+        engine.make_move(m);
+    }
+
+    let m = engine.go(NoControl, None, EngineLimit::depth(3)).unwrap();
+    assert_eq!(m, find_move(&board, "c6", "b8"));
+}
+
+#[test]
+fn repetition_across_root_is_scored_as_draw() {
+    // White is a queen up. Black to move has seen this position once before and the start
+    // position twice, returning the knight repeats the start position a third time.
+    let start = Board::from_fen("1n4k1/5ppp/8/8/8/8/5PPP/3Q2K1 w - - 0 1", EndChain).unwrap();
+    let mut board = start.clone();
+
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+    engine.set_board(board.clone());
+    for (from, to) in [
+        ("d1", "d2"),
+        ("b8", "c6"),
+        ("d2", "d1"),
+        ("c6", "b8"),
+        ("d1", "d2"),
+        ("b8", "c6"),
+        ("d2", "d1"),
+    ] {
+        let m = find_move(&board, from, to);
+        board.make_move(m);
+        engine.make_move(m);
+    }
+    engine.go(NoControl, None, EngineLimit::depth(4));
+    assert_eq!(engine.score(), 0);
+
+    // Without the played moves black is simply lost.
+    let analysis = engine.analyze(&board, EngineLimit::depth(4));
+    assert!(analysis.score < -500, "score {}", analysis.score);
+}
@@ -0,0 +1,116 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineControl, EngineLimit, Info, OptionValue},
+    gen::MoveGenerator,
+    Move,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Control which keeps the principal variation of the last completed iteration.
+#[derive(Clone, Default)]
+struct PvLog(Arc<Mutex<Vec<Move>>>);
+
+impl EngineControl for PvLog {
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    fn info(&self, info: Info) {
+        if let Info::Stats { pv, .. } = info {
+            *self.0.lock().unwrap() = pv;
+        }
+    }
+}
+
+// The knight on c3 takes the undefended queen on d5.
+const FEN: &str = "r1b1kbnr/ppp2ppp/8/3qp3/3P4/2N5/PPP2PPP/R1BQKBNR w KQkq - 0 5";
+
+fn tree_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "chess_alpha_beta_tree_{}_{}.dot",
+        name,
+        std::process::id()
+    ))
+}
+
+fn engine(plies: i32, file: &Path) -> AlphaBeta<PvLog> {
+    let mut engine = AlphaBeta::new();
+    engine.set_board(Board::from_fen(FEN, EndChain).unwrap());
+    engine.set_option("DumpTree".to_string(), OptionValue::Spin(plies));
+    engine.set_option(
+        "DumpTreeFile".to_string(),
+        OptionValue::String(file.display().to_string()),
+    );
+    engine
+}
+
+#[test]
+fn tree_matches_search() {
+    let file = tree_file("match");
+    let mut engine = engine(3, &file);
+    let control = PvLog::default();
+    engine.go(control.clone(), None, EngineLimit::depth(4));
+
+    let tree = engine.search_tree().unwrap();
+    assert!(!tree.truncated());
+
+    // The root has a child for every legal move.
+    let board = Board::from_fen(FEN, EndChain).unwrap();
+    let mut moves = Vec::new();
+    MoveGenerator::new().gen_moves::<chess_core::gen::gen_type::All, _, _>(&board, &mut moves);
+    let mut children: Vec<_> = tree
+        .root()
+        .children
+        .iter()
+        .map(|x| tree.node(*x).mov.unwrap().to_uci())
+        .collect();
+    let mut legal: Vec<_> = moves.iter().map(|x| x.to_uci()).collect();
+    children.sort();
+    legal.sort();
+    assert_eq!(children, legal);
+
+    // The best line starts like the reported principal variation.
+    let pv = control.0.lock().unwrap().clone();
+    let best = tree.best_path();
+    assert_eq!(best[0].to_uci(), "c3d5");
+    assert!(best.len() <= 3);
+    assert_eq!(best[..], pv[..best.len()]);
+
+    // No recorded node is deeper than three plies from the root.
+    for c in tree.root().children.iter() {
+        for c in tree.node(*c).children.iter() {
+            for c in tree.node(*c).children.iter() {
+                assert!(tree.node(*c).children.is_empty());
+            }
+        }
+    }
+
+    let dot = std::fs::read_to_string(&file).unwrap();
+    assert!(dot.starts_with("digraph"));
+    assert!(dot.contains("[color=red]"));
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn tree_node_limit() {
+    let file = tree_file("limit");
+    let mut engine = engine(3, &file);
+    engine.set_option("DumpTreeNodes".to_string(), OptionValue::Spin(50));
+    engine.go(PvLog::default(), None, EngineLimit::depth(4));
+
+    let tree = engine.search_tree().unwrap();
+    assert!(tree.truncated());
+    assert_eq!(tree.len(), 50);
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn no_tree_by_default() {
+    let mut engine = AlphaBeta::<PvLog>::new();
+    engine.go(PvLog::default(), None, EngineLimit::depth(3));
+    assert!(engine.search_tree().is_none());
+}
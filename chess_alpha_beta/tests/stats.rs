@@ -0,0 +1,77 @@
+mod common;
+
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+};
+use common::MessageLog;
+use std::time::Duration;
+
+const POSITION: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn stats_lines(show_stats: bool) -> Vec<String> {
+    let mut engine = AlphaBeta::<MessageLog>::new();
+    engine.set_option("ShowStats".to_string(), OptionValue::Check(show_stats));
+    engine.set_board(Board::from_fen(POSITION, EndChain).unwrap());
+    let log = MessageLog::default();
+    engine.go(log.clone(), None, EngineLimit::depth(4));
+    let lines = log.0.lock().unwrap();
+    lines
+        .iter()
+        .filter(|x| x.starts_with("stats"))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn reported_when_enabled() {
+    let lines = stats_lines(true);
+    // A line per iteration and the summary.
+    assert_eq!(lines.len(), 5, "{:?}", lines);
+    assert!(lines[0].starts_with("stats depth 1 "));
+    assert!(lines[3].starts_with("stats depth 4 "));
+    assert!(lines[4].starts_with("stats total "));
+
+    assert!(stats_lines(false).is_empty());
+}
+
+#[test]
+fn returned_when_enabled() {
+    let board = Board::from_fen(POSITION, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let analysis = engine.analyze(&board, EngineLimit::depth(4));
+    assert!(analysis.stats.is_none());
+
+    engine.set_option("ShowStats".to_string(), OptionValue::Check(true));
+    // The table of the previous search would cut the first iterations short.
+    engine.new_game();
+    let analysis = engine.analyze(&board, EngineLimit::depth(5));
+    let stats = analysis.stats.unwrap();
+    assert_eq!(stats.iterations.len(), 5);
+    for (i, iteration) in stats.iterations.iter().enumerate() {
+        assert_eq!(iteration.depth as usize, i + 1);
+        assert!(iteration.completed);
+    }
+    assert!(stats.branching_factor(0).is_none());
+    assert!(stats.branching_factor(4).unwrap() > 1.0);
+    assert!(stats.table_probes >= stats.table_hits);
+    assert!(stats.table_hits > 0);
+}
+
+#[test]
+fn iteration_nodes_sum_to_total() {
+    let board = Board::from_fen(POSITION, EndChain).unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("ShowStats".to_string(), OptionValue::Check(true));
+    let analysis = engine.analyze(&board, EngineLimit::depth(5));
+    let stats = analysis.stats.unwrap();
+    let sum: u64 = stats.iterations.iter().map(|x| x.nodes).sum();
+    assert_eq!(sum, analysis.nodes);
+
+    // Also when the last iteration is cut short.
+    let analysis = engine.analyze(&board, EngineLimit::time(Duration::from_millis(200)));
+    let stats = analysis.stats.unwrap();
+    let sum: u64 = stats.iterations.iter().map(|x| x.nodes).sum();
+    assert_eq!(sum, analysis.nodes);
+}
@@ -0,0 +1,89 @@
+use chess_alpha_beta::{test_util::assert_eval_symmetric, AlphaBeta, EvalTrace};
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, NoControl},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    Piece,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn trace(engine: &mut AlphaBeta<NoControl>, board: &Board) -> EvalTrace {
+    engine.set_board(board.clone());
+    engine.eval_trace()
+}
+
+/// Assert that every term of the evaluation is negated when the position is mirrored.
+fn assert_symmetric(engine: &mut AlphaBeta<NoControl>, board: &Board) {
+    let a = trace(engine, board);
+    let b = trace(engine, &board.mirror());
+    for (name, a, b) in [
+        ("material", a.material, b.material),
+        ("piece_square", a.piece_square, b.piece_square),
+        ("king", a.king, b.king),
+    ] {
+        assert_eq!(
+            a,
+            -b,
+            "term `{}` is not symmetric for position {}",
+            name,
+            board.to_fen()
+        );
+    }
+}
+
+#[test]
+fn eval_is_symmetric() {
+    let gen = MoveGenerator::new();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let mut rng = StdRng::seed_from_u64(0xc0ffee);
+
+    for _ in 0..50 {
+        let mut board = Board::start_position(EndChain);
+        for _ in 0..rng.gen_range(0..80) {
+            let mut moves = InlineBuffer::<256>::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if moves.len() == 0 {
+                break;
+            }
+            board.make_move(moves.get(rng.gen_range(0..moves.len())));
+            assert_symmetric(&mut engine, &board);
+        }
+    }
+}
+
+#[test]
+fn eval_is_symmetric_in_test_positions() {
+    let positions = [
+        // The perft positions.
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "8/8/8/KPp4r/8/8/8/7k w - c6 0 1",
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+        // Middle games with the kings castled to different sides and unequal material.
+        "2kr3r/ppp2ppp/2n1bn2/2b1p3/4P3/2NP1N2/PPP1BPPP/R1BQ1RK1 b - - 3 9",
+        "r1bq1rk1/pp3ppp/2n1pn2/3p4/1bPP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8",
+        "r2q1rk1/1b2bppp/p2p1n2/1pn1p3/4P3/1BN2N1P/PP1B1PP1/R2QR1K1 w - - 2 14",
+        "3r1rk1/p4ppp/1qb1p3/2p5/4P3/1PN2Q2/P1P2PPP/3R1RK1 b - - 0 18",
+        "6k1/1p3pp1/p1n4p/3N4/2P5/1P3P2/P4KPP/8 w - - 0 30",
+    ];
+    let mut engine = AlphaBeta::<NoControl>::new();
+    for fen in positions {
+        assert_eval_symmetric(&mut engine, fen);
+    }
+}
+
+#[test]
+#[should_panic(expected = "not symmetric")]
+fn asymmetric_eval_is_caught() {
+    // Counting only the pieces of white favours white in every position.
+    let mut eval = |board: &Board| {
+        Piece::player_pieces(false)
+            .map(|p| board.pieces[p].count() as i32)
+            .sum()
+    };
+    assert_eval_symmetric(&mut eval, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::MoveGenerator,
+    tablebase::{TablebaseProber, Wdl},
+    Move,
+};
+use common::SearchLog;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Probed positions, by their placement and side to move, and the most pieces of a probed
+/// position.
+#[derive(Clone, Default)]
+struct Probes(Arc<Mutex<(Vec<String>, u32)>>);
+
+/// Tablebase which knows the outcome of a few positions.
+struct Stub {
+    results: HashMap<String, Wdl>,
+    root: Option<Move>,
+    probes: Probes,
+}
+
+/// The placement and side to move of the position, the part of the fen a tablebase cares
+/// about.
+fn key(board: &Board) -> String {
+    let fen = board.to_fen();
+    fen.split(' ').take(2).collect::<Vec<_>>().join(" ")
+}
+
+impl Stub {
+    fn record(&self, board: &Board) {
+        let fen = board.to_fen();
+        let count = fen
+            .split(' ')
+            .next()
+            .unwrap()
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .count() as u32;
+        let mut probes = self.probes.0.lock().unwrap();
+        probes.0.push(key(board));
+        probes.1 = probes.1.max(count);
+    }
+}
+
+impl TablebaseProber for Stub {
+    fn max_pieces(&self) -> u32 {
+        5
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        self.record(board);
+        self.results.get(&key(board)).copied()
+    }
+
+    fn probe_root(&self, board: &Board) -> Option<Move> {
+        self.record(board);
+        self.root
+    }
+}
+
+#[test]
+fn search_uses_tablebase_verdict() {
+    // Taking the queen leaves a lost knight ending, taking the knight a drawn queen ending.
+    let board = Board::from_fen("7k/7p/8/3q4/8/8/8/K2Rn3 w - - 0 1", EndChain).unwrap();
+    let results = [
+        ("7k/7p/8/3R4/8/8/8/K3n3 b", Wdl::Win),
+        ("7k/7p/8/3q4/8/8/8/K3R3 b", Wdl::Draw),
+    ];
+    let probes = Probes::default();
+    let stub = Stub {
+        results: results.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        root: None,
+        probes: probes.clone(),
+    };
+
+    let mut engine = AlphaBeta::<NoControl>::new();
+    engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+    engine.set_tablebase(Some(Box::new(stub)));
+    let analysis = engine.analyze(&board, EngineLimit::depth(3));
+
+    assert_eq!(analysis.best_move.unwrap().to_uci(), "d1e1");
+    assert_eq!(analysis.score, 0);
+    assert!(engine.tablebase_hits() > 0);
+
+    let probes = probes.0.lock().unwrap();
+    assert!(probes.0.iter().any(|x| x == results[0].0));
+    assert!(probes.0.iter().any(|x| x == results[1].0));
+    // The root has 6 pieces, only the positions after a capture can be probed.
+    assert!(probes.1 <= 5);
+}
+
+#[test]
+fn root_move_from_tablebase() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", EndChain).unwrap();
+    let m = Move::from_uci_on_board("a1a7", &board, &gen).unwrap();
+    let stub = Stub {
+        results: HashMap::new(),
+        root: Some(m),
+        probes: Probes::default(),
+    };
+
+    let mut engine = AlphaBeta::new();
+    engine.set_tablebase(Some(Box::new(stub)));
+    engine.set_board(board);
+    let control = SearchLog::default();
+    assert_eq!(
+        engine.go(control.clone(), None, EngineLimit::depth(4)),
+        Some(m)
+    );
+    // The move is played without a search.
+    assert_eq!(control.0.lock().unwrap().0, 0);
+}
+
+#[test]
+fn missing_tablebase_falls_back_to_search() {
+    let mut engine = AlphaBeta::new();
+    engine.set_option(
+        "SyzygyPath".to_string(),
+        OptionValue::String("/nonexistent/syzygy".to_string()),
+    );
+    let control = SearchLog::default();
+    assert!(engine
+        .go(control.clone(), None, EngineLimit::depth(2))
+        .is_some());
+    let log = control.0.lock().unwrap();
+    assert!(log.0 > 0);
+    assert!(log
+        .1
+        .iter()
+        .any(|x| x.starts_with("failed to load tablebases")));
+}
@@ -0,0 +1,125 @@
+use chess_alpha_beta::{AlphaBeta, TimeLimit};
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn clock_reads_are_batched() {
+    let limit = TimeLimit::limit(Duration::from_secs(60));
+    let nodes = 5_000_000;
+    let start = Instant::now();
+    for n in 0..nodes {
+        assert!(!limit.should_stop(n));
+    }
+    let elapsed = start.elapsed().as_millis() as u64;
+
+    let reads = limit.clock_reads();
+    assert!(reads > 0);
+    // About one read per millisecond, with some slack for the interval to grow to the node
+    // rate, but never more than one read every `MIN_INTERVAL` nodes.
+    assert!(reads <= nodes / TimeLimit::MIN_INTERVAL);
+    assert!(
+        reads <= 2 * elapsed + nodes / TimeLimit::MAX_INTERVAL + 32,
+        "{} clock reads in {}ms",
+        reads,
+        elapsed
+    );
+}
+
+// Debug builds are too slow between clock reads to keep a tight bound.
+#[test]
+#[cfg_attr(debug_assertions, ignore)]
+fn move_time_overshoot() {
+    let board = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let time = Duration::from_millis(200);
+    let start = Instant::now();
+    let analysis = engine.analyze(&board, EngineLimit::time(time));
+    let elapsed = start.elapsed();
+
+    assert!(analysis.best_move.is_some());
+    assert!(
+        elapsed < time + Duration::from_millis(5),
+        "search overshot by {:?}",
+        elapsed - time
+    );
+}
+
+#[test]
+fn move_overhead_is_subtracted() {
+    let ms = Duration::from_millis;
+    let mut engine = AlphaBeta::<NoControl>::new();
+    // 30ms by default.
+    assert_eq!(
+        engine.time_budget(None, &EngineLimit::time(ms(1000))),
+        Some(ms(970))
+    );
+
+    engine.set_option("Move Overhead".to_string(), OptionValue::Spin(100));
+    assert_eq!(
+        engine.time_budget(None, &EngineLimit::time(ms(1000))),
+        Some(ms(900))
+    );
+    // A thirtieth of the clock.
+    assert_eq!(
+        engine.time_budget(Some(ms(60_000)), &EngineLimit::none()),
+        Some(ms(1900))
+    );
+    assert_eq!(
+        engine.time_budget(Some(ms(60_000)), &EngineLimit::time(ms(500))),
+        Some(ms(400))
+    );
+    assert_eq!(engine.time_budget(None, &EngineLimit::depth(3)), None);
+
+    // The budget stops at zero.
+    assert_eq!(
+        engine.time_budget(None, &EngineLimit::time(ms(50))),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(
+        engine.time_budget(Some(ms(900)), &EngineLimit::none()),
+        Some(Duration::ZERO)
+    );
+    // Even then a move is returned.
+    assert!(engine
+        .go(NoControl, Some(ms(900)), EngineLimit::none())
+        .is_some());
+
+    engine.set_option("Move Overhead".to_string(), OptionValue::Spin(0));
+    assert_eq!(
+        engine.time_budget(None, &EngineLimit::time(ms(50))),
+        Some(ms(50))
+    );
+}
+
+#[test]
+fn first_iteration_completes_without_budget() {
+    let ms = Duration::from_millis;
+    // The first iteration searches well over the nodes before the first read of the clock.
+    let board = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+    let mut engine = AlphaBeta::<NoControl>::new();
+    let expected = engine.analyze(&board, EngineLimit::depth(1)).best_move;
+    assert!(expected.is_some());
+
+    engine.set_option("Move Overhead".to_string(), OptionValue::Spin(1000));
+    for (time_left, limit) in [
+        (Some(ms(900)), EngineLimit::none()),
+        (Some(ms(1000)), EngineLimit::none()),
+        (None, EngineLimit::time(ms(50))),
+    ] {
+        assert_eq!(engine.time_budget(time_left, &limit), Some(Duration::ZERO));
+        engine.new_game();
+        engine.set_board(board.clone());
+        assert_eq!(engine.go(NoControl, time_left, limit), expected);
+    }
+}
@@ -0,0 +1,59 @@
+use chess_alpha_beta::{to_wdl, AlphaBeta};
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineControl, EngineLimit, Info, OptionValue, Wdl},
+};
+use std::sync::{Arc, Mutex};
+
+/// Control which collects the reported chances of every completed iteration.
+#[derive(Clone, Default)]
+struct WdlLog(Arc<Mutex<Vec<Option<Wdl>>>>);
+
+impl EngineControl for WdlLog {
+    fn should_stop(&self) -> bool {
+        false
+    }
+
+    fn info(&self, info: Info) {
+        if let Info::Stats { wdl, .. } = info {
+            self.0.lock().unwrap().push(wdl);
+        }
+    }
+}
+
+#[test]
+fn model() {
+    let even = to_wdl(0);
+    assert_eq!(even.win, even.loss);
+    assert!(even.draw > 700, "{:?}", even);
+
+    let winning = to_wdl(1500);
+    assert!(winning.win > 990, "{:?}", winning);
+    let losing = to_wdl(-1500);
+    assert!(losing.loss > 990, "{:?}", losing);
+
+    for score in [-3000, -250, -1, 0, 1, 100, 250, 3000] {
+        let wdl = to_wdl(score);
+        assert_eq!(wdl.win + wdl.draw + wdl.loss, 1000, "{}: {:?}", score, wdl);
+        assert!(to_wdl(score + 50).win >= wdl.win, "{}", score);
+    }
+}
+
+#[test]
+fn reported_when_enabled() {
+    let fen = "4k3/8/8/8/8/8/8/QQ2K3 w - - 0 1";
+    let mut engine = AlphaBeta::<WdlLog>::new();
+    engine.set_board(Board::from_fen(fen, EndChain).unwrap());
+    let log = WdlLog::default();
+    engine.go(log.clone(), None, EngineLimit::depth(2));
+    assert!(log.0.lock().unwrap().iter().all(|x| x.is_none()));
+
+    engine.set_option("UCI_ShowWDL".to_string(), OptionValue::Check(true));
+    let log = WdlLog::default();
+    engine.go(log.clone(), None, EngineLimit::depth(2));
+    let reported = log.0.lock().unwrap();
+    assert!(!reported.is_empty());
+    for wdl in reported.iter() {
+        assert!(wdl.unwrap().win > 990, "{:?}", wdl);
+    }
+}
@@ -9,13 +9,15 @@ use hyper_tls::HttpsConnector;
 use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::{
-    events::{Challenge, Event, FromNdJson},
+    config::Config,
+    events::{Challenge, ChallengeList, ChallengeStatus, Event, FromNdJson},
     game::Game,
-    Client, AUTHORITY, SCHEME,
+    Client,
 };
 use hyper::{
+    body,
     header::{HeaderValue, AUTHORIZATION},
-    Body, Client as BaseClient, Request, Uri,
+    Body, Client as BaseClient, Request,
 };
 
 #[derive(Clone)]
@@ -48,10 +50,11 @@ pub struct Bot {
     client: Client,
     token: String,
     stream: FromNdJson,
+    pub config: Config,
 }
 
 impl Bot {
-    pub async fn new(token_path: impl AsRef<Path>) -> Result<Self> {
+    pub async fn new(token_path: impl AsRef<Path>, config: Config) -> Result<Self> {
         let https = HttpsConnector::new();
         let client = BaseClient::builder().build::<_, hyper::Body>(https);
 
@@ -66,11 +69,7 @@ impl Bot {
 
         let token = token.trim().to_string();
 
-        let uri = Uri::builder()
-            .scheme(SCHEME)
-            .authority(AUTHORITY)
-            .path_and_query("/api/stream/event")
-            .build()?;
+        let uri = crate::api_uri(&config.server, "/api/stream/event")?;
 
         let req = Request::get(uri)
             //.version(Version::HTTP_2)
@@ -95,6 +94,7 @@ impl Bot {
             client,
             token,
             stream,
+            config,
         })
     }
 
@@ -120,12 +120,7 @@ impl Bot {
         reason: DeclineReason,
     ) -> Result<()> {
         let path = format!("/api/challenge/{}/decline?reason={}", challenge.id, reason);
-        let uri = Uri::builder()
-            .scheme(SCHEME)
-            .authority(AUTHORITY)
-            .path_and_query(path)
-            .build()
-            .unwrap();
+        let uri = crate::api_uri(&self.config.server, &path)?;
         let resp = self
             .client
             .request(
@@ -149,12 +144,7 @@ impl Bot {
 
     pub async fn accept_challenge(&self, challenge: &Challenge) -> Result<()> {
         let path = format!("/api/challenge/{}/accept", challenge.id);
-        let uri = Uri::builder()
-            .scheme(SCHEME)
-            .authority(AUTHORITY)
-            .path_and_query(path)
-            .build()
-            .unwrap();
+        let uri = crate::api_uri(&self.config.server, &path)?;
 
         let resp = self
             .client
@@ -174,6 +164,37 @@ impl Bot {
         Ok(())
     }
 
+    /// Returns the incoming challenges which have not yet been accepted or declined.
+    pub async fn incoming_challenges(&self) -> Result<Vec<Challenge>> {
+        let uri = crate::api_uri(&self.config.server, "/api/challenge")?;
+
+        let resp = self
+            .client
+            .request(
+                Request::get(uri)
+                    .header(AUTHORIZATION, format!("Bearer {}", self.token))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        let status = resp.status();
+        let bytes = body::to_bytes(resp.into_body()).await?;
+        if !status.is_success() {
+            bail!(
+                "challenge list request did not succeeded, body:`{}`",
+                std::str::from_utf8(&bytes).unwrap_or("Invalid utf-8")
+            );
+        }
+        let list: ChallengeList =
+            serde_json::from_slice(&bytes).context("Failed to parse challenge list")?;
+
+        Ok(list
+            .incoming
+            .into_iter()
+            .filter(|x| matches!(x.status, ChallengeStatus::Created))
+            .collect())
+    }
+
     pub fn should_decline(&self, challenge: &Challenge) -> Option<DeclineReason> {
         if challenge.variant.key != "standard" {
             return Some(DeclineReason::Varient);
@@ -182,28 +203,33 @@ impl Bot {
         let limit = challenge.time_control.limit.unwrap_or(u64::MAX);
         let increment = challenge.time_control.increment.unwrap_or(0);
 
-        if limit < 60 {
+        if limit < self.config.min_time {
             return Some(DeclineReason::TooFast);
         }
-        if limit > 60 * 20 {
+        if limit > self.config.max_time {
             return Some(DeclineReason::TooSlow);
         }
-        if increment > 5 {
+        if increment > self.config.max_increment {
             return Some(DeclineReason::TooSlow);
         }
+        if challenge.rated && !self.config.rated {
+            return Some(DeclineReason::Casual);
+        }
+        if !challenge.rated && !self.config.casual {
+            return Some(DeclineReason::Rated);
+        }
         None
     }
 
-    pub fn spawn_game(
-        &self,
-        game_id: String,
-        engine: impl AsRef<Path>,
-    ) -> impl Future<Output = Result<Game>> {
-        let path = engine.as_ref().to_path_buf();
+    pub fn spawn_game(&self, game_id: String) -> impl Future<Output = Result<Game>> {
+        let path = self.config.engine.clone();
+        let hash = self.config.engine_hash();
+        let profile = self.config.profile.clone();
         let client = self.client.clone();
+        let server = self.config.server.clone();
         let token = self.token.clone();
         async move {
-            Game::new(client, &path, game_id, token)
+            Game::new(client, server, &path, hash, profile, game_id, token)
                 .await
                 .map_err(anyhow::Error::from)
         }
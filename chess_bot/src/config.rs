@@ -1,6 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chess_uci::profile::EngineProfile;
+use serde::Deserialize;
+
+/// Configuration of the bot, read from `bot.toml`.
 #[derive(Deserialize, Debug)]
+#[serde(default)]
 pub struct Config {
-    #[serde(default)]
-    secret: Option<String>,
-    engine: String,
+    /// Address of the lichess server the bot connects to.
+    pub server: String,
+    /// Path to the uci engine, a new process is started for every game.
+    pub engine: PathBuf,
+    /// Maximum amount of games played at the same time.
+    pub max_games: usize,
+    /// Minimum initial clock time in seconds.
+    pub min_time: u64,
+    /// Maximum initial clock time in seconds.
+    pub max_time: u64,
+    /// Maximum clock increment in seconds.
+    pub max_increment: u64,
+    /// Accept rated games.
+    pub rated: bool,
+    /// Accept casual games.
+    pub casual: bool,
+    /// Total hash table size in MB, divided between the engines.
+    pub hash: u32,
+    /// Options for the engine, read from the profile next to the engine binary.
+    #[serde(skip)]
+    pub profile: EngineProfile,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server: crate::LICHESS.to_string(),
+            engine: PathBuf::from("./engine"),
+            max_games: 1,
+            min_time: 60,
+            max_time: 60 * 20,
+            max_increment: 5,
+            rated: true,
+            casual: true,
+            hash: 64,
+            profile: EngineProfile::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config from the given file, returns the default config if the file does not
+    /// exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut config = if path.exists() {
+            let text = std::fs::read_to_string(path).context("Failed to read config file")?;
+            toml::from_str::<Config>(&text).context("Failed to parse config file")?
+        } else {
+            info!("no config at `{}`, using defaults", path.display());
+            Config::default()
+        };
+        config.profile = EngineProfile::load(&config.engine)?;
+        Ok(config)
+    }
+
+    /// The hash table size in MB for a single engine.
+    pub fn engine_hash(&self) -> u32 {
+        (self.hash / self.max_games.max(1) as u32).max(1)
+    }
 }
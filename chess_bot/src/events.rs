@@ -30,6 +30,14 @@ pub enum ChallengeColor {
     Black,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ChallengeList {
+    #[serde(rename = "in")]
+    pub incoming: Vec<Challenge>,
+    #[serde(rename = "out")]
+    pub outgoing: Vec<Challenge>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Challenge {
@@ -6,6 +6,7 @@ use chess_core::{
     gen::{gen_type, MoveGenerator},
     Board, Player, Square,
 };
+use chess_uci::profile::{advertised_option, EngineProfile};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     process::{Child, ChildStdin, ChildStdout, Command},
@@ -17,6 +18,8 @@ pub struct Engine {
     child: Child,
     stdin: ChildStdin,
     stdout: BufReader<ChildStdout>,
+    /// Names of the options the engine advertised during the uci handshake.
+    options: Vec<String>,
     running: bool,
 }
 
@@ -46,31 +49,72 @@ impl Engine {
             stdout: BufReader::new(stdout),
             board: Board::start_position(EndChain),
             move_gen: MoveGenerator::new(),
+            options: Vec::new(),
             running: false,
         })
     }
 
-    async fn start_uci(stdin: &mut ChildStdin, stdout: &mut BufReader<ChildStdout>) {
-        let mut stdout = BufReader::new(stdout);
-        stdin.write_all(b"uci\n").await.unwrap();
+    /// Read lines from the engine until one consisting of the given token.
+    async fn read_until(
+        stdout: &mut BufReader<ChildStdout>,
+        token: &str,
+        mut f: impl FnMut(&str),
+    ) -> Result<()> {
         let mut line_buffer = String::new();
         loop {
             line_buffer.clear();
-            stdout.read_line(&mut line_buffer).await.unwrap();
+            if stdout.read_line(&mut line_buffer).await? == 0 {
+                bail!("Engine closed its output before `{}`", token);
+            }
             trace!("uci: {}", line_buffer.trim());
-            if line_buffer.trim() == "uciok" {
-                return;
+            if line_buffer.trim() == token {
+                return Ok(());
             }
+            f(line_buffer.trim());
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        self.stdin.write_all(b"uci\n").await?;
+        let options = &mut self.options;
+        tokio::time::timeout(
+            Duration::from_secs(2),
+            Self::read_until(&mut self.stdout, "uciok", |line| {
+                options.extend(advertised_option(line))
+            }),
+        )
+        .await
+        .context("Uci startup timedout")?
+    }
+
+    /// Set the options of the profile, options the engine doesn't have are skipped.
+    pub async fn apply_profile(&mut self, profile: &EngineProfile) -> Result<()> {
+        let (commands, unknown) = profile.setoption_commands(&self.options);
+        for name in unknown {
+            warn!("Engine has no option `{}`, ignoring it", name);
+        }
+        for cmd in commands {
+            self.stdin.write_all(cmd.as_bytes()).await?;
+            self.stdin.write_all(b"\n").await?;
+        }
+        Ok(())
+    }
+
+    /// Wait until the engine has processed all commands.
+    pub async fn ready(&mut self) -> Result<()> {
+        self.stdin.write_all(b"isready\n").await?;
         tokio::time::timeout(
             Duration::from_secs(2),
-            Self::start_uci(&mut self.stdin, &mut self.stdout),
+            Self::read_until(&mut self.stdout, "readyok", |_| {}),
         )
         .await
-        .context("Uci startup timedout")
+        .context("Engine did not respond to `isready`")?
+    }
+
+    pub async fn set_option(&mut self, name: &str, value: &str) -> Result<()> {
+        let cmd = format!("setoption name {} value {}\n", name, value);
+        self.stdin.write_all(cmd.as_bytes()).await?;
+        Ok(())
     }
 
     pub async fn set_position(&mut self, fen: &str, moves: &str) -> Result<()> {
@@ -1,17 +1,18 @@
 use std::{path::Path, time::Duration};
 
 use anyhow::{bail, Context, Result};
+use chess_uci::profile::EngineProfile;
 
 use futures_util::{future::Either, pin_mut};
 use hyper::{
     body,
     header::{AUTHORIZATION, CONTENT_TYPE},
-    Body, Request, Uri,
+    Body, Request,
 };
 
 use crate::{
     events::{ChatRoom, FromNdJson, GameEvent, GameStatus, Player},
-    Client, AUTHORITY, SCHEME,
+    Client,
 };
 
 use self::engine::Engine;
@@ -20,22 +21,26 @@ mod engine;
 
 pub struct Game {
     client: Client,
+    /// Address of the lichess server.
+    server: String,
     game_id: String,
     token: String,
     stream: Option<FromNdJson>,
     color: Player,
     engine: engine::Engine,
+    hash: u32,
+    profile: EngineProfile,
 }
 
 impl Game {
-    async fn start_game_stream(client: &Client, game_id: &str, token: &str) -> Result<FromNdJson> {
+    async fn start_game_stream(
+        client: &Client,
+        server: &str,
+        game_id: &str,
+        token: &str,
+    ) -> Result<FromNdJson> {
         let path = format!("/api/bot/game/stream/{}", game_id);
-        let uri = Uri::builder()
-            .scheme(SCHEME)
-            .authority(AUTHORITY)
-            .path_and_query(path)
-            .build()
-            .context("Failed to build uri")?;
+        let uri = crate::api_uri(server, &path)?;
 
         let req = Request::get(uri)
             .header(AUTHORIZATION, format!("Bearer {}", token))
@@ -67,7 +72,15 @@ impl Game {
         Ok(FromNdJson::new(resp.into_body()))
     }
 
-    pub async fn new(client: Client, path: &Path, game_id: String, token: String) -> Result<Self> {
+    pub async fn new(
+        client: Client,
+        server: String,
+        path: &Path,
+        hash: u32,
+        profile: EngineProfile,
+        game_id: String,
+        token: String,
+    ) -> Result<Self> {
         let engine = match Engine::new(path) {
             Ok(x) => x,
             Err(e) => {
@@ -77,6 +90,7 @@ impl Game {
                         "Sorry I will have to abort.",
                     ],
                     &client,
+                    &server,
                     &game_id,
                     &token,
                     true,
@@ -88,30 +102,50 @@ impl Game {
 
         Ok(Game {
             client,
+            server,
             game_id,
             token,
             stream: None,
             color: Player::White,
             engine,
+            hash,
+            profile,
         })
     }
 
+    /// Start the engine, setting the options of its profile after the hash size so the
+    /// profile can override it.
     pub async fn start_engine(&mut self) -> Result<()> {
-        self.engine.start().await
+        self.engine.start().await?;
+        self.engine
+            .set_option("Hash", &self.hash.to_string())
+            .await?;
+        self.engine.apply_profile(&self.profile).await?;
+        self.engine.ready().await
     }
 
     pub async fn connect_stream(&mut self) -> Result<()> {
-        self.stream =
-            Some(Self::start_game_stream(&self.client, &self.game_id, &self.token).await?);
+        self.stream = Some(
+            Self::start_game_stream(&self.client, &self.server, &self.game_id, &self.token).await?,
+        );
         Ok(())
     }
 
     pub async fn send_message(&self, message: &str, room: ChatRoom) {
-        Game::send_message_game(&self.client, &self.game_id, &self.token, message, room).await;
+        Game::send_message_game(
+            &self.client,
+            &self.server,
+            &self.game_id,
+            &self.token,
+            message,
+            room,
+        )
+        .await;
     }
 
     pub async fn send_message_game(
         client: &Client,
+        server: &str,
         game_id: &str,
         token: &str,
         message: &str,
@@ -126,11 +160,7 @@ impl Game {
 
             let stream = futures_util::stream::once(futures_util::future::ready(body));
 
-            let url = Uri::builder()
-                .scheme(SCHEME)
-                .authority(AUTHORITY)
-                .path_and_query(path)
-                .build()?;
+            let url = crate::api_uri(server, &path)?;
 
             let req = Request::post(url)
                 .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
@@ -153,19 +183,29 @@ impl Game {
     }
 
     pub async fn giveup(&self, texts: &[&str], abort: bool) {
-        Self::giveup_game(texts, &self.client, &self.game_id, &self.token, abort).await
+        Self::giveup_game(
+            texts,
+            &self.client,
+            &self.server,
+            &self.game_id,
+            &self.token,
+            abort,
+        )
+        .await
     }
 
     pub async fn giveup_game(
         texts: &[&str],
         client: &Client,
+        server: &str,
         game_id: &str,
         token: &str,
         abort: bool,
     ) {
         async {
             for text in texts.iter() {
-                Game::send_message_game(&client, &game_id, &token, text, ChatRoom::Player).await;
+                Game::send_message_game(client, server, game_id, token, text, ChatRoom::Player)
+                    .await;
             }
 
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -175,11 +215,7 @@ impl Game {
             } else {
                 format!("/api/bot/game/{}/resign", game_id)
             };
-            let url = Uri::builder()
-                .scheme(SCHEME)
-                .authority(AUTHORITY)
-                .path_and_query(path)
-                .build()?;
+            let url = crate::api_uri(server, &path)?;
 
             let req = Request::post(url)
                 .header(AUTHORIZATION, format!("Bearer {}", token))
@@ -198,12 +234,7 @@ impl Game {
 
     pub async fn make_move(&self, m: &str) -> Result<()> {
         let path = format!("/api/bot/game/{}/move/{}", self.game_id, m);
-        let uri = Uri::builder()
-            .scheme(SCHEME)
-            .authority(AUTHORITY)
-            .path_and_query(path)
-            .build()
-            .context("Failed to build uri")?;
+        let uri = crate::api_uri(&self.server, &path)?;
 
         let req = Request::post(uri)
             .header(AUTHORIZATION, format!("Bearer {}", &self.token))
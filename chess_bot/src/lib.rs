@@ -0,0 +1,173 @@
+#![allow(dead_code)]
+
+use std::{collections::HashSet, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use hyper::{body, client::HttpConnector, Body, Client as BaseClient, Response, Uri};
+use hyper_tls::HttpsConnector;
+
+use crate::{
+    bot::{Bot, DeclineReason},
+    events::{Challenge, Event},
+};
+
+#[macro_use]
+extern crate tracing;
+
+pub mod bot;
+pub mod config;
+pub mod events;
+pub mod game;
+
+type Client = BaseClient<HttpsConnector<HttpConnector>, Body>;
+
+/// Address of the lichess server.
+pub const LICHESS: &str = "https://lichess.org";
+
+/// Returns the uri of the path on the server with the given base address.
+fn api_uri(server: &str, path: &str) -> Result<Uri> {
+    format!("{}{}", server.trim_end_matches('/'), path)
+        .parse()
+        .context("Failed to build uri")
+}
+
+async fn handle_failed_response(resp: Response<Body>) -> Result<()> {
+    if !resp.status().is_success() {
+        match body::to_bytes(resp.into_body()).await {
+            Ok(x) => {
+                bail!(
+                    "request did not succeeded, body:`{}`",
+                    std::str::from_utf8(&x).unwrap_or("Invalid utf-8")
+                );
+            }
+            Err(e) => {
+                bail!("request did not succeeded, could not read body: {}", e)
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Accept or decline a challenge.
+///
+/// `accepted` contains the challenges which were accepted but for which the game has not yet
+/// started, `active` is the amount of games currently being played.
+pub async fn handle_challenge(
+    bot: &Bot,
+    challenge: &Challenge,
+    accepted: &mut HashSet<String>,
+    active: usize,
+) -> Result<()> {
+    if accepted.contains(&challenge.id) {
+        return Ok(());
+    }
+    if accepted.len() + active >= bot.config.max_games {
+        bot.decline_challenge(challenge, DeclineReason::Later)
+            .await?;
+    } else if let Some(reason) = bot.should_decline(challenge) {
+        bot.decline_challenge(challenge, reason).await?;
+    } else {
+        bot.accept_challenge(challenge).await?;
+        accepted.insert(challenge.id.clone());
+    }
+    Ok(())
+}
+
+/// Handle the events of the bot until the event stream ends.
+pub async fn run(bot: &mut Bot) -> Result<()> {
+    let mut accepted = HashSet::new();
+    let mut active_games = HashSet::new();
+
+    while let Some(e) = bot.next_event().await {
+        match e {
+            Event::Challenge { challenge } => {
+                info!("recieved challenge from `{}`", challenge.challenger.name);
+                handle_challenge(bot, &challenge, &mut accepted, active_games.len()).await?;
+            }
+            Event::GameStart { game } => {
+                // The game is registered before the engine is spawned so that a challenge or
+                // game start arriving in the meantime sees the correct amount of games.
+                accepted.remove(&game.id);
+                if !active_games.insert(game.id.clone()) {
+                    warn!("got a second start event for game `{}`", game.id);
+                    continue;
+                }
+
+                let game_future = bot.spawn_game(game.id.clone());
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    let mut game = match game_future.await {
+                        Ok(game) => game,
+                        Err(e) => {
+                            error!("could not create game: {:?}", e);
+                            return;
+                        }
+                    };
+
+                    match game.start_engine().await {
+                        Ok(()) => {}
+                        Err(e) => {
+                            error!("could not start engine : {:?}", e);
+                            game.giveup(
+                                &[
+                                    "Sorry, I seem to be unable to start my brain",
+                                    "I will have to abort",
+                                ],
+                                true,
+                            )
+                            .await;
+                            game.quit().await;
+                            return;
+                        }
+                    }
+
+                    match game.connect_stream().await {
+                        Ok(()) => {}
+                        Err(e) => {
+                            error!("could not start engine : {:?}", e);
+                            game.giveup(
+                                &[
+                                    "Sorry, I seem to have problems connecting to the game",
+                                    "I will have to abort",
+                                ],
+                                true,
+                            )
+                            .await;
+                            game.quit().await;
+                            return;
+                        }
+                    }
+
+                    game.start().await;
+                });
+            }
+            Event::GameFinish { game } => {
+                if !active_games.remove(&game.id) {
+                    error!("Got a finish event for a game which the bot was not playing");
+                } else {
+                    info!("game finished!");
+                }
+
+                // A slot is free again, look at challenges which are still waiting.
+                match bot.incoming_challenges().await {
+                    Ok(challenges) => {
+                        for challenge in challenges.iter() {
+                            handle_challenge(bot, challenge, &mut accepted, active_games.len())
+                                .await?;
+                        }
+                    }
+                    Err(e) => error!("could not retrieve challenges: {:?}", e),
+                }
+            }
+            Event::ChallengeCanceled { challenge } => {
+                accepted.remove(&challenge.id);
+            }
+            _ => {}
+        }
+    }
+
+    error!("event stream ended!");
+
+    Ok(())
+}
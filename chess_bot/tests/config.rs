@@ -0,0 +1,50 @@
+use chess_bot::{config::Config, LICHESS};
+
+fn config(hash: u32, max_games: usize) -> Config {
+    Config {
+        hash,
+        max_games,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn hash_is_divided_between_engines() {
+    assert_eq!(config(64, 1).engine_hash(), 64);
+    assert_eq!(config(64, 2).engine_hash(), 32);
+    // Rounded down, the engines never use more than the total together.
+    assert_eq!(config(64, 3).engine_hash(), 21);
+}
+
+#[test]
+fn hash_is_at_least_one_mb() {
+    assert_eq!(config(2, 4).engine_hash(), 1);
+    assert_eq!(config(0, 1).engine_hash(), 1);
+    // A config without games still gets a usable engine.
+    assert_eq!(config(64, 0).engine_hash(), 64);
+}
+
+#[test]
+fn load_config_file() {
+    let dir = std::env::temp_dir().join(format!("chess_bot_config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("bot.toml");
+    std::fs::write(
+        &path,
+        "server = \"http://127.0.0.1:8080\"\nmax_games = 3\nhash = 96\nrated = false\n",
+    )
+    .unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.server, "http://127.0.0.1:8080");
+    assert_eq!(config.max_games, 3);
+    assert_eq!(config.engine_hash(), 32);
+    assert!(!config.rated);
+    // Fields which are not in the file keep their default.
+    assert!(config.casual);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.server, LICHESS);
+    assert_eq!(config.max_games, 1);
+}
@@ -0,0 +1,381 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chess_bot::{bot::Bot, config::Config};
+use hyper::{
+    body::{self, Bytes},
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use tokio::sync::mpsc;
+
+const TOKEN: &str = "test-token";
+
+/// Engine which answers every search with the same move.
+const ENGINE: &str = "#!/bin/sh
+while read line; do
+    case \"$line\" in
+        uci) echo \"id name Mock\"; echo \"uciok\";;
+        isready) echo \"readyok\";;
+        go*) echo \"bestmove e2e4\";;
+        quit) exit 0;;
+    esac
+done
+";
+
+/// Lichess server which serves streams fed by the test and logs every other request.
+#[derive(Clone)]
+struct Mock {
+    /// Requests as `METHOD path`, with the body of chat messages appended.
+    log: mpsc::UnboundedSender<String>,
+    events: Arc<Mutex<Option<Body>>>,
+    games: Arc<Mutex<HashMap<String, Body>>>,
+    /// Response to the request for the incoming challenges.
+    challenges: Arc<Mutex<String>>,
+}
+
+impl Mock {
+    async fn handle(self, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let authorized = req
+            .headers()
+            .get("authorization")
+            .is_some_and(|x| x == &format!("Bearer {}", TOKEN));
+        if !authorized {
+            return Ok(status(StatusCode::UNAUTHORIZED));
+        }
+
+        let method = req.method().clone();
+        let path = req.uri().path_and_query().unwrap().to_string();
+        let content = body::to_bytes(req.into_body()).await.unwrap();
+        let mut line = format!("{} {}", method, path);
+        if path.ends_with("/chat") {
+            line.push(' ');
+            line.push_str(std::str::from_utf8(&content).unwrap());
+        }
+        self.log.send(line).ok();
+
+        let resp = if path == "/api/stream/event" {
+            self.events.lock().unwrap().take().map(Response::new)
+        } else if let Some(id) = path.strip_prefix("/api/bot/game/stream/") {
+            self.games.lock().unwrap().remove(id).map(Response::new)
+        } else if path == "/api/challenge" {
+            Some(Response::new(Body::from(
+                self.challenges.lock().unwrap().clone(),
+            )))
+        } else {
+            Some(Response::new(Body::from("{\"ok\":true}")))
+        };
+        Ok(resp.unwrap_or_else(|| status(StatusCode::NOT_FOUND)))
+    }
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = code;
+    resp
+}
+
+/// Sends lines of newline delimited json over a stream of the mock server.
+struct Stream(body::Sender);
+
+impl Stream {
+    async fn send(&mut self, json: String) {
+        self.0
+            .send_data(Bytes::from(json + "\n"))
+            .await
+            .expect("stream closed by the bot");
+    }
+}
+
+struct Harness {
+    mock: Mock,
+    log: mpsc::UnboundedReceiver<String>,
+    /// Every request received so far.
+    requests: Vec<String>,
+    addr: SocketAddr,
+    dir: PathBuf,
+}
+
+impl Harness {
+    async fn new(name: &str) -> (Self, Stream) {
+        let dir = std::env::temp_dir().join(format!("chess_bot_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (events, body) = Body::channel();
+        let (log, log_recv) = mpsc::unbounded_channel();
+        let mock = Mock {
+            log,
+            events: Arc::new(Mutex::new(Some(body))),
+            games: Arc::default(),
+            challenges: Arc::new(Mutex::new(challenge_list(&[]))),
+        };
+
+        let service = mock.clone();
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(move |_| {
+            let mock = service.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| mock.clone().handle(req))) }
+        }));
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        let harness = Harness {
+            mock,
+            log: log_recv,
+            requests: Vec::new(),
+            addr,
+            dir,
+        };
+        (harness, Stream(events))
+    }
+
+    /// Create a bot connected to the mock server.
+    async fn bot(&self, max_games: usize) -> Bot {
+        let token = self.dir.join("token.txt");
+        std::fs::write(&token, TOKEN).unwrap();
+
+        let engine = self.dir.join("engine");
+        std::fs::write(&engine, ENGINE).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&engine, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let config = Config {
+            server: format!("http://{}", self.addr),
+            engine,
+            max_games,
+            ..Config::default()
+        };
+        Bot::new(&token, config).await.unwrap()
+    }
+
+    /// Prepare the stream of the game, served once the bot asks for it.
+    fn game(&self, id: &str) -> Stream {
+        let (sender, body) = Body::channel();
+        self.mock.games.lock().unwrap().insert(id.to_string(), body);
+        Stream(sender)
+    }
+
+    /// Wait for a request starting with the given prefix.
+    async fn expect(&mut self, prefix: &str) {
+        let start = self.requests.len();
+        loop {
+            match tokio::time::timeout(Duration::from_secs(10), self.log.recv()).await {
+                Ok(Some(line)) => {
+                    let found = line.starts_with(prefix);
+                    self.requests.push(line);
+                    if found {
+                        return;
+                    }
+                }
+                _ => panic!(
+                    "no request starting with `{}`, got: {:?}",
+                    prefix,
+                    &self.requests[start..]
+                ),
+            }
+        }
+    }
+
+    /// Returns how many of all requests received so far start with the prefix.
+    fn count(&mut self, prefix: &str) -> usize {
+        while let Ok(line) = self.log.try_recv() {
+            self.requests.push(line);
+        }
+        self.requests
+            .iter()
+            .filter(|x| x.starts_with(prefix))
+            .count()
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn user(id: &str) -> String {
+    format!(
+        "{{\"id\":\"{}\",\"name\":\"{}\",\"title\":null,\"rating\":1500}}",
+        id, id
+    )
+}
+
+fn challenge(id: &str) -> String {
+    format!(
+        "{{\"id\":\"{id}\",\"url\":\"https://lichess.org/{id}\",\"status\":\"created\",\
+         \"challenger\":{},\"destUser\":{},\
+         \"variant\":{{\"key\":\"standard\",\"name\":\"Standard\",\"short\":\"Std\"}},\
+         \"rated\":false,\"timeControl\":{{\"type\":\"clock\",\"limit\":300,\"increment\":0}},\
+         \"color\":\"random\",\"speed\":\"blitz\"}}",
+        user("human"),
+        user("nnybot"),
+        id = id
+    )
+}
+
+fn challenge_event(id: &str) -> String {
+    format!("{{\"type\":\"challenge\",\"challenge\":{}}}", challenge(id))
+}
+
+fn challenge_list(ids: &[&str]) -> String {
+    let incoming: Vec<String> = ids.iter().map(|x| challenge(x)).collect();
+    format!("{{\"in\":[{}],\"out\":[]}}", incoming.join(","))
+}
+
+/// Event about the game of the challenge with the same id, as lichess uses the id of the
+/// challenge for its game.
+fn game_event(kind: &str, id: &str) -> String {
+    format!(
+        "{{\"type\":\"{}\",\"game\":{{\"id\":\"{}\",\"compat\":{{\"bot\":true,\"board\":true}}}}}}",
+        kind, id
+    )
+}
+
+fn game_state(moves: &str, status: &str) -> String {
+    format!(
+        "{{\"type\":\"gameState\",\"moves\":\"{}\",\"wtime\":300000,\"btime\":300000,\
+         \"winc\":0,\"binc\":0,\"status\":\"{}\"}}",
+        moves, status
+    )
+}
+
+/// The start of a game in which the bot plays white.
+fn game_full(id: &str) -> String {
+    format!(
+        "{{\"type\":\"gameFull\",\"id\":\"{}\",\"rated\":false,\
+         \"variant\":{{\"key\":\"standard\",\"name\":\"Standard\",\"short\":\"Std\"}},\
+         \"clock\":{{\"initial\":300000,\"increment\":0}},\"speed\":\"blitz\",\
+         \"white\":{},\"black\":{},\"createdAt\":0,\"initialFen\":\"startpos\",\"state\":{}}}",
+        id,
+        user("nnybot"),
+        user("human"),
+        game_state("", "started")
+    )
+}
+
+/// Play the game until the bot made its first move, after which the opponent resigns.
+async fn play(harness: &mut Harness, id: &str, stream: &mut Stream) {
+    stream.send(game_full(id)).await;
+    harness
+        .expect(&format!("POST /api/bot/game/{}/move/e2e4", id))
+        .await;
+    stream.send(game_state("e2e4", "resign")).await;
+    harness
+        .expect(&format!(
+            "POST /api/bot/game/{}/chat room=player&text=Good%20game",
+            id
+        ))
+        .await;
+}
+
+#[tokio::test]
+async fn two_overlapping_games() {
+    let (mut harness, mut events) = Harness::new("overlapping").await;
+    let mut bot = harness.bot(2).await;
+    let run = tokio::spawn(async move { chess_bot::run(&mut bot).await });
+    harness.expect("GET /api/stream/event").await;
+
+    events.send(challenge_event("g1")).await;
+    harness.expect("POST /api/challenge/g1/accept").await;
+    events.send(challenge_event("g2")).await;
+    harness.expect("POST /api/challenge/g2/accept").await;
+
+    let mut first = harness.game("g1");
+    let mut second = harness.game("g2");
+    events.send(game_event("gameStart", "g1")).await;
+    events.send(game_event("gameStart", "g2")).await;
+    // Both games are connected before either of them is played.
+    for _ in 0..2 {
+        harness.expect("GET /api/bot/game/stream/").await;
+    }
+
+    play(&mut harness, "g1", &mut first).await;
+    play(&mut harness, "g2", &mut second).await;
+
+    events.send(game_event("gameFinish", "g1")).await;
+    harness.expect("GET /api/challenge").await;
+    events.send(game_event("gameFinish", "g2")).await;
+    harness.expect("GET /api/challenge").await;
+
+    drop(events);
+    run.await.unwrap().unwrap();
+    assert_eq!(harness.count("POST /api/bot/game/g1/move/"), 1);
+    assert_eq!(harness.count("POST /api/bot/game/g2/move/"), 1);
+    let gave_up = harness
+        .requests
+        .iter()
+        .filter(|x| x.ends_with("/resign") || x.ends_with("/abort"))
+        .count();
+    assert_eq!(gave_up, 0, "{:?}", harness.requests);
+}
+
+#[tokio::test]
+async fn decline_later_at_capacity() {
+    let (mut harness, mut events) = Harness::new("capacity").await;
+    let mut bot = harness.bot(1).await;
+    let run = tokio::spawn(async move { chess_bot::run(&mut bot).await });
+    harness.expect("GET /api/stream/event").await;
+
+    events.send(challenge_event("g1")).await;
+    harness.expect("POST /api/challenge/g1/accept").await;
+    // The accepted challenge takes the only slot before its game starts.
+    events.send(challenge_event("c2")).await;
+    harness
+        .expect("POST /api/challenge/c2/decline?reason=later")
+        .await;
+
+    let mut game = harness.game("g1");
+    events.send(game_event("gameStart", "g1")).await;
+    harness.expect("GET /api/bot/game/stream/g1").await;
+    play(&mut harness, "g1", &mut game).await;
+
+    // The declined challenge is still waiting once the game is over, and is accepted now.
+    *harness.mock.challenges.lock().unwrap() = challenge_list(&["c2"]);
+    events.send(game_event("gameFinish", "g1")).await;
+    harness.expect("GET /api/challenge").await;
+    harness.expect("POST /api/challenge/c2/accept").await;
+
+    drop(events);
+    run.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn duplicate_game_start() {
+    let (mut harness, mut events) = Harness::new("duplicate").await;
+    let mut bot = harness.bot(1).await;
+    let run = tokio::spawn(async move { chess_bot::run(&mut bot).await });
+    harness.expect("GET /api/stream/event").await;
+
+    events.send(challenge_event("g1")).await;
+    harness.expect("POST /api/challenge/g1/accept").await;
+
+    // The second start arrives before the engine of the first one is spawned.
+    let mut game = harness.game("g1");
+    events.send(game_event("gameStart", "g1")).await;
+    events.send(game_event("gameStart", "g1")).await;
+    harness.expect("GET /api/bot/game/stream/g1").await;
+    play(&mut harness, "g1", &mut game).await;
+
+    events.send(game_event("gameFinish", "g1")).await;
+    harness.expect("GET /api/challenge").await;
+    drop(events);
+    run.await.unwrap().unwrap();
+
+    // Only a single game was played, a second one would have asked for the stream again.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(
+        harness.count("GET /api/bot/game/stream/g1"),
+        1,
+        "{:?}",
+        harness.requests
+    );
+}
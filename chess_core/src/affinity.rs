@@ -0,0 +1,168 @@
+//! Pinning threads to cpus and changing their priority.
+//!
+//! Only Linux is supported, elsewhere [`set_thread_affinity`] and [`set_thread_priority`]
+//! return an error and leave the thread as it is.
+
+use anyhow::{anyhow, bail, ensure, Result};
+
+/// Whether threads can be pinned and their priority changed on this platform.
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// The lowest and highest nice level of a thread, a higher level means a lower priority.
+pub const NICE_RANGE: (i32, i32) = (-20, 19);
+
+/// Parse a list of cpus like `0-3,6,8-9` into the sorted indices of the cpus.
+pub fn parse_cpu_mask(mask: &str) -> Result<Vec<usize>> {
+    let mut res = Vec::new();
+    for part in mask.split(',').map(str::trim) {
+        let parse = |x: &str| {
+            x.trim()
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid cpu `{}` in cpu mask `{}`", x.trim(), mask))
+        };
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse(first)?, parse(last)?);
+                ensure!(
+                    first <= last,
+                    "invalid cpu range `{}` in cpu mask `{}`",
+                    part,
+                    mask
+                );
+                res.extend(first..=last);
+            }
+            None => res.push(parse(part)?),
+        }
+    }
+    res.sort_unstable();
+    res.dedup();
+    Ok(res)
+}
+
+/// Returns the cpus as a list in the format read by [`parse_cpu_mask`], with consecutive cpus
+/// joined into ranges.
+pub fn format_cpu_mask(cpus: &[usize]) -> String {
+    let mut cpus = cpus.to_vec();
+    cpus.sort_unstable();
+    cpus.dedup();
+
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < cpus.len() {
+        let first = cpus[i];
+        while i + 1 < cpus.len() && cpus[i + 1] == cpus[i] + 1 {
+            i += 1;
+        }
+        if cpus[i] == first {
+            parts.push(first.to_string());
+        } else {
+            parts.push(format!("{}-{}", first, cpus[i]));
+        }
+        i += 1;
+    }
+    parts.join(",")
+}
+
+/// Split the cpus into the given amount of disjoint groups of consecutive cpus of equal size,
+/// leaving the remaining cpus unused.
+///
+/// Returns an empty list if there are fewer cpus than groups.
+pub fn split_cpus(cpus: &[usize], parts: usize) -> Vec<Vec<usize>> {
+    if parts == 0 || cpus.len() < parts {
+        return Vec::new();
+    }
+    let size = cpus.len() / parts;
+    cpus.chunks_exact(size)
+        .take(parts)
+        .map(|x| x.to_vec())
+        .collect()
+}
+
+/// Returns the cpus the current thread can run on.
+#[cfg(target_os = "linux")]
+pub fn thread_affinity() -> Result<Vec<usize>> {
+    // SAFETY: `cpu_set_t` is a plain bit set which the kernel fills in and only indices below
+    // its size are tested.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            bail!(
+                "failed to read the cpus of the thread: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+            .collect())
+    }
+}
+
+/// Returns the cpus the current thread can run on.
+#[cfg(not(target_os = "linux"))]
+pub fn thread_affinity() -> Result<Vec<usize>> {
+    bail!("reading the cpus of a thread is not supported on this platform")
+}
+
+/// Restrict the current thread to the given cpus.
+#[cfg(target_os = "linux")]
+pub fn set_thread_affinity(cpus: &[usize]) -> Result<()> {
+    ensure!(!cpus.is_empty(), "no cpus to run on");
+    // SAFETY: `cpu_set_t` is a plain bit set for which all zeroes is the empty set and the
+    // cpus are checked to be in the set before they are added.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            ensure!(
+                cpu < libc::CPU_SETSIZE as usize,
+                "cpu {} is out of range",
+                cpu
+            );
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            bail!(
+                "failed to pin thread to cpus {}: {}",
+                format_cpu_mask(cpus),
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Restrict the current thread to the given cpus.
+#[cfg(not(target_os = "linux"))]
+pub fn set_thread_affinity(_cpus: &[usize]) -> Result<()> {
+    bail!("pinning threads to cpus is not supported on this platform")
+}
+
+/// Set the nice level of the current thread, see [`NICE_RANGE`].
+///
+/// Lowering the level below 0 usually requires privileges.
+#[cfg(target_os = "linux")]
+pub fn set_thread_priority(nice: i32) -> Result<()> {
+    ensure!(
+        (NICE_RANGE.0..=NICE_RANGE.1).contains(&nice),
+        "nice level {} is out of range",
+        nice
+    );
+    // SAFETY: Plain system calls without pointers. On Linux `setpriority` with the id of a
+    // thread only changes that thread.
+    unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        if libc::setpriority(libc::PRIO_PROCESS, tid, nice) != 0 {
+            bail!(
+                "failed to set nice level {}: {}",
+                nice,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Set the nice level of the current thread, see [`NICE_RANGE`].
+#[cfg(not(target_os = "linux"))]
+pub fn set_thread_priority(_nice: i32) -> Result<()> {
+    bail!("changing thread priority is not supported on this platform")
+}
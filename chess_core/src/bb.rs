@@ -1,4 +1,4 @@
-use super::{Direction, Square};
+use super::{Direction, Player, Square};
 use std::{
     fmt::{self, Debug},
     iter::{IntoIterator, Iterator},
@@ -9,6 +9,10 @@ use std::{
 };
 
 /// A single bit board.
+///
+/// Bit 0 is the a1 square, bit 7 h1 and bit 63 h8. Square offsets, like those of [`Direction`],
+/// are positive towards the h-file and the 8th rank. The front of a square is towards the 8th
+/// rank for white and towards the 1st rank for black.
 #[derive(Eq, PartialEq, Clone, Copy, Default)]
 pub struct BB(pub u64);
 
@@ -93,26 +97,130 @@ impl BB {
         }
     }
 
+    /// Returns the squares on the first `n` files, starting at the a-file.
+    #[inline(always)]
+    const fn first_files(n: u32) -> u64 {
+        Self::FILE_A.0 * ((1 << n) - 1)
+    }
+
     /// Shift the board in a given direction, filters out squares which would wrap.
     #[inline(always)]
-    pub fn shift(self, direction: Direction) -> Self {
-        match direction {
-            Direction::NW => (self & !BB::FILE_A) << 7,
-            Direction::N => self << 8,
-            Direction::NE => (self & !BB::FILE_H) << 9,
-            Direction::E => (self & !BB::FILE_H) << 1,
-            Direction::SE => (self & !BB::FILE_H) >> 7,
-            Direction::S => self >> 8,
-            Direction::SW => (self & !BB::FILE_A) >> 9,
-            Direction::W => (self & !BB::FILE_A) >> 1,
+    pub const fn shift(self, direction: Direction) -> Self {
+        self.shift_by(direction.as_offset())
+    }
+
+    /// Shift the board by a square offset, filters out squares which would wrap around to the
+    /// other side of the board.
+    ///
+    /// The file of the offset is taken to be the one closest to zero, so 7 moves a square one
+    /// rank up and one file to the left, and 10 one rank up and two files to the right. Offsets
+    /// of a full board or more return the empty board.
+    #[inline(always)]
+    pub const fn shift_by(self, offset: i8) -> Self {
+        if offset >= 64 || offset <= -64 {
+            return BB::EMPTY;
+        }
+        let files = (offset % 8 + 12) % 8 - 4;
+        let keep = if files > 0 {
+            Self::first_files(8 - files as u32)
+        } else {
+            !Self::first_files((-files) as u32)
+        };
+        if offset < 0 {
+            BB((self.0 & keep) >> (-offset) as u32)
+        } else {
+            BB((self.0 & keep) << offset as u32)
+        }
+    }
+
+    /// Returns the squares attacked by pawns of the given player on the enabled squares.
+    #[inline(always)]
+    pub const fn pawn_attacks(self, player: Player) -> Self {
+        match player {
+            Player::White => BB(self.shift(Direction::NW).0 | self.shift(Direction::NE).0),
+            Player::Black => BB(self.shift(Direction::SW).0 | self.shift(Direction::SE).0),
         }
     }
 
+    /// Enable every square towards the 8th rank of an enabled square, including the square
+    /// itself.
+    #[inline]
+    pub const fn north_fill(self) -> Self {
+        let mut x = self.0;
+        x |= x << 8;
+        x |= x << 16;
+        x |= x << 32;
+        BB(x)
+    }
+
+    /// Enable every square towards the 1st rank of an enabled square, including the square
+    /// itself.
+    #[inline]
+    pub const fn south_fill(self) -> Self {
+        let mut x = self.0;
+        x |= x >> 8;
+        x |= x >> 16;
+        x |= x >> 32;
+        BB(x)
+    }
+
+    /// Enable every file which contains an enabled square.
+    #[inline]
+    pub const fn file_fill(self) -> Self {
+        BB(self.north_fill().0 | self.south_fill().0)
+    }
+
+    /// Enable every rank which contains an enabled square.
+    #[inline]
+    pub const fn rank_fill(self) -> Self {
+        let mut east = self.0;
+        east |= (east << 1) & !Self::first_files(1);
+        east |= (east << 2) & !Self::first_files(2);
+        east |= (east << 4) & !Self::first_files(4);
+        let mut west = self.0;
+        west |= (west >> 1) & Self::first_files(7);
+        west |= (west >> 2) & Self::first_files(6);
+        west |= (west >> 4) & Self::first_files(4);
+        BB(east | west)
+    }
+
+    /// Returns the squares in front of the enabled squares for the given player, not including
+    /// the squares themselves.
+    #[inline]
+    pub const fn front_span(self, player: Player) -> Self {
+        match player {
+            Player::White => self.shift(Direction::N).north_fill(),
+            Player::Black => self.shift(Direction::S).south_fill(),
+        }
+    }
+
+    /// Returns the squares which pawns of the given player on the enabled squares could attack
+    /// while advancing.
+    #[inline]
+    pub const fn attack_span(self, player: Player) -> Self {
+        let front = self.front_span(player);
+        BB(front.shift(Direction::W).0 | front.shift(Direction::E).0)
+    }
+
+    /// Returns the files, filled, which contain enabled squares but have no enabled squares on
+    /// either neighbouring file, like the files of isolated pawns.
+    #[inline]
+    pub const fn isolated_files(self) -> Self {
+        let files = self.file_fill();
+        let neighbours = files.shift(Direction::W).0 | files.shift(Direction::E).0;
+        BB(files.0 & !neighbours)
+    }
+
     /// Mirror the position of the board.
     pub fn flip(self) -> Self {
         Self(self.0.reverse_bits())
     }
 
+    /// Mirror the board vertically, swapping the first and last rank.
+    pub fn mirror(self) -> Self {
+        Self(self.0.swap_bytes())
+    }
+
     /// Are any squares enabled
     #[inline]
     pub fn any(self) -> bool {
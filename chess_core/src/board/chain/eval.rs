@@ -0,0 +1,204 @@
+pub use super::{EndChain, MoveChain};
+use crate::{
+    bb::BB,
+    util::{BoardArray, PieceArray},
+    ExtraState, Piece, Square,
+};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// A middle game and an end game score.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Psqt {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Psqt {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Psqt { mg, eg }
+    }
+}
+
+impl Add for Psqt {
+    type Output = Psqt;
+
+    #[inline(always)]
+    fn add(self, rhs: Psqt) -> Psqt {
+        Psqt::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl AddAssign for Psqt {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Psqt) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Psqt {
+    type Output = Psqt;
+
+    #[inline(always)]
+    fn sub(self, rhs: Psqt) -> Psqt {
+        Psqt::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl SubAssign for Psqt {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: Psqt) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Psqt {
+    type Output = Psqt;
+
+    #[inline(always)]
+    fn neg(self) -> Psqt {
+        Psqt::new(-self.mg, -self.eg)
+    }
+}
+
+/// The value of every piece on every square, material included, from the perspective of white.
+/// Values of black pieces should be negative.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PsqtTables {
+    pub values: PieceArray<BoardArray<Psqt>>,
+}
+
+impl PsqtTables {
+    /// Returns the sum of the values of all the pieces.
+    pub fn sum(&self, pieces: &PieceArray<BB>) -> Psqt {
+        let mut res = Psqt::default();
+        for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+            for s in pieces[p].iter() {
+                res += self.values[p][s];
+            }
+        }
+        res
+    }
+}
+
+/// Chain which keeps the sum of the piece square tables of the pieces on the board up to date.
+///
+/// Pieces in a pocket are not counted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalChain<C: MoveChain = EndChain> {
+    psqt: Psqt,
+    tables: &'static PsqtTables,
+    next: C,
+}
+
+impl EvalChain<EndChain> {
+    pub fn new(tables: &'static PsqtTables) -> Self {
+        Self::with(tables, EndChain)
+    }
+}
+
+impl<C: MoveChain> EvalChain<C> {
+    pub fn with(tables: &'static PsqtTables, chain: C) -> Self {
+        EvalChain {
+            psqt: Psqt::default(),
+            tables,
+            next: chain,
+        }
+    }
+
+    /// Returns the summed values of the pieces on the board.
+    #[inline(always)]
+    pub fn psqt(&self) -> Psqt {
+        self.psqt
+    }
+
+    pub fn tables(&self) -> &'static PsqtTables {
+        self.tables
+    }
+}
+
+impl<C: MoveChain> MoveChain for EvalChain<C> {
+    type Next = C;
+
+    #[inline(always)]
+    fn next_chain(&self) -> &Self::Next {
+        &self.next
+    }
+
+    #[inline(always)]
+    fn next_chain_mut(&mut self) -> &mut Self::Next {
+        &mut self.next
+    }
+
+    fn position(&mut self, pieces: &PieceArray<BB>, state: ExtraState) {
+        self.psqt = self.tables.sum(pieces);
+        self.next.position(pieces, state);
+    }
+
+    #[inline(always)]
+    fn move_start(&mut self, state: ExtraState) {
+        self.next.move_start(state);
+    }
+
+    #[inline(always)]
+    fn move_end(&mut self, state: ExtraState) {
+        self.next.move_end(state);
+    }
+
+    #[inline(always)]
+    fn undo_move_start(&mut self, state: ExtraState) {
+        self.next.undo_move_start(state);
+    }
+
+    #[inline(always)]
+    fn undo_move_end(&mut self, state: ExtraState) {
+        self.next.undo_move_end(state);
+    }
+
+    #[inline(always)]
+    fn move_piece(&mut self, piece: Piece, from: Square, to: Square) {
+        let values = &self.tables.values[piece];
+        self.psqt += values[to] - values[from];
+        self.next.move_piece(piece, from, to);
+    }
+
+    #[inline(always)]
+    fn take_piece(&mut self, taken: Piece, square: Square) {
+        self.psqt -= self.tables.values[taken][square];
+        self.next.take_piece(taken, square);
+    }
+
+    #[inline(always)]
+    fn untake_piece(&mut self, taken: Piece, square: Square) {
+        self.psqt += self.tables.values[taken][square];
+        self.next.untake_piece(taken, square);
+    }
+
+    #[inline(always)]
+    fn promote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
+        self.psqt += self.tables.values[promote][to] - self.tables.values[piece][from];
+        self.next.promote_piece(piece, promote, from, to);
+    }
+
+    #[inline(always)]
+    fn unpromote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
+        self.psqt += self.tables.values[piece][from] - self.tables.values[promote][to];
+        self.next.unpromote_piece(piece, promote, from, to);
+    }
+
+    #[inline(always)]
+    fn drop_piece(&mut self, piece: Piece, square: Square) {
+        self.psqt += self.tables.values[piece][square];
+        self.next.drop_piece(piece, square);
+    }
+
+    #[inline(always)]
+    fn undrop_piece(&mut self, piece: Piece, square: Square) {
+        self.psqt -= self.tables.values[piece][square];
+        self.next.undrop_piece(piece, square);
+    }
+
+    #[inline(always)]
+    fn pocket_piece(&mut self, piece: Piece, old_count: u8, new_count: u8) {
+        self.next.pocket_piece(piece, old_count, new_count);
+    }
+}
@@ -2,6 +2,8 @@ pub use super::{EndChain, MoveChain};
 use crate::{bb::BB, hash::Hasher, util::PieceArray, ExtraState, Piece, Square};
 
 /// Chain implementing zobrist hashing.
+///
+/// Every change is passed on to the next chain.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HashChain<C: MoveChain = EndChain> {
     pub hash: u64,
@@ -40,42 +42,73 @@ impl<C: MoveChain> MoveChain for HashChain<C> {
 
     fn position(&mut self, pieces: &PieceArray<BB>, state: ExtraState) {
         self.hash = self.hasher.build(pieces, state);
+        self.next.position(pieces, state);
     }
 
     fn move_start(&mut self, state: ExtraState) {
         self.hash ^= self.hasher.black();
         self.hash ^= self.hasher.castle()[state.castle as usize];
+        self.next.move_start(state);
     }
 
     /// Called when a move starts
     fn undo_move_start(&mut self, state: ExtraState) {
         self.hash ^= self.hasher.black();
         self.hash ^= self.hasher.castle()[state.castle as usize];
+        self.next.undo_move_start(state);
+    }
+
+    fn undo_move_end(&mut self, state: ExtraState) {
+        self.hash ^= self.hasher.castle()[state.castle as usize];
+        self.next.undo_move_end(state);
     }
 
     fn move_piece(&mut self, piece: Piece, from: Square, to: Square) {
         let hash_array = &self.hasher.pieces()[piece];
         self.hash ^= hash_array[from] ^ hash_array[to];
+        self.next.move_piece(piece, from, to);
     }
 
     fn take_piece(&mut self, taken: Piece, square: Square) {
         self.hash ^= self.hasher.pieces()[taken][square];
+        self.next.take_piece(taken, square);
     }
 
-    #[inline(always)]
     fn untake_piece(&mut self, taken: Piece, square: Square) {
-        self.take_piece(taken, square)
+        self.hash ^= self.hasher.pieces()[taken][square];
+        self.next.untake_piece(taken, square);
     }
 
     fn promote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
         self.hash ^= self.hasher.pieces()[piece][from];
         self.hash ^= self.hasher.pieces()[promote][to];
+        self.next.promote_piece(piece, promote, from, to);
     }
 
-    #[inline(always)]
     fn unpromote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
-        self.promote_piece(piece, promote, from, to);
+        self.hash ^= self.hasher.pieces()[piece][from];
+        self.hash ^= self.hasher.pieces()[promote][to];
+        self.next.unpromote_piece(piece, promote, from, to);
     }
 
-    fn move_end(&mut self, _state: ExtraState) {}
+    fn drop_piece(&mut self, piece: Piece, square: Square) {
+        self.hash ^= self.hasher.pieces()[piece][square];
+        self.next.drop_piece(piece, square);
+    }
+
+    fn undrop_piece(&mut self, piece: Piece, square: Square) {
+        self.hash ^= self.hasher.pieces()[piece][square];
+        self.next.undrop_piece(piece, square);
+    }
+
+    fn pocket_piece(&mut self, piece: Piece, old_count: u8, new_count: u8) {
+        let keys = &self.hasher.pocket()[piece];
+        self.hash ^= keys[old_count as usize] ^ keys[new_count as usize];
+        self.next.pocket_piece(piece, old_count, new_count);
+    }
+
+    fn move_end(&mut self, state: ExtraState) {
+        self.hash ^= self.hasher.castle()[state.castle as usize];
+        self.next.move_end(state);
+    }
 }
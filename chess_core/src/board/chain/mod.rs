@@ -1,5 +1,7 @@
+mod eval;
 mod hash;
 use crate::{bb::BB, util::PieceArray, ExtraState, Piece, Square};
+pub use eval::{EvalChain, Psqt, PsqtTables};
 pub use hash::HashChain;
 
 /// Trait for implementing additional behaviour when making a move
@@ -18,6 +20,8 @@ pub trait MoveChain {
 
     /// Called when a move starts
     fn undo_move_start(&mut self, state: ExtraState);
+    /// Called when a move has been undone, with the restored state.
+    fn undo_move_end(&mut self, state: ExtraState);
 
     fn move_piece(&mut self, piece: Piece, from: Square, to: Square);
 
@@ -26,6 +30,13 @@ pub trait MoveChain {
 
     fn promote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square);
     fn unpromote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square);
+
+    /// Called when a piece is dropped from a pocket onto the board.
+    fn drop_piece(&mut self, piece: Piece, square: Square);
+    fn undrop_piece(&mut self, piece: Piece, square: Square);
+
+    /// Called when the amount of a piece in a pocket changes.
+    fn pocket_piece(&mut self, piece: Piece, old_count: u8, new_count: u8);
 }
 
 /// A chain implementing only stub functions.
@@ -58,6 +69,8 @@ impl MoveChain for EndChain {
     /// Called when a move starts
     #[inline(always)]
     fn undo_move_start(&mut self, _state: ExtraState) {}
+    #[inline(always)]
+    fn undo_move_end(&mut self, _state: ExtraState) {}
 
     #[inline(always)]
     fn move_piece(&mut self, _piece: Piece, _from: Square, _to: Square) {}
@@ -70,4 +83,12 @@ impl MoveChain for EndChain {
     fn promote_piece(&mut self, _piece: Piece, _promote: Piece, _from: Square, _to: Square) {}
 
     fn unpromote_piece(&mut self, _piece: Piece, _promote: Piece, _from: Square, _to: Square) {}
+
+    #[inline(always)]
+    fn drop_piece(&mut self, _piece: Piece, _square: Square) {}
+    #[inline(always)]
+    fn undrop_piece(&mut self, _piece: Piece, _square: Square) {}
+
+    #[inline(always)]
+    fn pocket_piece(&mut self, _piece: Piece, _old_count: u8, _new_count: u8) {}
 }
@@ -1,162 +1,234 @@
-use super::{Board, MoveChain};
-use crate::{ExtraState, Piece, Player, Square};
-use anyhow::{anyhow, bail, ensure, Result};
+use super::{Board, MoveChain, Variant};
+use crate::{bb::BB, gen::MoveGenerator, hash::Hasher, ExtraState, Piece, Player, Square};
+use std::{error::Error, fmt};
+
+/// A field of a fen string.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum FenField {
+    Pieces,
+    /// The pieces in hand of a crazyhouse position.
+    Pocket,
+    Player,
+    Castling,
+    EnPassant,
+    HalfMoveClock,
+    FullMoveNumber,
+}
+
+impl fmt::Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FenField::Pieces => "piece placement",
+            FenField::Pocket => "pocket",
+            FenField::Player => "side to move",
+            FenField::Castling => "castling rights",
+            FenField::EnPassant => "en passant square",
+            FenField::HalfMoveClock => "half-move clock",
+            FenField::FullMoveNumber => "full-move number",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Why a fen string is not a valid position.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FenError {
+    /// A character in the piece placement which is not a piece or a count of empty squares, at
+    /// the given byte offset in the fen.
+    BadPieceChar {
+        offset: usize,
+        found: char,
+    },
+    /// The rank, numbered as on the board, doesn't contain exactly 8 squares.
+    RankOverflow {
+        rank: u8,
+    },
+    /// The piece placement doesn't contain exactly 8 ranks.
+    RankCount {
+        count: usize,
+    },
+    MissingField {
+        field: FenField,
+    },
+    InvalidField {
+        field: FenField,
+        value: String,
+    },
+    NoKing {
+        player: Player,
+    },
+    TooManyKings {
+        player: Player,
+    },
+    /// A pawn is on the first or last rank, where it can never be.
+    PawnOnBackRank {
+        square: Square,
+    },
+    /// The en passant square is not behind a pawn of the opponent which just moved two squares.
+    InvalidEnPassant {
+        square: Square,
+    },
+    /// The king of the given player can be taken while it is not their turn.
+    SideNotToMoveInCheck {
+        player: Player,
+    },
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::BadPieceChar { offset, found } => {
+                write!(f, "invalid piece `{}` at offset {}", found, offset)
+            }
+            FenError::RankOverflow { rank } => {
+                write!(f, "rank {} does not contain 8 squares", rank)
+            }
+            FenError::RankCount { count } => write!(f, "{} ranks instead of 8", count),
+            FenError::MissingField { field } => write!(f, "missing {}", field),
+            FenError::InvalidField { field, value } => write!(f, "invalid {} `{}`", field, value),
+            FenError::NoKing { player } => write!(f, "{:?} has no king", player),
+            FenError::TooManyKings { player } => write!(f, "{:?} has multiple kings", player),
+            FenError::PawnOnBackRank { square } => write!(f, "pawn on back rank {}", square),
+            FenError::InvalidEnPassant { square } => {
+                write!(f, "no pawn can be taken en passant on {}", square)
+            }
+            FenError::SideNotToMoveInCheck { player } => {
+                write!(f, "{:?} is in check while it is not their turn", player)
+            }
+        }
+    }
+}
+
+impl Error for FenError {}
 
 impl<C: MoveChain> Board<C> {
     /// Create a board position from a fen string.
-    /// Board's hash value has not been initialized
-    pub fn from_fen(fen: &str, mut chain: C) -> Result<Self> {
+    ///
+    /// The half-move clock and full-move number may be left out, they default to 0 and 1.
+    /// Positions which can't occur in a game, like those without a king or with pawns on the
+    /// back ranks, are rejected.
+    pub fn from_fen(fen: &str, chain: C) -> Result<Self, FenError> {
+        Self::parse_fen(fen, chain, false)
+    }
+
+    /// Create a board position from a fen string, fixing what can be fixed instead of returning
+    /// an error, for positions entered by hand.
+    ///
+    /// Missing fields after the piece placement get a default, pawns on the back ranks are
+    /// removed and en passant squares and castle rights which can't be right are cleared.
+    pub fn from_fen_lenient(fen: &str, chain: C) -> Result<Self, FenError> {
+        Self::parse_fen(fen, chain, true)
+    }
+
+    fn parse_fen(fen: &str, chain: C, lenient: bool) -> Result<Self, FenError> {
+        let fen = fen.trim();
         let mut board = Board::empty();
+        let mut fields = fen.split_whitespace();
 
-        let mut column = 0;
-        let mut row = 0;
-        let mut iterator = fen.chars();
+        let field = fields.next().ok_or(FenError::MissingField {
+            field: FenField::Pieces,
+        })?;
+        Self::parse_placement(&mut board, field)?;
 
-        for c in &mut iterator {
-            if let Some(off) = c.to_digit(9) {
-                column += off;
-                ensure!(column <= 8, "notation moved outside board");
-                continue;
-            }
-            let bitmap = match c {
-                '/' => {
-                    row += 1;
-                    ensure!(row <= 8, "notation moved outside board");
-                    ensure!(column <= 8, "notation moved to far in column");
-                    ensure!(column == 8, "notation did not use full row");
-                    column = 0;
-                    continue;
-                }
-                ' ' => {
-                    break;
-                }
-                'K' => {
-                    ensure!(
-                        board.pieces[Piece::WhiteKing].none(),
-                        "notation had multiple white kings!"
-                    );
-                    Piece::WhiteKing
-                }
-                'k' => {
-                    ensure!(
-                        board.pieces[Piece::BlackKing].none(),
-                        "notation had multiple white kings!"
-                    );
-                    Piece::BlackKing
-                }
-                'Q' => Piece::WhiteQueen,
-                'q' => Piece::BlackQueen,
-                'R' => Piece::WhiteRook,
-                'r' => Piece::BlackRook,
-                'B' => Piece::WhiteBishop,
-                'b' => Piece::BlackBishop,
-                'N' => Piece::WhiteKnight,
-                'n' => Piece::BlackKnight,
-                'P' => Piece::WhitePawn,
-                'p' => Piece::BlackPawn,
-                x => {
-                    bail!("invalid character: {}", x);
+        // Returns the next field, or `None` if it is missing and may be left out.
+        let mut next_field = |field: FenField| match fields.next() {
+            Some(x) => Ok(Some(x)),
+            None if lenient => Ok(None),
+            None => Err(FenError::MissingField { field }),
+        };
+        let invalid = |field: FenField, value: &str| FenError::InvalidField {
+            field,
+            value: value.to_string(),
+        };
+
+        board.state.player = match next_field(FenField::Player)? {
+            Some("w") | None => Player::White,
+            Some("b") => Player::Black,
+            Some(x) => return Err(invalid(FenField::Player, x)),
+        };
+
+        let castling = next_field(FenField::Castling)?;
+        match castling {
+            Some("-") | None => {}
+            Some(x) => {
+                // The rights have to be given in this order, each at most once.
+                let mut order = "KQkq".chars();
+                for c in x.chars() {
+                    if !order.any(|o| o == c) {
+                        return Err(invalid(FenField::Castling, x));
+                    }
+                    board.state.castle |= match c {
+                        'K' => ExtraState::WHITE_KING_CASTLE,
+                        'Q' => ExtraState::WHITE_QUEEN_CASTLE,
+                        'k' => ExtraState::BLACK_KING_CASTLE,
+                        _ => ExtraState::BLACK_QUEEN_CASTLE,
+                    };
                 }
-            };
-            ensure!(
-                column <= 7,
-                "notation tried to place piece outside the board"
-            );
-            board.pieces[bitmap] |= 1 << (7 - row) * 8 + column;
-            column += 1;
+            }
         }
 
-        if let Some(x) = iterator.next() {
-            match x {
-                'w' => {}
-                'b' => board.state.player = Player::Black,
-                x => bail!("invalid character '{}', expected on of 'w', 'b'", x),
+        let en_passant = match next_field(FenField::EnPassant)? {
+            Some("-") | None => None,
+            Some(x) => {
+                let mut chars = x.chars();
+                match (chars.next(), chars.next(), chars.next()) {
+                    (Some(c), Some(r), None) => Some(
+                        Self::postion_to_square(c, r)
+                            .ok_or_else(|| invalid(FenField::EnPassant, x))?,
+                    ),
+                    _ => return Err(invalid(FenField::EnPassant, x)),
+                }
             }
-        } else {
-            bail!("missing characters!")
+        };
+
+        if let Some(x) = fields.next() {
+            board.state.move_clock = x.parse().map_err(|_| invalid(FenField::HalfMoveClock, x))?;
+        }
+        if let Some(x) = fields.next() {
+            x.parse::<u32>()
+                .map_err(|_| invalid(FenField::FullMoveNumber, x))?;
         }
 
-        ensure!(
-            iterator.next() == Some(' '),
-            "missing space after current move indicator"
-        );
-
-        let mut cnt = 0;
-        loop {
-            match iterator.next() {
-                Some('-') => {
-                    ensure!(
-                        cnt == 0,
-                        "invalid character '-', expected one of 'K','Q','k','q',' '"
-                    );
-                    iterator.next();
-                    break;
-                }
-                Some(' ') => {
-                    ensure!(
-                        cnt != 0,
-                        "invalid character ' ', expected one of 'K','Q','k','q','-'"
-                    );
-                    break;
-                }
-                Some('K') => {
-                    ensure!(cnt == 0, "invalid order castle rights");
-                    cnt = 1;
-                    board.state.castle |= ExtraState::WHITE_KING_CASTLE;
-                }
-                Some('Q') => {
-                    ensure!(cnt <= 1, "invalid order castle rights");
-                    cnt = 2;
-                    board.state.castle |= ExtraState::WHITE_QUEEN_CASTLE;
-                }
-                Some('k') => {
-                    ensure!(cnt <= 2, "invalid order castle rights");
-                    cnt = 3;
-                    board.state.castle |= ExtraState::BLACK_KING_CASTLE;
-                }
-                Some('q') => {
-                    cnt = 4;
-                    board.state.castle |= ExtraState::BLACK_QUEEN_CASTLE;
-                }
-                Some(x) => {
-                    bail!("invalid character '{}'", x);
-                }
-                None => {
-                    bail!("missing characters")
-                }
+        for (player, king) in [
+            (Player::White, Piece::WhiteKing),
+            (Player::Black, Piece::BlackKing),
+        ] {
+            match board.pieces[king].count() {
+                0 => return Err(FenError::NoKing { player }),
+                1 => {}
+                _ => return Err(FenError::TooManyKings { player }),
             }
         }
 
-        match iterator.next() {
-            Some('-') => {}
-            Some(c) => {
-                if let Some(r) = iterator.next() {
-                    let idx = Self::postion_to_square(c, r)
-                        .ok_or(anyhow!("invalid position: invalid en passant square"))?;
-                    board.state.en_passant = idx.file();
-                } else {
-                    bail!("missing characters")
+        let back_rank = BB::RANK_1 | BB::RANK_8;
+        for pawn in [Piece::WhitePawn, Piece::BlackPawn] {
+            let on_back_rank = board.pieces[pawn] & back_rank;
+            if on_back_rank.any() {
+                if !lenient {
+                    return Err(FenError::PawnOnBackRank {
+                        square: on_back_rank.first_piece(),
+                    });
                 }
-            }
-            None => {
-                bail!("missing characters")
+                board.pieces[pawn] &= !back_rank;
             }
         }
 
-        //ensure!(iterator.next() == Some('-'), "invalid position");
-        let mut iterator = iterator.as_str().split_whitespace();
-
-        let half_time = iterator
-            .next()
-            .ok_or_else(|| anyhow!("invalid position"))
-            .and_then(|x| Ok(x.parse::<u8>()?))?;
-
-        board.state.move_clock = half_time;
+        if let Some(square) = en_passant {
+            if Self::valid_en_passant(&board, square) {
+                board.state.en_passant = square.file();
+            } else if !lenient {
+                return Err(FenError::InvalidEnPassant { square });
+            }
+        }
 
-        let _move_time = iterator
-            .next()
-            .ok_or_else(|| anyhow!("invalid position"))
-            .and_then(|x| Ok(x.parse::<u32>()?))?;
+        // Castling needs the king and the rook on their start squares.
+        let impossible = board.state.castle & !Self::possible_castle_rights(&board);
+        if impossible != 0 {
+            if !lenient {
+                return Err(invalid(FenField::Castling, castling.unwrap_or_default()));
+            }
+            board.state.castle &= !impossible;
+        }
 
         for p in Piece::WhiteKing.to(Piece::BlackPawn) {
             for s in board.pieces[p].iter() {
@@ -164,14 +236,165 @@ impl<C: MoveChain> Board<C> {
             }
         }
 
-        chain.position(&board.pieces, board.state);
+        let waiting = board.state.player.flip();
+        if MoveGenerator::new().king_attacked(&board, waiting) {
+            return Err(FenError::SideNotToMoveInCheck { player: waiting });
+        }
 
-        Ok(Board {
+        let mut board = Board {
             pieces: board.pieces,
             state: board.state,
             squares: board.squares,
+            variant: board.variant,
+            pocket: board.pocket,
             chain,
-        })
+            phase: 0,
+        };
+        board.init_chain();
+        Ok(board)
+    }
+
+    /// Parse the piece placement field, including the pieces in hand of a crazyhouse fen.
+    fn parse_placement(board: &mut Board, field: &str) -> Result<(), FenError> {
+        let mut ranks: Vec<&str> = field.split('/').collect();
+        let mut pocket = None;
+        if let Some(at) = field.find('[') {
+            let rest = &field[at + 1..];
+            pocket = Some(
+                rest.strip_suffix(']')
+                    .ok_or_else(|| FenError::InvalidField {
+                        field: FenField::Pocket,
+                        value: rest.to_string(),
+                    })?,
+            );
+            ranks = field[..at].split('/').collect();
+        } else if ranks.len() == 9 && !ranks[8].contains(|c: char| c.is_ascii_digit()) {
+            // The pieces in hand can also follow the board as a ninth rank.
+            pocket = ranks.pop();
+        }
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount { count: ranks.len() });
+        }
+
+        let mut offset = 0;
+        for (row, rank) in ranks.iter().enumerate() {
+            let rank_number = 8 - row as u8;
+            let mut column = 0;
+            // The square of the last placed piece, which a promoted marker applies to.
+            let mut last = None;
+            for (i, c) in rank.char_indices() {
+                match c {
+                    '1'..='8' => {
+                        column += c as u8 - b'0';
+                        last = None;
+                    }
+                    '~' => {
+                        let square = last.take().ok_or(FenError::BadPieceChar {
+                            offset: offset + i,
+                            found: c,
+                        })?;
+                        board.pocket.promoted |= BB::square(square);
+                        board.variant = Variant::Crazyhouse;
+                    }
+                    c => {
+                        let piece = Piece::from_char(c).ok_or(FenError::BadPieceChar {
+                            offset: offset + i,
+                            found: c,
+                        })?;
+                        if column >= 8 {
+                            return Err(FenError::RankOverflow { rank: rank_number });
+                        }
+                        let square = Square::from_file_rank(column, rank_number - 1);
+                        board.pieces[piece] |= BB::square(square);
+                        last = Some(square);
+                        column += 1;
+                    }
+                }
+                if column > 8 {
+                    return Err(FenError::RankOverflow { rank: rank_number });
+                }
+            }
+            if column != 8 {
+                return Err(FenError::RankOverflow { rank: rank_number });
+            }
+            offset += rank.len() + 1;
+        }
+
+        if let Some(pocket) = pocket {
+            Self::parse_pocket(board, pocket)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the pieces in hand of a crazyhouse fen.
+    fn parse_pocket(board: &mut Board, pocket: &str) -> Result<(), FenError> {
+        board.variant = Variant::Crazyhouse;
+        let invalid = || FenError::InvalidField {
+            field: FenField::Pocket,
+            value: pocket.to_string(),
+        };
+        for c in pocket.chars() {
+            if c == '-' {
+                continue;
+            }
+            let piece = Piece::from_char(c).ok_or_else(invalid)?;
+            if piece == Piece::WhiteKing
+                || piece == Piece::BlackKing
+                || board.pocket.pieces[piece] as usize >= Hasher::MAX_POCKET - 1
+            {
+                return Err(invalid());
+            }
+            board.pocket.pieces[piece] += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns whether a pawn of the opponent of the player to move can have just moved two
+    /// squares past the given square.
+    pub(super) fn valid_en_passant<M: MoveChain>(board: &Board<M>, square: Square) -> bool {
+        let (rank, pawn) = match board.state.player {
+            Player::White => (5, Piece::BlackPawn),
+            Player::Black => (2, Piece::WhitePawn),
+        };
+        if square.rank() != rank {
+            return false;
+        }
+        let (from, to) = match board.state.player {
+            Player::White => (rank + 1, rank - 1),
+            Player::Black => (rank - 1, rank + 1),
+        };
+        let from = Square::from_file_rank(square.file(), from);
+        let to = Square::from_file_rank(square.file(), to);
+        let occupied = Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .fold(BB::EMPTY, |acc, p| acc | board.pieces[p]);
+        (board.pieces[pawn] & BB::square(to)).any()
+            && (occupied & (BB::square(square) | BB::square(from))).none()
+    }
+
+    /// Returns the castle flags which are possible with the kings and rooks on the board.
+    pub(super) fn possible_castle_rights<M: MoveChain>(board: &Board<M>) -> u8 {
+        let on = |piece: Piece, file: u8, rank: u8| {
+            (board.pieces[piece] & BB::square(Square::from_file_rank(file, rank))).any()
+        };
+        let mut res = 0;
+        if on(Piece::WhiteKing, 4, 0) {
+            if on(Piece::WhiteRook, 7, 0) {
+                res |= ExtraState::WHITE_KING_CASTLE;
+            }
+            if on(Piece::WhiteRook, 0, 0) {
+                res |= ExtraState::WHITE_QUEEN_CASTLE;
+            }
+        }
+        if on(Piece::BlackKing, 4, 7) {
+            if on(Piece::BlackRook, 7, 7) {
+                res |= ExtraState::BLACK_KING_CASTLE;
+            }
+            if on(Piece::BlackRook, 0, 7) {
+                res |= ExtraState::BLACK_QUEEN_CASTLE;
+            }
+        }
+        res
     }
 
     fn postion_to_square(column: char, row: char) -> Option<Square> {
@@ -216,6 +439,10 @@ impl<C: MoveChain> Board<C> {
                         Piece::WhitePawn => res.push('P'),
                         Piece::BlackPawn => res.push('p'),
                     };
+                    let square = BB::square(Square::from_file_rank(file, rank));
+                    if (self.pocket.promoted & square).any() {
+                        res.push('~');
+                    }
                 } else {
                     count += 1;
                 }
@@ -227,6 +454,15 @@ impl<C: MoveChain> Board<C> {
                 res.push('/');
             }
         }
+        if self.variant == Variant::Crazyhouse {
+            res.push('[');
+            for p in Piece::WhiteQueen.to(Piece::BlackPawn) {
+                for _ in 0..self.pocket.pieces[p] {
+                    res.push(p.to_char());
+                }
+            }
+            res.push(']');
+        }
         res.push(' ');
         match self.state.player {
             Player::White => {
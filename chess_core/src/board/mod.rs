@@ -5,6 +5,7 @@ use crate::{
     util::{BoardArray, PieceArray},
     ExtraState, Move, Piece, Player, Square,
 };
+use anyhow::{bail, ensure, Result};
 use std::{
     fmt::{self, Debug},
     iter::Iterator,
@@ -12,7 +13,12 @@ use std::{
 
 mod chain;
 mod fen;
-pub use chain::{EndChain, HashChain, MoveChain};
+mod validate;
+mod variant;
+pub use chain::{EndChain, EvalChain, HashChain, MoveChain, Psqt, PsqtTables};
+pub use fen::{FenError, FenField};
+pub use validate::{BoardError, PositionError};
+pub use variant::{Pocket, Variant};
 
 /// A move which has been made on the board with
 /// extra information regarding undoing the move
@@ -20,6 +26,7 @@ pub use chain::{EndChain, HashChain, MoveChain};
 pub struct UnmakeMove {
     pub mov: Move,
     taken: Option<Piece>,
+    taken_promoted: bool,
     state: ExtraState,
 }
 
@@ -29,7 +36,11 @@ pub struct Board<C: MoveChain = EndChain> {
     pub pieces: PieceArray<BB>,
     pub state: ExtraState,
     pub squares: BoardArray<Option<Piece>>,
+    pub variant: Variant,
+    pub pocket: Pocket,
     pub chain: C,
+    /// Sum of the phase weights of the pieces on the board, kept up to date by moves.
+    phase: i32,
 }
 
 impl Board<EndChain> {
@@ -45,7 +56,10 @@ impl Board<EndChain> {
             pieces,
             squares,
             state,
+            variant: Variant::Standard,
+            pocket: Pocket::empty(),
             chain: EndChain,
+            phase: 0,
         }
     }
 }
@@ -57,7 +71,7 @@ where
     /// Returns a board in the start position
     ///
     /// Board has not initialized the hash value
-    pub fn start_position(mut chain: C) -> Self {
+    pub fn start_position(chain: C) -> Self {
         let mut res = Board::empty();
         res.state.castle = ExtraState::BLACK_KING_CASTLE
             | ExtraState::BLACK_QUEEN_CASTLE
@@ -101,21 +115,125 @@ where
             res.squares[Square::from_file_rank(f, 6)] = Some(Piece::BlackPawn);
         }
 
-        chain.position(&res.pieces, res.state);
-
-        Board {
+        let mut res = Board {
             pieces: res.pieces,
             squares: res.squares,
             state: res.state,
+            variant: res.variant,
+            pocket: res.pocket,
             chain,
+            phase: 0,
+        };
+        res.init_chain();
+        res
+    }
+
+    /// Returns a board in the start position for the given variant
+    pub fn start_position_variant(chain: C, variant: Variant) -> Self {
+        let mut res = Self::start_position(chain);
+        res.variant = variant;
+        res
+    }
+
+    /// Create a board from the pieces on it and the state of the position, for setting up
+    /// positions without going through a fen string.
+    ///
+    /// `castle` contains the castle flags of [`ExtraState`] and `en_passant` the square a pawn
+    /// can be taken on en passant. Returns an error if the position is not valid.
+    pub fn from_pieces(
+        pieces: &[(Square, Piece)],
+        player: Player,
+        castle: u8,
+        en_passant: Option<Square>,
+        chain: C,
+    ) -> Result<Self> {
+        let mut board = Board::empty();
+        for &(square, piece) in pieces {
+            ensure!(
+                board.squares[square].is_none(),
+                "multiple pieces on square {}",
+                square
+            );
+            board.squares[square] = Some(piece);
+            board.pieces[piece] |= BB::square(square);
+        }
+
+        ensure!(castle <= 0b1111, "invalid castle flags {:#b}", castle);
+        board.state.player = player;
+        board.state.castle = castle;
+        if let Some(square) = en_passant {
+            let rank = match player {
+                Player::White => 5,
+                Player::Black => 2,
+            };
+            ensure!(
+                square.rank() == rank,
+                "en passant square {} is not behind a pawn of the opponent",
+                square
+            );
+            board.state.en_passant = square.file();
         }
+
+        let errors = board.validate();
+        if !errors.is_empty() {
+            let errors: Vec<_> = errors.iter().map(|x| x.to_string()).collect();
+            bail!("invalid position: {}", errors.join(", "));
+        }
+
+        let mut board = Board {
+            pieces: board.pieces,
+            state: board.state,
+            squares: board.squares,
+            variant: board.variant,
+            pocket: board.pocket,
+            chain,
+            phase: 0,
+        };
+        board.init_chain();
+        Ok(board)
     }
 
     pub fn copy_position<H: MoveChain>(&mut self, b: &Board<H>) {
         self.squares = b.squares;
         self.pieces = b.pieces;
         self.state = b.state;
+        self.variant = b.variant;
+        self.pocket = b.pocket;
+        self.init_chain();
+    }
+
+    /// Setup the chain and the game phase from the current position.
+    fn init_chain(&mut self) {
+        self.phase = self.compute_phase();
         self.chain.position(&self.pieces, self.state);
+        for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+            let count = self.pocket.pieces[p];
+            if count != 0 {
+                self.chain.pocket_piece(p, 0, count);
+            }
+        }
+    }
+
+    /// The phase of the game at the start position, with all pieces on the board.
+    pub const MAX_PHASE: i32 = 24;
+
+    /// Returns the game phase, the sum of the [phase weights](Piece::phase_weight) of the
+    /// pieces on the board.
+    ///
+    /// The phase is [`MAX_PHASE`](Self::MAX_PHASE) at the start of the game and decreases
+    /// towards zero as pieces are taken. Promotions can raise it above the maximum.
+    #[inline]
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// Compute the game phase from the pieces on the board instead of using the value kept up
+    /// to date by moves.
+    pub fn compute_phase(&self) -> i32 {
+        Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .map(|p| self.pieces[p].count() as i32 * p.phase_weight())
+            .sum()
     }
 
     pub fn is_equal(&self, other: &Self) -> bool {
@@ -129,82 +247,36 @@ where
             return false;
         }
 
+        if self.pocket != other.pocket {
+            return false;
+        }
+
         true
     }
 
-    /// Checks whether the given position is valid.
-    pub fn is_valid(&self) -> bool {
-        let mut res = true;
-
-        if self.pieces[Piece::WhiteKing].count() != 1 {
-            res = false;
-            eprintln!(
-                "Wrong number of white kings\n{:?}",
-                self.pieces[Piece::WhiteKing]
-            );
-        }
-        if self.pieces[Piece::BlackKing].count() != 1 {
-            res = false;
-            eprintln!(
-                "Wrong number of black kings\n{:?}",
-                self.pieces[Piece::BlackKing]
-            );
-        }
-        for pa in Piece::WhiteKing.to(Piece::BlackPawn) {
-            for pb in Piece::WhiteKing.to(pa) {
-                if pa == pb {
-                    continue;
-                }
-                if !(self.pieces[pa] & self.pieces[pb]).none() {
-                    eprintln!(
-                        "Overlap in bitboards: {:?} {:?}\n{:?}{:?}",
-                        pa, pb, self.pieces[pa], self.pieces[pb]
-                    );
-                    res = false;
-                }
-            }
+    /// Returns the position mirrored vertically with the colors of all pieces swapped.
+    ///
+    /// The mirrored position is the same position from the perspective of the other player so
+    /// a symmetric evaluation should return the negated score.
+    pub fn mirror(&self) -> Self
+    where
+        C: Clone,
+    {
+        let mut res = self.clone();
+        for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+            res.pieces[p] = self.pieces[p.flip(true)].mirror();
+            res.pocket.pieces[p] = self.pocket.pieces[p.flip(true)];
         }
-
         for s in 0..64 {
             let s = Square::new(s);
-            if let Some(x) = self.squares[s] {
-                if !(self.pieces[x] & BB::square(s)).any() {
-                    eprintln!("mailbox-bitboard mismatch, Square {} should contain {:?} but bitboard does not:\n{:?}",s,x,self.pieces[x]);
-                    res = false;
-                }
-            } else {
-                let sb = BB::square(s);
-                for p in Piece::WhiteKing.to(Piece::BlackPawn) {
-                    if !(self.pieces[p] & sb).none() {
-                        eprintln!("mailbox-bitboard mismatch, Square {} should be empty but contains {:?} on bitboard\n{:?}",s,p,self.pieces[p]);
-                        res = false;
-                    }
-                }
-            }
+            res.squares[s.mirror()] = self.squares[s].map(|p| p.flip(true));
         }
-
+        res.pocket.promoted = self.pocket.promoted.mirror();
+        res.state = self.state.mirror();
+        res.init_chain();
         res
     }
 
-    /*
-    pub fn flip(mut self) -> Self {
-        for p in Piece::WhiteKing.to(Piece::WhitePawn) {
-            let cur = p;
-            let other = p.flip(true);
-            self.pieces[cur].flip();
-            self.pieces[other].flip();
-            let o = self.pieces[other];
-            self.pieces[cur] ^= o;
-            let c = self.pieces[cur];
-            self.pieces[other] ^= c;
-            let o = self.pieces[other];
-            self.pieces[cur] ^= o;
-        }
-        self.state = self.state.flip();
-        return self;
-    }
-    */
-
     #[inline]
     fn move_piece(&mut self, piece: Piece, from: Square, to: Square) {
         self.squares[to] = Some(piece);
@@ -215,6 +287,7 @@ where
 
     #[inline]
     fn take_piece(&mut self, taken: Piece, square: Square) {
+        self.phase -= taken.phase_weight();
         self.pieces[taken] ^= BB::square(square);
         self.squares[square] = None;
         self.chain.take_piece(taken, square);
@@ -222,6 +295,7 @@ where
 
     #[inline]
     fn untake_piece(&mut self, taken: Piece, square: Square) {
+        self.phase += taken.phase_weight();
         self.pieces[taken] ^= BB::square(square);
         self.squares[square] = Some(taken);
         self.chain.untake_piece(taken, square);
@@ -229,6 +303,7 @@ where
 
     #[inline]
     fn promote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
+        self.phase += promote.phase_weight() - piece.phase_weight();
         self.pieces[piece] ^= BB::square(from);
         self.pieces[promote] ^= BB::square(to);
         self.squares[from] = None;
@@ -237,6 +312,7 @@ where
     }
 
     fn unpromote_piece(&mut self, piece: Piece, promote: Piece, from: Square, to: Square) {
+        self.phase -= promote.phase_weight() - piece.phase_weight();
         self.pieces[piece] ^= BB::square(from);
         self.pieces[promote] ^= BB::square(to);
         self.squares[to] = None;
@@ -244,9 +320,130 @@ where
         self.chain.unpromote_piece(piece, promote, from, to);
     }
 
+    /// Add a piece to the pocket.
+    #[inline]
+    fn pocket_add(&mut self, piece: Piece) {
+        let count = self.pocket.pieces[piece];
+        self.pocket.pieces[piece] = count + 1;
+        self.chain.pocket_piece(piece, count, count + 1);
+    }
+
+    /// Remove a piece from the pocket.
+    #[inline]
+    fn pocket_remove(&mut self, piece: Piece) {
+        let count = self.pocket.pieces[piece];
+        debug_assert!(count > 0, "removed {:?} from an empty pocket", piece);
+        self.pocket.pieces[piece] = count - 1;
+        self.chain.pocket_piece(piece, count, count - 1);
+    }
+
+    fn make_drop(&mut self, m: Move) -> UnmakeMove {
+        debug_assert_eq!(self.variant, Variant::Crazyhouse);
+        let state = self.state;
+
+        self.chain.move_start(self.state);
+        self.state.en_passant = ExtraState::INVALID_ENPASSANT;
+
+        let to = m.to();
+        let piece = m.drop_piece(self.state.player);
+        assert!(self.squares[to].is_none(), "{}\n{:?}", m, self.squares);
+
+        self.pocket_remove(piece);
+        self.phase += piece.phase_weight();
+        self.pieces[piece] |= BB::square(to);
+        self.squares[to] = Some(piece);
+        self.chain.drop_piece(piece, to);
+
+        self.state.player = self.state.player.flip();
+        self.chain.move_end(self.state);
+        // Only a pawn drop is irreversible, like a pawn move.
+        if matches!(piece, Piece::WhitePawn | Piece::BlackPawn) {
+            self.state.move_clock = 0;
+        } else {
+            self.state.move_clock = self.state.move_clock.saturating_add(1);
+        }
+
+        UnmakeMove {
+            mov: m,
+            taken: None,
+            taken_promoted: false,
+            state,
+        }
+    }
+
+    fn unmake_drop(&mut self, mov: UnmakeMove) {
+        self.chain.undo_move_start(self.state);
+        self.state = mov.state;
+
+        let to = mov.mov.to();
+        let piece = mov.mov.drop_piece(self.state.player);
+
+        self.phase -= piece.phase_weight();
+        self.pieces[piece] ^= BB::square(to);
+        self.squares[to] = None;
+        self.chain.undrop_piece(piece, to);
+        self.pocket_add(piece);
+        self.chain.undo_move_end(self.state);
+    }
+
+    /// Update the pockets and promoted pieces after a move in crazyhouse.
+    /// Returns whether the taken piece was a promoted piece.
+    fn crazyhouse_move(&mut self, m: Move, taken: Option<Piece>) -> bool {
+        let player = self.state.player;
+        let from = BB::square(m.from());
+        let to = BB::square(m.to());
+
+        let mut taken_promoted = false;
+        if let Some(taken) = taken {
+            if m.ty() != Move::TYPE_EN_PASSANT {
+                taken_promoted = (self.pocket.promoted & to).any();
+                self.pocket.promoted &= !to;
+            }
+            if taken_promoted {
+                self.pocket_add(Piece::player_pawn(player));
+            } else {
+                self.pocket_add(taken.flip(true));
+            }
+        }
+
+        if m.ty() == Move::TYPE_PROMOTION {
+            self.pocket.promoted |= to;
+        } else if (self.pocket.promoted & from).any() {
+            self.pocket.promoted ^= from | to;
+        }
+
+        taken_promoted
+    }
+
+    /// Undo the pocket and promoted piece changes of a move in crazyhouse.
+    fn crazyhouse_unmove(&mut self, mov: UnmakeMove) {
+        let player = self.state.player;
+        let m = mov.mov;
+        let from = BB::square(m.from());
+        let to = BB::square(m.to());
+
+        if m.ty() == Move::TYPE_PROMOTION {
+            self.pocket.promoted &= !to;
+        } else if (self.pocket.promoted & to).any() {
+            self.pocket.promoted ^= from | to;
+        }
+
+        if let Some(taken) = mov.taken {
+            if mov.taken_promoted {
+                self.pocket.promoted |= to;
+                self.pocket_remove(Piece::player_pawn(player));
+            } else {
+                self.pocket_remove(taken.flip(true));
+            }
+        }
+    }
+
     /// Make a move on the board.
     pub fn make_move(&mut self, m: Move) -> UnmakeMove {
         assert_ne!(m, Move::INVALID);
+        if m.is_drop() {
+            return self.make_drop(m);
+        }
         //debug_assert!(self.hash != 0);
         let state = self.state;
 
@@ -307,13 +504,7 @@ where
             }
         } else if ty == Move::TYPE_PROMOTION {
             debug_assert_eq!(piece, Piece::player_pawn(self.state.player));
-            let promote = match m.promotion_piece() {
-                Move::PROMOTION_QUEEN => Piece::player_queen(self.state.player),
-                Move::PROMOTION_KNIGHT => Piece::player_knight(self.state.player),
-                Move::PROMOTION_BISHOP => Piece::player_bishop(self.state.player),
-                Move::PROMOTION_ROOK => Piece::player_rook(self.state.player),
-                _ => unreachable!(),
-            };
+            let promote = m.promotion_piece().piece(self.state.player);
 
             if let Some(taken) = taken {
                 self.take_piece(taken, to)
@@ -347,13 +538,19 @@ where
             _ => 0,
         };
 
+        let taken_promoted = if self.variant == Variant::Crazyhouse {
+            self.crazyhouse_move(m, taken)
+        } else {
+            false
+        };
+
         self.state.player = self.state.player.flip();
         self.state.castle &= !castle_mask;
 
         self.chain.move_end(self.state);
 
         if reversible {
-            self.state.move_clock += 1;
+            self.state.move_clock = self.state.move_clock.saturating_add(1);
         } else {
             self.state.move_clock = 0;
         }
@@ -361,15 +558,54 @@ where
         let res = UnmakeMove {
             mov: m,
             taken,
+            taken_promoted,
             state,
         };
         //self.moves.push(res);
         res
     }
 
+    /// Returns a copy of the board with the move made on it.
+    ///
+    /// This is the idiom for copy-make searches like MCTS, which copy positions and never take
+    /// a move back. Searches which walk the tree on a single board should use
+    /// [`make_move`](Self::make_move) with [`unmake_move`](Self::unmake_move) instead, which
+    /// avoids the copy.
+    pub fn make_move_copy(&self, m: Move) -> Self
+    where
+        C: Clone,
+    {
+        let mut res = self.clone();
+        res.make_move(m);
+        res
+    }
+
+    /// Pass the turn to the opponent without moving a piece, for null move pruning.
+    ///
+    /// Only the side to move changes and the en passant square is cleared, as a pawn which just
+    /// moved two squares can no longer be taken after a move of the other side. The pieces,
+    /// castle rights and half-move clock are left as they are. The returned value can only be
+    /// undone with [`unmake_null_move`](Self::unmake_null_move).
+    pub fn make_null_move(&mut self) -> UnmakeMove {
+        let state = self.state;
+        self.chain.move_start(self.state);
+        self.state.en_passant = ExtraState::INVALID_ENPASSANT;
+        self.state.player = self.state.player.flip();
+        self.chain.move_end(self.state);
+        UnmakeMove {
+            mov: Move::INVALID,
+            taken: None,
+            taken_promoted: false,
+            state,
+        }
+    }
+
     /// Undo a move
     pub fn unmake_move(&mut self, mov: UnmakeMove) {
         //debug_assert_eq!(self.moves.pop(), Some(mov));
+        if mov.mov.is_drop() {
+            return self.unmake_drop(mov);
+        }
         self.chain.undo_move_start(self.state);
 
         self.state = mov.state;
@@ -407,13 +643,7 @@ where
             }
         } else if ty == Move::TYPE_PROMOTION {
             let piece = Piece::player_pawn(self.state.player);
-            let promote = match mov.mov.promotion_piece() {
-                Move::PROMOTION_QUEEN => Piece::player_queen(self.state.player),
-                Move::PROMOTION_KNIGHT => Piece::player_knight(self.state.player),
-                Move::PROMOTION_BISHOP => Piece::player_bishop(self.state.player),
-                Move::PROMOTION_ROOK => Piece::player_rook(self.state.player),
-                _ => unreachable!(),
-            };
+            let promote = mov.mov.promotion_piece().piece(self.state.player);
 
             self.unpromote_piece(piece, promote, from, to);
 
@@ -433,12 +663,56 @@ where
         }
 
         self.state = mov.state;
+
+        if self.variant == Variant::Crazyhouse {
+            self.crazyhouse_unmove(mov);
+        }
+        self.chain.undo_move_end(self.state);
+    }
+
+    /// Undo a null move made with [`make_null_move`](Self::make_null_move), restoring the side
+    /// to move and the en passant square.
+    pub fn unmake_null_move(&mut self, mov: UnmakeMove) {
+        debug_assert_eq!(mov.mov, Move::INVALID);
+        self.chain.undo_move_start(self.state);
+        self.state = mov.state;
+        self.chain.undo_move_end(self.state);
     }
 
     #[inline(always)]
     pub fn on(&self, square: Square) -> Option<Piece> {
         self.squares[square]
     }
+
+    /// Returns the square of the king of the given player.
+    #[inline]
+    pub fn king_square(&self, player: Player) -> Square {
+        self.pieces[Piece::player_king(player)].first_piece()
+    }
+
+    /// Returns whether the game is drawn by the fifty-move rule, a hundred half-moves without a
+    /// capture or pawn move.
+    ///
+    /// Doesn't check whether the position is checkmate, which takes precedence.
+    #[inline]
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.state.move_clock >= crate::repetition::FIFTY_MOVE_PLIES
+    }
+
+    /// Returns the piece on the square together with the player it belongs to.
+    #[inline]
+    pub fn piece_at(&self, square: Square) -> Option<(Piece, Player)> {
+        self.squares[square].map(|x| (x, x.player()))
+    }
+
+    /// Returns the sum of the standard values of the pieces of the player on the board.
+    ///
+    /// Pieces in a crazyhouse pocket are not counted.
+    pub fn material_count(&self, player: Player) -> i32 {
+        Piece::player_pieces(player == Player::Black)
+            .map(|p| p.value() * self.pieces[p].count() as i32)
+            .sum()
+    }
 }
 
 impl<C: MoveChain + Debug> Debug for Board<C> {
@@ -457,6 +731,8 @@ impl<C: MoveChain + Debug> Debug for Board<C> {
             .field("black_rook", &self.pieces[Piece::BlackRook])
             .field("black_pawn", &self.pieces[Piece::BlackPawn])
             .field("state", &self.state)
+            .field("variant", &self.variant)
+            .field("pocket", &self.pocket)
             .field("squares", &self.squares)
             .field("chain", &self.chain)
             .finish()
@@ -477,6 +753,38 @@ impl<C: MoveChain + Debug> fmt::Display for Board<C> {
             }
             writeln!(f)?;
         }
+        if self.variant == Variant::Crazyhouse {
+            write!(f, "pocket: ")?;
+            for p in Piece::WhiteQueen.to(Piece::BlackPawn) {
+                for _ in 0..self.pocket.pieces[p] {
+                    write!(f, "{}", p.to_char())?;
+                }
+            }
+            writeln!(f)?;
+        }
         Ok(())
     }
 }
+
+/// Returns the material balance in centipawns from the perspective of the side to move, from
+/// the [values](Piece::value) of the pieces on the board and in the pockets.
+///
+/// Much cheaper than a full evaluation, for scoring positions where only the material matters.
+pub fn material_eval<C: MoveChain>(board: &Board<C>) -> i32 {
+    let balance: i32 = Piece::WhiteKing
+        .to(Piece::BlackPawn)
+        .map(|p| {
+            let count = board.pieces[p].count() as i32 + board.pocket.pieces[p] as i32;
+            let value = count * p.value() * 100;
+            if p.white() {
+                value
+            } else {
+                -value
+            }
+        })
+        .sum();
+    match board.state.player {
+        Player::White => balance,
+        Player::Black => -balance,
+    }
+}
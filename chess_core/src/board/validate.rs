@@ -0,0 +1,233 @@
+//! Consistency checks of a board.
+
+use super::{Board, MoveChain, Variant};
+use crate::{
+    bb::BB,
+    gen::{CheckersError, MoveGenerator},
+    ExtraState, Piece, Player, Square,
+};
+use std::{error::Error, fmt};
+
+/// A way in which a board is not a valid position.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum BoardError {
+    /// The player doesn't have exactly one king.
+    KingCount { player: Player, count: u8 },
+    /// The bitboards of two pieces share a square.
+    Overlap { a: Piece, b: Piece, squares: BB },
+    /// The square contains the piece but the bitboard of the piece doesn't.
+    MissingFromBitboard { square: Square, piece: Piece },
+    /// The bitboard of the piece contains the square but the square is empty.
+    MissingFromSquare { square: Square, piece: Piece },
+    /// A pawn is on the first or last rank, where it can never be.
+    PawnOnBackRank { square: Square },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::KingCount { player, count } => {
+                write!(f, "{:?} has {} kings instead of one", player, count)
+            }
+            BoardError::Overlap { a, b, squares } => {
+                write!(
+                    f,
+                    "bitboards of {:?} and {:?} overlap:\n{:?}",
+                    a, b, squares
+                )
+            }
+            BoardError::MissingFromBitboard { square, piece } => write!(
+                f,
+                "square {} contains {:?} but its bitboard does not",
+                square, piece
+            ),
+            BoardError::MissingFromSquare { square, piece } => write!(
+                f,
+                "bitboard of {:?} contains square {} but the square is empty",
+                piece, square
+            ),
+            BoardError::PawnOnBackRank { square } => write!(f, "pawn on back rank {}", square),
+        }
+    }
+}
+
+/// A way in which a board is not a position which can be reached in a game.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PositionError {
+    /// The board is not internally consistent, see [`Board::validate`].
+    Invalid(BoardError),
+    /// The king of the player who just moved is still in check.
+    SideNotToMoveInCheck { player: Player },
+    /// The king of the player to move is checked in a way no move could have caused.
+    Checkers(CheckersError),
+    /// The player has more than 8 pawns.
+    TooManyPawns { player: Player, count: u8 },
+    /// The player has more pieces beyond those of the start position than pawns which could
+    /// have promoted.
+    TooManyPieces {
+        player: Player,
+        promoted: u8,
+        pawns: u8,
+    },
+    /// A castle right of [`ExtraState`] without the king and rook on their start squares.
+    ImpossibleCastle { castle: u8 },
+    /// No pawn can have just moved two squares past the en passant square.
+    InvalidEnPassant { square: Square },
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::Invalid(e) => e.fmt(f),
+            PositionError::SideNotToMoveInCheck { player } => {
+                write!(f, "{:?} is in check while it is not their turn", player)
+            }
+            PositionError::Checkers(e) => write!(f, "impossible check: {}", e),
+            PositionError::TooManyPawns { player, count } => {
+                write!(f, "{:?} has {} pawns, more than 8", player, count)
+            }
+            PositionError::TooManyPieces {
+                player,
+                promoted,
+                pawns,
+            } => write!(
+                f,
+                "{:?} has {} promoted pieces but only {} of its pawns are missing",
+                player,
+                promoted,
+                8 - pawns
+            ),
+            PositionError::ImpossibleCastle { castle } => write!(
+                f,
+                "castle rights {:#06b} without the king and rook on their start squares",
+                castle
+            ),
+            PositionError::InvalidEnPassant { square } => {
+                write!(f, "no pawn can be taken en passant on {}", square)
+            }
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+impl<C: MoveChain> Board<C> {
+    /// Returns all the ways in which the board is not a valid position, empty if it is valid.
+    pub fn validate(&self) -> Vec<BoardError> {
+        let mut res = Vec::new();
+
+        for (player, king) in [
+            (Player::White, Piece::WhiteKing),
+            (Player::Black, Piece::BlackKing),
+        ] {
+            let count = self.pieces[king].count();
+            if count != 1 {
+                res.push(BoardError::KingCount { player, count });
+            }
+        }
+
+        for a in Piece::WhiteKing.to(Piece::BlackPawn) {
+            for b in Piece::WhiteKing.to(a) {
+                if a == b {
+                    continue;
+                }
+                let squares = self.pieces[a] & self.pieces[b];
+                if squares.any() {
+                    res.push(BoardError::Overlap { a, b, squares });
+                }
+            }
+        }
+
+        let pawns = self.pieces[Piece::WhitePawn] | self.pieces[Piece::BlackPawn];
+        for square in (pawns & (BB::RANK_1 | BB::RANK_8)).iter() {
+            res.push(BoardError::PawnOnBackRank { square });
+        }
+
+        for s in 0..64 {
+            let square = Square::new(s);
+            if let Some(piece) = self.squares[square] {
+                if (self.pieces[piece] & BB::square(square)).none() {
+                    res.push(BoardError::MissingFromBitboard { square, piece });
+                }
+            } else {
+                for piece in Piece::WhiteKing.to(Piece::BlackPawn) {
+                    if (self.pieces[piece] & BB::square(square)).any() {
+                        res.push(BoardError::MissingFromSquare { square, piece });
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Checks whether the given position is valid.
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    /// Checks whether the position can be reached in a game, for positions from users before
+    /// they are searched.
+    ///
+    /// This is stronger than [`is_valid`](Self::is_valid), which only checks whether the board
+    /// is consistent. Returns the first problem found. The amount of material is not checked in
+    /// crazyhouse, where dropped pieces come from the opponent.
+    pub fn is_legal_position(&self, gen: &MoveGenerator) -> Result<(), PositionError> {
+        if let Some(e) = self.validate().first() {
+            return Err(PositionError::Invalid(*e));
+        }
+
+        let waiting = self.state.player.flip();
+        if gen.king_attacked(self, waiting) {
+            return Err(PositionError::SideNotToMoveInCheck { player: waiting });
+        }
+        gen.validate_checkers(self, &gen.gen_info(self))
+            .map_err(PositionError::Checkers)?;
+
+        if self.variant != Variant::Crazyhouse {
+            for player in [Player::White, Player::Black] {
+                let pawns = self.pieces[Piece::player_pawn(player)].count();
+                if pawns > 8 {
+                    return Err(PositionError::TooManyPawns {
+                        player,
+                        count: pawns,
+                    });
+                }
+                let promoted: u8 = [
+                    (Piece::player_queen(player), 1),
+                    (Piece::player_rook(player), 2),
+                    (Piece::player_bishop(player), 2),
+                    (Piece::player_knight(player), 2),
+                ]
+                .iter()
+                .map(|&(piece, start)| self.pieces[piece].count().saturating_sub(start))
+                .sum();
+                if promoted > 8 - pawns {
+                    return Err(PositionError::TooManyPieces {
+                        player,
+                        promoted,
+                        pawns,
+                    });
+                }
+            }
+        }
+
+        let castle = self.state.castle & !Self::possible_castle_rights(self);
+        if castle != 0 {
+            return Err(PositionError::ImpossibleCastle { castle });
+        }
+
+        if self.state.en_passant != ExtraState::INVALID_ENPASSANT {
+            let rank = match self.state.player {
+                Player::White => 5,
+                Player::Black => 2,
+            };
+            let square = Square::from_file_rank(self.state.en_passant, rank);
+            if !Self::valid_en_passant(self, square) {
+                return Err(PositionError::InvalidEnPassant { square });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{bb::BB, util::PieceArray, Piece, Player};
+
+/// The rule set a board is played with.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Captured pieces switch color and go into the capturing player's pocket,
+    /// from which they can be dropped back onto the board.
+    Crazyhouse,
+}
+
+/// The pieces in hand for both players in a crazyhouse game.
+///
+/// Always empty when playing standard chess.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Pocket {
+    /// Amount of pieces in hand, indexed by the piece as it would be dropped.
+    pub pieces: PieceArray<u8>,
+    /// Squares containing pieces which are the result of a promotion.
+    /// These pieces revert to pawns when captured.
+    pub promoted: BB,
+}
+
+impl Pocket {
+    pub const fn empty() -> Self {
+        Pocket {
+            pieces: PieceArray::new_array([0; 12]),
+            promoted: BB::EMPTY,
+        }
+    }
+
+    /// Returns the amount of a piece in hand.
+    #[inline]
+    pub fn count(&self, piece: Piece) -> u8 {
+        self.pieces[piece]
+    }
+
+    /// Returns whether neither player has any pieces in hand.
+    pub fn is_empty(&self) -> bool {
+        Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .all(|p| self.pieces[p] == 0)
+    }
+
+    /// Returns whether the given player has any pieces in hand.
+    pub fn player_has_pieces(&self, player: Player) -> bool {
+        Piece::player_pieces(player == Player::Black).any(|p| self.pieces[p] != 0)
+    }
+}
+
+impl Default for Pocket {
+    fn default() -> Self {
+        Pocket::empty()
+    }
+}
@@ -0,0 +1,111 @@
+//! Chess clocks for games played with a time control.
+//!
+//! The clock doesn't read the time itself, the current time is passed in on every call so the
+//! bookkeeping can be driven by a game loop or a test alike.
+
+use crate::Player;
+use std::time::{Duration, Instant};
+
+/// The clocks of both players in a game.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    white: Duration,
+    black: Duration,
+    increment: Duration,
+    /// The player whose clock is running and since when.
+    running: Option<(Player, Instant)>,
+    /// The player who ran out of time before making a move.
+    flagged: Option<Player>,
+}
+
+impl Clock {
+    /// Create a clock where both players start with the given time and receive the increment
+    /// after every move.
+    pub fn new(initial: Duration, increment: Duration) -> Self {
+        Clock {
+            white: initial,
+            black: initial,
+            increment,
+            running: None,
+            flagged: None,
+        }
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    /// Returns the player whose clock is running.
+    pub fn running(&self) -> Option<Player> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// Start the clock of the given player, stopping the other clock without an increment.
+    pub fn start(&mut self, player: Player, now: Instant) {
+        self.stop(now);
+        self.running = Some((player, now));
+    }
+
+    /// Stop the running clock without adding the increment.
+    pub fn stop(&mut self, now: Instant) {
+        if let Some((player, since)) = self.running.take() {
+            let used = now.saturating_duration_since(since);
+            let time = self.time_mut(player);
+            let flagged = used > *time;
+            *time = time.saturating_sub(used);
+            if flagged {
+                self.flagged = Some(player);
+            }
+        }
+    }
+
+    /// The running player made a move: their clock is stopped and receives the increment, and
+    /// the clock of the opponent is started.
+    ///
+    /// A player who made the move after their time ran out doesn't receive the increment and
+    /// stays flagged.
+    pub fn press(&mut self, now: Instant) {
+        let Some((player, _)) = self.running else {
+            return;
+        };
+        self.stop(now);
+        if self.flagged != Some(player) {
+            let increment = self.increment;
+            *self.time_mut(player) += increment;
+        }
+        self.running = Some((player.flip(), now));
+    }
+
+    /// Returns the time the given player has left.
+    pub fn remaining(&self, player: Player, now: Instant) -> Duration {
+        let time = match player {
+            Player::White => self.white,
+            Player::Black => self.black,
+        };
+        match self.running {
+            Some((running, since)) if running == player => {
+                time.saturating_sub(now.saturating_duration_since(since))
+            }
+            _ => time,
+        }
+    }
+
+    /// Returns the player who ran out of time, if any.
+    pub fn flagged(&self, now: Instant) -> Option<Player> {
+        self.flagged.or_else(|| {
+            let (player, since) = self.running?;
+            let time = match player {
+                Player::White => self.white,
+                Player::Black => self.black,
+            };
+            (now.saturating_duration_since(since) > time).then_some(player)
+        })
+    }
+
+    fn time_mut(&mut self, player: Player) -> &mut Duration {
+        match player {
+            Player::White => &mut self.white,
+            Player::Black => &mut self.black,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! A interface for an chess engine
 
 use crate::{Board, Move};
-use std::{collections::HashMap, time::Duration};
+use std::{fmt, time::Duration};
 
 mod thread;
 pub use thread::{EngineThread, Response, ThreadController};
@@ -9,7 +9,10 @@ pub use thread::{EngineThread, Response, ThreadController};
 #[derive(Debug)]
 pub enum Info {
     // A best move found
-    BestMove { mov: Move, value: i32 },
+    BestMove {
+        mov: Move,
+        value: i32,
+    },
     // Engine has moved on to new depth
     Depth(u16),
     // New Principle variation
@@ -22,23 +25,139 @@ pub enum Info {
     TransHit(usize),
     // Engine completed a round
     Round,
+    // Periodic statistics of a running search
+    Progress {
+        nodes: usize,
+        nps: f32,
+        time: Duration,
+        // Permille of the transposition table in use
+        hashfull: u16,
+        tbhits: usize,
+    },
+    // Statistics of a completed iteration of the search
+    Stats {
+        depth: u16,
+        // Deepest ply reached, including the quiescence search
+        seldepth: u16,
+        nodes: usize,
+        nps: f32,
+        time: Duration,
+        // Permille of the transposition table in use
+        hashfull: u16,
+        // Score from the perspective of the side to move
+        score: Score,
+        // Expected outcome from the perspective of the side to move, if the engine reports it
+        wdl: Option<Wdl>,
+        pv: Vec<Move>,
+    },
+    // Message for the user, like statistics requested with an option
+    String(String),
+    // Details of the search, only shown to the user in debug mode
     Debug(String),
 }
 
-#[derive(Clone)]
+/// The evaluation of a position reported by an engine.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Score {
+    /// Score in centipawns.
+    Cp(i32),
+    /// Moves until mate, negative when the side to move is getting mated.
+    Mate(i32),
+}
+
+/// Formats the score as in the uci protocol, `cp 35` or `mate -3`.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Cp(x) => write!(f, "cp {}", x),
+            Score::Mate(x) => write!(f, "mate {}", x),
+        }
+    }
+}
+
+/// The chances of winning, drawing and losing a position in permil, reported by an engine.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Wdl {
+    pub win: u16,
+    pub draw: u16,
+    pub loss: u16,
+}
+
+/// Formats the chances as in the uci protocol, `wdl 250 700 50`.
+impl fmt::Display for Wdl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wdl {} {} {}", self.win, self.draw, self.loss)
+    }
+}
+
+/// The version of an engine, reported in the uci handshake to tell builds apart.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Formats the version as `v1.2.3`.
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The kind of an option an engine can be configured with, along with its default value.
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum OptionKind {
-    Check,
+    Check {
+        default: bool,
+    },
+    /// A number, the full range of an `i32` if there is no `min` or `max`.
     Spin {
         default: i32,
         min: Option<i32>,
         max: Option<i32>,
     },
-    Combo(Vec<String>),
+    /// One of a list of choices, set with [`OptionValue::Combo`] as the index of the choice.
+    Combo {
+        default: String,
+        choices: Vec<String>,
+    },
     Button,
-    String,
+    /// Text which can contain spaces, an empty default is shown as `<empty>`.
+    String {
+        default: String,
+    },
 }
 
-#[derive(Clone)]
+impl OptionKind {
+    /// A string option without a default.
+    pub fn string() -> Self {
+        OptionKind::String {
+            default: String::new(),
+        }
+    }
+
+    /// A combo option with the given choices, the first of which is the default.
+    pub fn combo<S: AsRef<str>>(choices: &[S]) -> Self {
+        let choices: Vec<String> = choices.iter().map(|x| x.as_ref().to_string()).collect();
+        OptionKind::Combo {
+            default: choices.first().cloned().unwrap_or_default(),
+            choices,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum OptionValue {
     Check(bool),
     Spin(i32),
@@ -64,11 +183,15 @@ impl EngineControl for NoControl {
     fn info(&self, _: Info) {}
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Debug)]
 pub struct EngineLimit {
     pub depth: Option<u32>,
     pub nodes: Option<u64>,
     pub time: Option<Duration>,
+    /// Search for a mate in this many moves.
+    pub mate: Option<u32>,
+    /// Only search these moves in the root position.
+    pub search_moves: Option<Vec<Move>>,
 }
 
 impl EngineLimit {
@@ -93,15 +216,36 @@ impl EngineLimit {
         }
     }
 
+    pub fn mate(moves: u32) -> Self {
+        EngineLimit {
+            mate: Some(moves),
+            ..Default::default()
+        }
+    }
+
     pub fn none() -> Self {
         Default::default()
     }
 
+    /// Returns how long the search may run, given the time left on the clock of the engine.
+    ///
+    /// Without a time limit a thirtieth of the time left is used.
+    pub fn search_time(&self, time_left: Option<Duration>) -> Option<Duration> {
+        match (self.time, time_left) {
+            (None, None) => None,
+            (Some(x), None) => Some(x),
+            (None, Some(x)) => Some(x / 30),
+            (Some(a), Some(b)) => Some(a.min(b / 30)),
+        }
+    }
+
     pub fn or(&self, other: EngineLimit) -> Self {
         EngineLimit {
             depth: self.depth.or(other.depth),
             nodes: self.nodes.or(other.nodes),
             time: self.time.or(other.time),
+            mate: self.mate.or(other.mate),
+            search_moves: self.search_moves.clone().or(other.search_moves),
         }
     }
 }
@@ -109,6 +253,8 @@ impl EngineLimit {
 pub trait Engine<C: EngineControl>: 'static {
     const AUTHOR: &'static str = "Mees Delzenne";
     const NAME: &'static str;
+    /// The version of the engine, if it has one.
+    const VERSION: Option<Version> = None;
 
     /// Run the search
     fn go(&mut self, control: C, time_left: Option<Duration>, limit: EngineLimit) -> Option<Move>;
@@ -132,9 +278,9 @@ pub trait Engine<C: EngineControl>: 'static {
     /// Only a hint
     fn new_game(&mut self) {}
 
-    /// Get the options
-    fn options(&self) -> HashMap<String, OptionKind> {
-        HashMap::new()
+    /// Get the options, in the order they should be shown to the user.
+    fn options(&self) -> Vec<(String, OptionKind)> {
+        Vec::new()
     }
 
     /// Set an option
@@ -1,12 +1,16 @@
 use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
 
 use crate::{
+    affinity,
+    board::EndChain,
     engine::{Engine, EngineControl, EngineLimit, Info, OptionValue},
     Board, Move,
 };
@@ -16,6 +20,9 @@ enum Cmd {
     SetOption(String, OptionValue),
     SetBoard(Board),
     MakeMove(Move),
+    NewGame,
+    Affinity(Vec<usize>),
+    Priority(i32),
     Go {
         time_left: Option<Duration>,
         limits: EngineLimit,
@@ -57,11 +64,45 @@ pub struct EngineThread {
     reciever: Receiver<Response>,
     cmd_send: Sender<Cmd>,
     controller: Arc<ThreadControllerInner>,
+    restarts: Arc<AtomicUsize>,
+}
+
+/// Returns the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(x) = payload.downcast_ref::<&str>() {
+        x.to_string()
+    } else if let Some(x) = payload.downcast_ref::<String>() {
+        x.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
 
 /// A struct for running an engine on a different thread.
+///
+/// If the engine panics the panic is reported as an [`Info::Debug`], a search in progress
+/// finishes with [`Response::Done(None)`](Response::Done) and the engine is recovered by
+/// replaying the last position, the moves made since and the last value of every option.
 impl EngineThread {
-    pub fn new<E>(mut engine: E) -> Self
+    /// Run the engine on a thread. After a panic the same engine instance keeps being used.
+    pub fn new<E>(engine: E) -> Self
+    where
+        E: Engine<ThreadController> + Send + 'static,
+    {
+        Self::spawn(engine, None)
+    }
+
+    /// Run the engine on a thread, replacing it with a fresh instance from `factory` after a
+    /// panic.
+    pub fn with_factory<E, F>(engine: E, factory: F) -> Self
+    where
+        E: Engine<ThreadController> + Send + 'static,
+        F: FnMut() -> E + Send + 'static,
+    {
+        Self::spawn(engine, Some(Box::new(factory)))
+    }
+
+    fn spawn<E>(mut engine: E, mut factory: Option<Box<dyn FnMut() -> E + Send>>) -> Self
     where
         E: Engine<ThreadController> + Send + 'static,
     {
@@ -72,23 +113,84 @@ impl EngineThread {
             quit: AtomicBool::new(false),
             sender,
         });
+        let restarts = Arc::new(AtomicUsize::new(0));
 
         let controller_move = controller.clone();
+        let restarts_move = restarts.clone();
         std::thread::spawn(move || {
+            // The position, the moves made from it and the last value of every option given to
+            // the engine, replayed after a panic.
+            let mut start = Board::start_position(EndChain);
+            let mut moves = Vec::new();
+            let mut options: Vec<(String, OptionValue)> = Vec::new();
+
             while let Some(x) = cmd_recv.recv().ok() {
-                match x {
+                let is_go = matches!(x, Cmd::Go { .. });
+                let res = panic::catch_unwind(AssertUnwindSafe(|| match x {
                     Cmd::SetBoard(b) => {
+                        start = b.clone();
+                        moves.clear();
                         engine.set_board(b);
                     }
                     Cmd::MakeMove(m) => {
+                        moves.push(m);
                         engine.make_move(m);
                     }
-                    Cmd::SetOption(name, value) => engine.set_option(name, value),
+                    Cmd::NewGame => {
+                        start = Board::start_position(EndChain);
+                        moves.clear();
+                        engine.new_game()
+                    }
+                    Cmd::SetOption(name, value) => {
+                        match options.iter_mut().find(|(x, _)| *x == name) {
+                            Some(x) => x.1 = value.clone(),
+                            None => options.push((name.clone(), value.clone())),
+                        }
+                        engine.set_option(name, value)
+                    }
+                    Cmd::Affinity(cpus) => {
+                        if let Err(e) = affinity::set_thread_affinity(&cpus) {
+                            let info = Info::String(e.to_string());
+                            controller_move.sender.send(Response::Info(info)).ok();
+                        }
+                    }
+                    Cmd::Priority(nice) => {
+                        if let Err(e) = affinity::set_thread_priority(nice) {
+                            let info = Info::String(e.to_string());
+                            controller_move.sender.send(Response::Info(info)).ok();
+                        }
+                    }
                     Cmd::Go { limits, time_left } => {
                         let res =
                             engine.go(ThreadController(controller_move.clone()), time_left, limits);
                         controller_move.sender.send(Response::Done(res)).ok();
                     }
+                }));
+
+                if let Err(payload) = res {
+                    if let Some(factory) = factory.as_mut() {
+                        engine = factory();
+                    }
+                    for (name, value) in options.iter() {
+                        engine.set_option(name.clone(), value.clone());
+                    }
+                    // Replay the moves so the engine knows the positions which were repeated.
+                    engine.set_board(start.clone());
+                    for m in moves.iter() {
+                        engine.make_move(*m);
+                    }
+                    restarts_move.fetch_add(1, Ordering::AcqRel);
+
+                    controller_move
+                        .sender
+                        .send(Response::Info(Info::Debug(format!(
+                            "engine panicked: {}",
+                            panic_message(&*payload)
+                        ))))
+                        .ok();
+                    if is_go {
+                        controller_move.sender.send(Response::Done(None)).ok();
+                    }
                 }
             }
         });
@@ -97,9 +199,15 @@ impl EngineThread {
             reciever,
             controller,
             cmd_send,
+            restarts,
         }
     }
 
+    /// Returns how often the engine was recovered after a panic.
+    pub fn restarts(&self) -> usize {
+        self.restarts.load(Ordering::Acquire)
+    }
+
     pub fn start(&self, time_left: Option<Duration>, limits: EngineLimit) {
         self.controller.quit.store(false, Ordering::Release);
         self.cmd_send.send(Cmd::Go { time_left, limits }).unwrap();
@@ -124,4 +232,22 @@ impl EngineThread {
     pub fn make_move(&self, m: Move) {
         self.cmd_send.send(Cmd::MakeMove(m)).unwrap();
     }
+
+    pub fn new_game(&self) {
+        self.cmd_send.send(Cmd::NewGame).unwrap();
+    }
+
+    /// Restrict the thread the engine searches on to the given cpus.
+    ///
+    /// Failing to do so is reported as an [`Info::String`].
+    pub fn set_affinity(&self, cpus: Vec<usize>) {
+        self.cmd_send.send(Cmd::Affinity(cpus)).unwrap();
+    }
+
+    /// Set the nice level of the thread the engine searches on.
+    ///
+    /// Failing to do so is reported as an [`Info::String`].
+    pub fn set_priority(&self, nice: i32) {
+        self.cmd_send.send(Cmd::Priority(nice)).unwrap();
+    }
 }
@@ -27,9 +27,9 @@ impl ExtraState {
         }
     }
 
-    pub fn flip(mut self) -> Self {
+    /// Returns the state of the vertically mirrored position, with colors swapped.
+    pub fn mirror(mut self) -> Self {
         self.castle = ((0b11) & self.castle) << 2 | ((0b11 << 2) & self.castle) >> 2;
-        self.en_passant = 7 - self.en_passant;
         self.player = self.player.flip();
         self
     }
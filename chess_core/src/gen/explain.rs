@@ -0,0 +1,220 @@
+//! Explanations of why a move is illegal, for giving feedback to human players.
+
+use super::{Black, MoveGenerator, Player, White};
+use crate::{bb::BB, board::MoveChain, Board, ExtraState, Square};
+use std::fmt;
+
+/// The reason a move from one square to another is not allowed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum IllegalReason {
+    /// There is no piece on the square moved from.
+    NoPiece,
+    /// The piece moved belongs to the opponent.
+    NotYourPiece,
+    /// The square moved to is occupied by a piece of the same player.
+    CaptureOwnPiece,
+    /// The piece doesn't move in that way.
+    WrongPieceMovement,
+    /// Another piece is in the way.
+    BlockedPath,
+    /// After the move the king is attacked by the piece on the given square.
+    LeavesKingInCheck { by: Square },
+    /// The player has lost the right to castle to that side.
+    NoCastleRights,
+    /// The king is in check by the piece on the given square, so it can't castle.
+    CastleWhileInCheck { by: Square },
+    /// The king passes or lands on a square attacked by the piece on the given square.
+    CastleThroughCheck { by: Square },
+}
+
+impl IllegalReason {
+    /// Returns the square of the piece attacking the king, if the reason involves one.
+    pub fn attacker(self) -> Option<Square> {
+        match self {
+            IllegalReason::LeavesKingInCheck { by }
+            | IllegalReason::CastleWhileInCheck { by }
+            | IllegalReason::CastleThroughCheck { by } => Some(by),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IllegalReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalReason::NoPiece => write!(f, "there is no piece to move"),
+            IllegalReason::NotYourPiece => write!(f, "that piece belongs to the opponent"),
+            IllegalReason::CaptureOwnPiece => write!(f, "you can't capture your own piece"),
+            IllegalReason::WrongPieceMovement => write!(f, "that piece doesn't move like that"),
+            IllegalReason::BlockedPath => write!(f, "another piece is in the way"),
+            IllegalReason::LeavesKingInCheck { by } => {
+                write!(f, "your king would be in check from {}", by)
+            }
+            IllegalReason::NoCastleRights => write!(f, "you can no longer castle to that side"),
+            IllegalReason::CastleWhileInCheck { by } => {
+                write!(f, "you can't castle while in check from {}", by)
+            }
+            IllegalReason::CastleThroughCheck { by } => {
+                write!(f, "you can't castle through check from {}", by)
+            }
+        }
+    }
+}
+
+impl MoveGenerator {
+    /// Explain why moving the piece on `from` to `to` is illegal.
+    ///
+    /// The legality checks are relaxed one at a time, so the reason returned is the most basic
+    /// rule the move breaks. Returns `None` if the move is legal.
+    pub fn explain_illegal<C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        from: Square,
+        to: Square,
+    ) -> Option<IllegalReason> {
+        match b.state.player {
+            crate::Player::White => self.explain_illegal_player::<White, C>(b, from, to),
+            crate::Player::Black => self.explain_illegal_player::<Black, C>(b, from, to),
+        }
+    }
+
+    fn explain_illegal_player<P: Player, C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        from: Square,
+        to: Square,
+    ) -> Option<IllegalReason> {
+        let piece = match b.on(from) {
+            Some(x) => x,
+            None => return Some(IllegalReason::NoPiece),
+        };
+        if piece.player() != P::PLAYER {
+            return Some(IllegalReason::NotYourPiece);
+        }
+
+        let info = self.gen_info(b);
+        if from == to || (info.my & BB::square(to)).any() {
+            return Some(IllegalReason::CaptureOwnPiece);
+        }
+
+        if piece == P::KING
+            && from == P::CASTLE_FROM
+            && (to == P::CASTLE_KING_TO || to == P::CASTLE_QUEEN_TO)
+        {
+            return self.explain_castle::<P, C>(b, to, info.occupied);
+        }
+
+        let king_sq = b.pieces[P::KING].first_piece();
+        let mut captured = BB::square(to) & info.their;
+        let moves = if piece == P::PAWN {
+            let forward = from + P::PAWN_MOVE.as_offset();
+            let attacks = BB::square(from).pawn_attacks(P::PLAYER);
+            if to == forward {
+                if (info.occupied & BB::square(to)).any() {
+                    return Some(IllegalReason::BlockedPath);
+                }
+            } else if (BB::square(from).shift(P::PAWN_MOVE) & P::RANK_3).any()
+                && to == forward + P::PAWN_MOVE.as_offset()
+            {
+                if (info.occupied & (BB::square(forward) | BB::square(to))).any() {
+                    return Some(IllegalReason::BlockedPath);
+                }
+            } else if (attacks & BB::square(to)).any() {
+                let en_passant = b.state.en_passant != ExtraState::INVALID_ENPASSANT
+                    && (BB::square(from) & P::RANK_5).any()
+                    && to
+                        == Square::from_file_rank(b.state.en_passant, from.rank())
+                            + P::PAWN_MOVE.as_offset();
+                if en_passant {
+                    captured = BB::square(to - P::PAWN_MOVE.as_offset());
+                } else if captured.none() {
+                    return Some(IllegalReason::WrongPieceMovement);
+                }
+            } else {
+                return Some(IllegalReason::WrongPieceMovement);
+            }
+            BB::square(to)
+        } else {
+            let (empty, moves) = if piece == P::KING {
+                let x = self.tables.king_attacks(from);
+                (x, x)
+            } else if piece == P::KNIGHT {
+                let x = self.tables.knight_attacks(from);
+                (x, x)
+            } else {
+                let mut empty = BB::EMPTY;
+                let mut moves = BB::EMPTY;
+                if piece == P::BISHOP || piece == P::QUEEN {
+                    empty |= self.tables.bishop_attacks(from, BB::EMPTY);
+                    moves |= self.tables.bishop_attacks(from, info.occupied);
+                }
+                if piece == P::ROOK || piece == P::QUEEN {
+                    empty |= self.tables.rook_attacks(from, BB::EMPTY);
+                    moves |= self.tables.rook_attacks(from, info.occupied);
+                }
+                (empty, moves)
+            };
+            if (empty & BB::square(to)).none() {
+                return Some(IllegalReason::WrongPieceMovement);
+            }
+            moves
+        };
+        if (moves & BB::square(to)).none() {
+            return Some(IllegalReason::BlockedPath);
+        }
+
+        // The move is possible for the piece, so the only remaining reason is the king being
+        // attacked after it.
+        let king_sq = if piece == P::KING { to } else { king_sq };
+        let occupied = (info.occupied ^ BB::square(from) ^ captured) | BB::square(to);
+        let attackers = self.attackers::<P, C>(b, king_sq, occupied) & !captured;
+        if attackers.any() {
+            return Some(IllegalReason::LeavesKingInCheck {
+                by: attackers.first_piece(),
+            });
+        }
+        None
+    }
+
+    fn explain_castle<P: Player, C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        to: Square,
+        occupied: BB,
+    ) -> Option<IllegalReason> {
+        let from = P::CASTLE_FROM;
+        let (flag, rook) = if to == P::CASTLE_KING_TO {
+            (
+                ExtraState::WHITE_KING_CASTLE << P::FLAG_SHIFT,
+                Square::from_file_rank(7, from.rank()),
+            )
+        } else {
+            (
+                ExtraState::WHITE_QUEEN_CASTLE << P::FLAG_SHIFT,
+                Square::from_file_rank(0, from.rank()),
+            )
+        };
+        if b.state.castle & flag == 0 {
+            return Some(IllegalReason::NoCastleRights);
+        }
+        if (self.tables.between(from, rook) & occupied).any() {
+            return Some(IllegalReason::BlockedPath);
+        }
+
+        let attackers = self.attackers::<P, C>(b, from, occupied);
+        if attackers.any() {
+            return Some(IllegalReason::CastleWhileInCheck {
+                by: attackers.first_piece(),
+            });
+        }
+        for sq in self.tables.between(from, to) | BB::square(to) {
+            let attackers = self.attackers::<P, C>(b, sq, occupied);
+            if attackers.any() {
+                return Some(IllegalReason::CastleThroughCheck {
+                    by: attackers.first_piece(),
+                });
+            }
+        }
+        None
+    }
+}
@@ -2,19 +2,29 @@
 
 use crate::{
     bb::BB,
-    board::{Board, MoveChain},
-    ExtraState, Move, Piece, Square,
+    board::{Board, MoveChain, Variant},
+    ExtraState, Move, Piece, Promotion, Square,
 };
 
 pub mod fill_7;
 
+mod explain;
+pub use explain::IllegalReason;
+
 mod types;
 pub use types::*;
 
 mod tables;
 use tables::Tables;
 
-use std::{mem::MaybeUninit, ptr};
+use std::{fmt, marker::PhantomData, mem::MaybeUninit, ptr};
+
+/// The size of a buffer which can hold all pseudo legal moves of any position, including the
+/// drops of crazyhouse.
+///
+/// The moves of the pieces on the board stay below 256, to which the drops of the four pieces
+/// on at most 62 empty squares and of pawns on at most 48 squares are added.
+pub const MAX_MOVES: usize = 256 + 4 * 62 + 48;
 
 /// A constant size buffer stored on the stack,
 /// Can be used for storing moves without allocation.
@@ -69,6 +79,80 @@ impl<const SIZE: usize, T: Copy> InlineBuffer<SIZE, T> {
             None
         }
     }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the initialized part of the buffer.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { &*(&self.moves[..self.len as usize] as *const [MaybeUninit<T>] as *const [T]) }
+    }
+
+    /// Returns the initialized part of the buffer.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { &mut *(&mut self.moves[..self.len as usize] as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+
+    /// Remove all values from the buffer, returning them in an iterator.
+    #[inline]
+    pub fn drain(&mut self) -> InlineIntoIter<SIZE, T> {
+        let res = InlineIntoIter {
+            buffer: *self,
+            cur: 0,
+        };
+        self.len = 0;
+        res
+    }
+}
+
+impl<const SIZE: usize, T: Copy> Default for InlineBuffer<SIZE, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const SIZE: usize, T: Copy> IntoIterator for &'a InlineBuffer<SIZE, T> {
+    type Item = T;
+    type IntoIter = InlineIter<'a, SIZE, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const SIZE: usize, T: Copy> IntoIterator for InlineBuffer<SIZE, T> {
+    type Item = T;
+    type IntoIter = InlineIntoIter<SIZE, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InlineIntoIter {
+            buffer: self,
+            cur: 0,
+        }
+    }
+}
+
+/// An iterator over the values of an owned buffer.
+pub struct InlineIntoIter<const SIZE: usize, T: Copy = Move> {
+    buffer: InlineBuffer<SIZE, T>,
+    cur: u16,
+}
+
+impl<const SIZE: usize, T: Copy> Iterator for InlineIntoIter<SIZE, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len == self.cur {
+            return None;
+        }
+        let res = unsafe { self.buffer.moves[self.cur as usize].assume_init() };
+        self.cur += 1;
+        Some(res)
+    }
 }
 
 pub struct InlineIter<'a, const SIZE: usize, T: Copy = Move> {
@@ -120,6 +204,15 @@ impl<const SIZE: usize> MoveList for InlineBuffer<SIZE> {
     }
 
     fn swap(&mut self, a: usize, b: usize) {
+        // Only the initialized part may be swapped, otherwise an uninitialized value could be
+        // moved into it.
+        assert!(
+            a < self.len as usize && b < self.len as usize,
+            "got idx: {} and {} while len is {}",
+            a,
+            b,
+            self.len
+        );
         self.moves.swap(a, b);
     }
 }
@@ -147,6 +240,7 @@ pub trait MoveList {
     fn truncate(&mut self, len: usize);
 
     /// Swap to moves at given index.
+    /// Should panic if either idx is outside bounds.
     fn swap(&mut self, a: usize, b: usize);
 }
 
@@ -180,6 +274,137 @@ impl MoveList for Vec<Move> {
     }
 }
 
+/// A move list which doesn't store moves, only counting how many were pushed.
+///
+/// Can only be used with generation which doesn't read moves back from the list, like the pseudo
+/// legal generation types, `get`, `set` and `swap` panic.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CountingSink {
+    count: usize,
+}
+
+impl CountingSink {
+    pub fn new() -> Self {
+        CountingSink { count: 0 }
+    }
+
+    /// Returns the amount of moves pushed.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl MoveList for CountingSink {
+    fn push(&mut self, _m: Move) {
+        self.count += 1;
+    }
+
+    fn get(&self, _idx: usize) -> Move {
+        panic!("a counting sink doesn't store moves")
+    }
+
+    fn set(&mut self, _idx: usize, _m: Move) {
+        panic!("a counting sink doesn't store moves")
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.count = self.count.min(len);
+    }
+
+    fn swap(&mut self, _a: usize, _b: usize) {
+        panic!("a counting sink doesn't store moves")
+    }
+}
+
+/// A list which only counts the pushed moves which are legal for `P`, used to count moves
+/// without storing them.
+struct LegalCount<'a, P, C: MoveChain> {
+    gen: &'a MoveGenerator,
+    board: &'a Board<C>,
+    info: &'a PositionInfo,
+    count: usize,
+    player: PhantomData<P>,
+}
+
+impl<'a, P: Player, C: MoveChain> MoveList for LegalCount<'a, P, C> {
+    fn push(&mut self, m: Move) {
+        if self.gen.is_legal_player::<P, C>(m, self.board, self.info) {
+            self.count += 1;
+        }
+    }
+
+    fn get(&self, _idx: usize) -> Move {
+        panic!("moves are only counted")
+    }
+
+    fn set(&mut self, _idx: usize, _m: Move) {
+        panic!("moves are only counted")
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.count = self.count.min(len);
+    }
+
+    fn swap(&mut self, _a: usize, _b: usize) {
+        panic!("moves are only counted")
+    }
+}
+
+/// Whether the game continues in a position, from the perspective of the player to move.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PositionStatus {
+    Normal,
+    Check,
+    Checkmate,
+    Stalemate,
+}
+
+/// A way in which the pieces checking the king of the player to move are impossible, a sign of
+/// a bug in the move generation or a broken board.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum CheckersError {
+    /// The king is on an attacked square without any checkers or the other way around.
+    Mismatch { attacked: BB, checkers: BB },
+    /// More than two pieces check the king.
+    TooMany { checkers: BB },
+    /// More than one pawn or knight checks the king, which no single move can discover.
+    PawnsOrKnights { checkers: BB },
+}
+
+impl fmt::Display for CheckersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckersError::Mismatch { attacked, checkers } => write!(
+                f,
+                "attacked squares disagree with the checkers\nattacked:\n{:?}\ncheckers:\n{:?}",
+                attacked, checkers
+            ),
+            CheckersError::TooMany { checkers } => {
+                write!(f, "more than two checkers:\n{:?}", checkers)
+            }
+            CheckersError::PawnsOrKnights { checkers } => {
+                write!(f, "more than one pawn or knight checker:\n{:?}", checkers)
+            }
+        }
+    }
+}
+
 /// Info about a position used in various move generation functions.
 pub struct PositionInfo {
     pub occupied: BB,
@@ -228,8 +453,7 @@ impl PositionInfo {
             attacked |= table.rook_attacks(b, occupied);
         }
 
-        attacked |= b.pieces[P::Opponent::PAWN].shift(P::Opponent::ATTACK_LEFT);
-        attacked |= b.pieces[P::Opponent::PAWN].shift(P::Opponent::ATTACK_RIGHT);
+        attacked |= b.pieces[P::Opponent::PAWN].pawn_attacks(P::Opponent::PLAYER);
 
         let mut pinners = Self::xray_rook_attacks(table, king_sq, occupied) & their_rooks;
         pinners |= Self::xray_bishop_attacks(table, king_sq, occupied) & their_bishops;
@@ -315,6 +539,55 @@ impl MoveGenerator {
         }
     }
 
+    /// Returns whether the player to move is mated, stalemated or in check.
+    pub fn position_status<M: MoveChain>(
+        &self,
+        b: &Board<M>,
+        info: &PositionInfo,
+    ) -> PositionStatus {
+        let check = self.checked_king(b, info);
+        match (self.has_legal_move(b, info), check) {
+            (true, false) => PositionStatus::Normal,
+            (true, true) => PositionStatus::Check,
+            (false, false) => PositionStatus::Stalemate,
+            (false, true) => PositionStatus::Checkmate,
+        }
+    }
+
+    #[inline]
+    pub fn has_legal_move<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> bool {
+        match b.state.player {
+            crate::Player::White => self.has_legal_move_player::<White, M>(b, info),
+            crate::Player::Black => self.has_legal_move_player::<Black, M>(b, info),
+        }
+    }
+
+    /// Returns the amount of legal moves in the position, without storing the moves.
+    pub fn legal_move_count<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> usize {
+        match b.state.player {
+            crate::Player::White => self.legal_move_count_player::<White, M>(b, info),
+            crate::Player::Black => self.legal_move_count_player::<Black, M>(b, info),
+        }
+    }
+
+    pub fn legal_move_count_player<P: Player, C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        info: &PositionInfo,
+    ) -> usize {
+        // Moves are checked for legality as they are generated, the pseudo legal moves don't
+        // need to be stored to be filtered afterwards.
+        let mut list = LegalCount::<P, C> {
+            gen: self,
+            board: b,
+            info,
+            count: 0,
+            player: PhantomData,
+        };
+        self.gen_moves_player_info::<P, gen_type::AllPseudo, _, C>(b, info, &mut list);
+        list.count
+    }
+
     #[inline]
     pub fn checked_king<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> bool {
         match b.state.player {
@@ -323,10 +596,97 @@ impl MoveGenerator {
         }
     }
 
+    /// Returns whether the king of the given player is attacked, whether or not it is their
+    /// turn.
+    pub fn king_attacked<M: MoveChain>(&self, b: &Board<M>, player: crate::Player) -> bool {
+        let occupied = Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .fold(BB::EMPTY, |acc, p| acc | b.pieces[p]);
+        let king = b.king_square(player);
+        match player {
+            crate::Player::White => self.is_attacked::<White, M>(b, king, occupied),
+            crate::Player::Black => self.is_attacked::<Black, M>(b, king, occupied),
+        }
+    }
+
+    /// Returns the pieces of the player to move which are pinned to their king.
+    #[inline]
+    pub fn pinned_pieces<M: MoveChain>(&self, _b: &Board<M>, info: &PositionInfo) -> BB {
+        info.blockers
+    }
+
+    /// Returns the pieces checking the king of the player to move.
+    pub fn checkers<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> BB {
+        let king_sq = b.king_square(b.state.player);
+        match b.state.player {
+            crate::Player::White => self.attackers::<White, M>(b, king_sq, info.occupied),
+            crate::Player::Black => self.attackers::<Black, M>(b, king_sq, info.occupied),
+        }
+    }
+
+    /// Checks whether the pieces checking the king of the player to move agree with the
+    /// attacked squares of the position info and could be the result of a single move.
+    ///
+    /// Move generation runs this check on every position when debug assertions are enabled.
+    pub fn validate_checkers<M: MoveChain>(
+        &self,
+        b: &Board<M>,
+        info: &PositionInfo,
+    ) -> Result<(), CheckersError> {
+        let king = b.pieces[Piece::player_king(b.state.player)];
+        let checkers = self.checkers(b, info);
+        if (info.attacked & king).any() != checkers.any() {
+            return Err(CheckersError::Mismatch {
+                attacked: info.attacked,
+                checkers,
+            });
+        }
+        if checkers.count() > 2 {
+            return Err(CheckersError::TooMany { checkers });
+        }
+        let opponent = b.state.player.flip();
+        let pawns_knights =
+            b.pieces[Piece::player_pawn(opponent)] | b.pieces[Piece::player_knight(opponent)];
+        if (checkers & pawns_knights).count() > 1 {
+            return Err(CheckersError::PawnsOrKnights { checkers });
+        }
+        Ok(())
+    }
+
+    /// Returns the squares between the king of the player to move and the sliders checking it.
+    ///
+    /// Empty when the king is not in check or only checked by knights, pawns or adjacent pieces.
+    pub fn check_rays<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> BB {
+        let king_sq = b.king_square(b.state.player);
+        let checkers = match b.state.player {
+            crate::Player::White => self.attackers::<White, M>(b, king_sq, info.occupied),
+            crate::Player::Black => self.attackers::<Black, M>(b, king_sq, info.occupied),
+        };
+        checkers
+            .iter()
+            .fold(BB::EMPTY, |acc, sq| acc | self.tables.between(sq, king_sq))
+    }
+
+    /// Returns the squares the piece on the given square can legally move to.
+    ///
+    /// Includes the square the king lands on when castling and the squares of en passant
+    /// captures and promotions. Empty if the square has no piece of the player to move.
+    pub fn legal_destinations<M: MoveChain>(&self, b: &Board<M>, from: Square) -> BB {
+        let mut moves = InlineBuffer::<MAX_MOVES>::new();
+        self.gen_moves::<gen_type::All, _, _>(b, &mut moves);
+        moves
+            .iter()
+            .filter(|m| !m.is_drop() && m.from() == from)
+            .fold(BB::EMPTY, |acc, m| acc | BB::square(m.to()))
+    }
+
     pub fn drawn<M: MoveChain>(&self, b: &Board<M>, info: &PositionInfo) -> bool {
-        if b.state.move_clock == 50 {
+        if b.is_fifty_move_draw() {
             return true;
         }
+        if !b.pocket.is_empty() {
+            return false;
+        }
         let piece_count = info.occupied.count();
         if piece_count > 5 {
             return false;
@@ -377,8 +737,7 @@ impl MoveGenerator {
             | self.tables.rook_attacks(king_sq, info.occupied)
                 & (b.pieces[P::Opponent::QUEEN] | b.pieces[P::Opponent::ROOK])
             | self.tables.knight_attacks(king_sq) & b.pieces[P::Opponent::KNIGHT]
-            | b.pieces[P::KING].shift(P::ATTACK_LEFT) & b.pieces[P::Opponent::PAWN]
-            | b.pieces[P::KING].shift(P::ATTACK_RIGHT) & b.pieces[P::Opponent::PAWN];
+            | b.pieces[P::KING].pawn_attacks(P::PLAYER) & b.pieces[P::Opponent::PAWN];
         attackers.count() > 0
     }
 
@@ -393,8 +752,7 @@ impl MoveGenerator {
             | self.tables.rook_attacks(king_sq, info.occupied)
                 & (b.pieces[P::Opponent::QUEEN] | b.pieces[P::Opponent::ROOK])
             | self.tables.knight_attacks(king_sq) & b.pieces[P::Opponent::KNIGHT]
-            | b.pieces[P::KING].shift(P::ATTACK_LEFT) & b.pieces[P::Opponent::PAWN]
-            | b.pieces[P::KING].shift(P::ATTACK_RIGHT) & b.pieces[P::Opponent::PAWN];
+            | b.pieces[P::KING].pawn_attacks(P::PLAYER) & b.pieces[P::Opponent::PAWN];
 
         let attackers_count = attackers.count();
 
@@ -447,7 +805,58 @@ impl MoveGenerator {
                 }
             }
         }
-        true
+        list.clear();
+        self.gen_drops::<P, _, _>(b, &mut list, blockers & !info.occupied);
+        list.len() == 0
+    }
+
+    /// Returns whether the player has a legal move.
+    ///
+    /// Moves are generated a kind of piece at a time, stopping at the first legal move.
+    pub fn has_legal_move_player<P: Player, C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        info: &PositionInfo,
+    ) -> bool {
+        let mut list = InlineBuffer::<MAX_MOVES>::new();
+        let any_legal = |list: &InlineBuffer<MAX_MOVES>| {
+            list.iter()
+                .any(|m| self.is_legal_player::<P, _>(m, b, info))
+        };
+
+        let target = !info.my;
+        if (info.attacked & b.pieces[P::KING]).any() {
+            self.gen_evasion::<P, gen_type::All, _, _>(b, info, &mut list, target);
+            return any_legal(&list);
+        }
+
+        let king_sq = b.pieces[P::KING].first_piece();
+        for s in self.tables.king_attacks(king_sq) & target {
+            list.push(Move::normal(king_sq, s));
+        }
+        if any_legal(&list) {
+            return true;
+        }
+        list.clear();
+        self.gen_moves_knight::<P, _, _>(b, &mut list, target);
+        if any_legal(&list) {
+            return true;
+        }
+        list.clear();
+        self.gen_pawn_moves::<P, _, _>(b, info, &mut list, target);
+        if any_legal(&list) {
+            return true;
+        }
+        list.clear();
+        self.gen_moves_sliders::<P, gen_type::All, _, _>(b, info, &mut list, target);
+        if any_legal(&list) {
+            return true;
+        }
+        // Castling is only possible if the king can already move to the square next to it, so
+        // only drops remain.
+        list.clear();
+        self.gen_drops::<P, _, _>(b, &mut list, !info.occupied);
+        any_legal(&list)
     }
 
     pub fn gen_moves_player<P: Player, T: GenType, M: MoveList, C: MoveChain>(
@@ -469,6 +878,11 @@ impl MoveGenerator {
     ) {
         let target = if T::QUIET { !info.my } else { info.their };
 
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_checkers(b, info) {
+            panic!("impossible checkers in {}: {}", b.to_fen(), e);
+        }
+
         if (info.attacked & b.pieces[P::KING]).any() {
             self.gen_evasion::<P, T, _, _>(b, &info, list, target);
         } else {
@@ -501,12 +915,17 @@ impl MoveGenerator {
             | self.tables.rook_attacks(king_sq, info.occupied)
                 & (b.pieces[P::Opponent::QUEEN] | b.pieces[P::Opponent::ROOK])
             | self.tables.knight_attacks(king_sq) & b.pieces[P::Opponent::KNIGHT]
-            | b.pieces[P::KING].shift(P::ATTACK_LEFT) & b.pieces[P::Opponent::PAWN]
-            | b.pieces[P::KING].shift(P::ATTACK_RIGHT) & b.pieces[P::Opponent::PAWN];
+            | b.pieces[P::KING].pawn_attacks(P::PLAYER) & b.pieces[P::Opponent::PAWN];
 
         let attackers_count = attackers.count();
 
-        assert!(attackers_count > 0);
+        debug_assert!(
+            attackers_count > 0,
+            "generating evasions without check in {}\nattacked:\n{:?}\ncheckers:\n{:?}",
+            b.to_fen(),
+            info.attacked,
+            attackers
+        );
 
         let mut king_moves = self.tables.king_attacks(king_sq) & target & !info.attacked;
         for p in attackers & !(b.pieces[P::Opponent::KNIGHT] | b.pieces[P::Opponent::PAWN]) {
@@ -530,6 +949,9 @@ impl MoveGenerator {
         self.gen_moves_sliders::<P, T, M, _>(b, info, list, blockers);
         self.gen_moves_knight::<P, M, _>(b, list, blockers);
         self.gen_pawn_moves::<P, M, _>(b, info, list, blockers);
+        if T::QUIET {
+            self.gen_drops::<P, M, _>(b, list, blockers & !info.occupied);
+        }
     }
 
     pub fn gen_moves_pseudo<P: Player, T: GenType, M: MoveList, C: MoveChain>(
@@ -547,8 +969,7 @@ impl MoveGenerator {
         {
             let mut target = target;
             if T::CHECKS {
-                target |= b.pieces[P::Opponent::KING].shift(P::Opponent::ATTACK_LEFT)
-                    | b.pieces[P::Opponent::KING].shift(P::Opponent::ATTACK_LEFT)
+                target |= b.pieces[P::Opponent::KING].pawn_attacks(P::Opponent::PLAYER)
             }
             self.gen_pawn_moves::<P, M, _>(b, info, list, target);
         }
@@ -564,6 +985,7 @@ impl MoveGenerator {
         self.gen_moves_sliders::<P, T, M, _>(b, info, list, target);
         if T::QUIET {
             self.gen_castle::<P, M, _>(b, info, list);
+            self.gen_drops::<P, M, _>(b, list, !info.occupied);
         }
     }
 
@@ -667,11 +1089,36 @@ impl MoveGenerator {
         }
     }
 
+    /// Generate crazyhouse drops from the pocket onto the target squares.
+    /// Dropping a piece can never leave the king in check so all drops onto the target are legal.
+    #[inline]
+    pub fn gen_drops<P: Player, M: MoveList, C: MoveChain>(
+        &self,
+        b: &Board<C>,
+        list: &mut M,
+        target: BB,
+    ) {
+        if b.variant != Variant::Crazyhouse {
+            return;
+        }
+        for p in Piece::player_promote_pieces(P::IS_BLACK) {
+            if b.pocket.pieces[p] != 0 {
+                for s in target {
+                    list.push(Move::drop(p, s));
+                }
+            }
+        }
+        if b.pocket.pieces[P::PAWN] != 0 {
+            for s in target & !(BB::RANK_1 | BB::RANK_8) {
+                list.push(Move::drop(P::PAWN, s));
+            }
+        }
+    }
+
     pub fn gen_promotions<M: MoveList>(&self, from: Square, to: Square, list: &mut M) {
-        list.push(Move::promotion(from, to, Move::PROMOTION_QUEEN));
-        list.push(Move::promotion(from, to, Move::PROMOTION_ROOK));
-        list.push(Move::promotion(from, to, Move::PROMOTION_KNIGHT));
-        list.push(Move::promotion(from, to, Move::PROMOTION_BISHOP));
+        for promotion in Promotion::ALL {
+            list.push(Move::promotion(from, to, promotion));
+        }
     }
 
     pub fn gen_moves_sliders<P: Player, T: GenType, M: MoveList, C: MoveChain>(
@@ -727,6 +1174,132 @@ impl MoveGenerator {
             }
         }
     }
+
+    /// Returns whether the move can be made by the player to move, ignoring whether it leaves
+    /// the king in check.
+    ///
+    /// Cheap enough to check a move from the transposition table before it is tried, where a
+    /// key collision can return a move of a different position. When the king is not in check,
+    /// together with [`MoveGenerator::is_legal`] this accepts exactly the generated moves.
+    pub fn is_pseudo_legal<C: MoveChain>(&self, m: Move, b: &Board<C>) -> bool {
+        if m == Move::INVALID {
+            return false;
+        }
+        let player = b.state.player;
+        if m.is_drop() {
+            let piece = m.drop_piece(player);
+            // Pawns can't be dropped on the first or last rank.
+            let back_rank = (BB::square(m.to()) & (BB::RANK_1 | BB::RANK_8)).any();
+            return b.variant == Variant::Crazyhouse
+                && b.pocket.pieces[piece] != 0
+                && b.on(m.to()).is_none()
+                && !(piece == Piece::player_pawn(player) && back_rank);
+        }
+        if m.is_castle() {
+            return match player {
+                crate::Player::White => self.is_pseudo_legal_castle::<White, C>(m, b),
+                crate::Player::Black => self.is_pseudo_legal_castle::<Black, C>(m, b),
+            };
+        }
+        if m.is_en_passant() {
+            return match player {
+                crate::Player::White => self.is_pseudo_legal_en_passant::<White, C>(m, b),
+                crate::Player::Black => self.is_pseudo_legal_en_passant::<Black, C>(m, b),
+            };
+        }
+        let (from, to) = (m.from(), m.to());
+        let piece = match b.on(from) {
+            Some(x) if x.player() == player => x,
+            _ => return false,
+        };
+        if b.on(to).is_some_and(|x| x.player() == player) {
+            return false;
+        }
+
+        if piece == Piece::player_pawn(player) {
+            let (forward, start_rank, last_rank) = match player {
+                crate::Player::White => (1, 1, 7),
+                crate::Player::Black => (-1, 6, 0),
+            };
+            if m.is_promotion() != (to.rank() == last_rank) {
+                return false;
+            }
+            let rank_diff = to.rank() as i8 - from.rank() as i8;
+            if m.is_double_pawn() {
+                let between =
+                    Square::from_file_rank(from.file(), (from.rank() as i8 + forward) as u8);
+                return from.rank() == start_rank
+                    && to.file() == from.file()
+                    && rank_diff == 2 * forward
+                    && b.on(between).is_none()
+                    && b.on(to).is_none();
+            }
+            if rank_diff != forward {
+                return false;
+            }
+            return match (to.file() as i8 - from.file() as i8).abs() {
+                0 => b.on(to).is_none(),
+                1 => b.on(to).is_some(),
+                _ => false,
+            };
+        }
+
+        if m.is_promotion() || m.is_double_pawn() {
+            return false;
+        }
+        let occupied = Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .fold(BB::EMPTY, |acc, p| acc | b.pieces[p]);
+        let attacks = if piece == Piece::player_king(player) {
+            self.tables.king_attacks(from)
+        } else if piece == Piece::player_knight(player) {
+            self.tables.knight_attacks(from)
+        } else if piece == Piece::player_bishop(player) {
+            self.tables.bishop_attacks(from, occupied)
+        } else if piece == Piece::player_rook(player) {
+            self.tables.rook_attacks(from, occupied)
+        } else {
+            self.tables.bishop_attacks(from, occupied) | self.tables.rook_attacks(from, occupied)
+        };
+        (attacks & BB::square(to)).any()
+    }
+
+    /// Whether the castle has its rights and nothing stands between the king and the rook.
+    /// Attacks on the squares the king passes are left to [`MoveGenerator::is_legal`].
+    fn is_pseudo_legal_castle<P: Player, C: MoveChain>(&self, m: Move, b: &Board<C>) -> bool {
+        let (flag, rook) = if m.to() == P::CASTLE_KING_TO {
+            (
+                ExtraState::WHITE_KING_CASTLE << P::FLAG_SHIFT,
+                Square::from_file_rank(7, P::CASTLE_FROM.rank()),
+            )
+        } else if m.to() == P::CASTLE_QUEEN_TO {
+            (
+                ExtraState::WHITE_QUEEN_CASTLE << P::FLAG_SHIFT,
+                Square::from_file_rank(0, P::CASTLE_FROM.rank()),
+            )
+        } else {
+            return false;
+        };
+        let occupied = Piece::WhiteKing
+            .to(Piece::BlackPawn)
+            .fold(BB::EMPTY, |acc, p| acc | b.pieces[p]);
+        m.from() == P::CASTLE_FROM
+            && b.on(P::CASTLE_FROM) == Some(P::KING)
+            && b.state.castle & flag != 0
+            && (self.tables.between(P::CASTLE_FROM, rook) & occupied).none()
+    }
+
+    /// Whether a pawn of the player can capture onto the en passant square.
+    fn is_pseudo_legal_en_passant<P: Player, C: MoveChain>(&self, m: Move, b: &Board<C>) -> bool {
+        if b.state.en_passant == ExtraState::INVALID_ENPASSANT {
+            return false;
+        }
+        let target = (BB::FILE_A << b.state.en_passant) & P::Opponent::RANK_3;
+        (BB::square(m.to()) & target).any()
+            && (BB::square(m.from()).pawn_attacks(P::PLAYER) & target).any()
+            && b.on(m.from()) == Some(P::PAWN)
+    }
+
     pub fn is_legal<C: MoveChain>(&self, m: Move, b: &Board<C>, info: &PositionInfo) -> bool {
         match b.state.player {
             crate::Player::White => self.is_legal_player::<White, C>(m, b, info),
@@ -734,6 +1307,58 @@ impl MoveGenerator {
         }
     }
 
+    /// Returns the pieces of the opponent of `P` attacking the square with the given occupancy.
+    fn attackers<P: Player, C: MoveChain>(&self, b: &Board<C>, sq: Square, occupied: BB) -> BB {
+        self.tables.bishop_attacks(sq, occupied)
+            & (b.pieces[P::Opponent::QUEEN] | b.pieces[P::Opponent::BISHOP])
+            | self.tables.rook_attacks(sq, occupied)
+                & (b.pieces[P::Opponent::QUEEN] | b.pieces[P::Opponent::ROOK])
+            | self.tables.knight_attacks(sq) & b.pieces[P::Opponent::KNIGHT]
+            | self.tables.king_attacks(sq) & b.pieces[P::Opponent::KING]
+            | BB::square(sq).pawn_attacks(P::PLAYER) & b.pieces[P::Opponent::PAWN]
+    }
+
+    /// Returns whether the square is attacked by the opponent of `P` with the given occupancy.
+    fn is_attacked<P: Player, C: MoveChain>(&self, b: &Board<C>, sq: Square, occupied: BB) -> bool {
+        self.attackers::<P, C>(b, sq, occupied).any()
+    }
+
+    /// Verify a castle by computing the attacks on the squares the king passes, with the king
+    /// and rook on their new squares.
+    fn is_legal_castle<P: Player, C: MoveChain>(
+        &self,
+        m: Move,
+        b: &Board<C>,
+        info: &PositionInfo,
+    ) -> bool {
+        let from = m.from();
+        let to = m.to();
+        let (rook_from, rook_to) = if to.file() > from.file() {
+            (
+                Square::from_file_rank(7, from.rank()),
+                Square::from_file_rank(5, from.rank()),
+            )
+        } else {
+            (
+                Square::from_file_rank(0, from.rank()),
+                Square::from_file_rank(3, from.rank()),
+            )
+        };
+
+        if self.is_attacked::<P, C>(b, from, info.occupied) {
+            return false;
+        }
+
+        let occupied = info.occupied
+            ^ BB::square(from)
+            ^ BB::square(to)
+            ^ BB::square(rook_from)
+            ^ BB::square(rook_to);
+        (self.tables.between(from, to) | BB::square(to))
+            .iter()
+            .all(|sq| !self.is_attacked::<P, C>(b, sq, occupied))
+    }
+
     pub fn is_legal_player<P: Player, C: MoveChain>(
         &self,
         m: Move,
@@ -743,6 +1368,11 @@ impl MoveGenerator {
         let from = m.from();
         let to = m.to();
 
+        if from == to {
+            debug_assert!(m.is_drop());
+            return true;
+        }
+
         if m.ty() == Move::TYPE_EN_PASSANT {
             let king_sq = b.pieces[P::KING].first_piece();
             let captured = to - P::PAWN_MOVE.as_offset();
@@ -757,8 +1387,12 @@ impl MoveGenerator {
                     .none();
         }
 
+        if m.ty() == Move::TYPE_CASTLE {
+            return self.is_legal_castle::<P, C>(m, b, info);
+        }
+
         if Some(P::KING) == b.on(from) {
-            return m.ty() == Move::TYPE_CASTLE || (BB::square(m.to()) & info.attacked).none();
+            return (BB::square(m.to()) & info.attacked).none();
         }
 
         return (info.blockers & BB::square(m.from())).none()
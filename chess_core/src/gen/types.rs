@@ -43,6 +43,8 @@ pub mod gen_type {
 pub trait Player {
     type Opponent: Player;
 
+    const PLAYER: crate::Player;
+
     const ATTACK_LEFT: Direction;
     const ATTACK_RIGHT: Direction;
     const LEFT: Direction;
@@ -77,6 +79,8 @@ pub struct Black;
 impl Player for White {
     type Opponent = Black;
 
+    const PLAYER: crate::Player = crate::Player::White;
+
     const ATTACK_LEFT: Direction = Direction::NW;
     const ATTACK_RIGHT: Direction = Direction::NE;
     const LEFT: Direction = Direction::W;
@@ -108,6 +112,8 @@ impl Player for White {
 impl Player for Black {
     type Opponent = White;
 
+    const PLAYER: crate::Player = crate::Player::Black;
+
     const ATTACK_LEFT: Direction = Direction::SE;
     const ATTACK_RIGHT: Direction = Direction::SW;
     const LEFT: Direction = Direction::E;
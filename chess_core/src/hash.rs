@@ -14,12 +14,17 @@ static mut PIECES: PieceArray<BoardArray<u64>> =
     PieceArray::new_array([BoardArray::new_array([0; 64]); 12]);
 static mut CASTLE: [u64; 16] = [0; 16];
 static mut BLACK: u64 = 0;
+static mut POCKET: PieceArray<[u64; Hasher::MAX_POCKET]> =
+    PieceArray::new_array([[0; Hasher::MAX_POCKET]; 12]);
 
 /// A zobrist hashing table for a position
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub struct Hasher;
 
 impl Hasher {
+    /// Maximum amount of a single piece which can be in a pocket, plus one for the empty pocket.
+    pub const MAX_POCKET: usize = 17;
+
     pub fn new() -> Self {
         HASH_INITIALIZED.call_once(|| {
             let mut pieces = PieceArray::new(BoardArray::new(0));
@@ -35,11 +40,19 @@ impl Hasher {
                 castle[i] = random.gen();
             }
             let black = random.gen();
+            // Generated last and zero for empty pockets so standard chess hashes are unaffected.
+            let mut pocket = PieceArray::new([0; Self::MAX_POCKET]);
+            for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+                for c in 1..Self::MAX_POCKET {
+                    pocket[p][c] = random.gen();
+                }
+            }
 
             unsafe {
                 PIECES = pieces;
                 CASTLE = castle;
                 BLACK = black;
+                POCKET = pocket;
             }
         });
 
@@ -61,6 +74,12 @@ impl Hasher {
         unsafe { BLACK }
     }
 
+    /// Returns the keys for the amount of a piece in a pocket.
+    #[inline(always)]
+    pub fn pocket(&self) -> &PieceArray<[u64; Self::MAX_POCKET]> {
+        unsafe { &*std::ptr::addr_of!(POCKET) }
+    }
+
     pub fn build(&self, pieces: &PieceArray<BB>, state: ExtraState) -> u64 {
         let mut res = 0;
 
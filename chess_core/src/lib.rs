@@ -1,20 +1,27 @@
 #![allow(dead_code)]
 
 //mod gen;
+pub mod affinity;
 pub mod bb;
 pub mod board;
+pub mod clock;
 pub mod engine;
 mod extra_state;
 pub mod gen;
 pub mod hash;
 mod mov;
+pub mod move_index;
+pub mod openings;
 mod piece;
+pub mod polyglot;
+pub mod repetition;
 mod square;
+pub mod tablebase;
 pub mod util;
 
-pub use board::{Board, UnmakeMove};
+pub use board::{material_eval, Board, UnmakeMove};
 pub use extra_state::ExtraState;
-pub use mov::Move;
+pub use mov::{Move, Promotion};
 pub use piece::Piece;
 pub use square::Square;
 
@@ -1,9 +1,106 @@
-use super::Square;
-use std::fmt;
+use super::{
+    board::{Board, MoveChain},
+    gen::{gen_type, MoveGenerator},
+    Piece, Player, Square,
+};
+use anyhow::{anyhow, Result};
+use std::{fmt, str::FromStr};
+
+/// The piece a pawn promotes to.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Promotion {
+    Queen,
+    Knight,
+    Rook,
+    Bishop,
+}
+
+impl Promotion {
+    /// All promotions in the order the move generator produces them.
+    pub const ALL: [Promotion; 4] = [
+        Promotion::Queen,
+        Promotion::Rook,
+        Promotion::Knight,
+        Promotion::Bishop,
+    ];
+
+    /// Returns the bits of the promotion in the move encoding.
+    #[inline]
+    pub const fn bits(self) -> u16 {
+        (self as u16) << Move::PROMOTION_SHIFT
+    }
+
+    /// Returns the promotion encoded in the bits of a move.
+    #[inline]
+    pub const fn from_bits(bits: u16) -> Self {
+        match (bits & Move::PROMOTION_MASK) >> Move::PROMOTION_SHIFT {
+            0 => Promotion::Queen,
+            1 => Promotion::Knight,
+            2 => Promotion::Rook,
+            _ => Promotion::Bishop,
+        }
+    }
+
+    /// Returns the piece the pawn of the given player becomes.
+    #[inline]
+    pub fn piece(self, player: Player) -> Piece {
+        match self {
+            Promotion::Queen => Piece::player_queen(player),
+            Promotion::Knight => Piece::player_knight(player),
+            Promotion::Rook => Piece::player_rook(player),
+            Promotion::Bishop => Piece::player_bishop(player),
+        }
+    }
+
+    /// Returns the promotion to the given piece, if pawns can promote to it.
+    pub fn from_piece(piece: Piece) -> Option<Self> {
+        match piece {
+            Piece::WhiteQueen | Piece::BlackQueen => Some(Promotion::Queen),
+            Piece::WhiteKnight | Piece::BlackKnight => Some(Promotion::Knight),
+            Piece::WhiteRook | Piece::BlackRook => Some(Promotion::Rook),
+            Piece::WhiteBishop | Piece::BlackBishop => Some(Promotion::Bishop),
+            _ => None,
+        }
+    }
+
+    /// Returns the lowercase letter of the piece, as used in UCI notation.
+    pub fn to_char(self) -> char {
+        match self {
+            Promotion::Queen => 'q',
+            Promotion::Knight => 'n',
+            Promotion::Rook => 'r',
+            Promotion::Bishop => 'b',
+        }
+    }
+
+    /// Parse the lowercase letter of the piece.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'q' => Some(Promotion::Queen),
+            'n' => Some(Promotion::Knight),
+            'r' => Some(Promotion::Rook),
+            'b' => Some(Promotion::Bishop),
+            _ => None,
+        }
+    }
+}
 
 /// A move on the board.
 ///
-/// Encoded as from to with possible extra info regarding promotions, en passants or castles.
+/// Moves are encoded in 16 bits:
+///
+/// | bits  | contents                                                    |
+/// |-------|-------------------------------------------------------------|
+/// | 0-5   | square moved from                                           |
+/// | 6-11  | square moved to                                             |
+/// | 12-13 | type: normal, castle, promotion or en passant               |
+/// | 14-15 | the [`Promotion`] for promotions, set for double pawn moves |
+///
+/// Crazyhouse drops are encoded with the from and to square both being the target square and
+/// bits 12-15 containing the kind of piece dropped.
+///
+/// All bits are in use, so whether a move captures can only be told with the board, see
+/// [`Move::is_capture`].
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Move(u16);
 
@@ -19,19 +116,13 @@ impl Move {
     pub const TYPE_EN_PASSANT: u16 = 3 << 12;
     pub const TYPE_NORMAL: u16 = 0;
 
-    pub const PROMOTION_MASK: u16 = 0b11 << 14;
-
-    pub const PROMOTION_QUEEN: u16 = 0 << 14;
-    pub const PROMOTION_KNIGHT: u16 = 1 << 14;
-    pub const PROMOTION_ROOK: u16 = 2 << 14;
-    pub const PROMOTION_BISHOP: u16 = 3 << 14;
+    const PROMOTION_SHIFT: u16 = 14;
+    const PROMOTION_MASK: u16 = 0b11 << Self::PROMOTION_SHIFT;
 
-    pub const DOUBLE_MOVE_PAWN: u16 = 1 << 14;
+    const DOUBLE_MOVE_PAWN: u16 = 1 << 14;
 
-    #[inline]
-    pub fn new(from: Square, to: Square, ty: u16, promotion: u16) -> Self {
-        Self(from.get() as u16 | (to.get() as u16) << 6 | ty | promotion)
-    }
+    pub const DROP_SHIFT: u16 = 12;
+    pub const DROP_MASK: u16 = 0b1111 << Self::DROP_SHIFT;
 
     #[inline]
     pub fn normal(from: Square, to: Square) -> Self {
@@ -49,15 +140,28 @@ impl Move {
     }
 
     #[inline]
-    pub fn promotion(from: Square, to: Square, promotion: u16) -> Self {
-        debug_assert!((Self::PROMOTION_QUEEN..=Self::PROMOTION_BISHOP).contains(&promotion));
-        Self(from.get() as u16 | (to.get() as u16) << 6 | Self::TYPE_PROMOTION | promotion)
+    pub fn promotion(from: Square, to: Square, promotion: Promotion) -> Self {
+        Self(from.get() as u16 | (to.get() as u16) << 6 | Self::TYPE_PROMOTION | promotion.bits())
     }
 
     pub fn en_passant(from: Square, to: Square) -> Self {
         Self(from.get() as u16 | (to.get() as u16) << 6 | Self::TYPE_EN_PASSANT)
     }
 
+    /// Create a move dropping a piece from the pocket onto the given square.
+    /// The color of the piece is ignored, dropped pieces always belong to the player to move.
+    ///
+    /// Panics if the piece is a king.
+    #[inline]
+    pub fn drop(piece: Piece, to: Square) -> Self {
+        assert!(
+            piece != Piece::WhiteKing && piece != Piece::BlackKing,
+            "kings can't be dropped"
+        );
+        let kind = (piece as u16 % 6) - 1;
+        Self(to.get() as u16 | (to.get() as u16) << 6 | kind << Self::DROP_SHIFT)
+    }
+
     #[inline]
     pub fn from(self) -> Square {
         Square::new((self.0 & Self::FROM_MASK) as u8)
@@ -73,16 +177,69 @@ impl Move {
         self.0 & Self::TYPE_MASK
     }
 
+    /// Returns whether this move promotes a pawn.
+    #[inline]
+    pub fn is_promotion(self) -> bool {
+        self.ty() == Self::TYPE_PROMOTION && !self.is_drop()
+    }
+
+    /// Returns whether this move is a castle, in which case from and to are the squares of the
+    /// king.
+    #[inline]
+    pub fn is_castle(self) -> bool {
+        self.ty() == Self::TYPE_CASTLE && !self.is_drop()
+    }
+
+    /// Returns whether this move captures a pawn en passant.
+    #[inline]
+    pub fn is_en_passant(self) -> bool {
+        self.ty() == Self::TYPE_EN_PASSANT && !self.is_drop()
+    }
+
+    /// Returns whether this move is a pawn moving two squares forward.
+    #[inline]
+    pub fn is_double_pawn(self) -> bool {
+        self.ty() == Self::TYPE_NORMAL && self.0 & Self::PROMOTION_MASK != 0 && !self.is_drop()
+    }
+
+    /// Returns whether this move captures a piece in the given position.
+    ///
+    /// En passant moves are captures even though the square moved to is empty.
+    #[inline]
+    pub fn is_capture<C: MoveChain>(self, board: &Board<C>) -> bool {
+        if self.is_drop() || self.is_castle() {
+            return false;
+        }
+        self.is_en_passant() || board.on(self.to()).is_some()
+    }
+
     #[inline]
     pub fn is_double_move(self) -> bool {
         debug_assert!(self.ty() == Self::TYPE_NORMAL);
         self.0 & Self::PROMOTION_MASK != 0
     }
 
+    /// Returns whether this move drops a piece from the pocket.
     #[inline]
-    pub fn promotion_piece(self) -> u16 {
+    pub fn is_drop(self) -> bool {
+        self.0 & Self::FROM_MASK == (self.0 & Self::TO_MASK) >> 6 && self != Self::INVALID
+    }
+
+    /// Returns the piece dropped by the given player.
+    #[inline]
+    pub fn drop_piece(self, player: Player) -> Piece {
+        debug_assert!(self.is_drop());
+        let kind = ((self.0 & Self::DROP_MASK) >> Self::DROP_SHIFT) as u8 + 1;
+        match player {
+            Player::White => Piece::from_u8(kind),
+            Player::Black => Piece::from_u8(kind + 6),
+        }
+    }
+
+    #[inline]
+    pub fn promotion_piece(self) -> Promotion {
         debug_assert_eq!(self.ty(), Self::TYPE_PROMOTION);
-        self.0 & Self::PROMOTION_MASK
+        Promotion::from_bits(self.0)
     }
 
     pub fn from_name(name: &str) -> Option<Self> {
@@ -90,10 +247,231 @@ impl Move {
         let to = Square::from_name(&name[2..])?;
         Some(Self::normal(from, to))
     }
+
+    /// Returns the move in the coordinate notation used by UCI, like `e2e4`, `e7e8q` or `P@e4`.
+    ///
+    /// The invalid move is written as the null move `0000`.
+    pub fn to_uci(self) -> String {
+        if self == Self::INVALID {
+            return "0000".to_string();
+        }
+        if self.is_drop() {
+            return format!("{}@{}", self.drop_piece(Player::White).to_char(), self.to());
+        }
+        let mut res = format!("{}{}", self.from(), self.to());
+        if self.is_promotion() {
+            res.push(self.promotion_piece().to_char());
+        }
+        res
+    }
+
+    /// Returns the move in the standard algebraic notation used by PGN, like `Nf3`, `exd5`,
+    /// `e8=Q+` or `O-O`. The move must be legal in the position on the board.
+    pub fn to_san<C: MoveChain + Clone>(self, board: &Board<C>, gen: &MoveGenerator) -> String {
+        let mut res = String::new();
+        if self.is_castle() {
+            if self.to().file() > self.from().file() {
+                res.push_str("O-O");
+            } else {
+                res.push_str("O-O-O");
+            }
+        } else if self.is_drop() {
+            res = self.to_uci();
+        } else {
+            let piece = board
+                .on(self.from())
+                .expect("no piece on the square the move is from");
+            let capture = self.is_capture(board);
+            if piece == Piece::player_pawn(piece.player()) {
+                if capture {
+                    res.push((b'a' + self.from().file()) as char);
+                }
+            } else {
+                res.push(piece.to_char().to_ascii_uppercase());
+                // Name the file, the rank or both of the origin square if another piece of the
+                // same kind can move to the same square.
+                let mut moves = Vec::new();
+                gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+                let others: Vec<Square> = moves
+                    .iter()
+                    .filter(|m| {
+                        !m.is_drop()
+                            && m.to() == self.to()
+                            && m.from() != self.from()
+                            && board.on(m.from()) == Some(piece)
+                    })
+                    .map(|m| m.from())
+                    .collect();
+                if !others.is_empty() {
+                    let from = self.from();
+                    if others.iter().all(|x| x.file() != from.file()) {
+                        res.push((b'a' + from.file()) as char);
+                    } else if others.iter().all(|x| x.rank() != from.rank()) {
+                        res.push((b'1' + from.rank()) as char);
+                    } else {
+                        res.push_str(&from.to_string());
+                    }
+                }
+            }
+            if capture {
+                res.push('x');
+            }
+            res.push_str(&self.to().to_string());
+            if self.is_promotion() {
+                res.push('=');
+                res.push(self.promotion_piece().to_char().to_ascii_uppercase());
+            }
+        }
+
+        let after = board.make_move_copy(self);
+        let mut moves = Vec::new();
+        let info = gen.gen_moves::<gen_type::All, _, _>(&after, &mut moves);
+        if gen.checked_king(&after, &info) {
+            res.push(if moves.is_empty() { '#' } else { '+' });
+        }
+        res
+    }
+
+    /// Find the legal move in the position written in UCI coordinate notation.
+    pub fn from_uci_on_board<C: MoveChain>(
+        name: &str,
+        board: &Board<C>,
+        gen: &MoveGenerator,
+    ) -> Option<Self> {
+        let mut moves = Vec::new();
+        gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+        Self::from_uci_in(name, &moves)
+    }
+
+    /// Find the legal move written in UCI coordinate notation like
+    /// [`from_uci_on_board`](Self::from_uci_on_board), without generating every legal move.
+    ///
+    /// The move is built from the piece on its square and then checked on its own. Drops and
+    /// positions in check fall back to generating the legal moves.
+    pub fn from_uci_fast<C: MoveChain>(
+        name: &str,
+        board: &Board<C>,
+        gen: &MoveGenerator,
+    ) -> Option<Self> {
+        let parsed: Move = name.parse().ok()?;
+        if parsed == Move::INVALID {
+            return None;
+        }
+        if parsed.is_drop() {
+            return Self::from_uci_on_board(name, board, gen);
+        }
+
+        let (from, to) = (parsed.from(), parsed.to());
+        let player = board.state.player;
+        let piece = board.on(from)?;
+        let pawn = piece == Piece::player_pawn(player);
+        let file_diff = (to.file() as i8 - from.file() as i8).abs();
+        let m = if parsed.is_promotion() {
+            parsed
+        } else if piece == Piece::player_king(player) && file_diff == 2 {
+            Self::castle(from, to)
+        } else if pawn && file_diff == 1 && board.on(to).is_none() {
+            Self::en_passant(from, to)
+        } else if pawn && (to.rank() as i8 - from.rank() as i8).abs() == 2 {
+            Self::double_pawn(from, to)
+        } else {
+            parsed
+        };
+        let info = gen.gen_info(board);
+        // The legality check of a single move doesn't consider check evasions.
+        if gen.checked_king(board, &info) {
+            return Self::from_uci_on_board(name, board, gen);
+        }
+        (gen.is_pseudo_legal(m, board) && gen.is_legal(m, board, &info)).then_some(m)
+    }
+
+    /// Find the move written in UCI coordinate notation in a list of already generated legal
+    /// moves.
+    pub fn from_uci_in(name: &str, moves: &[Move]) -> Option<Self> {
+        let parsed: Move = name.parse().ok()?;
+        if parsed.is_drop() {
+            return moves.iter().copied().find(|m| {
+                m.is_drop()
+                    && m.to() == parsed.to()
+                    && m.drop_piece(Player::White) == parsed.drop_piece(Player::White)
+            });
+        }
+        let promotion = parsed.is_promotion().then(|| parsed.promotion_piece());
+        Self::from_squares_in(parsed.from(), parsed.to(), promotion, moves)
+    }
+
+    /// Find the move between two squares in a list of already generated legal moves.
+    ///
+    /// Promotions are only found when given the piece to promote to, other moves only without.
+    pub fn from_squares_in(
+        from: Square,
+        to: Square,
+        promotion: Option<Promotion>,
+        moves: &[Move],
+    ) -> Option<Self> {
+        moves.iter().copied().find(|m| {
+            !m.is_drop()
+                && m.from() == from
+                && m.to() == to
+                && m.is_promotion().then(|| m.promotion_piece()) == promotion
+        })
+    }
+}
+
+/// Parses a move in UCI coordinate notation.
+///
+/// Without a board the kind of move can't be fully determined: castles, en passant captures
+/// and double pawn moves are parsed as normal moves. Use [`Move::from_uci_on_board`] to find
+/// the actual move in a position. The null move `0000` is parsed as [`Move::INVALID`].
+impl FromStr for Move {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "0000" {
+            return Ok(Self::INVALID);
+        }
+        let invalid = || anyhow!("invalid move `{}`", s);
+
+        if s.get(1..2) == Some("@") && s.len() == 4 {
+            let piece = s
+                .chars()
+                .next()
+                .and_then(|x| Piece::from_char(x.to_ascii_uppercase()))
+                .filter(|x| *x != Piece::WhiteKing)
+                .ok_or_else(invalid)?;
+            let to = s.get(2..).and_then(Square::from_name).ok_or_else(invalid)?;
+            return Ok(Self::drop(piece, to));
+        }
+
+        let from = s.get(..2).and_then(Square::from_name).ok_or_else(invalid)?;
+        let to = s
+            .get(2..4)
+            .and_then(Square::from_name)
+            .ok_or_else(invalid)?;
+        if from == to {
+            return Err(invalid());
+        }
+        let mut rest = s[4..].chars();
+        let promotion = match (rest.next(), rest.next()) {
+            (None, _) => return Ok(Self::normal(from, to)),
+            (Some(x), None) => Promotion::from_char(x).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        Ok(Self::promotion(from, to, promotion))
+    }
 }
 
 impl fmt::Debug for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_drop() {
+            return f
+                .debug_struct("Move")
+                .field("value", &self.0)
+                .field("notation", &format!("{}", self))
+                .field("drop", &self.drop_piece(Player::White))
+                .field("to", &self.to())
+                .finish();
+        }
         f.debug_struct("Move")
             .field("value", &self.0)
             .field("notation", &format!("{}", self))
@@ -103,7 +481,7 @@ impl fmt::Debug for Move {
             .field(
                 "promotion",
                 &if self.ty() == Self::TYPE_PROMOTION {
-                    Some(self.promotion_piece() >> 14)
+                    Some(self.promotion_piece())
                 } else {
                     None
                 },
@@ -118,6 +496,15 @@ impl fmt::Display for Move {
             return write!(f, "INVALID");
         }
 
+        if self.is_drop() {
+            return write!(
+                f,
+                "{}@{}",
+                self.drop_piece(Player::White).to_char(),
+                self.to()
+            );
+        }
+
         if self.ty() == Self::TYPE_CASTLE {
             if self.to() == Square::C1 || self.to() == Square::C8 {
                 return write!(f, "O-O({},{})", self.from(), self.to());
@@ -127,13 +514,7 @@ impl fmt::Display for Move {
         }
         write!(f, "{}{}", self.from(), self.to())?;
         if self.ty() == Self::TYPE_PROMOTION {
-            let piece = match self.promotion_piece() {
-                Self::PROMOTION_QUEEN => "Q",
-                Self::PROMOTION_KNIGHT => "K",
-                Self::PROMOTION_ROOK => "R",
-                Self::PROMOTION_BISHOP => "B",
-                _ => unreachable!(),
-            };
+            let piece = self.promotion_piece().to_char().to_ascii_uppercase();
             return write!(f, "={}", piece);
         }
         if self.ty() == Self::TYPE_EN_PASSANT {
@@ -0,0 +1,108 @@
+//! A mapping of moves to indices in a fixed size vector, for use as the policy output of a
+//! neural network.
+//!
+//! Moves are mapped by their from and to square, with a separate range for underpromotions.
+//! Squares are mirrored when black is to move so the side to move always moves up the board.
+//! Drops are not supported.
+
+use crate::{
+    board::{Board, MoveChain},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MAX_MOVES},
+    Move, Piece, Player, Promotion, Square,
+};
+
+/// Amount of indices for moves which are identified by their from and to square.
+const SQUARE_MOVES: usize = 64 * 64;
+
+/// Size of the policy vector.
+pub const POLICY_SIZE: usize = SQUARE_MOVES + 8 * 3 * 3;
+
+const UNDERPROMOTIONS: [Promotion; 3] = [Promotion::Knight, Promotion::Bishop, Promotion::Rook];
+
+fn orient(square: Square, player: Player) -> Square {
+    match player {
+        Player::White => square,
+        Player::Black => square.mirror(),
+    }
+}
+
+/// Returns the index of a move made by the given player.
+pub fn encode(m: Move, player: Player) -> usize {
+    debug_assert!(!m.is_drop(), "drops can't be encoded");
+    let from = orient(m.from(), player);
+    let to = orient(m.to(), player);
+
+    if m.is_promotion() && m.promotion_piece() != Promotion::Queen {
+        let piece = UNDERPROMOTIONS
+            .iter()
+            .position(|x| *x == m.promotion_piece())
+            .unwrap();
+        let direction = (to.file() + 1 - from.file()) as usize;
+        SQUARE_MOVES + (from.file() as usize * 3 + direction) * 3 + piece
+    } else {
+        from.get() as usize * 64 + to.get() as usize
+    }
+}
+
+/// Returns the move with the given index in the given position.
+///
+/// The result is only meaningful for indices of legal moves.
+pub fn decode<C: MoveChain>(index: usize, board: &Board<C>) -> Move {
+    let player = board.state.player;
+
+    let (from, to, promotion) = if index < SQUARE_MOVES {
+        (
+            orient(Square::new((index / 64) as u8), player),
+            orient(Square::new((index % 64) as u8), player),
+            Promotion::Queen,
+        )
+    } else {
+        let index = index - SQUARE_MOVES;
+        let file = (index / 9) as u8;
+        let to_file = (file + ((index / 3) % 3) as u8).wrapping_sub(1);
+        (
+            orient(Square::from_file_rank(file, 6), player),
+            orient(Square::from_file_rank(to_file, 7), player),
+            UNDERPROMOTIONS[index % 3],
+        )
+    };
+
+    let piece = board.on(from);
+    if piece == Some(Piece::player_pawn(player)) {
+        if orient(to, player).rank() == 7 {
+            return Move::promotion(from, to, promotion);
+        }
+        if (to.rank() as i8 - from.rank() as i8).abs() == 2 {
+            return Move::double_pawn(from, to);
+        }
+        if to.file() != from.file() && board.on(to).is_none() {
+            return Move::en_passant(from, to);
+        }
+    } else if piece == Some(Piece::player_king(player))
+        && (to.file() as i8 - from.file() as i8).abs() == 2
+    {
+        return Move::castle(from, to);
+    }
+    Move::normal(from, to)
+}
+
+/// Returns for every index whether it is a legal move in the given position.
+///
+/// If `underpromotions` is false, promotions to pieces other than the queen are masked out.
+pub fn legal_move_mask<C: MoveChain>(
+    board: &Board<C>,
+    gen: &MoveGenerator,
+    underpromotions: bool,
+) -> Vec<bool> {
+    let mut moves = InlineBuffer::<MAX_MOVES>::new();
+    gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+
+    let mut mask = vec![false; POLICY_SIZE];
+    for m in moves.iter() {
+        if !underpromotions && m.is_promotion() && m.promotion_piece() != Promotion::Queen {
+            continue;
+        }
+        mask[encode(m, board.state.player)] = true;
+    }
+    mask
+}
@@ -0,0 +1,132 @@
+//! Classification of openings by their code in the Encyclopaedia of Chess Openings.
+//!
+//! The known positions are read from a table bundled with the crate. A game is classified by
+//! the deepest position in the table it reached, so transpositions into the same position get
+//! the same classification regardless of move order.
+
+use crate::{
+    board::{EndChain, HashChain, MoveChain},
+    gen::MoveGenerator,
+    Board, Move,
+};
+use anyhow::{anyhow, ensure, Result};
+use std::{collections::HashMap, fmt, str::FromStr, sync::OnceLock};
+
+/// The bundled table of openings, one per line as `eco<TAB>name<TAB>moves` with the moves in
+/// UCI notation from the start position.
+const OPENINGS: &str = include_str!("openings.tsv");
+
+/// An opening code from the Encyclopaedia of Chess Openings, like `C60`.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct EcoCode {
+    volume: u8,
+    number: u8,
+}
+
+impl EcoCode {
+    /// Create a code from its volume, `A` through `E`, and its number within the volume.
+    pub fn new(volume: char, number: u8) -> Option<Self> {
+        if !('A'..='E').contains(&volume) || number > 99 {
+            return None;
+        }
+        Some(EcoCode {
+            volume: volume as u8 - b'A',
+            number,
+        })
+    }
+
+    pub fn volume(self) -> char {
+        (b'A' + self.volume) as char
+    }
+
+    pub fn number(self) -> u8 {
+        self.number
+    }
+}
+
+impl fmt::Display for EcoCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:02}", self.volume(), self.number)
+    }
+}
+
+impl FromStr for EcoCode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid ECO code `{}`", s);
+        ensure!(s.len() == 3 && s.is_ascii(), invalid());
+        let volume = s.chars().next().ok_or_else(invalid)?;
+        let number = s[1..].parse().map_err(|_| invalid())?;
+        Self::new(volume, number).ok_or_else(invalid)
+    }
+}
+
+struct Opening {
+    eco: EcoCode,
+    name: &'static str,
+}
+
+struct Table {
+    positions: HashMap<u64, Opening>,
+    /// The most moves of any line in the table.
+    max_plies: usize,
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let gen = MoveGenerator::new();
+        let mut positions = HashMap::new();
+        let mut max_plies = 0;
+        for line in OPENINGS.lines().skip(1).filter(|x| !x.is_empty()) {
+            let mut fields = line.split('\t');
+            let (eco, name, moves) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(eco), Some(name), Some(moves)) => (eco, name, moves),
+                _ => panic!("invalid line in openings table: `{}`", line),
+            };
+            let eco = eco.parse().unwrap();
+
+            let mut board = Board::start_position(HashChain::new());
+            let mut plies = 0;
+            for name in moves.split_whitespace() {
+                let m = Move::from_uci_on_board(name, &board, &gen)
+                    .unwrap_or_else(|| panic!("illegal move `{}` in opening `{}`", name, line));
+                board.make_move(m);
+                plies += 1;
+            }
+            max_plies = max_plies.max(plies);
+            positions.insert(board.chain.hash, Opening { eco, name });
+        }
+        Table {
+            positions,
+            max_plies,
+        }
+    })
+}
+
+/// Classify a game played from the start position.
+///
+/// Returns the code and name of the deepest known opening position reached by the moves.
+pub fn classify(moves: &[Move]) -> Option<(EcoCode, &'static str)> {
+    classify_from(&Board::start_position(EndChain), moves)
+}
+
+/// Classify a game played from the given position.
+pub fn classify_from<C: MoveChain>(
+    start: &Board<C>,
+    moves: &[Move],
+) -> Option<(EcoCode, &'static str)> {
+    let table = table();
+    let mut board = Board::start_position(HashChain::new());
+    board.copy_position(start);
+
+    let mut res = table.positions.get(&board.chain.hash);
+    for m in moves.iter().take(table.max_plies) {
+        board.make_move(*m);
+        if let Some(x) = table.positions.get(&board.chain.hash) {
+            res = Some(x);
+        }
+    }
+    res.map(|x| (x.eco, x.name))
+}
@@ -124,6 +124,24 @@ impl Piece {
             Piece::BlackPawn => 'p',
         }
     }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'K' => Some(Piece::WhiteKing),
+            'k' => Some(Piece::BlackKing),
+            'Q' => Some(Piece::WhiteQueen),
+            'q' => Some(Piece::BlackQueen),
+            'R' => Some(Piece::WhiteRook),
+            'r' => Some(Piece::BlackRook),
+            'B' => Some(Piece::WhiteBishop),
+            'b' => Some(Piece::BlackBishop),
+            'N' => Some(Piece::WhiteKnight),
+            'n' => Some(Piece::BlackKnight),
+            'P' => Some(Piece::WhitePawn),
+            'p' => Some(Piece::BlackPawn),
+            _ => None,
+        }
+    }
 }
 
 pub struct PieceIter {
@@ -186,4 +204,27 @@ impl Piece {
     pub fn white(self) -> bool {
         (self as u8) < 6
     }
+
+    /// Returns the standard value of the piece in pawns, the king is worth nothing.
+    pub fn value(self) -> i32 {
+        match self {
+            Piece::WhiteKing | Piece::BlackKing => 0,
+            Piece::WhiteQueen | Piece::BlackQueen => 9,
+            Piece::WhiteRook | Piece::BlackRook => 5,
+            Piece::WhiteBishop | Piece::BlackBishop => 3,
+            Piece::WhiteKnight | Piece::BlackKnight => 3,
+            Piece::WhitePawn | Piece::BlackPawn => 1,
+        }
+    }
+
+    /// Returns how much the piece counts towards the game phase, pawns and kings don't count.
+    pub fn phase_weight(self) -> i32 {
+        match self {
+            Piece::WhiteQueen | Piece::BlackQueen => 4,
+            Piece::WhiteRook | Piece::BlackRook => 2,
+            Piece::WhiteBishop | Piece::BlackBishop => 1,
+            Piece::WhiteKnight | Piece::BlackKnight => 1,
+            Piece::WhiteKing | Piece::BlackKing | Piece::WhitePawn | Piece::BlackPawn => 0,
+        }
+    }
 }
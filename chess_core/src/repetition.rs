@@ -0,0 +1,158 @@
+//! Detection of the draws which depend on the history of a game: threefold repetition and the
+//! fifty-move rule.
+
+use crate::{
+    board::{HashChain, MoveChain},
+    Board,
+};
+use std::fmt;
+
+/// The amount of half-moves without a capture or pawn move after which the game is drawn.
+pub const FIFTY_MOVE_PLIES: u8 = 100;
+
+/// The rule by which a game was drawn.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DrawRule {
+    Repetition,
+    FiftyMoves,
+}
+
+impl fmt::Display for DrawRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawRule::Repetition => write!(f, "threefold repetition"),
+            DrawRule::FiftyMoves => write!(f, "fifty-move rule"),
+        }
+    }
+}
+
+/// The positions of a game and of the line searched from it, for detecting repetitions in an
+/// engine.
+///
+/// Every entry is the hash of a position and whether the move leading to it was irreversible,
+/// a capture or a pawn move. Positions before an irreversible move can't occur again, so
+/// lookups stop at the last one.
+#[derive(Clone, Default, Debug)]
+pub struct GameHistory {
+    entries: Vec<(u64, bool)>,
+}
+
+impl GameHistory {
+    pub fn new() -> Self {
+        GameHistory {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a position with the given hash, reached by an irreversible move if `irreversible`.
+    ///
+    /// The first position of a game should be added as irreversible.
+    #[inline]
+    pub fn push(&mut self, hash: u64, irreversible: bool) {
+        self.entries.push((hash, irreversible));
+    }
+
+    /// Remove the last position.
+    #[inline]
+    pub fn pop(&mut self) {
+        self.entries.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns how often the position with the given hash occurs in the history since the last
+    /// irreversible move, including the current position if it was added.
+    pub fn repetitions(&self, hash: u64) -> u32 {
+        let mut count = 0;
+        for &(x, irreversible) in self.entries.iter().rev() {
+            if x == hash {
+                count += 1;
+            }
+            if irreversible {
+                break;
+            }
+        }
+        count
+    }
+}
+
+/// The hashes of the positions played in a game, the last one being the current position.
+#[derive(Clone, Default, Debug)]
+pub struct PositionHistory {
+    hashes: Vec<u64>,
+}
+
+impl PositionHistory {
+    pub fn new() -> Self {
+        PositionHistory { hashes: Vec::new() }
+    }
+
+    /// Returns the hash by which the positions of the history are compared.
+    pub fn hash<C: MoveChain>(board: &Board<C>) -> u64 {
+        let mut res = Board::start_position(HashChain::new());
+        res.copy_position(board);
+        res.chain.hash
+    }
+
+    /// Add the position on the board after a move was made.
+    pub fn push<C: MoveChain>(&mut self, board: &Board<C>) {
+        self.hashes.push(Self::hash(board));
+    }
+
+    /// Remove the last position after the move leading to it was taken back.
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns how often the current position occurred, including itself.
+    ///
+    /// Only the positions since the last irreversible move are compared, `move_clock` is the
+    /// amount of half-moves since then.
+    pub fn repetitions(&self, move_clock: u8) -> usize {
+        let Some(current) = self.hashes.last() else {
+            return 0;
+        };
+        self.hashes
+            .iter()
+            .rev()
+            .take(move_clock as usize + 1)
+            .filter(|x| *x == current)
+            .count()
+    }
+
+    /// Returns the rule by which the game is drawn in the current position on the board.
+    ///
+    /// Doesn't check whether the position is checkmate, which takes precedence over the
+    /// fifty-move rule.
+    pub fn draw_rule<C: MoveChain>(&self, board: &Board<C>) -> Option<DrawRule> {
+        if self.repetitions(board.state.move_clock) >= 3 {
+            return Some(DrawRule::Repetition);
+        }
+        if board.is_fifty_move_draw() {
+            return Some(DrawRule::FiftyMoves);
+        }
+        None
+    }
+}
@@ -1,3 +1,4 @@
+use crate::Player;
 use std::{
     fmt::{self, Display},
     ops::{Add, Sub},
@@ -93,6 +94,32 @@ impl Square {
     pub fn flip(self) -> Self {
         Square(63 - self.0)
     }
+
+    /// Mirrors the square vertically, a1 becomes a8.
+    #[inline]
+    pub fn mirror(self) -> Self {
+        Square(self.0 ^ 56)
+    }
+
+    /// Returns the square drawn at the given column and row, counted from the top left, of a
+    /// board viewed from the side of the given player.
+    pub fn from_view(column: u8, row: u8, bottom: Player) -> Self {
+        let square = Square::from_file_rank(column, 7 - row);
+        match bottom {
+            Player::White => square,
+            Player::Black => square.flip(),
+        }
+    }
+
+    /// Returns the column and row, counted from the top left, the square is drawn at on a board
+    /// viewed from the side of the given player.
+    pub fn to_view(self, bottom: Player) -> (u8, u8) {
+        let square = match bottom {
+            Player::White => self,
+            Player::Black => self.flip(),
+        };
+        (square.file(), 7 - square.rank())
+    }
 }
 
 impl Add<u8> for Square {
@@ -0,0 +1,101 @@
+//! Probing endgame tablebases.
+//!
+//! A tablebase knows the outcome of every position with few enough pieces under perfect play.
+//! Engines go through the [`TablebaseProber`] trait so the format of the tables is independent
+//! of the search. [`Syzygy`] finds the tables of the Syzygy format in a directory, decoding
+//! them is not implemented yet so its probes always miss.
+
+use crate::{board::Board, Move};
+use anyhow::{ensure, Context, Result};
+use std::fs;
+
+/// The outcome of a position under perfect play, from the perspective of the side to move.
+///
+/// A cursed win is a win which the fifty-move rule turns into a draw, a blessed loss a loss
+/// which it saves.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+/// A source of tablebase results.
+///
+/// Positions are only probed when they have no more than [`max_pieces`](Self::max_pieces)
+/// pieces and no castling rights, the results assume the half-move clock was just reset.
+pub trait TablebaseProber: Send {
+    /// The most pieces, kings included, of the positions in the tables.
+    fn max_pieces(&self) -> u32;
+
+    /// Returns the outcome of the position, `None` if it is not in the tables.
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Returns the move which keeps the outcome of the position and resets the half-move clock
+    /// the soonest, `None` if the position is not in the tables.
+    fn probe_root(&self, board: &Board) -> Option<Move>;
+}
+
+/// Tables in the Syzygy format.
+pub struct Syzygy {
+    /// Names of the tables found, like `KQvK`.
+    tables: Vec<String>,
+    max_pieces: u32,
+}
+
+impl Syzygy {
+    /// Find the WDL tables, files ending in `.rtbw`, in the directories of the path.
+    ///
+    /// Multiple directories are separated by `;` on Windows and `:` elsewhere, as in the
+    /// `SyzygyPath` option of other engines.
+    pub fn open(path: &str) -> Result<Self> {
+        let separator = if cfg!(windows) { ';' } else { ':' };
+        let mut tables = Vec::new();
+        for dir in path.split(separator).filter(|x| !x.is_empty()) {
+            let entries = fs::read_dir(dir)
+                .with_context(|| format!("failed to read tablebase directory `{}`", dir))?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().is_some_and(|x| x == "rtbw") {
+                    if let Some(name) = path.file_stem() {
+                        tables.push(name.to_string_lossy().into_owned());
+                    }
+                }
+            }
+        }
+        ensure!(!tables.is_empty(), "no tablebase files in `{}`", path);
+        tables.sort();
+        tables.dedup();
+        let max_pieces = tables
+            .iter()
+            .map(|x| {
+                x.chars()
+                    .filter(|c| c.is_ascii_alphabetic() && *c != 'v')
+                    .count() as u32
+            })
+            .max()
+            .unwrap_or(0);
+        Ok(Syzygy { tables, max_pieces })
+    }
+
+    /// Returns the names of the tables found, like `KQvK`.
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+}
+
+impl TablebaseProber for Syzygy {
+    fn max_pieces(&self) -> u32 {
+        self.max_pieces
+    }
+
+    fn probe_wdl(&self, _: &Board) -> Option<Wdl> {
+        None
+    }
+
+    fn probe_root(&self, _: &Board) -> Option<Move> {
+        None
+    }
+}
@@ -0,0 +1,32 @@
+use chess_core::affinity::{format_cpu_mask, parse_cpu_mask, split_cpus};
+
+#[test]
+fn parse_masks() {
+    assert_eq!(parse_cpu_mask("0-3").unwrap(), [0, 1, 2, 3]);
+    assert_eq!(parse_cpu_mask("8-9, 6,0").unwrap(), [0, 6, 8, 9]);
+    assert_eq!(parse_cpu_mask("2,1-3").unwrap(), [1, 2, 3]);
+    assert!(parse_cpu_mask("").is_err());
+    assert!(parse_cpu_mask("3-1").is_err());
+    assert!(parse_cpu_mask("0-x").is_err());
+}
+
+#[test]
+fn format_masks() {
+    assert_eq!(format_cpu_mask(&[0, 1, 2, 3]), "0-3");
+    assert_eq!(format_cpu_mask(&[9, 0, 6, 8]), "0,6,8-9");
+    assert_eq!(format_cpu_mask(&[]), "");
+    let mask = "0-3,5,7-12";
+    assert_eq!(format_cpu_mask(&parse_cpu_mask(mask).unwrap()), mask);
+}
+
+#[test]
+fn split_into_disjoint_groups() {
+    let cpus: Vec<_> = (0..16).collect();
+    let groups = split_cpus(&cpus, 2);
+    assert_eq!(format_cpu_mask(&groups[0]), "0-7");
+    assert_eq!(format_cpu_mask(&groups[1]), "8-15");
+
+    // Cpus which don't fit in equal groups are left unused.
+    assert_eq!(split_cpus(&[0, 1, 4, 5, 6], 2), [vec![0, 1], vec![4, 5]]);
+    assert!(split_cpus(&[3], 2).is_empty());
+}
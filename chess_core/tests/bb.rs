@@ -0,0 +1,113 @@
+use chess_core::{bb::BB, Direction, Player, Square};
+
+/// Offsets of the eight directions and of the knight jumps, with their change in file.
+const OFFSETS: &[(i8, i8)] = &[
+    (7, -1),
+    (8, 0),
+    (9, 1),
+    (1, 1),
+    (-7, 1),
+    (-8, 0),
+    (-9, -1),
+    (-1, -1),
+    (17, 1),
+    (15, -1),
+    (10, 2),
+    (6, -2),
+    (-17, -1),
+    (-15, 1),
+    (-10, -2),
+    (-6, 2),
+];
+
+fn bb(squares: &[&str]) -> BB {
+    squares.iter().fold(BB::EMPTY, |bb, name| {
+        bb | BB::square(Square::from_name(name).unwrap())
+    })
+}
+
+#[test]
+fn shift_never_wraps() {
+    for s in 0..64 {
+        let square = Square::new(s);
+        for &(offset, files) in OFFSETS {
+            let shifted = BB::square(square).shift_by(offset);
+            let file = square.file() as i8 + files;
+            let target = s as i8 + offset;
+            if (0..8).contains(&file) && (0..64).contains(&target) {
+                assert_eq!(
+                    shifted,
+                    BB::square(Square::new(target as u8)),
+                    "{:?} by {}",
+                    square,
+                    offset
+                );
+            } else {
+                assert!(shifted.none(), "{:?} by {} wrapped", square, offset);
+            }
+        }
+    }
+}
+
+#[test]
+fn shift_matches_directions() {
+    for s in 0..64 {
+        let board = BB::square(Square::new(s));
+        for d in 0..8 {
+            let d = Direction::from_u8(d).unwrap();
+            assert_eq!(board.shift(d), board.shift_by(d.as_offset()));
+        }
+    }
+    assert!(BB::FULL.shift_by(64).none());
+    assert!(BB::FULL.shift_by(-100).none());
+}
+
+#[test]
+fn pawn_attacks() {
+    let pawns = bb(&["a2", "e4", "h7"]);
+    assert_eq!(
+        pawns.pawn_attacks(Player::White),
+        bb(&["b3", "d5", "f5", "g8"])
+    );
+    assert_eq!(
+        pawns.pawn_attacks(Player::Black),
+        bb(&["b1", "d3", "f3", "g6"])
+    );
+}
+
+#[test]
+fn fills() {
+    let squares = bb(&["b3", "g6"]);
+    assert_eq!(squares.file_fill(), BB::FILE_B | BB::FILE_G);
+    assert_eq!(squares.rank_fill(), BB::RANK_3 | BB::RANK_6);
+    assert_eq!(BB::A1.rank_fill(), BB::RANK_1);
+    assert_eq!(BB::H8.rank_fill(), BB::RANK_8);
+    assert_eq!(BB::A8.file_fill(), BB::FILE_A);
+}
+
+#[test]
+fn spans() {
+    let pawn = bb(&["d4"]);
+    assert_eq!(
+        pawn.front_span(Player::White),
+        bb(&["d5", "d6", "d7", "d8"])
+    );
+    assert_eq!(pawn.front_span(Player::Black), bb(&["d3", "d2", "d1"]));
+    assert_eq!(
+        pawn.attack_span(Player::White),
+        bb(&["c5", "c6", "c7", "c8", "e5", "e6", "e7", "e8"])
+    );
+    assert_eq!(
+        bb(&["a6"]).attack_span(Player::Black),
+        bb(&["b5", "b4", "b3", "b2", "b1"])
+    );
+    assert!(BB::RANK_8.front_span(Player::White).none());
+}
+
+#[test]
+fn isolated_files() {
+    // Pawns on a, c, d and h: the a and h pawns are isolated.
+    let pawns = bb(&["a2", "c3", "d4", "h2"]);
+    assert_eq!(pawns.isolated_files(), BB::FILE_A | BB::FILE_H);
+    assert!(bb(&["b2", "c2"]).isolated_files().none());
+}
@@ -0,0 +1,35 @@
+use chess_core::{Player, Square};
+
+fn square(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn corners_from_white() {
+    assert_eq!(Square::from_view(0, 0, Player::White), square("a8"));
+    assert_eq!(Square::from_view(7, 0, Player::White), square("h8"));
+    assert_eq!(Square::from_view(0, 7, Player::White), square("a1"));
+    assert_eq!(Square::from_view(7, 7, Player::White), square("h1"));
+}
+
+#[test]
+fn corners_from_black() {
+    assert_eq!(Square::from_view(0, 0, Player::Black), square("h1"));
+    assert_eq!(Square::from_view(7, 0, Player::Black), square("a1"));
+    assert_eq!(Square::from_view(0, 7, Player::Black), square("h8"));
+    assert_eq!(Square::from_view(7, 7, Player::Black), square("a8"));
+}
+
+#[test]
+fn view_round_trip() {
+    for bottom in [Player::White, Player::Black] {
+        for i in 0..64 {
+            let s = Square::new(i);
+            let (column, row) = s.to_view(bottom);
+            assert!(column < 8 && row < 8);
+            assert_eq!(Square::from_view(column, row, bottom), s);
+        }
+    }
+    assert_eq!(square("e2").to_view(Player::White), (4, 6));
+    assert_eq!(square("e2").to_view(Player::Black), (3, 1));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    Board,
+};
+use common::perft;
+
+/// Returns the castle moves in the position.
+fn castles(fen: &str) -> Vec<String> {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let mut buffer = InlineBuffer::<256>::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+    buffer
+        .iter()
+        .filter(|m| m.is_castle())
+        .map(|m| format!("{}{}", m.from(), m.to()))
+        .collect()
+}
+
+#[test]
+fn castle_legality() {
+    let cases: &[(&str, &[&str])] = &[
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &["e1g1", "e1c1"]),
+        ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &["e8g8", "e8c8"]),
+        // In check.
+        ("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1", &[]),
+        // Passing an attacked square.
+        ("4k3/8/b7/8/8/8/8/R3K2R w KQ - 0 1", &["e1c1"]),
+        ("4k3/8/8/8/8/8/6p1/R3K2R w KQ - 0 1", &["e1c1"]),
+        // Landing on an attacked square.
+        ("4k3/8/8/8/8/7b/8/R3K2R w KQ - 0 1", &["e1c1"]),
+        ("4k3/8/8/8/8/7n/8/R3K2R w KQ - 0 1", &["e1c1"]),
+        ("4k3/8/8/8/8/4n3/8/R3K2R w KQ - 0 1", &[]),
+        // Only the rook passes an attacked square.
+        ("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", &["e1g1", "e1c1"]),
+        ("4k3/8/8/8/8/8/3n4/R3K2R w KQ - 0 1", &["e1c1"]),
+        ("r3k2r/8/8/8/8/8/8/1R2K1R1 b kq - 0 1", &["e8c8"]),
+        ("r3k2r/8/8/8/8/8/8/2R1K2R b kq - 0 1", &["e8g8"]),
+    ];
+    for (fen, expected) in cases {
+        let mut found = castles(fen);
+        found.sort();
+        let mut expected: Vec<_> = expected.iter().map(|x| x.to_string()).collect();
+        expected.sort();
+        assert_eq!(found, expected, "castles in {}", fen);
+    }
+}
+
+#[test]
+fn perft_counts() {
+    let gen = MoveGenerator::new();
+    let positions: &[(&str, &[usize])] = &[
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8902, 197281],
+        ),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        ),
+        (
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43238],
+        ),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        ),
+    ];
+    for (fen, counts) in positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                perft(&gen, &mut board, depth + 1),
+                *count,
+                "perft {} of {}",
+                depth + 1,
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn promotion_capture_on_rook_square_removes_castle_right() {
+    let gen = MoveGenerator::new();
+    let cases = [
+        ("r3k2r/6P1/8/8/8/8/8/4K3 w kq - 0 1", "g7h8q", "q"),
+        ("r3k2r/1P6/8/8/8/8/8/4K3 w kq - 0 1", "b7a8n", "k"),
+        ("4k3/8/8/8/8/8/6p1/R3K2R b KQ - 0 1", "g2h1r", "Q"),
+        ("4k3/8/8/8/8/8/1p6/R3K2R b KQ - 0 1", "b2a1b", "K"),
+    ];
+    for (fen, m, castle) in cases {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        let m = chess_core::Move::from_uci_on_board(m, &board, &gen).unwrap();
+        let undo = board.make_move(m);
+        assert_eq!(board.to_fen().split(' ').nth(2), Some(castle), "{}", fen);
+        board.unmake_move(undo);
+        assert_eq!(board.to_fen().split(' ').nth(2), fen.split(' ').nth(2));
+    }
+}
@@ -0,0 +1,67 @@
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, CheckersError, InlineBuffer, MoveGenerator},
+    Board,
+};
+
+/// Validate the checkers in every position to the given depth, returns the amount of positions
+/// in check.
+fn check_all(gen: &MoveGenerator, b: &mut Board, depth: usize) -> usize {
+    let mut buffer = InlineBuffer::<512>::new();
+    let info = gen.gen_moves::<gen_type::All, _, _>(b, &mut buffer);
+    if let Err(e) = gen.validate_checkers(b, &info) {
+        panic!("{}: {}", b.to_fen(), e);
+    }
+    let mut checks = gen.checkers(b, &info).any() as usize;
+    if depth == 0 {
+        return checks;
+    }
+    for m in buffer.iter() {
+        let undo = b.make_move(m);
+        checks += check_all(gen, b, depth - 1);
+        b.unmake_move(undo);
+    }
+    checks
+}
+
+#[test]
+fn perft_positions_have_consistent_checkers() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ];
+    let mut checks = 0;
+    for fen in positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        checks += check_all(&gen, &mut board, 3);
+    }
+    // Drops give many more moves per position.
+    let mut board = Board::from_fen("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1", EndChain).unwrap();
+    checks += check_all(&gen, &mut board, 1);
+    assert!(checks > 0);
+}
+
+#[test]
+fn impossible_checkers() {
+    let gen = MoveGenerator::new();
+    let error = |fen: &str| {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        gen.validate_checkers(&board, &gen.gen_info(&board))
+    };
+
+    // A discovered check together with the check of the moved piece.
+    assert_eq!(error("4k3/8/8/8/1b6/3n4/8/4K3 w - - 0 1"), Ok(()));
+    assert!(matches!(
+        error("4k3/8/8/8/8/3n1n2/8/4K3 w - - 0 1"),
+        Err(CheckersError::PawnsOrKnights { .. })
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/3n4/8/r3K2q w - - 0 1"),
+        Err(CheckersError::TooMany { .. })
+    ));
+}
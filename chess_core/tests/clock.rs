@@ -0,0 +1,53 @@
+use chess_core::{clock::Clock, Player};
+use std::time::{Duration, Instant};
+
+fn secs(x: u64) -> Duration {
+    Duration::from_secs(x)
+}
+
+#[test]
+fn moves_use_time_and_add_increment() {
+    let start = Instant::now();
+    let mut clock = Clock::new(secs(60), secs(2));
+    clock.start(Player::White, start);
+    assert_eq!(clock.remaining(Player::White, start + secs(10)), secs(50));
+    assert_eq!(clock.remaining(Player::Black, start + secs(10)), secs(60));
+
+    clock.press(start + secs(10));
+    assert_eq!(clock.running(), Some(Player::Black));
+    assert_eq!(clock.remaining(Player::White, start + secs(30)), secs(52));
+    assert_eq!(clock.remaining(Player::Black, start + secs(30)), secs(40));
+
+    clock.press(start + secs(30));
+    assert_eq!(clock.running(), Some(Player::White));
+    assert_eq!(clock.remaining(Player::Black, start + secs(30)), secs(42));
+    assert_eq!(clock.flagged(start + secs(30)), None);
+}
+
+#[test]
+fn flag_falls_while_thinking() {
+    let start = Instant::now();
+    let mut clock = Clock::new(secs(5), secs(1));
+    clock.start(Player::White, start);
+    clock.press(start + secs(1));
+    assert_eq!(clock.flagged(start + secs(6)), None);
+    assert_eq!(clock.flagged(start + secs(7)), Some(Player::Black));
+    assert_eq!(
+        clock.remaining(Player::Black, start + secs(7)),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn late_move_keeps_flag() {
+    let start = Instant::now();
+    let mut clock = Clock::new(secs(5), secs(1));
+    clock.start(Player::White, start);
+    clock.press(start + secs(8));
+    assert_eq!(clock.flagged(start + secs(8)), Some(Player::White));
+    assert_eq!(
+        clock.remaining(Player::White, start + secs(8)),
+        Duration::ZERO
+    );
+    assert_eq!(clock.running(), Some(Player::Black));
+}
@@ -0,0 +1,25 @@
+use chess_core::{
+    board::MoveChain,
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    Board,
+};
+
+/// Count the leaf nodes of the move tree of the given depth.
+pub fn perft<C: MoveChain>(gen: &MoveGenerator, b: &mut Board<C>, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    // Crazyhouse positions with full pockets can have well over 256 moves.
+    let mut buffer = InlineBuffer::<512>::new();
+    gen.gen_moves::<gen_type::All, _, _>(b, &mut buffer);
+    if depth == 1 {
+        return buffer.len();
+    }
+    let mut count = 0;
+    for m in buffer.iter() {
+        let undo = b.make_move(m);
+        count += perft(gen, b, depth - 1);
+        b.unmake_move(undo);
+    }
+    count
+}
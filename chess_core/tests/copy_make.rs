@@ -0,0 +1,34 @@
+use chess_core::{
+    board::{Board, HashChain},
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+};
+
+#[test]
+fn make_move_copy_equals_make_move() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "8/8/8/KPp4r/8/8/8/7k w - c6 0 1",
+    ];
+    for fen in positions {
+        let board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let mut buffer = InlineBuffer::<256>::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+        for m in buffer.iter() {
+            let copy = board.make_move_copy(m);
+
+            let mut made = board.clone();
+            made.make_move(m);
+            assert!(copy.is_equal(&made), "{} in {}", m, fen);
+            assert_eq!(copy.chain.hash, made.chain.hash, "{} in {}", m, fen);
+            assert_eq!(copy.phase(), made.phase(), "{} in {}", m, fen);
+        }
+        // The original board is left alone.
+        assert_eq!(
+            board.to_fen(),
+            Board::from_fen(fen, HashChain::new()).unwrap().to_fen()
+        );
+    }
+}
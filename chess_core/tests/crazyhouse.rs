@@ -0,0 +1,78 @@
+mod common;
+
+use chess_core::{
+    board::{Board, HashChain, Variant},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, MAX_MOVES},
+    Move, Piece, Square,
+};
+use common::perft;
+
+/// Perft results for crazyhouse test positions as published by python-chess and Fairy-Stockfish.
+#[test]
+fn crazyhouse_perft() {
+    let gen = MoveGenerator::new();
+    let positions: &[(&str, &[usize])] = &[
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            &[20, 400, 8902, 197281],
+        ),
+        ("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1", &[301, 75353]),
+        ("4k3/1Q~6/8/8/4b3/8/Kpp5/8/ b - - 0 1", &[20, 360, 5445]),
+    ];
+    for (fen, counts) in positions {
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        assert_eq!(board.variant, Variant::Crazyhouse);
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                perft(&gen, &mut board, depth + 1),
+                *count,
+                "{} depth {}",
+                fen,
+                depth + 1
+            );
+        }
+        // Making and unmaking drops leaves the hash as it was.
+        assert_eq!(
+            board.chain.hash,
+            Board::from_fen(fen, HashChain::new()).unwrap().chain.hash
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "kings can't be dropped")]
+fn kings_are_not_dropped() {
+    Move::drop(Piece::BlackKing, Square::E1);
+}
+
+#[test]
+fn full_pockets_fit_in_buffer() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(
+        "r3k2r/8/8/8/8/8/8/R3K2R[QRBNPqrbnp] w KQkq - 0 1",
+        HashChain::new(),
+    )
+    .unwrap();
+    let mut moves = InlineBuffer::<MAX_MOVES>::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+    assert_eq!(moves.len(), 306);
+    let info = gen.gen_info(&board);
+    assert_eq!(gen.legal_move_count(&board, &info), 306);
+    assert!(gen.has_legal_move(&board, &info));
+}
+
+#[test]
+fn only_pawn_drops_reset_move_clock() {
+    let gen = MoveGenerator::new();
+    let mut board =
+        Board::from_fen("4k3/8/8/8/8/8/8/4K3[Nn] w - - 10 30", HashChain::new()).unwrap();
+    board.make_move(Move::from_uci_on_board("N@c3", &board, &gen).unwrap());
+    assert_eq!(board.state.move_clock, 11);
+
+    let mut board =
+        Board::from_fen("4k3/8/8/8/8/8/8/4K3[Pp] w - - 10 30", HashChain::new()).unwrap();
+    let undo = board.make_move(Move::from_uci_on_board("P@c3", &board, &gen).unwrap());
+    assert_eq!(board.state.move_clock, 0);
+    board.unmake_move(undo);
+    assert_eq!(board.state.move_clock, 10);
+}
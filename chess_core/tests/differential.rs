@@ -0,0 +1,146 @@
+//! Plays random games and compares the board after every make and unmake with a board built
+//! from scratch from its pieces.
+//!
+//! Run with `cargo test -p chess_core --features differential`, or `--all-features`. Release
+//! builds play a million moves and debug builds a hundred thousand, the amount can be changed
+//! with the `DIFFERENTIAL_MOVES` environment variable.
+#![cfg(feature = "differential")]
+
+use chess_core::{
+    board::{Board, HashChain},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    ExtraState, Move, Piece, Player, Square,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::env;
+
+type HashBoard = Board<HashChain>;
+
+const POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    // Promotions which can take the rooks in the corners.
+    "r3k2r/1P4P1/8/8/8/8/1p4p1/R3K2R w KQkq - 0 1",
+    "rn2k2r/PPP3PP/8/8/8/8/ppp3pp/RN2K2R b KQkq - 0 1",
+];
+
+/// Build the position of the board again from its pieces and state.
+fn rebuild(board: &HashBoard) -> HashBoard {
+    let pieces: Vec<_> = (0..64)
+        .map(Square::new)
+        .filter_map(|s| board.squares[s].map(|p| (s, p)))
+        .collect();
+    let en_passant = (board.state.en_passant != ExtraState::INVALID_ENPASSANT).then(|| {
+        let rank = match board.state.player {
+            Player::White => 5,
+            Player::Black => 2,
+        };
+        Square::from_file_rank(board.state.en_passant, rank)
+    });
+    let mut res = Board::from_pieces(
+        &pieces,
+        board.state.player,
+        board.state.castle,
+        en_passant,
+        HashChain::new(),
+    )
+    .unwrap_or_else(|e| panic!("{}: {:#}", board.to_fen(), e));
+    // The half-move clock is not part of the position or its hash.
+    res.state.move_clock = board.state.move_clock;
+    res
+}
+
+/// Returns the castle rights which still have their king and rook on the board.
+fn possible_castle(board: &HashBoard) -> u8 {
+    let at = |s: &str, p: Piece| board.squares[Square::from_name(s).unwrap()] == Some(p);
+    let mut res = 0;
+    for (king, rook, flag, piece) in [
+        ("e1", "h1", ExtraState::WHITE_KING_CASTLE, Piece::WhiteRook),
+        ("e1", "a1", ExtraState::WHITE_QUEEN_CASTLE, Piece::WhiteRook),
+        ("e8", "h8", ExtraState::BLACK_KING_CASTLE, Piece::BlackRook),
+        ("e8", "a8", ExtraState::BLACK_QUEEN_CASTLE, Piece::BlackRook),
+    ] {
+        let king_piece = if piece == Piece::WhiteRook {
+            Piece::WhiteKing
+        } else {
+            Piece::BlackKing
+        };
+        if at(king, king_piece) && at(rook, piece) {
+            res |= flag;
+        }
+    }
+    res
+}
+
+fn compare(board: &HashBoard, context: &dyn Fn() -> String) {
+    assert!(board.is_valid(), "{}: {:?}", context(), board.validate());
+    let fresh = rebuild(board);
+    assert!(board.is_equal(&fresh), "{}", context());
+    assert_eq!(board.squares, fresh.squares, "{}", context());
+    assert_eq!(board.chain.hash, fresh.chain.hash, "{}", context());
+    assert_eq!(
+        board.state.castle & !possible_castle(board),
+        0,
+        "castle rights without king and rook, {}",
+        context()
+    );
+}
+
+#[test]
+fn make_unmake_matches_fresh_board() {
+    let total: u64 = env::var("DIFFERENTIAL_MOVES")
+        .ok()
+        .map(|x| x.parse().expect("invalid DIFFERENTIAL_MOVES"))
+        .unwrap_or(if cfg!(debug_assertions) {
+            100_000
+        } else {
+            1_000_000
+        });
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(0x5EED_1864);
+
+    let mut moves = 0;
+    let mut game = 0;
+    while moves < total {
+        let fen = POSITIONS[game % POSITIONS.len()];
+        game += 1;
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let mut played: Vec<Move> = Vec::new();
+        for _ in 0..300 {
+            let mut buffer = InlineBuffer::<256>::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+            if buffer.len() == 0 {
+                break;
+            }
+            let m = buffer.get(rng.gen_range(0..buffer.len()));
+            let context = |board: &HashBoard| {
+                let line: Vec<_> = played.iter().map(|m| m.to_uci()).collect();
+                format!(
+                    "{} after {} in {} moves {}",
+                    m,
+                    board.to_fen(),
+                    fen,
+                    line.join(" ")
+                )
+            };
+
+            let before = board.clone();
+            let undo = board.make_move(m);
+            compare(&board, &|| context(&board));
+            board.unmake_move(undo);
+            assert!(board.is_equal(&before), "unmake of {}", context(&board));
+            assert_eq!(
+                board.chain.hash,
+                before.chain.hash,
+                "unmake of {}",
+                context(&board)
+            );
+
+            board.make_move(m);
+            played.push(m);
+            moves += 1;
+        }
+    }
+}
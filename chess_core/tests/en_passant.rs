@@ -0,0 +1,73 @@
+mod common;
+
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    Board,
+};
+use common::perft;
+
+/// Returns the en passant moves in the position.
+fn en_passants(fen: &str) -> Vec<String> {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let mut buffer = InlineBuffer::<256>::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+    buffer
+        .iter()
+        .filter(|m| m.is_en_passant())
+        .map(|m| format!("{}{}", m.from(), m.to()))
+        .collect()
+}
+
+#[test]
+fn en_passant_legality() {
+    let cases: &[(&str, &[&str])] = &[
+        ("4k3/8/8/K1Pp4/8/8/8/8 w - d6 0 1", &["c5d6"]),
+        ("4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1", &["c5d6", "e5d6"]),
+        // Both pawns leave the rank of the king.
+        ("8/8/8/K1Pp3r/8/8/8/7k w - d6 0 1", &[]),
+        ("8/8/8/r2pP2K/8/8/8/k7 w - d6 0 1", &[]),
+        ("8/8/8/8/k2pP2R/8/8/7K b - e3 0 1", &[]),
+        // A pawn in between keeps the rank blocked.
+        ("8/8/8/K1Pp1p1r/8/8/8/7k w - d6 0 1", &["c5d6"]),
+        // The captured pawn blocks a diagonal to the king.
+        ("7k/8/8/2Pp4/8/8/K7/8 w - d6 0 1", &["c5d6"]),
+        ("6bk/8/8/2Pp4/8/8/K7/8 w - d6 0 1", &[]),
+        // The capturing pawn stays on the pinned diagonal.
+        ("5b1k/8/8/2Pp4/8/K7/8/8 w - d6 0 1", &["c5d6"]),
+        // The capturing pawn is the only piece between the king and a bishop.
+        ("7k/8/5b2/3pP3/8/8/1K6/8 w - d6 0 1", &[]),
+        ("8/1k6/8/8/3Pp3/5B2/8/7K b - d3 0 1", &[]),
+        // The king is in check from the rook the pawns would uncover.
+        ("8/8/8/2pPK2r/8/8/8/k7 w - c6 0 1", &[]),
+        ("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", &[]),
+    ];
+    for (fen, expected) in cases {
+        let mut found = en_passants(fen);
+        found.sort();
+        let mut expected: Vec<_> = expected.iter().map(|x| x.to_string()).collect();
+        expected.sort();
+        assert_eq!(found, expected, "en passants in {}", fen);
+    }
+}
+
+#[test]
+fn perft_en_passant() {
+    let gen = MoveGenerator::new();
+    let positions: &[(&str, usize, usize)] = &[
+        // Avoid an illegal en passant capture.
+        ("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1134888),
+        ("8/8/8/8/k1p4R/8/3P4/3K4 w - - 0 1", 6, 1134888),
+        ("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1015133),
+        ("8/b2p2k1/8/2P5/8/4K3/8/8 b - - 0 1", 6, 1015133),
+        ("8/5bk1/8/2Pp4/8/1K6/8/8 w - d6 0 1", 6, 824064),
+        // En passant capture checks the opponent.
+        ("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1440467),
+        ("8/5k2/8/2Pp4/2B5/1K6/8/8 w - d6 0 1", 6, 1440467),
+    ];
+    for (fen, depth, count) in positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        assert_eq!(perft(&gen, &mut board, *depth), *count, "perft of {}", fen);
+    }
+}
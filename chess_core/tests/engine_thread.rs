@@ -0,0 +1,112 @@
+use chess_core::{
+    board::EndChain,
+    engine::{Engine, EngineControl, EngineLimit, EngineThread, Info, OptionValue, Response},
+    gen::MoveGenerator,
+    Board, Move,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Engine which records the calls made on it, panicking on search if `panic` is set.
+struct Recorder {
+    calls: Arc<Mutex<Vec<String>>>,
+    panic: bool,
+}
+
+impl<C: EngineControl> Engine<C> for Recorder {
+    const NAME: &'static str = "Recorder";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        if self.panic {
+            panic!("first search");
+        }
+        self.calls.lock().unwrap().push("go".to_string());
+        None
+    }
+
+    fn set_board(&mut self, board: Board) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("board {}", board.to_fen()));
+    }
+
+    fn make_move(&mut self, m: Move) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("move {}", m.to_uci()));
+    }
+
+    fn set_option(&mut self, name: String, value: OptionValue) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("option {} {:?}", name, value));
+    }
+}
+
+fn wait_done(thread: &EngineThread) -> Vec<Info> {
+    let mut infos = Vec::new();
+    loop {
+        match thread.recv().recv().unwrap() {
+            Response::Info(x) => infos.push(x),
+            Response::Done(_) => return infos,
+        }
+    }
+}
+
+#[test]
+fn recovery_replays_moves_and_options() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let engine = Recorder {
+        calls: Arc::default(),
+        panic: true,
+    };
+    let fresh = calls.clone();
+    let thread = EngineThread::with_factory(engine, move || Recorder {
+        calls: fresh.clone(),
+        panic: false,
+    });
+
+    thread.set_option("Hash".to_string(), OptionValue::Spin(16));
+    thread.set_option("Ponder".to_string(), OptionValue::Check(true));
+    thread.set_option("Hash".to_string(), OptionValue::Spin(64));
+
+    let gen = MoveGenerator::new();
+    let mut board = Board::start_position(EndChain);
+    thread.set_board(board.clone());
+    for name in ["g1f3", "g8f6", "f3g1", "f6g8"].iter() {
+        let m = Move::from_uci_on_board(name, &board, &gen).unwrap();
+        board.make_move(m);
+        thread.make_move(m);
+    }
+
+    thread.start(None, EngineLimit::depth(1));
+    let infos = wait_done(&thread);
+    assert!(infos
+        .iter()
+        .any(|x| matches!(x, Info::Debug(x) if x == "engine panicked: first search")));
+    assert_eq!(thread.restarts(), 1);
+
+    thread.start(None, EngineLimit::depth(1));
+    wait_done(&thread);
+
+    // The calls of the fresh engine, with a single value per option.
+    let start = Board::start_position(EndChain).to_fen();
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "option Hash Spin(64)".to_string(),
+            "option Ponder Check(true)".to_string(),
+            format!("board {}", start),
+            "move g1f3".to_string(),
+            "move g8f6".to_string(),
+            "move f3g1".to_string(),
+            "move f6g8".to_string(),
+            "go".to_string(),
+        ]
+    );
+}
@@ -0,0 +1,122 @@
+use chess_core::{
+    board::{Board, EvalChain, HashChain, MoveChain, Psqt, PsqtTables},
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    util::{BoardArray, PieceArray},
+    Piece, Square,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Tables with a random value for every piece on every square.
+fn random_tables() -> &'static PsqtTables {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let mut values = PieceArray::new(BoardArray::new(Psqt::default()));
+    for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+        for s in 0..64 {
+            values[p][Square::new(s)] =
+                Psqt::new(rng.gen_range(-1000..1000), rng.gen_range(-1000..1000));
+        }
+    }
+    Box::leak(Box::new(PsqtTables { values }))
+}
+
+type EvalBoard = Board<HashChain<EvalChain>>;
+
+/// Make every move to the given depth, checking the accumulated values and the hash against a
+/// recomputation after every move and undo.
+fn check_chain(gen: &MoveGenerator, b: &mut EvalBoard, depth: usize) {
+    if depth == 0 {
+        return;
+    }
+    let tables = b.chain.next_chain().tables();
+    let mut buffer = InlineBuffer::<512>::new();
+    gen.gen_moves::<gen_type::All, _, _>(b, &mut buffer);
+    for m in buffer.iter() {
+        let psqt = b.chain.next_chain().psqt();
+        let hash = b.chain.hash;
+        let undo = b.make_move(m);
+        assert_eq!(
+            b.chain.next_chain().psqt(),
+            tables.sum(&b.pieces),
+            "after {} in {}",
+            m,
+            b.to_fen()
+        );
+        let mut fresh = Board::start_position(HashChain::new());
+        fresh.copy_position(b);
+        assert_eq!(
+            b.chain.hash,
+            fresh.chain.hash,
+            "after {} in {}",
+            m,
+            b.to_fen()
+        );
+        check_chain(gen, b, depth - 1);
+        b.unmake_move(undo);
+        assert_eq!(
+            b.chain.next_chain().psqt(),
+            psqt,
+            "after undoing {} in {}",
+            m,
+            b.to_fen()
+        );
+        assert_eq!(b.chain.hash, hash, "after undoing {} in {}", m, b.to_fen());
+    }
+}
+
+#[test]
+fn accumulated_values_match_recomputation() {
+    let gen = MoveGenerator::new();
+    let tables = random_tables();
+    let positions = [
+        // Castling and en passant.
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        // Promotions with and without captures.
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K w kq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1",
+    ];
+    for fen in positions {
+        let mut board = Board::from_fen(fen, HashChain::with(EvalChain::new(tables))).unwrap();
+        assert_eq!(
+            board.chain.next_chain().psqt(),
+            tables.sum(&board.pieces),
+            "{}",
+            fen
+        );
+        check_chain(&gen, &mut board, 3);
+    }
+}
+
+#[test]
+fn random_playouts() {
+    let gen = MoveGenerator::new();
+    let tables = random_tables();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..20 {
+        let mut board = Board::start_position(HashChain::with(EvalChain::new(tables)));
+        for _ in 0..200 {
+            let mut buffer = InlineBuffer::<512>::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+            if buffer.as_slice().is_empty() {
+                break;
+            }
+            let moves = buffer.as_slice();
+            board.make_move(moves[rng.gen_range(0..moves.len())]);
+            assert_eq!(
+                board.chain.next_chain().psqt(),
+                tables.sum(&board.pieces),
+                "{}",
+                board.to_fen()
+            );
+        }
+    }
+}
+
+#[test]
+fn drops_are_counted() {
+    let gen = MoveGenerator::new();
+    let tables = random_tables();
+    let fen = "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R[Pn] w KQkq - 0 1";
+    let mut board = Board::from_fen(fen, HashChain::with(EvalChain::new(tables))).unwrap();
+    check_chain(&gen, &mut board, 2);
+}
@@ -0,0 +1,124 @@
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, IllegalReason, InlineBuffer, MoveGenerator},
+    Board, Square,
+};
+
+fn explain(fen: &str, from: &str, to: &str) -> Option<IllegalReason> {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    gen.explain_illegal(
+        &board,
+        Square::from_name(from).unwrap(),
+        Square::from_name(to).unwrap(),
+    )
+}
+
+fn sq(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[test]
+fn reasons() {
+    use IllegalReason::*;
+
+    let cases: &[(&str, &str, &str, IllegalReason)] = &[
+        (START, "e4", "e5", NoPiece),
+        (START, "e7", "e5", NotYourPiece),
+        (START, "a1", "a2", CaptureOwnPiece),
+        (START, "b1", "b3", WrongPieceMovement),
+        (START, "e2", "d3", WrongPieceMovement),
+        (START, "e2", "e5", WrongPieceMovement),
+        (START, "a1", "a4", BlockedPath),
+        ("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1", "e2", "e3", BlockedPath),
+        ("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1", "e2", "e4", BlockedPath),
+        // The bishop on b4 pins the knight.
+        (
+            "4k3/8/8/8/1b6/8/3N4/4K3 w - - 0 1",
+            "d2",
+            "f3",
+            LeavesKingInCheck { by: sq("b4") },
+        ),
+        // The king steps into the rook's file.
+        (
+            "3rk3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "e1",
+            "d1",
+            LeavesKingInCheck { by: sq("d8") },
+        ),
+        // Ignoring a check.
+        (
+            "4r1k1/8/8/8/8/8/P7/4K3 w - - 0 1",
+            "a2",
+            "a3",
+            LeavesKingInCheck { by: sq("e8") },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/R3K2R w Q - 0 1",
+            "e1",
+            "g1",
+            NoCastleRights,
+        ),
+        ("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1", "e1", "c1", BlockedPath),
+        (
+            "4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+            "e1",
+            "g1",
+            CastleThroughCheck { by: sq("f8") },
+        ),
+        (
+            "4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+            "e1",
+            "g1",
+            CastleWhileInCheck { by: sq("e8") },
+        ),
+    ];
+    for (fen, from, to, reason) in cases {
+        assert_eq!(
+            explain(fen, from, to),
+            Some(*reason),
+            "{}{} in {}",
+            from,
+            to,
+            fen
+        );
+    }
+}
+
+#[test]
+fn agrees_with_move_generator() {
+    let gen = MoveGenerator::new();
+    for fen in [
+        START,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/2Pp4/8/8/8/4K3 w - d6 0 1",
+        "8/8/8/K1Pp3r/8/8/8/7k w - d6 0 1",
+        // The pinned bishop may still move along the pin.
+        "4k3/8/8/8/1b6/8/3B4/4K3 w - - 0 1",
+        "4r1k1/8/8/8/8/8/P7/4K3 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        let mut moves = InlineBuffer::<256>::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+        for from in 0..64 {
+            for to in 0..64 {
+                let (from, to) = (Square::new(from), Square::new(to));
+                let legal = moves.iter().any(|m| m.from() == from && m.to() == to);
+                let reason = gen.explain_illegal(&board, from, to);
+                assert_eq!(
+                    reason.is_none(),
+                    legal,
+                    "{}{} in {}: {:?}",
+                    from,
+                    to,
+                    fen,
+                    reason
+                );
+            }
+        }
+    }
+}
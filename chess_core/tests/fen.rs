@@ -0,0 +1,288 @@
+use chess_core::{
+    board::{EndChain, FenError, FenField},
+    Board, Player, Square,
+};
+
+#[test]
+fn optional_move_counters() {
+    let full = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+    let short = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+        EndChain,
+    )
+    .unwrap();
+    assert!(full.is_equal(&short));
+    assert_eq!(short.state.move_clock, 0);
+
+    // Trailing whitespace as in the perft suite entries.
+    let padded = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1   \n",
+        EndChain,
+    )
+    .unwrap();
+    assert!(full.is_equal(&padded));
+
+    let board = Board::from_fen("8/8/8/8/k2Pp3/8/8/4K3 b - d3 12 40", EndChain).unwrap();
+    assert_eq!(board.state.move_clock, 12);
+    let board = Board::from_fen("8/8/8/8/k2Pp3/8/8/4K3 b - d3 7", EndChain).unwrap();
+    assert_eq!(board.state.move_clock, 7);
+    let board = Board::from_fen("8/8/8/8/k2Pp3/8/8/4K3 b - d3", EndChain).unwrap();
+    assert_eq!(board.state.en_passant, 3);
+}
+
+#[test]
+fn malformed_fens() {
+    use FenError::*;
+
+    let invalid = |field, value: &str| InvalidField {
+        field,
+        value: value.to_string(),
+    };
+    let square = |name| Square::from_name(name).unwrap();
+    let cases = [
+        // Too few or too many ranks.
+        ("4k3/8/8/8/8/8/4K3 w - - 0 1", RankCount { count: 7 }),
+        ("4k3/8/8/8/8/8/8/8/4K3 w - - 0 1", RankCount { count: 9 }),
+        ("4k3/8/8/8/8/8/8/8/8/4K3 w - - 0 1", RankCount { count: 10 }),
+        // Ranks with too few or too many squares.
+        ("4k3/8/8/8/8/8/7/4K3 w - - 0 1", RankOverflow { rank: 2 }),
+        (
+            "4k3/8/8/8/8/8/9/4K3 w - - 0 1",
+            BadPieceChar {
+                offset: 14,
+                found: '9',
+            },
+        ),
+        ("4k3/8/8/8/8/8/8/4K4 w - - 0 1", RankOverflow { rank: 1 }),
+        ("4k3/8/8/8/8/8/54/4K3 w - - 0 1", RankOverflow { rank: 2 }),
+        ("4k3/8/8/8/8/8/8/4K3P w - - 0 1", RankOverflow { rank: 1 }),
+        ("4k4/8/8/8/8/8/8/4K3 w - - 0 1", RankOverflow { rank: 8 }),
+        // Invalid pieces.
+        (
+            "4k3/8/8/8/8/8/8/4K2X w - - 0 1",
+            BadPieceChar {
+                offset: 19,
+                found: 'X',
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/0K7 w - - 0 1",
+            BadPieceChar {
+                offset: 16,
+                found: '0',
+            },
+        ),
+        (
+            "~4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            BadPieceChar {
+                offset: 0,
+                found: '~',
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3[QX] w - - 0 1",
+            invalid(FenField::Pocket, "QX"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3[K] w - - 0 1",
+            invalid(FenField::Pocket, "K"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3[Q w - - 0 1",
+            invalid(FenField::Pocket, "Q"),
+        ),
+        // Missing or invalid fields.
+        (
+            "",
+            MissingField {
+                field: FenField::Pieces,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3",
+            MissingField {
+                field: FenField::Player,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w",
+            MissingField {
+                field: FenField::Castling,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w -",
+            MissingField {
+                field: FenField::EnPassant,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+            invalid(FenField::Player, "x"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w QK - 0 1",
+            invalid(FenField::Castling, "QK"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w KK - 0 1",
+            invalid(FenField::Castling, "KK"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w Kx - 0 1",
+            invalid(FenField::Castling, "Kx"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1",
+            invalid(FenField::Castling, "KQkq"),
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R4K1R w KQkq - 0 1",
+            invalid(FenField::Castling, "KQkq"),
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K3 w Kkq - 0 1",
+            invalid(FenField::Castling, "Kkq"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+            invalid(FenField::EnPassant, "e9"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e66 0 1",
+            invalid(FenField::EnPassant, "e66"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+            invalid(FenField::HalfMoveClock, "x"),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 x",
+            invalid(FenField::FullMoveNumber, "x"),
+        ),
+        // Impossible positions.
+        (
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            NoKing {
+                player: Player::Black,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/8 w - - 0 1",
+            NoKing {
+                player: Player::White,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/K3K3 w - - 0 1",
+            TooManyKings {
+                player: Player::White,
+            },
+        ),
+        (
+            "4k2k/8/8/8/8/8/8/4K3 w - - 0 1",
+            TooManyKings {
+                player: Player::Black,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/3PK3 w - - 0 1",
+            PawnOnBackRank {
+                square: square("d1"),
+            },
+        ),
+        (
+            "p3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            PawnOnBackRank {
+                square: square("a8"),
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+            InvalidEnPassant {
+                square: square("e6"),
+            },
+        ),
+        (
+            "4k3/8/8/4p3/8/8/8/4K3 w - e3 0 1",
+            InvalidEnPassant {
+                square: square("e3"),
+            },
+        ),
+        (
+            "4k3/4p3/8/4p3/8/8/8/4K3 w - e6 0 1",
+            InvalidEnPassant {
+                square: square("e6"),
+            },
+        ),
+        (
+            "4k3/8/8/4P3/8/8/8/4K3 w - e6 0 1",
+            InvalidEnPassant {
+                square: square("e6"),
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K2r b - - 0 1",
+            SideNotToMoveInCheck {
+                player: Player::White,
+            },
+        ),
+        (
+            "8/8/8/8/8/8/3k4/4K3 w - - 0 1",
+            SideNotToMoveInCheck {
+                player: Player::Black,
+            },
+        ),
+    ];
+    for (fen, error) in cases {
+        assert_eq!(Board::from_fen(fen, EndChain).err(), Some(error), "{}", fen);
+    }
+}
+
+#[test]
+fn error_messages() {
+    let err = Board::from_fen("4k3/8/8/8/8/8/8/4K2X w - - 0 1", EndChain).unwrap_err();
+    assert_eq!(err.to_string(), "invalid piece `X` at offset 19");
+    let err = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w Kx - 0 1", EndChain).unwrap_err();
+    assert_eq!(err.to_string(), "invalid castling rights `Kx`");
+}
+
+#[test]
+fn lenient() {
+    // Missing fields get their defaults.
+    let board = Board::from_fen_lenient("4k3/8/8/8/8/8/8/4K3", EndChain).unwrap();
+    assert!(board.to_fen().starts_with("4k3/8/8/8/8/8/8/4K3 w - - "));
+
+    // Pawns on the back ranks are removed, impossible castle rights and en passant squares
+    // cleared.
+    let board = Board::from_fen_lenient("P3k2r/8/8/8/8/8/8/3pK3 w KQk e6 0 1", EndChain).unwrap();
+    assert!(board.to_fen().starts_with("4k2r/8/8/8/8/8/8/4K3 w k - "));
+    assert!(board.is_valid());
+
+    // What can't be fixed is still an error.
+    for (fen, error) in [
+        (
+            "8/8/8/8/8/8/8/4K3",
+            FenError::NoKing {
+                player: Player::Black,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/4R3/4K3 w",
+            FenError::SideNotToMoveInCheck {
+                player: Player::Black,
+            },
+        ),
+    ] {
+        assert_eq!(
+            Board::from_fen_lenient(fen, EndChain).err(),
+            Some(error),
+            "{}",
+            fen
+        );
+    }
+}
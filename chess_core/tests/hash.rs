@@ -0,0 +1,64 @@
+use chess_core::{
+    board::{Board, HashChain},
+    gen::{gen_type, MoveGenerator},
+    Move,
+};
+
+const POSITIONS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 3 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+];
+
+fn play(board: &mut Board<HashChain>, moves: &str) {
+    let gen = MoveGenerator::new();
+    for name in moves.split_whitespace() {
+        let m = Move::from_uci_on_board(name, board, &gen).unwrap();
+        board.make_move(m);
+    }
+}
+
+#[test]
+fn make_unmake_restores_hash() {
+    let gen = MoveGenerator::new();
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let original = board.chain.hash;
+        let mut moves = Vec::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+        for m in moves {
+            let undo = board.make_move(m);
+            // Also when the move changes the castle rights.
+            let fresh = Board::from_fen(&board.to_fen(), HashChain::new()).unwrap();
+            assert_eq!(board.chain.hash, fresh.chain.hash, "{} in {}", m, fen);
+            board.unmake_move(undo);
+            assert_eq!(board.chain.hash, original, "{} in {}", m, fen);
+        }
+    }
+}
+
+#[test]
+fn castle_rights_change_hash() {
+    let hash = |fen: &str| Board::from_fen(fen, HashChain::new()).unwrap().chain.hash;
+    let rights = ["KQkq", "Qkq", "Kkq", "kq", "K", "-"];
+    for (i, a) in rights.iter().enumerate() {
+        for b in rights[i + 1..].iter() {
+            assert_ne!(
+                hash(&format!("r3k2r/8/8/8/8/8/8/R3K2R w {} - 0 1", a)),
+                hash(&format!("r3k2r/8/8/8/8/8/8/R3K2R w {} - 0 1", b)),
+                "{} and {}",
+                a,
+                b
+            );
+        }
+    }
+
+    // The same pieces after moving the king back and forth, without the right to castle.
+    let mut board =
+        Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", HashChain::new()).unwrap();
+    let original = board.chain.hash;
+    play(&mut board, "e1f1 e8f8 f1e1 f8e8");
+    assert_ne!(board.chain.hash, original);
+    assert_eq!(board.chain.hash, hash("r3k2r/8/8/8/8/8/8/R3K2R w - - 4 3"));
+}
@@ -0,0 +1,141 @@
+use chess_core::gen::{InlineBuffer, MoveList};
+use chess_core::{Move, Square};
+
+fn buffer(n: u8) -> InlineBuffer<16> {
+    let mut buffer = InlineBuffer::new();
+    for i in 0..n {
+        buffer.push(Move::normal(Square::new(i), Square::new(i + 8)));
+    }
+    buffer
+}
+
+#[test]
+fn as_slice_len() {
+    let mut buffer = buffer(5);
+    assert_eq!(buffer.as_slice().len(), 5);
+    buffer.pop();
+    assert_eq!(buffer.as_slice().len(), 4);
+    buffer.clear();
+    assert!(buffer.as_slice().is_empty());
+}
+
+#[test]
+fn swap_remove() {
+    let mut buffer = buffer(4);
+    let last = buffer.get(3);
+    buffer.swap_remove(1);
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.get(1), last);
+    assert_eq!(buffer.as_slice()[1], last);
+}
+
+#[test]
+fn iteration_order() {
+    let mut buffer = buffer(6);
+    let expected: Vec<Move> = (0..6).map(|i| buffer.get(i)).collect();
+
+    assert_eq!(buffer.iter().collect::<Vec<_>>(), expected);
+    assert_eq!((&buffer).into_iter().collect::<Vec<_>>(), expected);
+    assert_eq!(buffer.as_slice(), &expected[..]);
+    assert_eq!(buffer.into_iter().collect::<Vec<_>>(), expected);
+
+    assert_eq!(buffer.drain().collect::<Vec<_>>(), expected);
+    assert!(buffer.as_slice().is_empty());
+}
+
+#[test]
+fn empty_buffer() {
+    let mut buffer = InlineBuffer::<16>::new();
+    assert_eq!(buffer.len(), 0);
+    assert_eq!(buffer.pop(), None);
+    buffer.truncate(0);
+    assert!(buffer.as_slice().is_empty());
+    assert_eq!(buffer.iter().count(), 0);
+}
+
+#[test]
+fn push_get_pop() {
+    let mut buffer = buffer(3);
+    assert_eq!(buffer.len(), 3);
+    for i in 0..3 {
+        assert_eq!(
+            buffer.get(i as usize),
+            Move::normal(Square::new(i), Square::new(i + 8))
+        );
+    }
+    let last = buffer.get(2);
+    assert_eq!(buffer.pop(), Some(last));
+    assert_eq!(buffer.len(), 2);
+    buffer.pop();
+    buffer.pop();
+    assert_eq!(buffer.pop(), None);
+}
+
+#[test]
+fn swap() {
+    let mut buffer = buffer(3);
+    let (a, c) = (buffer.get(0), buffer.get(2));
+    buffer.swap(0, 2);
+    assert_eq!(buffer.get(0), c);
+    assert_eq!(buffer.get(2), a);
+    buffer.swap(1, 1);
+    assert_eq!(buffer.len(), 3);
+}
+
+#[test]
+#[should_panic]
+fn swap_past_len() {
+    // Index 5 is within the capacity but was never initialized.
+    let mut buffer = buffer(3);
+    buffer.swap(0, 5);
+}
+
+#[test]
+#[should_panic]
+fn swap_empty() {
+    let mut buffer = InlineBuffer::<16>::new();
+    buffer.swap(0, 0);
+}
+
+#[test]
+#[should_panic]
+fn get_past_len() {
+    let buffer = buffer(3);
+    buffer.get(3);
+}
+
+#[test]
+fn swap_remove_last() {
+    let mut buffer = buffer(3);
+    buffer.swap_remove(2);
+    buffer.swap_remove(1);
+    buffer.swap_remove(0);
+    assert_eq!(buffer.len(), 0);
+    assert!(buffer.as_slice().is_empty());
+}
+
+#[test]
+#[should_panic]
+fn swap_remove_empty() {
+    let mut buffer = InlineBuffer::<16>::new();
+    buffer.swap_remove(0);
+}
+
+#[test]
+fn truncate() {
+    let mut buffer = buffer(5);
+    let expected: Vec<Move> = (0..2).map(|i| buffer.get(i)).collect();
+    buffer.truncate(2);
+    assert_eq!(buffer.as_slice(), &expected[..]);
+    buffer.truncate(2);
+    assert_eq!(buffer.len(), 2);
+    buffer.truncate(0);
+    assert_eq!(buffer.pop(), None);
+}
+
+#[test]
+#[should_panic]
+fn truncate_past_len() {
+    let mut buffer = buffer(2);
+    buffer.truncate(3);
+}
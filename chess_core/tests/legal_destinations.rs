@@ -0,0 +1,68 @@
+use chess_core::{bb::BB, board::EndChain, gen::MoveGenerator, Board, Square};
+
+fn bb(squares: &[&str]) -> BB {
+    squares.iter().fold(BB::EMPTY, |bb, name| {
+        bb | BB::square(Square::from_name(name).unwrap())
+    })
+}
+
+fn destinations(fen: &str, from: &str) -> BB {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    gen.legal_destinations(&board, Square::from_name(from).unwrap())
+}
+
+#[test]
+fn pinned_piece() {
+    // The rook on e2 is pinned to the king by the rook on e8 and can only move along the file.
+    let fen = "4r2k/8/8/8/8/8/4R3/4K3 w - - 0 1";
+    assert_eq!(
+        destinations(fen, "e2"),
+        bb(&["e3", "e4", "e5", "e6", "e7", "e8"])
+    );
+
+    // Without the pin it can also move along the rank.
+    let fen = "7k/8/8/8/8/8/4R3/4K3 w - - 0 1";
+    assert_eq!(destinations(fen, "e2").count(), 13);
+
+    // A pinned knight can't move at all.
+    let fen = "4r2k/8/8/8/8/8/4N3/4K3 w - - 0 1";
+    assert!(destinations(fen, "e2").none());
+}
+
+#[test]
+fn queen_in_the_open() {
+    let fen = "8/8/7k/8/3Q4/8/K7/8 w - - 0 1";
+    let res = destinations(fen, "d4");
+    assert_eq!(res.count(), 27);
+    let corners = bb(&["a1", "h8", "d1", "d8", "a4", "h4", "a7", "g1"]);
+    assert_eq!(res & corners, corners);
+}
+
+#[test]
+fn special_moves() {
+    // The squares the king lands on when castling.
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(
+        destinations(fen, "e1"),
+        bb(&["c1", "d1", "d2", "e2", "f2", "f1", "g1"])
+    );
+
+    // En passant and promotion.
+    let fen = "4k3/1P6/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(destinations(fen, "e5"), bb(&["d6", "e6"]));
+    assert_eq!(destinations(fen, "b7"), bb(&["b8"]));
+}
+
+#[test]
+fn not_the_player_to_move() {
+    let board = Board::start_position(EndChain);
+    let gen = MoveGenerator::new();
+    assert_eq!(
+        gen.legal_destinations(&board, Square::G1),
+        bb(&["f3", "h3"])
+    );
+    assert!(gen.legal_destinations(&board, Square::G8).none());
+    let empty = Square::from_name("e4").unwrap();
+    assert!(gen.legal_destinations(&board, empty).none());
+}
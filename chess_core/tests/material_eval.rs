@@ -0,0 +1,24 @@
+use chess_core::{
+    board::{Board, EndChain},
+    material_eval,
+};
+
+fn eval(fen: &str) -> i32 {
+    material_eval(&Board::from_fen(fen, EndChain).unwrap())
+}
+
+#[test]
+fn up_a_rook() {
+    // White has taken the rook on a8.
+    let fen = "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1";
+    assert_eq!(eval(fen), 500);
+    let fen = "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQk - 0 1";
+    assert_eq!(eval(fen), -500);
+}
+
+#[test]
+fn balanced() {
+    assert_eq!(material_eval(&Board::start_position(EndChain)), 0);
+    // Trading a knight for a bishop keeps the balance.
+    assert_eq!(eval("4k3/8/3n4/8/8/3B4/8/4K3 w - - 0 1"), 0);
+}
@@ -0,0 +1,47 @@
+use chess_core::{
+    board::{Board, HashChain},
+    ExtraState,
+};
+
+const FENS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K1R1 w Qkq - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+];
+
+#[test]
+fn mirror_start_position() {
+    let board = Board::start_position(HashChain::new());
+    let mirror = board.mirror();
+    assert!(mirror
+        .to_fen()
+        .starts_with("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - "));
+}
+
+#[test]
+fn mirror_recomputes_hash() {
+    for fen in FENS {
+        let board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let mirror = board.mirror();
+        assert!(mirror.is_valid());
+
+        let from_fen = Board::from_fen(&mirror.to_fen(), HashChain::new()).unwrap();
+        assert_eq!(mirror.chain.hash, from_fen.chain.hash);
+
+        let back = mirror.mirror();
+        assert!(back.is_equal(&board));
+        assert_eq!(back.chain.hash, board.chain.hash);
+    }
+}
+
+#[test]
+fn mirror_swaps_castling() {
+    let board = Board::from_fen(FENS[1], HashChain::new()).unwrap();
+    let castle = board.mirror().state.castle;
+    assert_eq!(
+        castle,
+        ExtraState::WHITE_KING_CASTLE
+            | ExtraState::WHITE_QUEEN_CASTLE
+            | ExtraState::BLACK_QUEEN_CASTLE
+    );
+}
@@ -0,0 +1,70 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::{gen_type, CountingSink, InlineBuffer, MoveGenerator, MoveList},
+};
+
+/// Compare the count of legal moves with the generated moves in every position to the given
+/// depth.
+fn check_counts(gen: &MoveGenerator, b: &mut Board, depth: usize) {
+    let mut buffer = InlineBuffer::<512>::new();
+    let info = gen.gen_moves::<gen_type::All, _, _>(b, &mut buffer);
+    assert_eq!(
+        gen.legal_move_count(b, &info),
+        buffer.len(),
+        "{}",
+        b.to_fen()
+    );
+    if depth == 0 {
+        return;
+    }
+    for m in buffer.iter() {
+        let undo = b.make_move(m);
+        check_counts(gen, b, depth - 1);
+        b.unmake_move(undo);
+    }
+}
+
+#[test]
+fn legal_move_count_matches_generated_moves() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "8/8/8/KPp4r/8/8/8/7k w - c6 0 1",
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+    ];
+    for fen in positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        check_counts(&gen, &mut board, 2);
+    }
+}
+
+#[test]
+fn counting_sink_matches_buffer() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] b - - 0 1",
+    ];
+    for fen in positions {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+
+        let mut buffer = InlineBuffer::<512>::new();
+        let mut sink = CountingSink::new();
+        gen.gen_moves::<gen_type::AllPseudo, _, _>(&board, &mut buffer);
+        gen.gen_moves::<gen_type::AllPseudo, _, _>(&board, &mut sink);
+        assert_eq!(sink.count(), buffer.len(), "{}", fen);
+
+        buffer.clear();
+        sink.clear();
+        gen.gen_moves::<gen_type::CapturesChecksPseudo, _, _>(&board, &mut buffer);
+        gen.gen_moves::<gen_type::CapturesChecksPseudo, _, _>(&board, &mut sink);
+        assert_eq!(sink.len(), buffer.len(), "{}", fen);
+    }
+}
@@ -0,0 +1,88 @@
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    move_index::{self, POLICY_SIZE},
+    Board, Move, Promotion, Square,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashSet;
+
+const FENS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+];
+
+fn check_position(gen: &MoveGenerator, board: &Board) {
+    let mut moves = InlineBuffer::<256>::new();
+    gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+
+    let mut indices = HashSet::new();
+    for m in moves.iter() {
+        let index = move_index::encode(m, board.state.player);
+        assert!(index < POLICY_SIZE);
+        assert_eq!(
+            move_index::decode(index, board),
+            m,
+            "in position {}",
+            board.to_fen()
+        );
+        assert!(indices.insert(index), "index {} used twice", index);
+    }
+
+    let mask = move_index::legal_move_mask(board, gen, true);
+    assert_eq!(mask.iter().filter(|x| **x).count(), moves.len());
+}
+
+#[test]
+fn round_trip_random_positions() {
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut positions = 0;
+    while positions < 1000 {
+        let fen = FENS[rng.gen_range(0..FENS.len())];
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        for _ in 0..rng.gen_range(0..60) {
+            check_position(&gen, &board);
+            positions += 1;
+
+            let mut moves = InlineBuffer::<256>::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if moves.len() == 0 {
+                break;
+            }
+            board.make_move(moves.get(rng.gen_range(0..moves.len())));
+        }
+    }
+}
+
+#[test]
+fn black_sees_itself_moving_up() {
+    let gen = MoveGenerator::new();
+    let white = Board::start_position(EndChain);
+    let black = white.mirror();
+    assert_eq!(
+        move_index::legal_move_mask(&white, &gen, true),
+        move_index::legal_move_mask(&black, &gen, true)
+    );
+}
+
+#[test]
+fn underpromotions_filtered() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen("8/1P2k3/8/8/8/8/8/4K3 w - - 0 1", EndChain).unwrap();
+    let all = move_index::legal_move_mask(&board, &gen, true);
+    let filtered = move_index::legal_move_mask(&board, &gen, false);
+    assert_eq!(
+        all.iter().filter(|x| **x).count(),
+        filtered.iter().filter(|x| **x).count() + 3
+    );
+
+    let queen = Move::promotion(
+        Square::from_name("b7").unwrap(),
+        Square::from_name("b8").unwrap(),
+        Promotion::Queen,
+    );
+    assert!(filtered[move_index::encode(queen, board.state.player)]);
+}
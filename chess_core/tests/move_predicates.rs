@@ -0,0 +1,92 @@
+use chess_core::{
+    board::EndChain,
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    Board, Move, Piece, Promotion, Square,
+};
+
+/// Returns the legal move between the given squares, preferring a queen promotion.
+fn find(board: &Board, from: &str, to: &str) -> Move {
+    let gen = MoveGenerator::new();
+    let mut moves = InlineBuffer::<256>::new();
+    gen.gen_moves::<gen_type::All, _, _>(board, &mut moves);
+    let from = Square::from_name(from).unwrap();
+    let to = Square::from_name(to).unwrap();
+    moves
+        .iter()
+        .filter(|m| m.from() == from && m.to() == to)
+        .find(|m| !m.is_promotion() || m.promotion_piece() == Promotion::Queen)
+        .unwrap()
+}
+
+fn board(fen: &str) -> Board {
+    Board::from_fen(fen, EndChain).unwrap()
+}
+
+#[test]
+fn promotion() {
+    let b = board("3rk3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+    let push = find(&b, "c7", "c8");
+    assert!(push.is_promotion());
+    assert!(!push.is_capture(&b));
+
+    let take = find(&b, "c7", "d8");
+    assert!(take.is_promotion());
+    assert!(take.is_capture(&b));
+    assert!(!take.is_castle() && !take.is_en_passant() && !take.is_double_pawn());
+}
+
+#[test]
+fn castle() {
+    let b = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    for to in ["g1", "c1"] {
+        let m = find(&b, "e1", to);
+        assert!(m.is_castle());
+        assert!(!m.is_capture(&b));
+        assert!(!m.is_promotion() && !m.is_en_passant() && !m.is_double_pawn());
+    }
+    assert!(!find(&b, "e1", "f1").is_castle());
+}
+
+#[test]
+fn en_passant() {
+    let b = board("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    let m = find(&b, "e5", "d6");
+    assert!(b.on(m.to()).is_none());
+    assert!(m.is_en_passant());
+    assert!(m.is_capture(&b));
+    assert!(!m.is_promotion() && !m.is_castle() && !m.is_double_pawn());
+
+    let push = find(&b, "e5", "e6");
+    assert!(!push.is_en_passant());
+    assert!(!push.is_capture(&b));
+}
+
+#[test]
+fn double_pawn() {
+    let b = board("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let m = find(&b, "e2", "e4");
+    assert!(m.is_double_pawn());
+    assert!(!m.is_capture(&b));
+    assert!(!find(&b, "e2", "e3").is_double_pawn());
+    assert!(!find(&b, "g1", "f3").is_double_pawn());
+}
+
+#[test]
+fn capture() {
+    let b = board("4k3/8/8/3p4/8/4N3/8/4K3 w - - 0 1");
+    assert!(find(&b, "e3", "d5").is_capture(&b));
+    assert!(!find(&b, "e3", "c4").is_capture(&b));
+}
+
+#[test]
+fn drops() {
+    let b = board("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    for p in Piece::WhiteQueen.to(Piece::WhitePawn) {
+        let m = Move::drop(p, Square::from_name("d4").unwrap());
+        assert!(!m.is_promotion());
+        assert!(!m.is_castle());
+        assert!(!m.is_en_passant());
+        assert!(!m.is_double_pawn());
+        assert!(!m.is_capture(&b));
+    }
+}
@@ -0,0 +1,57 @@
+use chess_core::{
+    board::{Board, HashChain},
+    ExtraState, Player,
+};
+
+const POSITIONS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 3 1",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "8/8/8/KPp4r/8/8/8/7k w - c6 0 1",
+];
+
+#[test]
+fn unmake_restores_board() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let original = board.clone();
+        let undo = board.make_null_move();
+        board.unmake_null_move(undo);
+        assert!(board.is_equal(&original), "{}", fen);
+        assert_eq!(board.chain.hash, original.chain.hash, "{}", fen);
+        assert_eq!(board.to_fen(), original.to_fen());
+    }
+}
+
+#[test]
+fn only_side_to_move_and_en_passant_change() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        let original = board.clone();
+        board.make_null_move();
+        assert_eq!(board.pieces, original.pieces, "{}", fen);
+        assert_eq!(board.state.player, original.state.player.flip());
+        assert_eq!(board.state.en_passant, ExtraState::INVALID_ENPASSANT);
+        assert_eq!(board.state.castle, original.state.castle);
+        assert_eq!(board.state.move_clock, original.state.move_clock);
+
+        // The hash is the same as that of the position set up directly.
+        let fresh = Board::from_fen(&board.to_fen(), HashChain::new()).unwrap();
+        assert!(board.is_equal(&fresh), "{}", fen);
+        assert_eq!(board.chain.hash, fresh.chain.hash, "{}", fen);
+    }
+}
+
+#[test]
+fn en_passant_cleared_and_restored() {
+    let mut board = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", HashChain::new()).unwrap();
+    let en_passant = board.state.en_passant;
+    assert_ne!(en_passant, ExtraState::INVALID_ENPASSANT);
+
+    let undo = board.make_null_move();
+    assert_eq!(board.state.player, Player::Black);
+    assert_eq!(board.state.en_passant, ExtraState::INVALID_ENPASSANT);
+    board.unmake_null_move(undo);
+    assert_eq!(board.state.player, Player::White);
+    assert_eq!(board.state.en_passant, en_passant);
+}
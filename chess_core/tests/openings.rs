@@ -0,0 +1,61 @@
+use chess_core::board::EndChain;
+use chess_core::{
+    gen::MoveGenerator,
+    openings::{classify, EcoCode},
+    Board, Move,
+};
+
+/// Convert moves in UCI notation played from the start position.
+fn moves(names: &str) -> Vec<Move> {
+    let gen = MoveGenerator::new();
+    let mut board = Board::start_position(EndChain);
+    names
+        .split_whitespace()
+        .map(|name| {
+            let m = Move::from_uci_on_board(name, &board, &gen).unwrap();
+            board.make_move(m);
+            m
+        })
+        .collect()
+}
+
+#[test]
+fn ruy_lopez() {
+    let (eco, name) = classify(&moves("e2e4 e7e5 g1f3 b8c6 f1b5")).unwrap();
+    assert!(eco >= EcoCode::new('C', 60).unwrap(), "{}", eco);
+    assert!(name.starts_with("Ruy Lopez"));
+
+    // Leaving known theory keeps the deepest classification.
+    let (eco, _) = classify(&moves(
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7 d2d3 d7d6 c2c3",
+    ))
+    .unwrap();
+    assert_eq!(eco.to_string(), "C84");
+}
+
+#[test]
+fn transpositions() {
+    let a = classify(&moves("d2d4 d7d5 c2c4 e7e6 b1c3 g8f6"));
+    let b = classify(&moves("d2d4 g8f6 c2c4 e7e6 b1c3 d7d5"));
+    let c = classify(&moves("c2c4 e7e6 b1c3 d7d5 d2d4 g8f6"));
+    assert_eq!(a.unwrap().0.to_string(), "D35");
+    assert_eq!(a, b);
+    assert_eq!(a, c);
+}
+
+#[test]
+fn unknown_openings() {
+    assert_eq!(classify(&[]), None);
+    assert_eq!(classify(&moves("a2a3")), None);
+}
+
+#[test]
+fn eco_codes() {
+    let code: EcoCode = "B90".parse().unwrap();
+    assert_eq!(code.volume(), 'B');
+    assert_eq!(code.number(), 90);
+    assert_eq!(EcoCode::new('A', 4).unwrap().to_string(), "A04");
+    for invalid in &["", "F00", "A1", "A100", "a00"] {
+        assert!(invalid.parse::<EcoCode>().is_err(), "{}", invalid);
+    }
+}
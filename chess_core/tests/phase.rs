@@ -0,0 +1,82 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::{gen_type, InlineBuffer, MoveGenerator},
+    Move,
+};
+
+/// Make every move to the given depth, checking the phase against a recomputation after every
+/// move and undo.
+fn check_phase(gen: &MoveGenerator, b: &mut Board, depth: usize) {
+    if depth == 0 {
+        return;
+    }
+    let mut buffer = InlineBuffer::<512>::new();
+    gen.gen_moves::<gen_type::All, _, _>(b, &mut buffer);
+    for m in buffer.iter() {
+        let phase = b.phase();
+        let undo = b.make_move(m);
+        assert_eq!(
+            b.phase(),
+            b.compute_phase(),
+            "after {} in {}",
+            m,
+            b.to_fen()
+        );
+        check_phase(gen, b, depth - 1);
+        b.unmake_move(undo);
+        assert_eq!(b.phase(), phase, "after undoing {} in {}", m, b.to_fen());
+    }
+}
+
+#[test]
+fn start_phase() {
+    let board = Board::start_position(EndChain);
+    assert_eq!(board.phase(), Board::<EndChain>::MAX_PHASE);
+    assert_eq!(Board::empty().phase(), 0);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K1N1 w Q - 0 1", EndChain).unwrap();
+    assert_eq!(board.phase(), 3);
+}
+
+#[test]
+fn capture_and_promotion() {
+    let gen = MoveGenerator::new();
+    let mut board = Board::from_fen("r3k3/1P6/1n6/8/8/8/8/4K1N1 w - - 0 1", EndChain).unwrap();
+    assert_eq!(board.phase(), 4);
+
+    // Taking the rook while promoting to a queen, then losing the queen to the knight.
+    let mut undo = Vec::new();
+    for (name, phase) in [("b7a8q", 6), ("b6a8", 2), ("g1f3", 2)] {
+        let m = Move::from_uci_on_board(name, &board, &gen).unwrap();
+        undo.push(board.make_move(m));
+        assert_eq!(board.phase(), phase, "after {}", name);
+        assert_eq!(board.phase(), board.compute_phase(), "after {}", name);
+    }
+    while let Some(m) = undo.pop() {
+        board.unmake_move(m);
+    }
+    assert_eq!(board.phase(), 4);
+
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/1p6/R3K3 b Q - 0 1", EndChain).unwrap();
+    let m = Move::from_uci_on_board("b2a1n", &board, &gen).unwrap();
+    let undo = board.make_move(m);
+    assert_eq!(board.phase(), 1);
+    board.unmake_move(undo);
+    assert_eq!(board.phase(), 2);
+}
+
+#[test]
+fn phase_through_search() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+    ];
+    for fen in positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        assert_eq!(board.phase(), board.compute_phase(), "{}", fen);
+        check_phase(&gen, &mut board, 3);
+    }
+}
@@ -0,0 +1,42 @@
+use chess_core::{board::EndChain, Board, Piece, Player, Square};
+
+fn sq(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn start_position_pieces() {
+    let board = Board::start_position(EndChain);
+    assert_eq!(
+        board.piece_at(sq("e1")),
+        Some((Piece::WhiteKing, Player::White))
+    );
+    assert_eq!(
+        board.piece_at(sq("d8")),
+        Some((Piece::BlackQueen, Player::Black))
+    );
+    assert_eq!(
+        board.piece_at(sq("g1")),
+        Some((Piece::WhiteKnight, Player::White))
+    );
+    assert_eq!(
+        board.piece_at(sq("h7")),
+        Some((Piece::BlackPawn, Player::Black))
+    );
+    for rank in 2..6 {
+        for file in 0..8 {
+            assert_eq!(board.piece_at(Square::from_file_rank(file, rank)), None);
+        }
+    }
+}
+
+#[test]
+fn material_count() {
+    let board = Board::start_position(EndChain);
+    assert_eq!(board.material_count(Player::White), 39);
+    assert_eq!(board.material_count(Player::Black), 39);
+
+    let board = Board::from_fen("4k3/pp6/8/8/8/8/8/R2QK3 w - - 0 1", EndChain).unwrap();
+    assert_eq!(board.material_count(Player::White), 14);
+    assert_eq!(board.material_count(Player::Black), 2);
+}
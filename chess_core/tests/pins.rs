@@ -0,0 +1,55 @@
+use chess_core::{bb::BB, board::EndChain, gen::MoveGenerator, Board, Player, Square};
+
+fn bb(squares: &[&str]) -> BB {
+    squares.iter().fold(BB::EMPTY, |bb, name| {
+        bb | BB::square(Square::from_name(name).unwrap())
+    })
+}
+
+fn pins_and_rays(fen: &str) -> (BB, BB) {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let info = gen.gen_info(&board);
+    (
+        gen.pinned_pieces(&board, &info),
+        gen.check_rays(&board, &info),
+    )
+}
+
+#[test]
+fn king_square() {
+    let board = Board::start_position(EndChain);
+    assert_eq!(board.king_square(Player::White), Square::E1);
+    assert_eq!(board.king_square(Player::Black), Square::E8);
+}
+
+#[test]
+fn pinned_knight() {
+    // The bishop on b4 pins the knight on d2, the knight on f3 is free.
+    let (pinned, rays) = pins_and_rays("4k3/8/8/8/1b6/5N2/3N4/4K3 w - - 0 1");
+    assert_eq!(pinned, bb(&["d2"]));
+    assert!(rays.none());
+
+    // Two pieces on the line, neither is pinned.
+    let (pinned, _) = pins_and_rays("4k3/8/8/8/1b6/2N5/3N4/4K3 w - - 0 1");
+    assert!(pinned.none());
+
+    // Pieces of the opponent can't be pinned to the king.
+    let (pinned, _) = pins_and_rays("4k3/8/8/8/1b6/8/3n4/4K3 w - - 0 1");
+    assert!(pinned.none());
+}
+
+#[test]
+fn check_rays() {
+    // Rook check along the file.
+    let (_, rays) = pins_and_rays("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(rays, bb(&["e2", "e3", "e4", "e5", "e6", "e7"]));
+
+    // Double check by a bishop and a knight, only the bishop has a ray.
+    let (_, rays) = pins_and_rays("6k1/8/8/b7/8/3n4/8/4K3 w - - 0 1");
+    assert_eq!(rays, bb(&["b4", "c3", "d2"]));
+
+    // Adjacent checks have no squares in between.
+    let (_, rays) = pins_and_rays("6k1/8/8/8/8/8/3q4/4K3 w - - 0 1");
+    assert!(rays.none());
+}
@@ -0,0 +1,80 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::MoveGenerator,
+    polyglot::{key_indices, piece_index},
+    Move, Piece, Square,
+};
+
+fn play(moves: &str) -> Board {
+    let gen = MoveGenerator::new();
+    let mut board = Board::start_position(EndChain);
+    for name in moves.split_whitespace() {
+        let m = Move::from_uci_on_board(name, &board, &gen).unwrap();
+        board.make_move(m);
+    }
+    board
+}
+
+#[test]
+fn piece_indices() {
+    // Index of a piece is 64 * kind + 8 * rank + file, with kinds ordered black pawn, white
+    // pawn, black knight and so on.
+    assert_eq!(piece_index(Piece::BlackPawn, Square::A1), 0);
+    assert_eq!(
+        piece_index(Piece::WhitePawn, Square::from_name("e2").unwrap()),
+        64 + 12
+    );
+    assert_eq!(piece_index(Piece::WhiteKing, Square::E1), 11 * 64 + 4);
+    assert_eq!(piece_index(Piece::BlackKing, Square::E8), 10 * 64 + 60);
+}
+
+#[test]
+fn start_position_indices() {
+    let indices = key_indices(&Board::start_position(EndChain));
+    // All pieces, the four castle rights and white to move.
+    assert_eq!(indices.len(), 32 + 4 + 1);
+    assert_eq!(&indices[32..], &[768, 769, 770, 771, 780]);
+}
+
+#[test]
+fn en_passant_only_when_capture_possible() {
+    // After 1.e4 no black pawn can take on e3.
+    let indices = key_indices(&play("e2e4"));
+    assert!(indices.iter().all(|x| !(772..780).contains(x)));
+    assert!(!indices.contains(&780));
+
+    // After 1.e4 Nf6 2.e5 d5 the pawn on e5 can take on d6.
+    let indices = key_indices(&play("e2e4 g8f6 e4e5 d7d5"));
+    assert!(indices.contains(&(772 + 3)));
+}
+
+#[test]
+fn known_keys() {
+    assert_eq!(
+        Board::start_position(EndChain).polyglot_key(),
+        0x463b96181691fc9c
+    );
+    assert_eq!(play("e2e4").polyglot_key(), 0x823c9b50fd114196);
+    assert_eq!(play("e2e4 d7d5").polyglot_key(), 0x0756b94461c50fb0);
+    assert_eq!(play("e2e4 d7d5 e4e5").polyglot_key(), 0x662fafb965db29d4);
+    assert_eq!(
+        play("e2e4 d7d5 e4e5 f7f5").polyglot_key(),
+        0x22a48b5a8e47ff78
+    );
+    assert_eq!(
+        play("e2e4 d7d5 e4e5 f7f5 e1e2").polyglot_key(),
+        0x652a607ca3f242c1
+    );
+    assert_eq!(
+        play("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7").polyglot_key(),
+        0x00fdd303c946bdd9
+    );
+    assert_eq!(
+        play("a2a4 b7b5 h2h4 b5b4 c2c4").polyglot_key(),
+        0x3c8123ea7b067637
+    );
+    assert_eq!(
+        play("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3").polyglot_key(),
+        0x5c3f9b829b279560
+    );
+}
@@ -0,0 +1,83 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, PositionStatus},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn status(fen: &str) -> PositionStatus {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let info = gen.gen_info(&board);
+    gen.position_status(&board, &info)
+}
+
+#[test]
+fn classifies_positions() {
+    let cases = [
+        // Back rank mate.
+        (
+            "4R1k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+            PositionStatus::Checkmate,
+        ),
+        // Smothered mate.
+        (
+            "6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1",
+            PositionStatus::Checkmate,
+        ),
+        // Double check where only the king could move.
+        ("4k3/4r3/8/8/8/5n2/8/4K3 w - - 0 1", PositionStatus::Check),
+        // Queen stalemate in the corner.
+        ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", PositionStatus::Stalemate),
+        // Only the pawn could move, but it is blocked.
+        (
+            "8/8/8/8/8/1p6/1P6/K1k5 w - - 0 1",
+            PositionStatus::Stalemate,
+        ),
+        // The only legal move is an en passant capture.
+        ("8/8/8/2k5/3Pp3/8/8/4K2b b - d3 0 1", PositionStatus::Check),
+        // Mate can be prevented by blocking with a pawn.
+        (
+            "6k1/5p1p/8/8/8/8/6PP/R5K1 b - - 0 1",
+            PositionStatus::Normal,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            PositionStatus::Normal,
+        ),
+        (
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            PositionStatus::Checkmate,
+        ),
+        (
+            "rnbqkbnr/ppppp2p/5p2/6pQ/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 1 3",
+            PositionStatus::Checkmate,
+        ),
+    ];
+    for (fen, expected) in cases.iter() {
+        assert_eq!(status(fen), *expected, "{}", fen);
+    }
+}
+
+#[test]
+fn agrees_with_move_generation() {
+    // Play random games and compare against generating all legal moves.
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(0x5747);
+    for _ in 0..50 {
+        let mut board = Board::start_position(EndChain);
+        for _ in 0..200 {
+            let mut moves = InlineBuffer::<256>::new();
+            let info = gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            assert_eq!(
+                gen.has_legal_move(&board, &info),
+                moves.len() > 0,
+                "{}",
+                board.to_fen()
+            );
+            if moves.len() == 0 {
+                break;
+            }
+            board.make_move(moves.get(rng.gen_range(0..moves.len())));
+        }
+    }
+}
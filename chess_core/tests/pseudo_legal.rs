@@ -0,0 +1,138 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::{gen_type, MoveGenerator},
+    Move,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn board(fen: &str) -> Board {
+    Board::from_fen(fen, EndChain).unwrap()
+}
+
+fn mov(name: &str, board: &Board, gen: &MoveGenerator) -> Move {
+    Move::from_uci_on_board(name, board, gen).unwrap()
+}
+
+/// Moves of earlier positions are checked against later positions, like moves from the
+/// transposition table after a key collision.
+#[test]
+fn accepts_exactly_the_generated_moves() {
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(5);
+    let start_positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K w kq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+    ];
+    for fen in start_positions {
+        let mut board = board(fen);
+        let mut seen = Vec::new();
+        for _ in 0..60 {
+            let mut moves = Vec::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if moves.is_empty() {
+                break;
+            }
+            let info = gen.gen_info(&board);
+            for m in moves.iter() {
+                assert!(
+                    gen.is_pseudo_legal(*m, &board),
+                    "{} in {}",
+                    m,
+                    board.to_fen()
+                );
+            }
+            // The single move legality check doesn't handle check evasions.
+            if !gen.checked_king(&board, &info) {
+                for m in seen.iter() {
+                    assert_eq!(
+                        gen.is_pseudo_legal(*m, &board) && gen.is_legal(*m, &board, &info),
+                        moves.contains(m),
+                        "{} in {}",
+                        m,
+                        board.to_fen()
+                    );
+                }
+            }
+            for m in moves.iter() {
+                if !seen.contains(m) {
+                    seen.push(*m);
+                }
+            }
+            board.make_move(moves[rng.gen_range(0..moves.len())]);
+        }
+    }
+}
+
+#[test]
+fn valid_table_move() {
+    let gen = MoveGenerator::new();
+    let b = board("r3k2r/pppq1ppp/2n1bn2/3pp3/3PP3/2N1BN2/PPPQ1PPP/R3K2R w KQkq - 0 1");
+    for name in ["e1g1", "e1c1", "e4d5", "c3b5", "a2a4", "d2d3"] {
+        let m = mov(name, &b, &gen);
+        assert!(gen.is_pseudo_legal(m, &b), "{}", name);
+    }
+
+    let b = board("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    assert!(gen.is_pseudo_legal(mov("e5d6", &b, &gen), &b));
+    assert!(!gen.is_pseudo_legal(Move::INVALID, &b));
+}
+
+#[test]
+fn stale_table_move() {
+    let gen = MoveGenerator::new();
+    let b = board("r3k2r/pppq1ppp/2n1bn2/3pp3/3PP3/2N1BN2/PPPQ1PPP/R3K2R w KQkq - 0 1");
+    let moves: Vec<_> = ["e1g1", "e1c1", "c3b5", "a2a4", "d2d3", "e4d5"]
+        .iter()
+        .map(|x| mov(x, &b, &gen))
+        .collect();
+    let [castle, long_castle, knight, double_pawn, queen, capture] = moves[..] else {
+        unreachable!()
+    };
+
+    // The opponent is to move, none of the pieces are theirs.
+    let other = board("r3k2r/pppq1ppp/2n1bn2/3pp3/3PP3/2N1BN1P/PPPQ1PP1/R3K2R b KQkq - 0 1");
+    for m in moves.iter() {
+        assert!(!gen.is_pseudo_legal(*m, &other), "{}", m);
+    }
+
+    // Castling rights are gone or the way to the rook is blocked.
+    let b = board("r3k2r/pppq1ppp/2n1bn2/3pp3/3PP3/2N1BN2/PPPQ1PPP/R3K2R w Qkq - 0 1");
+    assert!(!gen.is_pseudo_legal(castle, &b));
+    assert!(gen.is_pseudo_legal(long_castle, &b));
+    let b = board("r3k2r/pppq1ppp/2n1bn2/3pp3/3PP3/2N1BN2/PPPQ1PPP/RN2K2R w KQkq - 0 1");
+    assert!(!gen.is_pseudo_legal(long_castle, &b));
+
+    // The knight and queen are gone, a pawn blocks the double pawn move and the pawn to capture
+    // moved away.
+    let b = board("r3k2r/pppq1ppp/2n1bn2/4p3/3PP3/p1PnBN2/PP3PPP/R3K2R w KQkq - 0 1");
+    for m in [knight, double_pawn, queen, capture] {
+        assert!(!gen.is_pseudo_legal(m, &b), "{}", m);
+    }
+
+    // En passant is only possible directly after the double pawn move.
+    let b = board("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    let en_passant = mov("e5d6", &b, &gen);
+    let b = board("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2");
+    assert!(!gen.is_pseudo_legal(en_passant, &b));
+
+    // Drops need the piece in the pocket and an empty square.
+    let b = board("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1");
+    let drop = mov("N@d4", &b, &gen);
+    assert!(gen.is_pseudo_legal(drop, &b));
+    let b = board("4k3/8/8/8/8/8/8/4K3[n] w - - 0 1");
+    assert!(!gen.is_pseudo_legal(drop, &b));
+    let b = board("4k3/8/8/8/3p4/8/8/4K3[N] w - - 0 1");
+    assert!(!gen.is_pseudo_legal(drop, &b));
+
+    // Pawns are never dropped on the first or last rank.
+    let b = board("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1");
+    assert!(gen.is_pseudo_legal(mov("P@d4", &b, &gen), &b));
+    for name in ["P@d1", "P@d8"] {
+        let m: Move = name.parse().unwrap();
+        assert!(!gen.is_pseudo_legal(m, &b), "{}", name);
+    }
+}
@@ -0,0 +1,112 @@
+use chess_core::{
+    board::EndChain,
+    gen::MoveGenerator,
+    repetition::{DrawRule, GameHistory, PositionHistory},
+    Board, Move,
+};
+
+fn play(board: &mut Board, history: &mut PositionHistory, moves: &str) {
+    let gen = MoveGenerator::new();
+    for name in moves.split_whitespace() {
+        let m = Move::from_uci_on_board(name, board, &gen).unwrap();
+        board.make_move(m);
+        history.push(board);
+    }
+}
+
+#[test]
+fn knight_shuffle_is_threefold() {
+    let mut board = Board::start_position(EndChain);
+    let mut history = PositionHistory::new();
+    history.push(&board);
+
+    play(&mut board, &mut history, "g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(history.repetitions(board.state.move_clock), 2);
+    assert_eq!(history.draw_rule(&board), None);
+
+    play(&mut board, &mut history, "g1f3 g8f6 f3g1");
+    assert_eq!(history.draw_rule(&board), None);
+    play(&mut board, &mut history, "f6g8");
+    assert_eq!(history.repetitions(board.state.move_clock), 3);
+    assert_eq!(history.draw_rule(&board), Some(DrawRule::Repetition));
+
+    // Taking back the last move undoes the repetition.
+    history.pop();
+    assert_eq!(history.repetitions(board.state.move_clock - 1), 2);
+}
+
+#[test]
+fn pawn_move_resets_repetitions() {
+    let mut board = Board::start_position(EndChain);
+    let mut history = PositionHistory::new();
+    history.push(&board);
+
+    play(&mut board, &mut history, "e2e4 e7e5 g1f3 g8f6 f3g1 f6g8");
+    play(&mut board, &mut history, "g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(history.draw_rule(&board), Some(DrawRule::Repetition));
+
+    // The same pieces after a different pawn move are a new position.
+    play(&mut board, &mut history, "d2d4 d7d5 g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(history.repetitions(board.state.move_clock), 2);
+    assert_eq!(history.draw_rule(&board), None);
+}
+
+#[test]
+fn fifty_moves() {
+    let board = Board::from_fen("8/8/4k3/8/8/3K4/8/7R w - - 99 80", EndChain).unwrap();
+    let mut history = PositionHistory::new();
+    history.push(&board);
+    assert_eq!(history.draw_rule(&board), None);
+
+    let mut board = board;
+    play(&mut board, &mut history, "h1h2");
+    assert_eq!(board.state.move_clock, 100);
+    assert_eq!(history.draw_rule(&board), Some(DrawRule::FiftyMoves));
+    assert_eq!(DrawRule::FiftyMoves.to_string(), "fifty-move rule");
+}
+
+#[test]
+fn fifty_move_flag() {
+    let mut board = Board::from_fen("8/8/4k3/8/8/3K4/8/7R w - - 98 80", EndChain).unwrap();
+    let mut history = PositionHistory::new();
+    history.push(&board);
+    assert!(!board.is_fifty_move_draw());
+
+    // The rule counts half-moves, the flag flips on the hundredth.
+    play(&mut board, &mut history, "h1h2");
+    assert_eq!(board.state.move_clock, 99);
+    assert!(!board.is_fifty_move_draw());
+    play(&mut board, &mut history, "e6e5");
+    assert_eq!(board.state.move_clock, 100);
+    assert!(board.is_fifty_move_draw());
+}
+
+#[test]
+fn move_clock_saturates() {
+    let mut board = Board::from_fen("8/8/4k3/8/8/3K4/8/7R w - - 254 200", EndChain).unwrap();
+    let mut history = PositionHistory::new();
+    play(&mut board, &mut history, "h1h2 e6e5");
+    assert_eq!(board.state.move_clock, 255);
+    assert!(board.is_fifty_move_draw());
+}
+
+#[test]
+fn game_history_stops_at_irreversible_moves() {
+    let mut history = GameHistory::new();
+    history.push(1, true);
+    history.push(2, false);
+    history.push(1, false);
+    assert_eq!(history.repetitions(1), 2);
+    assert_eq!(history.repetitions(3), 0);
+
+    // After a capture or pawn move the earlier positions don't count.
+    history.push(3, true);
+    history.push(1, false);
+    assert_eq!(history.repetitions(1), 1);
+    assert_eq!(history.repetitions(3), 1);
+
+    history.pop();
+    history.pop();
+    assert_eq!(history.repetitions(1), 2);
+    assert_eq!(history.len(), 3);
+}
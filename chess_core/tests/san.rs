@@ -0,0 +1,62 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::{gen_type, MoveGenerator},
+    Move,
+};
+
+fn san(fen: &str, uci: &str) -> String {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(fen, EndChain).unwrap();
+    let m = Move::from_uci_on_board(uci, &board, &gen).unwrap();
+    m.to_san(&board, &gen)
+}
+
+#[test]
+fn san_names() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let cases = [
+        (start, "e2e4", "e4"),
+        (start, "g1f3", "Nf3"),
+        (kiwipete, "e1g1", "O-O"),
+        (kiwipete, "e1c1", "O-O-O"),
+        (kiwipete, "d5e6", "dxe6"),
+        (kiwipete, "e5f7", "Nxf7"),
+        (kiwipete, "f3f6", "Qxf6"),
+        (kiwipete, "g2h3", "gxh3"),
+        // Only the knight on e5 reaches c4, the one on b6 belongs to black.
+        (kiwipete, "e5c4", "Nc4"),
+        // Rooks on the same rank are told apart by file, on the same file by rank.
+        ("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1", "Rad1"),
+        ("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3", "R1a3"),
+        // Three queens need both file and rank.
+        ("8/8/3k4/8/4Q2Q/8/K7/7Q w - - 0 1", "h4e1", "Qh4e1"),
+        ("4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1", "e5d6", "exd6"),
+        ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", "b8=Q+"),
+        ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n", "b8=N"),
+        ("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", "Ra8#"),
+        ("2k5/8/8/8/8/8/8/4K3[N] w - - 0 1", "N@b6", "N@b6+"),
+    ];
+    for (fen, uci, expected) in cases {
+        assert_eq!(san(fen, uci), expected, "{} in {}", uci, fen);
+    }
+}
+
+#[test]
+fn san_names_are_unique() {
+    let gen = MoveGenerator::new();
+    let positions = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K b kq - 0 1",
+        "8/8/3k4/8/4Q2Q/8/K7/7Q w - - 0 1",
+    ];
+    for fen in positions {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        let mut moves = Vec::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+        let mut names: Vec<_> = moves.iter().map(|m| m.to_san(&board, &gen)).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), moves.len(), "{}", fen);
+    }
+}
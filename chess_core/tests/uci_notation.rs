@@ -0,0 +1,228 @@
+use chess_core::{
+    board::{EndChain, HashChain},
+    gen::{gen_type, MoveGenerator},
+    Board, Move, Piece, Player, Promotion, Square,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn square(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn round_trip_legal_moves() {
+    let gen = MoveGenerator::new();
+    let positions = &[
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        // Castles both ways, captures and double pawn moves.
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        // Promotions with and without captures.
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K b kq - 0 1",
+        // En passant.
+        "4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1",
+    ];
+    for fen in positions {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        let mut moves = Vec::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+        for m in moves {
+            let name = m.to_uci();
+            assert_eq!(
+                Move::from_uci_on_board(&name, &board, &gen),
+                Some(m),
+                "{} in {}",
+                name,
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn round_trip_drops() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+        HashChain::new(),
+    )
+    .unwrap();
+    let mut moves = Vec::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+    assert!(moves.iter().any(|m| m.is_drop()));
+    for m in moves {
+        let name = m.to_uci();
+        assert_eq!(Move::from_uci_on_board(&name, &board, &gen), Some(m));
+    }
+    assert_eq!(
+        Move::drop(Piece::WhiteKnight, square("e4")).to_uci(),
+        "N@e4"
+    );
+}
+
+#[test]
+fn move_names() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K b kq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+
+    let m = Move::from_uci_on_board("b2a1n", &board, &gen).unwrap();
+    assert!(m.is_promotion());
+    assert_eq!(m.promotion_piece(), Promotion::Knight);
+    assert_eq!(m.to_uci(), "b2a1n");
+    // A promotion needs a piece.
+    assert_eq!(Move::from_uci_on_board("b2a1", &board, &gen), None);
+    assert_eq!(Move::from_uci_on_board("e8e7", &board, &gen), None);
+
+    let board = Board::from_fen("4k3/8/8/2PpP3/8/8/8/4K3 w - d6 0 1", EndChain).unwrap();
+    assert!(Move::from_uci_on_board("e5d6", &board, &gen)
+        .unwrap()
+        .is_en_passant());
+
+    let board = Board::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        EndChain,
+    )
+    .unwrap();
+    assert!(Move::from_uci_on_board("e8g8", &board, &gen)
+        .unwrap()
+        .is_castle());
+    assert!(Move::from_uci_on_board("e8c8", &board, &gen)
+        .unwrap()
+        .is_castle());
+
+    let board = Board::start_position(EndChain);
+    assert!(Move::from_uci_on_board("e2e4", &board, &gen)
+        .unwrap()
+        .is_double_pawn());
+}
+
+#[test]
+fn parse_without_board() {
+    assert_eq!(
+        "e2e4".parse::<Move>().unwrap(),
+        Move::normal(square("e2"), square("e4"))
+    );
+    assert_eq!(
+        "a7a8q".parse::<Move>().unwrap(),
+        Move::promotion(square("a7"), square("a8"), Promotion::Queen)
+    );
+    assert_eq!(
+        "p@d5".parse::<Move>().unwrap(),
+        Move::drop(Piece::WhitePawn, square("d5"))
+    );
+    assert_eq!("0000".parse::<Move>().unwrap(), Move::INVALID);
+    assert_eq!(Move::INVALID.to_uci(), "0000");
+
+    for invalid in &["", "e2", "e2e9", "e2e4x", "e2e2", "K@e4", "a7a8k"] {
+        assert!(invalid.parse::<Move>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn promotion_encoding() {
+    for promotion in Promotion::ALL {
+        let m = Move::promotion(square("b7"), square("a8"), promotion);
+        assert!(m.is_promotion());
+        assert_eq!(m.promotion_piece(), promotion);
+        assert_eq!(m.from(), square("b7"));
+        assert_eq!(m.to(), square("a8"));
+        assert_eq!(m.to_uci(), format!("b7a8{}", promotion.to_char()));
+        assert_eq!(m.to_uci().parse::<Move>().unwrap(), m);
+        assert_eq!(
+            Promotion::from_piece(promotion.piece(Player::Black)),
+            Some(promotion)
+        );
+    }
+    assert_eq!(Promotion::from_piece(Piece::WhitePawn), None);
+    assert_eq!(
+        Move::promotion(square("b7"), square("a8"), Promotion::Knight).to_string(),
+        "b7a8=N"
+    );
+}
+
+#[test]
+fn moves_from_squares() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", EndChain).unwrap();
+    let mut moves = Vec::new();
+    gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+
+    // A pawn reaching the last rank needs the piece to promote to.
+    assert_eq!(
+        Move::from_squares_in(square("a7"), square("a8"), None, &moves),
+        None
+    );
+    for promotion in Promotion::ALL {
+        for to in ["a8", "b8"] {
+            let m =
+                Move::from_squares_in(square("a7"), square(to), Some(promotion), &moves).unwrap();
+            assert!(moves.contains(&m));
+            assert_eq!(m.promotion_piece(), promotion);
+        }
+    }
+
+    let m = Move::from_squares_in(square("e1"), square("d2"), None, &moves).unwrap();
+    assert!(moves.contains(&m));
+    assert_eq!(
+        Move::from_squares_in(square("e1"), square("d2"), Some(Promotion::Queen), &moves),
+        None
+    );
+    assert_eq!(
+        Move::from_squares_in(square("e1"), square("e3"), None, &moves),
+        None
+    );
+}
+
+/// Every name of a move between two squares, with every promotion.
+fn all_names() -> Vec<String> {
+    let mut res = Vec::new();
+    for from in 0..64 {
+        for to in 0..64 {
+            if from == to {
+                continue;
+            }
+            let name = format!("{}{}", Square::new(from), Square::new(to));
+            for p in ["", "q", "r", "b", "n"] {
+                res.push(format!("{}{}", name, p));
+            }
+        }
+    }
+    res
+}
+
+#[test]
+fn fast_lookup_matches_generation() {
+    let gen = MoveGenerator::new();
+    let names = all_names();
+    let mut rng = StdRng::seed_from_u64(11);
+    let start_positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K w kq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    ];
+    for fen in start_positions {
+        let mut board = Board::from_fen(fen, EndChain).unwrap();
+        for _ in 0..40 {
+            let mut moves = Vec::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut moves);
+            if moves.is_empty() {
+                break;
+            }
+            for name in names.iter() {
+                assert_eq!(
+                    Move::from_uci_fast(name, &board, &gen),
+                    Move::from_uci_in(name, &moves),
+                    "{} in {}",
+                    name,
+                    board.to_fen()
+                );
+            }
+            board.make_move(moves[rng.gen_range(0..moves.len())]);
+        }
+    }
+}
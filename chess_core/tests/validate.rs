@@ -0,0 +1,151 @@
+use chess_core::{
+    bb::BB,
+    board::{Board, BoardError, EndChain, HashChain, PositionError},
+    gen::MoveGenerator,
+    ExtraState, Piece, Player, Square,
+};
+
+#[test]
+fn start_position_is_valid() {
+    let board = Board::start_position(EndChain);
+    assert_eq!(board.validate(), Vec::new());
+    assert!(board.is_valid());
+}
+
+#[test]
+fn reports_missing_king() {
+    // Fen strings without a king are rejected, so the king is taken off the board directly.
+    let mut board = Board::from_fen("K7/8/8/8/8/8/8/7k w - - 0 1", EndChain).unwrap();
+    board.pieces[Piece::BlackKing] = BB::EMPTY;
+    board.squares[Square::H1] = None;
+    let errors = board.validate();
+    assert_eq!(
+        errors,
+        vec![BoardError::KingCount {
+            player: Player::Black,
+            count: 0
+        }]
+    );
+    assert!(!board.is_valid());
+    assert_eq!(errors[0].to_string(), "Black has 0 kings instead of one");
+}
+
+#[test]
+fn from_pieces() {
+    let pieces = [
+        (Square::E1, Piece::WhiteKing),
+        (Square::from_name("e5").unwrap(), Piece::WhitePawn),
+        (Square::E8, Piece::BlackKing),
+        (Square::from_name("d5").unwrap(), Piece::BlackPawn),
+        (Square::H8, Piece::BlackRook),
+    ];
+    let board = Board::from_pieces(
+        &pieces,
+        Player::White,
+        ExtraState::BLACK_KING_CASTLE,
+        Some(Square::from_name("d6").unwrap()),
+        HashChain::new(),
+    )
+    .unwrap();
+    let fen = "4k2r/8/8/3pP3/8/8/8/4K3 w k d6 0 1";
+    assert!(board
+        .to_fen()
+        .starts_with("4k2r/8/8/3pP3/8/8/8/4K3 w k d6 "));
+    assert!(board.is_valid());
+
+    let from_fen = Board::from_fen(fen, HashChain::new()).unwrap();
+    assert_eq!(board.chain.hash, from_fen.chain.hash);
+}
+
+#[test]
+fn from_pieces_rejects_impossible_positions() {
+    let kings = [
+        (Square::E1, Piece::WhiteKing),
+        (Square::E8, Piece::BlackKing),
+    ];
+    let setup = |extra: &[(Square, Piece)]| {
+        let mut pieces = kings.to_vec();
+        pieces.extend_from_slice(extra);
+        Board::from_pieces(&pieces, Player::White, 0, None, EndChain)
+    };
+    assert!(setup(&[]).is_ok());
+
+    let err = setup(&[(Square::A1, Piece::WhiteKing)]).unwrap_err();
+    assert!(err.to_string().contains("White has 2 kings"), "{}", err);
+    let err = setup(&[(Square::A8, Piece::WhitePawn)]).unwrap_err();
+    assert!(err.to_string().contains("pawn on back rank a8"), "{}", err);
+    assert!(setup(&[(Square::H1, Piece::BlackPawn)]).is_err());
+    assert!(setup(&[(Square::E1, Piece::WhiteQueen)]).is_err());
+
+    // The en passant square has to be behind a pawn which just moved two squares.
+    let err = Board::from_pieces(&kings, Player::White, 0, Some(Square::D1), EndChain).unwrap_err();
+    assert!(err.to_string().contains("en passant"), "{}", err);
+}
+
+#[test]
+fn legal_positions() {
+    let gen = MoveGenerator::new();
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        // Three queens after two promotions.
+        "4k3/8/8/8/8/8/2PPPP2/QQQ1K3 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        assert_eq!(board.is_legal_position(&gen), Ok(()), "{}", fen);
+    }
+}
+
+#[test]
+fn side_not_to_move_in_check() {
+    // Fen strings with the waiting king in check are rejected, so the board is set up directly.
+    let pieces = [
+        (Square::E1, Piece::WhiteKing),
+        (Square::E8, Piece::BlackKing),
+        (Square::from_name("e4").unwrap(), Piece::WhiteRook),
+    ];
+    let board = Board::from_pieces(&pieces, Player::White, 0, None, EndChain).unwrap();
+    assert!(board.is_valid());
+    let err = board.is_legal_position(&MoveGenerator::new()).unwrap_err();
+    assert_eq!(
+        err,
+        PositionError::SideNotToMoveInCheck {
+            player: Player::Black
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Black is in check while it is not their turn"
+    );
+}
+
+#[test]
+fn nine_pawns() {
+    let gen = MoveGenerator::new();
+    let board = Board::from_fen("4k3/8/8/8/8/P7/PPPPPPPP/4K3 w - - 0 1", EndChain).unwrap();
+    assert!(board.is_valid());
+    assert_eq!(
+        board.is_legal_position(&gen),
+        Err(PositionError::TooManyPawns {
+            player: Player::White,
+            count: 9
+        })
+    );
+
+    // Two extra queens need two promoted pawns.
+    let board = Board::from_fen("qqqk4/ppppppp1/8/8/8/8/8/4K3 b - - 0 1", EndChain).unwrap();
+    let err = board.is_legal_position(&gen).unwrap_err();
+    assert_eq!(
+        err,
+        PositionError::TooManyPieces {
+            player: Player::Black,
+            promoted: 2,
+            pawns: 7
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Black has 2 promoted pieces but only 1 of its pawns are missing"
+    );
+}
@@ -0,0 +1,203 @@
+//! The static evaluation of the alpha-beta engine, usable by any engine.
+//!
+//! Positions are scored in centipawns by their material, the placement of the pieces and the
+//! placement of the kings, blended between the middle and end game by the material left.
+
+use chess_core::{
+    board::{Board, MoveChain, Psqt, PsqtTables},
+    util::{BoardArray, PieceArray},
+    Piece, Square,
+};
+use std::sync::OnceLock;
+
+pub const PAWN_VALUE: i32 = 100;
+pub const KNIGHT_VALUE: i32 = 320;
+pub const BISHOP_VALUE: i32 = 325;
+pub const ROOK_VALUE: i32 = 500;
+pub const QUEEN_VALUE: i32 = 975;
+
+const FULL_PIECE_VALUE: i32 = QUEEN_VALUE + BISHOP_VALUE * 2 + KNIGHT_VALUE * 2 + ROOK_VALUE * 2;
+/// Value of every piece, indexed by [`Piece`].
+pub const PIECE_VALUE: [i32; 12] = [
+    0,
+    QUEEN_VALUE,
+    BISHOP_VALUE,
+    KNIGHT_VALUE,
+    ROOK_VALUE,
+    PAWN_VALUE,
+    0,
+    QUEEN_VALUE,
+    BISHOP_VALUE,
+    KNIGHT_VALUE,
+    ROOK_VALUE,
+    PAWN_VALUE,
+];
+
+/// The terms of a static evaluation, from the perspective of white.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvalTrace {
+    pub material: i32,
+    pub piece_square: i32,
+    /// King placement, blended between the middle and end game tables.
+    pub king: i32,
+}
+
+impl EvalTrace {
+    pub fn total(&self) -> i32 {
+        self.material + self.piece_square + self.king
+    }
+}
+
+const PAWN_TABLE: BoardArray<i32> = BoardArray::new_array([
+    0, 0, 0, 0, 0, 0, 0, 0, 50, 50, 50, 50, 50, 50, 50, 50, 10, 10, 20, 30, 30, 20, 10, 10, 5, 5,
+    10, 27, 27, 10, 5, 5, 0, 0, 0, 25, 25, 0, 0, 0, 5, -5, -10, 0, 0, -10, -5, 5, 5, 10, 10, -25,
+    -25, 10, 10, 5, 0, 0, 0, 0, 0, 0, 0, 0,
+]);
+
+const KNIGHT_TABLE: BoardArray<i32> = BoardArray::new_array([
+    -50, -40, -30, -30, -30, -30, -40, -50, -40, -20, 0, 0, 0, 0, -20, -40, -30, 0, 10, 15, 15, 10,
+    0, -30, -30, 5, 15, 20, 20, 15, 5, -30, -30, 0, 15, 20, 20, 15, 0, -30, -30, 5, 10, 15, 15, 10,
+    5, -30, -40, -20, 0, 5, 5, 0, -20, -40, -50, -40, -20, -30, -30, -20, -40, -50,
+]);
+
+const BISHOP_TABLE: BoardArray<i32> = BoardArray::new_array([
+    -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10, 10, 5, 0,
+    -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10, 10, 10, 10, 10, 10, 10,
+    -10, -10, 5, 0, 0, 0, 0, 5, -10, -20, -10, -40, -10, -10, -40, -10, -20,
+]);
+
+const ROOK_TABLE: BoardArray<i32> = BoardArray::new_array([
+    0, 0, 0, 0, 0, 0, 0, 0, 5, 10, 10, 10, 10, 10, 10, 5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0,
+    0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, 0, 0,
+    0, 5, 5, 0, 0, 0,
+]);
+
+const KING_TABLE: BoardArray<i32> = BoardArray::new_array([
+    -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40,
+    -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -20, -30, -30, -40, -40, -30,
+    -30, -20, -10, -20, -20, -20, -20, -20, -20, -10, 20, 20, 0, 0, 0, 0, 20, 20, 20, 30, 10, 0, 0,
+    10, 30, 20,
+]);
+
+const KING_END_TABLE: BoardArray<i32> = BoardArray::new_array([
+    -50, -40, -30, -20, -20, -30, -40, -50, -30, -20, -10, 0, 0, -10, -20, -30, -30, -10, 20, 30,
+    30, 20, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30,
+    -10, 20, 30, 30, 20, -10, -30, -30, -30, 0, 0, 0, 0, -30, -30, -50, -30, -30, -30, -30, -30,
+    -30, -50,
+]);
+
+/// Returns the material and piece square values of every piece except the kings, which depend
+/// on the phase of the game.
+///
+/// The evaluation has no separate end game values for these pieces, so both phases hold the same
+/// value. An [`EvalChain`](chess_core::board::EvalChain) with these tables keeps the evaluation
+/// without the kings up to date while moves are made.
+pub fn psqt_tables() -> &'static PsqtTables {
+    static TABLES: OnceLock<PsqtTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut values = PieceArray::new(BoardArray::new(Psqt::default()));
+        for s in 0..64 {
+            let s = Square::new(s);
+            for (white, black, table) in [
+                (Piece::WhiteBishop, Piece::BlackBishop, &BISHOP_TABLE),
+                (Piece::WhiteKnight, Piece::BlackKnight, &KNIGHT_TABLE),
+                (Piece::WhiteRook, Piece::BlackRook, &ROOK_TABLE),
+                (Piece::WhitePawn, Piece::BlackPawn, &PAWN_TABLE),
+            ] {
+                let value = PIECE_VALUE[white as usize] + table[s.flip()];
+                values[white][s] = Psqt::new(value, value);
+                let value = PIECE_VALUE[black as usize] + table[s];
+                values[black][s] = -Psqt::new(value, value);
+            }
+            // The queen has no table.
+            let value = QUEEN_VALUE;
+            values[Piece::WhiteQueen][s] = Psqt::new(value, value);
+            values[Piece::BlackQueen][s] = -Psqt::new(value, value);
+        }
+        PsqtTables { values }
+    })
+}
+
+/// Returns the difference in the value of the placement of the kings, blended between the
+/// middle and end game tables by the material left of each player.
+pub fn king_score<C: MoveChain>(b: &Board<C>) -> i32 {
+    let white_piece_value: i32 = Piece::WhiteQueen
+        .to(Piece::WhiteRook)
+        .map(|x| b.pieces[x].count() as i32 * PIECE_VALUE[x as usize])
+        .sum();
+
+    let black_piece_value: i32 = Piece::BlackQueen
+        .to(Piece::BlackRook)
+        .map(|x| b.pieces[x].count() as i32 * PIECE_VALUE[x as usize])
+        .sum();
+
+    let white_earlygame = white_piece_value as f32 / FULL_PIECE_VALUE as f32;
+    let black_earlygame = black_piece_value as f32 / FULL_PIECE_VALUE as f32;
+
+    let white_king_sq = b.pieces[Piece::WhiteKing].first_piece();
+    let black_king_sq = b.pieces[Piece::BlackKing].first_piece();
+
+    let white_king_score = (KING_TABLE[white_king_sq.flip()] as f32 * white_earlygame
+        + KING_END_TABLE[white_king_sq.flip()] as f32 * (1.0 - white_earlygame))
+        as i32;
+    let black_king_score = (KING_TABLE[black_king_sq] as f32 * black_earlygame
+        + KING_END_TABLE[black_king_sq] as f32 * (1.0 - black_earlygame))
+        as i32;
+
+    white_king_score - black_king_score
+}
+
+/// Returns the separate terms of the evaluation of the position, computed from scratch.
+pub fn trace<C: MoveChain>(b: &Board<C>) -> EvalTrace {
+    let white_piece_value: i32 = Piece::WhiteQueen
+        .to(Piece::WhiteRook)
+        .map(|x| b.pieces[x].count() as i32 * PIECE_VALUE[x as usize])
+        .sum();
+
+    let black_piece_value: i32 = Piece::BlackQueen
+        .to(Piece::BlackRook)
+        .map(|x| b.pieces[x].count() as i32 * PIECE_VALUE[x as usize])
+        .sum();
+
+    let material = white_piece_value - black_piece_value
+        + (b.pieces[Piece::WhitePawn].count() as i32 - b.pieces[Piece::BlackPawn].count() as i32)
+            * PAWN_VALUE;
+
+    let mut piece_square = 0;
+    for p in b.pieces[Piece::WhiteBishop].iter() {
+        piece_square += BISHOP_TABLE[p.flip()]
+    }
+    for p in b.pieces[Piece::WhiteKnight].iter() {
+        piece_square += KNIGHT_TABLE[p.flip()]
+    }
+    for p in b.pieces[Piece::WhiteRook].iter() {
+        piece_square += ROOK_TABLE[p.flip()]
+    }
+    for p in b.pieces[Piece::WhitePawn].iter() {
+        piece_square += PAWN_TABLE[p.flip()]
+    }
+
+    for p in b.pieces[Piece::BlackBishop].iter() {
+        piece_square -= BISHOP_TABLE[p]
+    }
+    for p in b.pieces[Piece::BlackKnight].iter() {
+        piece_square -= KNIGHT_TABLE[p]
+    }
+    for p in b.pieces[Piece::BlackRook].iter() {
+        piece_square -= ROOK_TABLE[p]
+    }
+    for p in b.pieces[Piece::BlackPawn].iter() {
+        piece_square -= PAWN_TABLE[p]
+    }
+
+    EvalTrace {
+        material,
+        piece_square,
+        king: king_score(b),
+    }
+}
+
+/// Returns the evaluation of the position from the perspective of white, computed from scratch.
+pub fn evaluate<C: MoveChain>(b: &Board<C>) -> i32 {
+    trace(b).total()
+}
@@ -0,0 +1,41 @@
+use chess_core::board::{Board, EndChain, EvalChain, HashChain, MoveChain};
+
+const POSITIONS: [&str; 3] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "8/8/4k3/3p4/3K4/4P3/8/8 b - - 0 1",
+];
+
+#[test]
+fn start_position_is_even() {
+    assert_eq!(chess_eval::evaluate(&Board::start_position(EndChain)), 0);
+}
+
+#[test]
+fn mirrored_positions_score_the_negative() {
+    for fen in POSITIONS.iter() {
+        let board = Board::from_fen(fen, EndChain).unwrap();
+        assert_eq!(
+            chess_eval::evaluate(&board),
+            -chess_eval::evaluate(&board.mirror()),
+            "{}",
+            fen
+        );
+    }
+}
+
+#[test]
+fn tables_match_the_evaluation() {
+    for fen in POSITIONS.iter() {
+        let chain = HashChain::with(EvalChain::new(chess_eval::psqt_tables()));
+        let board = Board::from_fen(fen, chain).unwrap();
+        let trace = chess_eval::trace(&board);
+        assert_eq!(
+            board.chain.next_chain().psqt().mg + chess_eval::king_score(&board),
+            trace.total(),
+            "{}",
+            fen
+        );
+        assert_eq!(chess_eval::evaluate(&board), trace.total());
+    }
+}
@@ -0,0 +1,130 @@
+//! Generate training data by letting the engine play games against itself.
+//!
+//! ```text
+//! selfplay [--games N] [--max-nodes N] [--threads N] [--shard-games N]
+//!          [--temperature-plies N] [--resign FRACTION] [--max-plies N] [--out DIR]
+//! ```
+//!
+//! Games are played in parallel, the samples of every `--shard-games` finished games are written
+//! to a shard in the output directory and the index of the shards is written at the end.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use chess_core::{board::EndChain, Board};
+use chess_mcts::selfplay::{self, SelfPlayOptions};
+use std::{
+    env, fs,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+struct Args {
+    games: usize,
+    threads: usize,
+    shard_games: usize,
+    out: PathBuf,
+    options: SelfPlayOptions,
+}
+
+fn parse_value<T: FromStr>(name: &str, value: Option<String>) -> Result<T> {
+    let value = value.ok_or_else(|| anyhow!("missing value for `{}`", name))?;
+    value
+        .parse()
+        .map_err(|_| anyhow!("invalid value `{}` for `{}`", value, name))
+}
+
+fn parse_args() -> Result<Args> {
+    let mut res = Args {
+        games: 10,
+        threads: thread::available_parallelism().map_or(1, |x| x.get()),
+        shard_games: 100,
+        out: PathBuf::from("selfplay"),
+        options: SelfPlayOptions::default(),
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => res.games = parse_value(&arg, args.next())?,
+            "--threads" => res.threads = parse_value(&arg, args.next())?,
+            "--shard-games" => res.shard_games = parse_value(&arg, args.next())?,
+            "--out" => res.out = parse_value(&arg, args.next())?,
+            "--max-nodes" => res.options.max_nodes = parse_value(&arg, args.next())?,
+            "--temperature-plies" => {
+                res.options.temperature_plies = parse_value(&arg, args.next())?
+            }
+            "--resign" => res.options.resign_threshold = parse_value(&arg, args.next())?,
+            "--max-plies" => res.options.max_plies = parse_value(&arg, args.next())?,
+            x => bail!("unknown argument `{}`", x),
+        }
+    }
+    ensure!(res.threads > 0, "need at least one thread");
+    ensure!(res.shard_games > 0, "shards need at least one game");
+    ensure!(res.options.max_nodes > 0, "need at least one node per move");
+    Ok(res)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    fs::create_dir_all(&args.out)
+        .with_context(|| format!("failed to create `{}`", args.out.display()))?;
+
+    let next_game = AtomicUsize::new(0);
+    let (send, recv) = mpsc::channel();
+    let mut shards = Vec::new();
+
+    thread::scope(|s| -> Result<()> {
+        for _ in 0..args.threads {
+            let send = send.clone();
+            let next_game = &next_game;
+            let args = &args;
+            s.spawn(move || {
+                let start = Board::start_position(EndChain);
+                let mut rng = rand::thread_rng();
+                while next_game.fetch_add(1, Ordering::Relaxed) < args.games {
+                    let samples = selfplay::play_game(&start, &args.options, &mut rng);
+                    if send.send(samples).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(send);
+
+        let mut games = 0;
+        let mut samples = Vec::new();
+        for game in recv {
+            games += 1;
+            let outcome = game.first().map_or(0, |x| x.outcome);
+            println!(
+                "game {}/{}: {} plies, outcome {} for white",
+                games,
+                args.games,
+                game.len(),
+                outcome
+            );
+            samples.extend(game);
+            if games % args.shard_games == 0 || games == args.games {
+                let name = format!("shard_{:05}.bin", shards.len());
+                selfplay::write_shard(&args.out.join(&name), &samples)?;
+                shards.push((name, samples.len()));
+                samples.clear();
+            }
+        }
+        Ok(())
+    })?;
+
+    selfplay::write_index(&args.out, &shards)?;
+    let total: usize = shards.iter().map(|x| x.1).sum();
+    println!(
+        "wrote {} samples in {} shards to `{}`",
+        total,
+        shards.len(),
+        args.out.display()
+    );
+    Ok(())
+}
@@ -2,16 +2,26 @@
 #![allow(dead_code)]
 
 mod list;
+mod policy;
+pub mod selfplay;
 use chess_core::{
-    board::{Board, EndChain},
+    board::{Board as BaseBoard, EndChain, HashChain},
     engine::{Engine, EngineControl, Info, OptionKind, OptionValue},
     gen::{gen_type, Black, InlineBuffer, MoveGenerator, MoveList, PositionInfo, White},
     hash::Hasher,
-    Move, Piece, Player, UnmakeMove,
+    material_eval, Move, Piece, Player, Promotion, UnmakeMove,
 };
 use list::{InlineVec, List, NodeId};
+pub use policy::RolloutPolicy;
 use rand::Rng;
-use std::{collections::HashMap, fs, io, mem};
+use std::{
+    collections::HashMap,
+    fs, io, mem,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+type Board = BaseBoard<HashChain>;
 
 pub struct Node {
     parent: Option<NodeId>,
@@ -45,6 +55,21 @@ pub struct Options {
     max_rollout: usize,
     exploration: f32,
     playouts: u32,
+    policy: RolloutPolicy,
+    /// Progressive widening constant, a node exposes at most `widening * sqrt(simulations)`
+    /// children.
+    widening: f32,
+    /// Score rollouts which are still going after `max_rollout` moves by the material balance
+    /// instead of as a draw.
+    material_bias: bool,
+    /// Stop rollouts after this many plies and score them by the static evaluation, 0 to play
+    /// them out.
+    eval_plies: usize,
+    /// Evaluation in centipawns at which a stopped rollout scores about 73% for the side ahead,
+    /// the scale of the sigmoid converting the evaluation into a score.
+    eval_temperature: f32,
+    /// File to write the explored tree to in dot format after every search, if any.
+    dump_tree: Option<PathBuf>,
 }
 
 impl Default for Options {
@@ -53,6 +78,12 @@ impl Default for Options {
             max_rollout: 10_000,
             exploration: (2.0f32).sqrt(),
             playouts: 3,
+            policy: RolloutPolicy::Random,
+            widening: 1.0,
+            material_bias: false,
+            eval_plies: 0,
+            eval_temperature: 200.0,
+            dump_tree: None,
         }
     }
 }
@@ -72,10 +103,13 @@ impl<C: EngineControl> Mcts<C> {
     const SCORE_WIN: f32 = 1.0;
     const SCORE_DRAW: f32 = 0.5;
     const SCORE_LOSE: f32 = 0.0;
+    /// Material balance in centipawns at which an unfinished rollout scores about 88% for the
+    /// side ahead.
+    const MATERIAL_SCALE: f32 = 500.0;
 
     pub fn new() -> Self {
         let mut list = List::new();
-        let board = Board::start_position(EndChain);
+        let board = Board::start_position(HashChain::new());
         let move_gen = MoveGenerator::new();
         Mcts {
             options: Default::default(),
@@ -96,7 +130,10 @@ impl<C: EngineControl> Mcts<C> {
 
         // Selection
         loop {
-            if self.list[cur_node].fully_expanded() {
+            let node = &self.list[cur_node];
+            if node.fully_expanded()
+                || node.children.len() >= self.expansion_limit(node.simulations)
+            {
                 let mut best = None;
                 let mut best_score = f32::MIN;
 
@@ -150,9 +187,12 @@ impl<C: EngineControl> Mcts<C> {
         }
     }
 
-    fn simulate(&mut self, node: NodeId, board: &Board, rng: &mut impl rand::Rng) -> f32 {
-        const MAX_ROLLOUT: usize = 10_000;
+    /// Returns the amount of children a node with the given amount of simulations may have.
+    fn expansion_limit(&self, simulations: u32) -> usize {
+        ((self.options.widening * (simulations as f32).sqrt()).ceil() as usize).max(1)
+    }
 
+    fn simulate(&mut self, node: NodeId, board: &Board, rng: &mut impl rand::Rng) -> f32 {
         let node = &self.list[node];
         let mut score = 0.0;
 
@@ -166,60 +206,143 @@ impl<C: EngineControl> Mcts<C> {
         }
 
         for _ in 0..self.options.playouts {
-            let mut b = board.clone();
             let pick = rng.gen::<usize>() % node.moves.len();
-            let first_move = node.moves.get(pick);
-            b.make_move(first_move);
-            let mut move_buffer = InlineBuffer::<128>::new();
-            let mut info = self
-                .move_gen
-                .gen_moves::<gen_type::AllPseudo, _, _>(&b, &mut move_buffer);
+            let b = board.make_move_copy(node.moves.get(pick));
+            score += self.rollout(&b, rng).0;
+        }
+        score
+    }
 
-            'rollout: for i in 0..MAX_ROLLOUT {
-                if self.move_gen.drawn(&b, &info) {
-                    score += Self::SCORE_DRAW;
-                    break;
-                }
+    /// Play a random game from the given board.
+    ///
+    /// Returns the score for the player to move on the given board together with the
+    /// number of moves played before the game ended.
+    /// A position which repeats during the rollout is scored as a draw, as is a game which is
+    /// still going after `max_rollout` moves unless it is scored by the material. With
+    /// `eval_plies` set the game is instead stopped after that many moves and scored by the
+    /// static evaluation.
+    pub fn rollout(&self, board: &Board, rng: &mut impl rand::Rng) -> (f32, usize) {
+        let mut b = board.clone();
+        let mut move_buffer = InlineBuffer::<128>::new();
+        let mut info = self
+            .move_gen
+            .gen_moves::<gen_type::AllPseudo, _, _>(&b, &mut move_buffer);
+        let mut hashes = vec![b.chain.hash];
+
+        let plies = match self.options.eval_plies {
+            0 => self.options.max_rollout,
+            x => x.min(self.options.max_rollout),
+        };
+        for i in 0..plies {
+            if self.move_gen.drawn(&b, &info) {
+                return (Self::SCORE_DRAW, i);
+            }
 
-                let mov = loop {
-                    if move_buffer.len() == 0 {
-                        if (node.info.attacked & b.pieces[Piece::player_king(b.state.player)]).any()
-                        {
-                            if b.state.player == board.state.player {
-                                score += Self::SCORE_WIN;
-                            }
-                        } else {
-                            score += Self::SCORE_DRAW;
+            let mov = match self.pick_move(&mut b, &info, &mut move_buffer, rng) {
+                Some(x) => x,
+                None => {
+                    if (info.attacked & b.pieces[Piece::player_king(b.state.player)]).any() {
+                        if b.state.player == board.state.player {
+                            return (Self::SCORE_LOSE, i);
                         }
-                        break 'rollout;
+                        return (Self::SCORE_WIN, i);
                     }
+                    return (Self::SCORE_DRAW, i);
+                }
+            };
 
-                    let pick = rng.gen::<usize>() % move_buffer.len();
-                    let mov = move_buffer.get(pick);
-                    if self.move_gen.is_legal(mov, &b, &info) {
-                        if move_buffer.len() > 1 && self.should_retry(mov, &b, rng) {
-                            move_buffer.swap_remove(pick);
-                        } else {
-                            break mov;
-                        }
+            b.make_move(mov);
+
+            // Only positions since the last irreversible move can repeat.
+            if b.state.move_clock == 0 {
+                hashes.clear();
+            } else if hashes.contains(&b.chain.hash) {
+                return (Self::SCORE_DRAW, i + 1);
+            }
+            hashes.push(b.chain.hash);
+
+            move_buffer.clear();
+            info = self
+                .move_gen
+                .gen_moves::<gen_type::AllPseudo, _, _>(&b, &mut move_buffer);
+        }
+        if self.options.eval_plies > 0 {
+            let mut eval = chess_eval::evaluate(&b);
+            if board.state.player == Player::Black {
+                eval = -eval;
+            }
+            let score = 1.0 / (1.0 + (-eval as f32 / self.options.eval_temperature).exp());
+            return (score, plies);
+        }
+        if !self.options.material_bias {
+            return (Self::SCORE_DRAW, self.options.max_rollout);
+        }
+        let mut material = material_eval(&b);
+        if b.state.player != board.state.player {
+            material = -material;
+        }
+        let score = Self::SCORE_DRAW + (material as f32 / Self::MATERIAL_SCALE).tanh() * 0.5;
+        (score, self.options.max_rollout)
+    }
+
+    /// Pick the next move of a rollout from the pseudo legal moves according to the rollout
+    /// policy.
+    ///
+    /// Returns `None` if none of the moves are legal.
+    pub fn pick_move(
+        &self,
+        b: &mut Board,
+        info: &PositionInfo,
+        moves: &mut InlineBuffer<128>,
+        rng: &mut impl rand::Rng,
+    ) -> Option<Move> {
+        if self.options.policy == RolloutPolicy::Random {
+            while moves.len() > 0 {
+                let pick = rng.gen::<usize>() % moves.len();
+                let mov = moves.get(pick);
+                if self.move_gen.is_legal(mov, b, info) {
+                    if moves.len() > 1 && self.should_retry(mov, b, rng) {
+                        moves.swap_remove(pick);
                     } else {
-                        move_buffer.swap_remove(pick);
+                        return Some(mov);
                     }
-                };
+                } else {
+                    moves.swap_remove(pick);
+                }
+            }
+            return None;
+        }
 
-                b.make_move(mov);
-                move_buffer.clear();
-                info = self
-                    .move_gen
-                    .gen_moves::<gen_type::AllPseudo, _, _>(&b, &mut move_buffer);
+        let mut weights = [0u32; 128];
+        let mut legal = [false; 128];
+        let mut total = 0;
+        let mut legal_count = 0;
+        for (i, mov) in moves.iter().enumerate() {
+            if self.move_gen.is_legal(mov, b, info) {
+                legal[i] = true;
+                legal_count += 1;
+                weights[i] = self.options.policy.weight(mov, b, &self.move_gen);
+                total += weights[i];
+            }
+        }
+        if legal_count == 0 {
+            return None;
+        }
+        if total == 0 {
+            // Only quiet moves of the tactical policy weigh nothing, which are then all equally
+            // likely.
+            weights = legal.map(u32::from);
+            total = legal_count;
+        }
 
-                if i == MAX_ROLLOUT - 1 {
-                    score += Self::SCORE_DRAW;
-                    break;
-                }
+        let mut pick = rng.gen_range(0..total);
+        for (i, mov) in moves.iter().enumerate() {
+            if pick < weights[i] {
+                return Some(mov);
             }
+            pick -= weights[i];
         }
-        score
+        unreachable!()
     }
 
     fn should_retry(&self, mov: Move, b: &Board, rng: &mut impl rand::Rng) -> bool {
@@ -228,18 +351,56 @@ impl<C: EngineControl> Mcts<C> {
         }
 
         rng.gen::<f32>() < 0.5
-            && (b.on(mov.to()).is_none()
-                || mov.ty() == Move::TYPE_PROMOTION
-                    && mov.promotion_piece() != Move::PROMOTION_QUEEN)
+            && (!mov.is_capture(b)
+                || mov.is_promotion() && mov.promotion_piece() != Promotion::Queen)
+    }
+
+    /// Start a new search tree from the current board.
+    pub fn reset(&mut self) {
+        self.iterations = 0;
+        self.list.clear();
+        self.root = self
+            .list
+            .insert(Node::new(None, &self.board, &self.move_gen));
+    }
+
+    /// Run the given amount of iterations on the current search tree.
+    pub fn run(&mut self, iterations: u32) {
+        for _ in 0..iterations {
+            self.iteration();
+            self.iterations += 1;
+        }
+    }
+
+    /// Returns the amount of children of the root which have been expanded.
+    pub fn root_children(&self) -> usize {
+        self.list[self.root].children.len()
+    }
+
+    /// Returns the expanded moves of the root with their amount of simulations and the
+    /// fraction of them won by the player to move at the root.
+    pub fn root_visits(&self) -> Vec<(Move, u32, f32)> {
+        self.list[self.root]
+            .children
+            .iter()
+            .copied()
+            .map(|(c, m)| {
+                let n = &self.list[c];
+                (m, n.simulations, n.score / n.simulations as f32)
+            })
+            .collect()
     }
 
-    pub fn dump_tree(&self) {
+    /// Write the children of the root and the line of best children below it to the given file
+    /// in dot format.
+    pub fn dump_tree(&self, path: &Path) -> io::Result<()> {
         use io::Write;
-        let mut file = fs::File::create("mcts.dot").unwrap();
-        writeln!(file, "digraph mcts{{").unwrap();
-        writeln!(file, "{} [label=\"root\"];", self.root.0).unwrap();
-        self.dump_tree_rec(&mut file, self.root).unwrap();
-        writeln!(file, "}}").unwrap();
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "digraph mcts{{")?;
+        writeln!(file, "{} [label=\"root\"];", self.root.0)?;
+        self.dump_tree_rec(&mut file, self.root)?;
+        writeln!(file, "}}")?;
+        file.flush()
     }
 
     fn dump_tree_rec(&self, f: &mut impl io::Write, node: NodeId) -> io::Result<()> {
@@ -270,16 +431,17 @@ impl<C: EngineControl> Mcts<C> {
 impl<C: EngineControl> Engine<C> for Mcts<C> {
     const NAME: &'static str = "Random play MCTS";
 
-    fn set_board(&mut self, board: Board) {
-        self.board = board;
+    fn set_board(&mut self, board: BaseBoard) {
+        self.board.copy_position(&board);
     }
 
     fn make_move(&mut self, m: Move) {
         self.board.make_move(m);
     }
 
-    fn options(&self) -> HashMap<String, OptionKind> {
-        [
+    fn options(&self) -> Vec<(String, OptionKind)> {
+        let defaults = Options::default();
+        vec![
             (
                 "playouts".to_string(),
                 OptionKind::Spin {
@@ -288,7 +450,22 @@ impl<C: EngineControl> Engine<C> for Mcts<C> {
                     min: Some(1),
                 },
             ),
-            ("exploration".to_string(), OptionKind::String),
+            (
+                "exploration".to_string(),
+                OptionKind::String {
+                    default: defaults.exploration.to_string(),
+                },
+            ),
+            (
+                "widening".to_string(),
+                OptionKind::String {
+                    default: defaults.widening.to_string(),
+                },
+            ),
+            (
+                "rollout_policy".to_string(),
+                OptionKind::combo(&RolloutPolicy::NAMES),
+            ),
             (
                 "max_rollout".to_string(),
                 OptionKind::Spin {
@@ -297,10 +474,30 @@ impl<C: EngineControl> Engine<C> for Mcts<C> {
                     min: Some(20),
                 },
             ),
+            (
+                "material_bias".to_string(),
+                OptionKind::Check { default: false },
+            ),
+            (
+                "eval_plies".to_string(),
+                OptionKind::Spin {
+                    default: 0,
+                    max: Some(1000),
+                    min: Some(0),
+                },
+            ),
+            (
+                "eval_temperature".to_string(),
+                OptionKind::String {
+                    default: defaults.eval_temperature.to_string(),
+                },
+            ),
+            ("dump_tree_file".to_string(), OptionKind::string()),
+            (
+                "UCI_AnalyseMode".to_string(),
+                OptionKind::Check { default: false },
+            ),
         ]
-        .iter()
-        .cloned()
-        .collect()
     }
     fn set_option(&mut self, name: String, value: OptionValue) {
         match name.as_str() {
@@ -316,11 +513,55 @@ impl<C: EngineControl> Engine<C> for Mcts<C> {
                     }
                 }
             }
+            "widening" => {
+                if let OptionValue::String(x) = value {
+                    if let Ok(x) = x.parse() {
+                        self.options.widening = x;
+                    }
+                }
+            }
+            "rollout_policy" => {
+                if let OptionValue::Combo(x) = value {
+                    if let Some(x) = RolloutPolicy::from_index(x) {
+                        self.options.policy = x;
+                    }
+                }
+            }
             "max_rollout" => {
                 if let OptionValue::Spin(x) = value {
                     self.options.max_rollout = x as usize;
                 }
             }
+            "material_bias" => {
+                if let OptionValue::Check(x) = value {
+                    self.options.material_bias = x;
+                }
+            }
+            "eval_plies" => {
+                if let OptionValue::Spin(x) = value {
+                    self.options.eval_plies = x.max(0) as usize;
+                }
+            }
+            "eval_temperature" => {
+                if let OptionValue::String(x) = value {
+                    if let Ok(x) = x.parse::<f32>() {
+                        if x > 0.0 {
+                            self.options.eval_temperature = x;
+                        }
+                    }
+                }
+            }
+            "dump_tree_file" => {
+                if let OptionValue::String(x) = value {
+                    self.options.dump_tree = if x.is_empty() || x == "<empty>" {
+                        None
+                    } else {
+                        Some(PathBuf::from(x))
+                    };
+                }
+            }
+            // Without contempt or a book the search is the same when analysing.
+            "UCI_AnalyseMode" => {}
             _ => {}
         }
     }
@@ -328,24 +569,41 @@ impl<C: EngineControl> Engine<C> for Mcts<C> {
     fn go(
         &mut self,
         control: C,
-        _time_left: Option<std::time::Duration>,
-        _limit: chess_core::engine::EngineLimit,
+        time_left: Option<std::time::Duration>,
+        limit: chess_core::engine::EngineLimit,
     ) -> Option<Move> {
         self.control = control;
+        let start = Instant::now();
+        let search_time = limit.search_time(time_left);
 
-        self.iterations = 0;
-        self.list.clear();
-        self.root = self
-            .list
-            .insert(Node::new(None, &self.board, &self.move_gen));
+        self.reset();
+
+        if let Some(search_moves) = limit.search_moves.as_ref() {
+            let root = &mut self.list[self.root];
+            let mut filtered = InlineBuffer::<128>::new();
+            for m in root.moves.iter().filter(|m| search_moves.contains(m)) {
+                filtered.push(m);
+            }
+            if filtered.len() > 0 {
+                root.moves = filtered;
+            }
+        }
 
         if self.list[self.root].moves.len() == 0 {
             return None;
         }
 
-        while !self.control.should_stop() {
+        // Always run one iteration so there is a move to return. Every iteration counts as a
+        // node for the node limit.
+        loop {
             self.iteration();
             self.iterations += 1;
+            if self.control.should_stop()
+                || limit.nodes.is_some_and(|x| self.iterations as u64 >= x)
+                || search_time.is_some_and(|x| start.elapsed() >= x)
+            {
+                break;
+            }
         }
 
         self.control
@@ -372,7 +630,15 @@ impl<C: EngineControl> Engine<C> for Mcts<C> {
 
         self.control.info(Info::Debug(format!("score: {}", score)));
 
-        self.dump_tree();
+        if let Some(path) = self.options.dump_tree.as_ref() {
+            if let Err(e) = self.dump_tree(path) {
+                self.control.info(Info::Debug(format!(
+                    "failed to write tree to {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        }
 
         return m;
     }
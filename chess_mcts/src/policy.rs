@@ -0,0 +1,99 @@
+use chess_core::{board::MoveChain, gen::MoveGenerator, Board, Move, Piece, Promotion};
+
+/// How moves are picked during a rollout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RolloutPolicy {
+    /// Every legal move is equally likely.
+    #[default]
+    Random,
+    /// Captures are more likely, weighted by the value of the captured piece.
+    Captures,
+    /// Moves which give check are more likely.
+    Checks,
+    /// Only captures, queen promotions and checks are played while there are any, captures
+    /// picked by the most valuable victim and then by the least valuable attacker. Other moves
+    /// are played when there are none, each equally likely.
+    Tactical,
+}
+
+impl RolloutPolicy {
+    /// The names of the policies as used in the uci option, in order of index.
+    pub const NAMES: [&'static str; 4] = ["random", "captures", "checks", "tactical"];
+
+    pub fn from_index(idx: usize) -> Option<Self> {
+        match idx {
+            0 => Some(RolloutPolicy::Random),
+            1 => Some(RolloutPolicy::Captures),
+            2 => Some(RolloutPolicy::Checks),
+            3 => Some(RolloutPolicy::Tactical),
+            _ => None,
+        }
+    }
+
+    /// Returns the relative chance of a legal move being picked.
+    ///
+    /// Quiet moves of the tactical policy weigh nothing, they are only picked when every legal
+    /// move weighs nothing.
+    pub fn weight<C: MoveChain>(self, m: Move, b: &mut Board<C>, gen: &MoveGenerator) -> u32 {
+        match self {
+            RolloutPolicy::Random => 1,
+            RolloutPolicy::Captures => {
+                let victim = if m.is_en_passant() {
+                    Some(Piece::WhitePawn)
+                } else {
+                    b.on(m.to())
+                };
+                1 + victim.map(Self::victim_value).unwrap_or(0)
+            }
+            RolloutPolicy::Checks => {
+                let undo = b.make_move(m);
+                let check = gen.checked_king(b, &gen.gen_info(b));
+                b.unmake_move(undo);
+                if check {
+                    8
+                } else {
+                    1
+                }
+            }
+            RolloutPolicy::Tactical => {
+                let attacker = b.on(m.from()).map_or(0, Piece::value) as u32;
+                let victim = if m.is_en_passant() {
+                    Piece::WhitePawn.value()
+                } else {
+                    b.on(m.to()).map_or(0, Piece::value)
+                } as u32;
+                let promotion = if m.is_promotion() && m.promotion_piece() == Promotion::Queen {
+                    Piece::WhiteQueen.value() - Piece::WhitePawn.value()
+                } else {
+                    0
+                } as u32;
+                let gain = victim + promotion;
+                if gain > 0 {
+                    // Most valuable victim, least valuable attacker.
+                    10 + gain * 10 - attacker
+                } else if Self::gives_check(m, b, gen) {
+                    10
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    fn gives_check<C: MoveChain>(m: Move, b: &mut Board<C>, gen: &MoveGenerator) -> bool {
+        let undo = b.make_move(m);
+        let check = gen.king_attacked(b, b.state.player);
+        b.unmake_move(undo);
+        check
+    }
+
+    fn victim_value(piece: Piece) -> u32 {
+        match piece.flip(!piece.white()) {
+            Piece::WhiteQueen => 36,
+            Piece::WhiteRook => 20,
+            Piece::WhiteBishop | Piece::WhiteKnight => 12,
+            Piece::WhitePawn => 4,
+            _ => 0,
+        }
+    }
+}
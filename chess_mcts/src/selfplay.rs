@@ -0,0 +1,327 @@
+//! Self play games for generating training data.
+//!
+//! Every position of a game is stored as a [`Sample`] together with the distribution of the
+//! visits of the search over its moves and the outcome of the game. Samples are written to
+//! shards, files in a simple binary format, which are listed in an index next to them.
+//!
+//! A shard starts with the magic bytes `CSP1` and the amount of samples as a `u32`, followed by
+//! the samples. All integers are little endian. A sample consists of:
+//!
+//! - the 12 bitboards of [`Sample::planes`] as `u64`,
+//! - the castle flags, the en passant file or 8 if there is none, and the move clock as `u8`,
+//! - the outcome as `i8`,
+//! - the amount of policy entries as `u16`, followed by every entry as a `u16` index and a
+//!   `f32` fraction of the visits.
+//!
+//! The index is a text file named [`INDEX_FILE`] with a line per shard containing the file name
+//! of the shard and its amount of samples, separated by a tab.
+
+use crate::Mcts;
+use anyhow::{anyhow, ensure, Context, Result};
+use chess_core::{
+    board::{Board, EndChain, MoveChain},
+    engine::{Engine, NoControl},
+    gen::{MoveGenerator, PositionStatus},
+    move_index,
+    repetition::PositionHistory,
+    ExtraState, Piece, Player,
+};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const MAGIC: &[u8; 4] = b"CSP1";
+
+/// Name of the index of the shards in a directory.
+pub const INDEX_FILE: &str = "index.txt";
+
+/// A position from a self play game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// The bitboards of the pieces in the order of [`Piece`], with the position mirrored and
+    /// the colors swapped when black is to move so the side to move is always white.
+    pub planes: [u64; 12],
+    /// Castle flags of [`ExtraState`] in the oriented position.
+    pub castle: u8,
+    pub en_passant: Option<u8>,
+    pub move_clock: u8,
+    /// The fraction of the visits of the search for every searched move, by the index of the
+    /// move in [`move_index`].
+    pub policy: Vec<(u16, f32)>,
+    /// Outcome of the game for the side to move, 1 for a win, 0 for a draw and -1 for a loss.
+    pub outcome: i8,
+}
+
+impl Sample {
+    /// Create a sample of the position on the board with an unknown outcome.
+    pub fn new<C: MoveChain + Clone>(board: &Board<C>, policy: Vec<(u16, f32)>) -> Self {
+        let oriented = match board.state.player {
+            Player::White => board.clone(),
+            Player::Black => board.mirror(),
+        };
+        let mut planes = [0; 12];
+        for p in Piece::WhiteKing.to(Piece::BlackPawn) {
+            planes[p as usize] = oriented.pieces[p].0;
+        }
+        let en_passant = oriented.state.en_passant;
+        Sample {
+            planes,
+            castle: oriented.state.castle,
+            en_passant: (en_passant != ExtraState::INVALID_ENPASSANT).then_some(en_passant),
+            move_clock: oriented.state.move_clock,
+            policy,
+            outcome: 0,
+        }
+    }
+
+    fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        for plane in self.planes.iter() {
+            w.write_all(&plane.to_le_bytes())?;
+        }
+        w.write_all(&[
+            self.castle,
+            self.en_passant.unwrap_or(ExtraState::INVALID_ENPASSANT),
+            self.move_clock,
+            self.outcome as u8,
+        ])?;
+        w.write_all(&(self.policy.len() as u16).to_le_bytes())?;
+        for (index, fraction) in self.policy.iter() {
+            w.write_all(&index.to_le_bytes())?;
+            w.write_all(&fraction.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut impl Read) -> Result<Self> {
+        let mut planes = [0; 12];
+        for plane in planes.iter_mut() {
+            *plane = u64::from_le_bytes(read_bytes(r)?);
+        }
+        let [castle, en_passant, move_clock, outcome] = read_bytes(r)?;
+        ensure!(castle <= 0b1111, "invalid castle flags {:#b}", castle);
+        ensure!(
+            en_passant <= ExtraState::INVALID_ENPASSANT,
+            "invalid en passant file {}",
+            en_passant
+        );
+        let outcome = outcome as i8;
+        ensure!((-1..=1).contains(&outcome), "invalid outcome {}", outcome);
+
+        let len = u16::from_le_bytes(read_bytes(r)?);
+        let mut policy = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let index = u16::from_le_bytes(read_bytes(r)?);
+            ensure!(
+                (index as usize) < move_index::POLICY_SIZE,
+                "invalid policy index {}",
+                index
+            );
+            policy.push((index, f32::from_le_bytes(read_bytes(r)?)));
+        }
+
+        Ok(Sample {
+            planes,
+            castle,
+            en_passant: (en_passant != ExtraState::INVALID_ENPASSANT).then_some(en_passant),
+            move_clock,
+            policy,
+            outcome,
+        })
+    }
+}
+
+fn read_bytes<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut res = [0; N];
+    r.read_exact(&mut res)?;
+    Ok(res)
+}
+
+/// Write the samples to a shard at the given path.
+pub fn write_shard(path: &Path, samples: &[Sample]) -> Result<()> {
+    let file = fs::File::create(path)
+        .with_context(|| format!("failed to create shard `{}`", path.display()))?;
+    let mut w = BufWriter::new(file);
+    w.write_all(MAGIC)?;
+    w.write_all(&(samples.len() as u32).to_le_bytes())?;
+    for sample in samples {
+        sample.write(&mut w)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// Iterates over the samples in a shard.
+pub struct ShardReader {
+    reader: BufReader<fs::File>,
+    remaining: u32,
+}
+
+impl ShardReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("failed to open shard `{}`", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let magic: [u8; 4] = read_bytes(&mut reader)?;
+        ensure!(&magic == MAGIC, "`{}` is not a shard", path.display());
+        let remaining = u32::from_le_bytes(read_bytes(&mut reader)?);
+        Ok(ShardReader { reader, remaining })
+    }
+
+    /// Returns the amount of samples which have not been read yet.
+    pub fn remaining(&self) -> usize {
+        self.remaining as usize
+    }
+}
+
+impl Iterator for ShardReader {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Result<Sample>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let res = Sample::read(&mut self.reader);
+        if res.is_err() {
+            // The rest of the shard can't be found after a broken sample.
+            self.remaining = 0;
+        }
+        Some(res)
+    }
+}
+
+/// Write the index of the shards in a directory, given the file names of the shards and their
+/// amount of samples.
+pub fn write_index(dir: &Path, shards: &[(String, usize)]) -> Result<()> {
+    let mut text = String::new();
+    for (name, samples) in shards {
+        text.push_str(&format!("{}\t{}\n", name, samples));
+    }
+    let path = dir.join(INDEX_FILE);
+    fs::write(&path, text).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+/// Read the index of the shards in a directory, returns the paths of the shards and their
+/// amount of samples.
+pub fn read_index(dir: &Path) -> Result<Vec<(PathBuf, usize)>> {
+    let path = dir.join(INDEX_FILE);
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+    text.lines()
+        .filter(|x| !x.is_empty())
+        .map(|line| {
+            let (name, samples) = line
+                .split_once('\t')
+                .ok_or_else(|| anyhow!("invalid line in shard index: `{}`", line))?;
+            let samples = samples
+                .parse()
+                .with_context(|| format!("invalid line in shard index: `{}`", line))?;
+            Ok((dir.join(name), samples))
+        })
+        .collect()
+}
+
+/// Settings of self play games.
+#[derive(Clone, Debug)]
+pub struct SelfPlayOptions {
+    /// Iterations of the search for every move.
+    pub max_nodes: u32,
+    /// Amount of plies at the start of a game for which moves are picked at random, in
+    /// proportion to their visits. Afterwards the most visited move is played.
+    pub temperature_plies: usize,
+    /// A player resigns when the best move wins less than this fraction of its simulations, 0
+    /// to never resign.
+    pub resign_threshold: f32,
+    /// Games which are still going after this many plies are adjudicated a draw.
+    pub max_plies: usize,
+}
+
+impl Default for SelfPlayOptions {
+    fn default() -> Self {
+        SelfPlayOptions {
+            max_nodes: 800,
+            temperature_plies: 30,
+            resign_threshold: 0.05,
+            max_plies: 512,
+        }
+    }
+}
+
+/// Play a game against itself from the given position and return a sample of every position
+/// in which a move was searched.
+pub fn play_game(
+    start: &Board<EndChain>,
+    options: &SelfPlayOptions,
+    rng: &mut impl Rng,
+) -> Vec<Sample> {
+    let gen = MoveGenerator::new();
+    let mut mcts = Mcts::<NoControl>::new();
+    let mut board = start.clone();
+    let mut history = PositionHistory::new();
+    history.push(&board);
+
+    let mut samples = Vec::new();
+    // The player who lost the game, if any.
+    let loser = loop {
+        let info = gen.gen_info(&board);
+        match gen.position_status(&board, &info) {
+            PositionStatus::Checkmate => break Some(board.state.player),
+            PositionStatus::Stalemate => break None,
+            _ => {}
+        }
+        if history.draw_rule(&board).is_some()
+            || gen.drawn(&board, &info)
+            || samples.len() >= options.max_plies
+        {
+            break None;
+        }
+
+        mcts.set_board(board.clone());
+        mcts.reset();
+        mcts.run(options.max_nodes);
+        let visits = mcts.root_visits();
+        let total: u32 = visits.iter().map(|x| x.1).sum();
+        if total == 0 {
+            break None;
+        }
+
+        let player = board.state.player;
+        let policy = visits
+            .iter()
+            .map(|(m, simulations, _)| {
+                let index = move_index::encode(*m, player) as u16;
+                (index, *simulations as f32 / total as f32)
+            })
+            .collect();
+        samples.push((player, Sample::new(&board, policy)));
+
+        let (best, _, win_rate) = visits.iter().copied().max_by_key(|x| x.1).unwrap();
+        if win_rate < options.resign_threshold {
+            break Some(player);
+        }
+
+        let m = if samples.len() <= options.temperature_plies {
+            let weights = WeightedIndex::new(visits.iter().map(|x| x.1)).unwrap();
+            visits[weights.sample(rng)].0
+        } else {
+            best
+        };
+        board.make_move(m);
+        history.push(&board);
+    };
+
+    samples
+        .into_iter()
+        .map(|(player, mut sample)| {
+            sample.outcome = match loser {
+                Some(x) if x == player => -1,
+                Some(_) => 1,
+                None => 0,
+            };
+            sample
+        })
+        .collect()
+}
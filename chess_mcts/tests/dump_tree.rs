@@ -0,0 +1,27 @@
+use chess_core::engine::{Engine, EngineLimit, NoControl, OptionValue};
+use chess_mcts::Mcts;
+use std::{fs, path::Path};
+
+#[test]
+fn tree_is_written_to_the_given_file() {
+    let file = std::env::temp_dir().join(format!("chess_mcts_tree_{}.dot", std::process::id()));
+    let mut mcts = Mcts::<NoControl>::new();
+    mcts.set_option(
+        "dump_tree_file".to_string(),
+        OptionValue::String(file.display().to_string()),
+    );
+    assert!(mcts.go(NoControl, None, EngineLimit::nodes(200)).is_some());
+
+    let dot = fs::read_to_string(&file).unwrap();
+    fs::remove_file(&file).unwrap();
+    assert!(dot.starts_with("digraph mcts{"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("->"));
+}
+
+#[test]
+fn tree_is_not_written_by_default() {
+    let mut mcts = Mcts::<NoControl>::new();
+    assert!(mcts.go(NoControl, None, EngineLimit::nodes(200)).is_some());
+    assert!(!Path::new("mcts.dot").exists());
+}
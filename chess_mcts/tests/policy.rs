@@ -0,0 +1,198 @@
+use chess_core::{
+    board::{Board, HashChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList, PositionStatus},
+    material_eval,
+    repetition::PositionHistory,
+    Player, Square,
+};
+use chess_mcts::Mcts;
+use rand::{rngs::StdRng, SeedableRng};
+
+const TACTICAL: usize = 3;
+
+/// Count how often the rook takes the undefended queen.
+fn queen_captures(mcts: &Mcts<NoControl>) -> usize {
+    let mut board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", HashChain::new()).unwrap();
+    let gen = MoveGenerator::new();
+    let mut rng = rand::thread_rng();
+    let mut count = 0;
+    for _ in 0..1000 {
+        let mut moves = InlineBuffer::new();
+        let info = gen.gen_moves::<gen_type::AllPseudo, _, _>(&board, &mut moves);
+        let m = mcts
+            .pick_move(&mut board, &info, &mut moves, &mut rng)
+            .unwrap();
+        if m.to() == Square::from_name("d5").unwrap() {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[test]
+fn capture_policy_prefers_free_queen() {
+    let mut mcts = Mcts::<NoControl>::new();
+    let random = queen_captures(&mcts);
+
+    mcts.set_option("rollout_policy".to_string(), OptionValue::Combo(1));
+    let captures = queen_captures(&mcts);
+
+    assert!(
+        captures > random * 5,
+        "captures: {}, random: {}",
+        captures,
+        random
+    );
+}
+
+#[test]
+fn tactical_policy_prefers_free_queen() {
+    let mut mcts = Mcts::<NoControl>::new();
+    mcts.set_option("rollout_policy".to_string(), OptionValue::Combo(TACTICAL));
+    // Only the capture and the check on e2 are played, the capture of the queen far more often.
+    let captures = queen_captures(&mcts);
+    assert!(captures > 800, "captures: {}", captures);
+}
+
+#[test]
+fn tactical_policy_picks_legal_moves() {
+    let mut mcts = Mcts::<NoControl>::new();
+    mcts.set_option("rollout_policy".to_string(), OptionValue::Combo(TACTICAL));
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(0x7ac7);
+
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        // Pinned pieces which can capture along and off the pin.
+        "4k3/4r3/8/8/4B3/3n4/4N3/4K3 w - - 0 1",
+        // In check, with captures that don't resolve it.
+        "4k3/8/8/8/1b6/8/3P1q2/4K2R w K - 0 1",
+        // An en passant capture which exposes the king.
+        "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+        // A promotion by capture.
+        "2r1k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+    ];
+    for fen in fens.iter() {
+        let mut board = Board::from_fen(fen, HashChain::new()).unwrap();
+        for _ in 0..100 {
+            let mut legal = InlineBuffer::<128>::new();
+            gen.gen_moves::<gen_type::All, _, _>(&board, &mut legal);
+            let mut moves = InlineBuffer::new();
+            let info = gen.gen_moves::<gen_type::AllPseudo, _, _>(&board, &mut moves);
+            match mcts.pick_move(&mut board, &info, &mut moves, &mut rng) {
+                Some(m) => {
+                    assert!(
+                        legal.iter().any(|x| x == m),
+                        "illegal move {} in {}",
+                        m,
+                        board.to_fen()
+                    );
+                    board.make_move(m);
+                }
+                None => {
+                    assert_eq!(legal.len(), 0, "no move picked in {}", board.to_fen());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn eval_scores_stopped_rollouts() {
+    let mut mcts = Mcts::<NoControl>::new();
+    mcts.set_option("eval_plies".to_string(), OptionValue::Spin(2));
+    let mut rng = rand::thread_rng();
+
+    // White is a rook and three pawns up, which two plies can't change.
+    let board = Board::from_fen("4k3/8/8/8/8/8/PPP5/R3K3 b - - 0 1", HashChain::new()).unwrap();
+    for _ in 0..20 {
+        let (score, plies) = mcts.rollout(&board, &mut rng);
+        assert_eq!(plies, 2);
+        assert!(score < 0.05, "score {} for the side a rook down", score);
+    }
+
+    // A larger temperature makes the score less certain.
+    mcts.set_option(
+        "eval_temperature".to_string(),
+        OptionValue::String("2000".to_string()),
+    );
+    let (score, _) = mcts.rollout(&board, &mut rng);
+    assert!((0.2..0.5).contains(&score), "score {}", score);
+}
+
+/// Play a game between the engines at a fixed amount of iterations per move and return the
+/// winner, if any.
+///
+/// Neither engine is strong enough to reliably mate, so games which are still going after 200
+/// plies are won by the side at least a minor piece ahead.
+fn play_game(white: &mut Mcts<NoControl>, black: &mut Mcts<NoControl>) -> Option<Player> {
+    let gen = MoveGenerator::new();
+    let mut board = Board::start_position(chess_core::board::EndChain);
+    let mut history = PositionHistory::new();
+    history.push(&board);
+    for _ in 0..200 {
+        let info = gen.gen_info(&board);
+        match gen.position_status(&board, &info) {
+            PositionStatus::Checkmate => return Some(board.state.player.flip()),
+            PositionStatus::Stalemate => return None,
+            _ => {}
+        }
+        if history.draw_rule(&board).is_some() || gen.drawn(&board, &info) {
+            return None;
+        }
+        let engine = match board.state.player {
+            Player::White => &mut *white,
+            Player::Black => &mut *black,
+        };
+        engine.set_board(board.clone());
+        let m = engine.go(NoControl, None, EngineLimit::nodes(400)).unwrap();
+        board.make_move(m);
+        history.push(&board);
+    }
+    let material = material_eval(&board);
+    if material.abs() < 300 {
+        None
+    } else if material > 0 {
+        Some(board.state.player)
+    } else {
+        Some(board.state.player.flip())
+    }
+}
+
+#[test]
+#[ignore = "plays full games, takes minutes"]
+fn tactical_rollouts_beat_uniform_rollouts() {
+    let mut uniform = Mcts::<NoControl>::new();
+    let mut tactical = Mcts::<NoControl>::new();
+    tactical.set_option("rollout_policy".to_string(), OptionValue::Combo(TACTICAL));
+    tactical.set_option("eval_plies".to_string(), OptionValue::Spin(8));
+
+    let games = 20;
+    // Points of the tactical engine, two per win and one per draw.
+    let mut points = 0;
+    for game in 0..games {
+        let tactical_color = if game % 2 == 0 {
+            Player::White
+        } else {
+            Player::Black
+        };
+        let winner = if tactical_color == Player::White {
+            play_game(&mut tactical, &mut uniform)
+        } else {
+            play_game(&mut uniform, &mut tactical)
+        };
+        points += match winner {
+            Some(x) if x == tactical_color => 2,
+            Some(_) => 0,
+            None => 1,
+        };
+    }
+    assert!(
+        points >= games * 2 * 3 / 4,
+        "tactical scored {} of {} points",
+        points,
+        games * 2
+    );
+}
@@ -0,0 +1,26 @@
+use chess_core::{
+    board::{Board, HashChain},
+    engine::NoControl,
+};
+use chess_mcts::Mcts;
+
+#[test]
+fn locked_position_rollout_is_draw_by_repetition() {
+    // Pawns are locked and neither king can reach the other side, only king moves remain.
+    let board = Board::from_fen(
+        "k7/8/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/K7 w - - 0 1",
+        HashChain::new(),
+    )
+    .unwrap();
+    let mcts = Mcts::<NoControl>::new();
+    let mut rng = rand::thread_rng();
+
+    let mut total = 0;
+    for _ in 0..100 {
+        let (score, length) = mcts.rollout(&board, &mut rng);
+        assert_eq!(score, 0.5);
+        total += length;
+    }
+    // Without repetition detection the rollout only ends at the fifty move rule.
+    assert!(total / 100 < 25, "average rollout length {}", total / 100);
+}
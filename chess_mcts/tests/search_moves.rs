@@ -0,0 +1,52 @@
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineControl, EngineLimit, Info},
+    gen::MoveGenerator,
+    Move,
+};
+use chess_mcts::Mcts;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Control which stops the search after a fixed amount of iterations.
+#[derive(Default)]
+struct Iterations(AtomicU32);
+
+impl EngineControl for Iterations {
+    fn should_stop(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| x.checked_sub(1))
+            .is_err()
+    }
+
+    fn info(&self, _: Info) {}
+}
+
+fn go(uci: &[&str]) -> Option<Move> {
+    let gen = MoveGenerator::new();
+    let board = Board::start_position(EndChain);
+    let moves = uci
+        .iter()
+        .map(|x| Move::from_uci_on_board(x, &board, &gen).unwrap())
+        .collect();
+    let mut mcts = Mcts::new();
+    mcts.set_board(board);
+    let limit = EngineLimit {
+        search_moves: Some(moves),
+        ..EngineLimit::none()
+    };
+    mcts.go(Iterations(AtomicU32::new(200)), None, limit)
+}
+
+#[test]
+fn only_search_moves_are_played() {
+    let m = go(&["a2a3"]).unwrap();
+    assert_eq!(m.to_uci(), "a2a3");
+
+    let m = go(&["h2h3", "b1a3"]).unwrap().to_uci();
+    assert!(m == "h2h3" || m == "b1a3", "played {}", m);
+}
+
+#[test]
+fn empty_search_moves_search_everything() {
+    assert!(go(&[]).is_some());
+}
@@ -0,0 +1,86 @@
+use chess_core::{
+    board::{Board, EndChain},
+    gen::MoveGenerator,
+    move_index, Move, Piece,
+};
+use chess_mcts::selfplay::{self, Sample, SelfPlayOptions, ShardReader};
+
+#[test]
+fn samples_are_oriented_for_the_side_to_move() {
+    let gen = MoveGenerator::new();
+    let mut board = Board::start_position(EndChain);
+    let white = Sample::new(&board, Vec::new());
+
+    let m = Move::from_uci_on_board("e2e4", &board, &gen).unwrap();
+    board.make_move(m);
+    let black = Sample::new(&board, Vec::new());
+
+    // Black's pieces are seen as white's, its pawns still on their start rank.
+    assert_eq!(
+        black.planes[Piece::WhitePawn as usize],
+        white.planes[Piece::WhitePawn as usize]
+    );
+    assert_ne!(
+        black.planes[Piece::BlackPawn as usize],
+        white.planes[Piece::BlackPawn as usize]
+    );
+    assert_eq!(black.castle, white.castle);
+    assert_eq!(black.en_passant, Some(4));
+    assert_eq!(white.en_passant, None);
+}
+
+#[test]
+fn shard_round_trip() {
+    let dir = std::env::temp_dir().join(format!("chess_mcts_selfplay_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 12 40", EndChain).unwrap();
+    let mut sample = Sample::new(&board, vec![(0, 0.25), (4095, 0.75)]);
+    sample.outcome = -1;
+    let samples = vec![
+        sample,
+        Sample::new(&Board::start_position(EndChain), Vec::new()),
+    ];
+
+    selfplay::write_shard(&dir.join("shard.bin"), &samples).unwrap();
+    selfplay::write_index(&dir, &[("shard.bin".to_string(), samples.len())]).unwrap();
+
+    let index = selfplay::read_index(&dir).unwrap();
+    assert_eq!(index, vec![(dir.join("shard.bin"), 2)]);
+    let reader = ShardReader::open(&index[0].0).unwrap();
+    assert_eq!(reader.remaining(), 2);
+    let read: Vec<_> = reader.collect::<anyhow::Result<_>>().unwrap();
+    assert_eq!(read, samples);
+
+    std::fs::write(dir.join("broken.bin"), b"CSP1\x01\x00\x00\x00\x00").unwrap();
+    let mut reader = ShardReader::open(&dir.join("broken.bin")).unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+    assert!(ShardReader::open(&dir.join(selfplay::INDEX_FILE)).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn self_play_game() {
+    let start = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", EndChain).unwrap();
+    let options = SelfPlayOptions {
+        max_nodes: 20,
+        temperature_plies: 4,
+        resign_threshold: 0.0,
+        max_plies: 16,
+    };
+    let samples = selfplay::play_game(&start, &options, &mut rand::thread_rng());
+    assert!(!samples.is_empty() && samples.len() <= 16);
+
+    let first = &samples[0];
+    assert_eq!(first.policy.iter().map(|x| x.1).sum::<f32>().round(), 1.0);
+    assert!(first
+        .policy
+        .iter()
+        .all(|x| (x.0 as usize) < move_index::POLICY_SIZE));
+    // The outcome alternates sign between the players.
+    for pair in samples.windows(2) {
+        assert_eq!(pair[0].outcome, -pair[1].outcome);
+    }
+}
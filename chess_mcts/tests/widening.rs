@@ -0,0 +1,12 @@
+use chess_core::engine::NoControl;
+use chess_mcts::Mcts;
+
+#[test]
+fn wide_node_is_expanded_progressively() {
+    let mut mcts = Mcts::<NoControl>::new();
+    mcts.reset();
+    mcts.run(20);
+    // The start position has 20 legal moves.
+    assert!(mcts.root_children() < 20);
+    assert!(mcts.root_children() > 1);
+}
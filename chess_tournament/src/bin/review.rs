@@ -0,0 +1,42 @@
+//! Review a played game with the alpha beta engine, listing how much every move lost compared
+//! to the move the engine prefers.
+//!
+//! The game file contains the game as the argument of a uci `position` command, for example
+//! `startpos moves e2e4 e7e5 g1f3`.
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result};
+use chess_core::engine::EngineLimit;
+use chess_uci::review::{Game, GameReview};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// File containing the game to review.
+    game: PathBuf,
+    /// Time in seconds spent searching every position.
+    #[structopt(long, default_value = "1.0")]
+    movetime: f32,
+    /// Also write the review as json to this file.
+    #[structopt(long)]
+    json: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Opt::from_args();
+    let text = fs::read_to_string(&args.game)
+        .with_context(|| format!("could not read game file `{}`", args.game.display()))?;
+    let game = Game::parse(&text).context("failed to parse game")?;
+
+    let limit = EngineLimit::time(Duration::from_secs_f32(args.movetime));
+    let review = GameReview::review(&game, limit);
+    println!("{}", review);
+
+    if let Some(path) = args.json {
+        let file = fs::File::create(&path)
+            .with_context(|| format!("could not create `{}`", path.display()))?;
+        serde_json::to_writer_pretty(file, &review).context("could not write review")?;
+    }
+    Ok(())
+}
@@ -1,11 +1,18 @@
 use std::{
+    collections::HashMap,
     fs::{self, File},
     os::unix::prelude::PermissionsExt,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
-use chess_core::Player;
+use chess_core::{affinity, Player};
+use chess_uci::{
+    profile::EngineProfile,
+    runner::{Handicap, UciPlayer},
+    CPU_MASK_OPTION,
+};
 use serde::{Deserialize, Serialize};
 
 mod elo;
@@ -20,6 +27,55 @@ pub struct Config {
     start_positions: Vec<StartPosition>,
     time: f32,
     increment: Option<f32>,
+    /// Handicaps of engines, by the file name of the engine.
+    #[serde(default)]
+    handicaps: HashMap<String, Handicap>,
+    /// Pin the two engines of a game to disjoint halves of the cpus with their `CpuMask`
+    /// option.
+    #[serde(default)]
+    pin_engines: bool,
+}
+
+impl Config {
+    /// Returns the handicap the engine plays with.
+    pub fn handicap(&self, engine: &EngineData) -> Handicap {
+        engine
+            .path
+            .file_name()
+            .and_then(|x| self.handicaps.get(x.to_string_lossy().as_ref()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the profiles the white and black engine are started with.
+    ///
+    /// With `pin_engines` the profiles set the `CpuMask` option of the engines to disjoint
+    /// halves of the cpus, unless there is only one.
+    pub fn profiles(
+        &self,
+        white: &EngineData,
+        black: &EngineData,
+    ) -> (EngineProfile, EngineProfile) {
+        let mut profiles = (white.profile.clone(), black.profile.clone());
+        if !self.pin_engines {
+            return profiles;
+        }
+        let cpus = affinity::thread_affinity().unwrap_or_default();
+        match affinity::split_cpus(&cpus, 2).as_slice() {
+            [a, b] => {
+                for (profile, cpus) in [(&mut profiles.0, a), (&mut profiles.1, b)] {
+                    profile
+                        .options
+                        .retain(|(name, _)| !name.eq_ignore_ascii_case(CPU_MASK_OPTION));
+                    profile
+                        .options
+                        .push((CPU_MASK_OPTION.to_string(), affinity::format_cpu_mask(cpus)));
+                }
+            }
+            _ => println!("WARNING: not enough cpus to pin engines, running them unpinned"),
+        }
+        profiles
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -61,6 +117,19 @@ pub struct GamePlayed {
     opponent: PathBuf,
     color: Color,
     start_position: String,
+    /// ECO code of the opening played, if known.
+    #[serde(default)]
+    eco: Option<String>,
+    #[serde(default)]
+    opening: Option<String>,
+    /// Handicaps of the engine and its opponent in the game.
+    #[serde(default, skip_serializing_if = "Handicap::is_none")]
+    handicap: Handicap,
+    #[serde(default, skip_serializing_if = "Handicap::is_none")]
+    opponent_handicap: Handicap,
+    /// Time left on the clocks of white and black after every move.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    clock_history: Vec<(Duration, Duration)>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Debug)]
@@ -91,10 +160,26 @@ impl GameOutcome {
 #[derive(Deserialize, Serialize)]
 pub struct EngineData {
     path: PathBuf,
+    /// The option profile the engine is played with.
+    #[serde(default)]
+    profile: EngineProfile,
+    /// The name and version the engine reported when it was last started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     elo: f64,
     games: Vec<GamePlayed>,
 }
 
+impl EngineData {
+    /// The name of the engine shown in the output.
+    pub fn name(&self) -> String {
+        self.profile
+            .name
+            .clone()
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
 fn main() -> Result<()> {
     let config_file = File::open("./config.json").context("Could not find config json")?;
     let config = serde_json::from_reader(config_file).context("Failed to parse config file")?;
@@ -127,11 +212,33 @@ fn update_state(s: &mut State) -> Result<()> {
         }
 
         let path = entry.path();
+        let profile = EngineProfile::load(&path)?;
+        let id = UciPlayer::with_profile(&path, &profile)?
+            .id()
+            .map(str::to_string);
 
         // Test if engine pressent in state;
-        if s.0.iter().find(|x| x.path == path).is_none() {
+        if let Some(engine) = s.0.iter_mut().find(|x| x.path == path) {
+            if engine.profile != profile {
+                println!(
+                    "WARNING: profile of {} changed since its previous games, its rating mixes both",
+                    path.display()
+                );
+                engine.profile = profile;
+            }
+            if engine.id.is_some() && engine.id != id {
+                println!(
+                    "WARNING: {} now identifies as {}, its rating mixes both builds",
+                    path.display(),
+                    id.as_deref().unwrap_or("nothing")
+                );
+            }
+            engine.id = id;
+        } else {
             s.0.push(EngineData {
                 path,
+                profile,
+                id,
                 elo: 1500.0,
                 games: Vec::new(),
             });
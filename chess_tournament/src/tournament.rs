@@ -30,9 +30,9 @@ pub fn start(config: &Config, state: &mut State) -> Result<()> {
         let pos = thread_rng().gen_range(0..config.start_positions.len());
         println!(
             "SCHEDULED: {}(elo: {}) vs {}(elo: {}) on {}",
-            state.0[g].path.display(),
+            state.0[g].name(),
             state.0[g].elo,
-            state.0[other].path.display(),
+            state.0[other].name(),
             state.0[other].elo,
             config.start_positions[pos].name
         );
@@ -98,61 +98,78 @@ pub fn play_game(
     position: usize,
 ) -> Result<()> {
     let (first, second) = ref_mut_two(&mut state.0, first, second);
-
-    let outcome = game::play(
-        &first.path,
-        &second.path,
-        &config.start_positions[position].fen,
-        config.time,
-        config.increment,
-    )?;
-
-    update_elo(
-        &mut first.elo,
-        &mut second.elo,
-        outcome,
-        config.k_factor as f64,
-    );
+    let first_handicap = config.handicap(first);
+    let second_handicap = config.handicap(second);
+    // Ratings are for engines playing on equal terms, games with a handicap are only recorded.
+    let rated = first_handicap.is_none() && second_handicap.is_none();
+    let start_position = &config.start_positions[position];
+
+    let (outcome, opening, clock_history) = game::play(config, first, second, &start_position.fen)?;
+
+    if rated {
+        update_elo(
+            &mut first.elo,
+            &mut second.elo,
+            outcome,
+            config.k_factor as f64,
+        );
+    }
 
     first.games.push(crate::GamePlayed {
         outcome,
         opponent: second.path.clone(),
         color: Color::White,
-        start_position: config.start_positions[position].name.clone(),
+        start_position: start_position.name.clone(),
+        eco: opening.map(|x| x.0.to_string()),
+        opening: opening.map(|x| x.1.to_string()),
+        handicap: first_handicap,
+        opponent_handicap: second_handicap,
+        clock_history: clock_history.clone(),
     });
 
     second.games.push(crate::GamePlayed {
         outcome: outcome.flip(),
         opponent: first.path.clone(),
         color: Color::Black,
-        start_position: config.start_positions[position].name.clone(),
+        start_position: start_position.name.clone(),
+        eco: opening.map(|x| x.0.to_string()),
+        opening: opening.map(|x| x.1.to_string()),
+        handicap: second_handicap,
+        opponent_handicap: first_handicap,
+        clock_history,
     });
 
-    let outcome = game::play(
-        &second.path,
-        &first.path,
-        &config.start_positions[position].fen,
-        config.time,
-        config.increment,
-    )?;
-
-    update_elo(
-        &mut first.elo,
-        &mut second.elo,
-        outcome.flip(),
-        config.k_factor as f64,
-    );
+    let (outcome, opening, clock_history) = game::play(config, second, first, &start_position.fen)?;
+
+    if rated {
+        update_elo(
+            &mut first.elo,
+            &mut second.elo,
+            outcome.flip(),
+            config.k_factor as f64,
+        );
+    }
     second.games.push(crate::GamePlayed {
         outcome,
         opponent: first.path.clone(),
         color: Color::White,
-        start_position: config.start_positions[position].name.clone(),
+        start_position: start_position.name.clone(),
+        eco: opening.map(|x| x.0.to_string()),
+        opening: opening.map(|x| x.1.to_string()),
+        handicap: second_handicap,
+        opponent_handicap: first_handicap,
+        clock_history: clock_history.clone(),
     });
     first.games.push(crate::GamePlayed {
         outcome: outcome.flip(),
         opponent: second.path.clone(),
         color: Color::Black,
-        start_position: config.start_positions[position].name.clone(),
+        start_position: start_position.name.clone(),
+        eco: opening.map(|x| x.0.to_string()),
+        opening: opening.map(|x| x.1.to_string()),
+        handicap: first_handicap,
+        opponent_handicap: second_handicap,
+        clock_history,
     });
 
     Ok(())
@@ -0,0 +1,56 @@
+use std::{fs, os::unix::fs::PermissionsExt, path::Path, process::Command};
+
+const CONFIG: &str = r#"{
+    "k_factor": 32.0,
+    "initial_games": 0,
+    "tournament_games": 0,
+    "start_positions": [],
+    "time": 1.0
+}"#;
+
+/// Write an engine which identifies itself with the given version.
+fn write_engine(path: &Path, version: &str) {
+    let script = format!(
+        r#"#!/bin/sh
+while read -r line; do
+    case "$line" in
+        uci) echo "id name stub {}"; echo "uciok" ;;
+        isready) echo "readyok" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#,
+        version
+    );
+    fs::write(path, script).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn run_tournament(dir: &Path) -> String {
+    let status = Command::new(env!("CARGO_BIN_EXE_chess_tournament"))
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::read_to_string(dir.join("state.json")).unwrap()
+}
+
+#[test]
+fn state_records_engine_version() {
+    let dir = std::env::temp_dir().join(format!("chess_tournament_state_{}", std::process::id()));
+    fs::create_dir_all(dir.join("engines")).unwrap();
+    fs::write(dir.join("config.json"), CONFIG).unwrap();
+    let engine = dir.join("engines").join("stub");
+
+    write_engine(&engine, "v1.0.0");
+    let state = run_tournament(&dir);
+    assert!(state.contains(r#""id": "stub v1.0.0""#), "{}", state);
+
+    // Replacing the engine with a new build updates the recorded version.
+    write_engine(&engine, "v1.1.0");
+    let state = run_tournament(&dir);
+    assert!(state.contains(r#""id": "stub v1.1.0""#), "{}", state);
+    assert!(!state.contains("v1.0.0"), "{}", state);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
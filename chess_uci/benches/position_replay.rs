@@ -0,0 +1,73 @@
+use chess_core::{
+    board::EndChain,
+    engine::{Engine, EngineControl, EngineLimit},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    Board, Move,
+};
+use chess_uci::{Uci, UciMove};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::time::Duration;
+
+/// Engine which does nothing, so only the position handling is measured.
+struct Idle;
+
+impl<C: EngineControl> Engine<C> for Idle {
+    const NAME: &'static str = "Idle";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        None
+    }
+
+    fn set_board(&mut self, _: Board) {}
+
+    fn make_move(&mut self, _: Move) {}
+}
+
+/// Returns the position commands a GUI would send while playing a random game.
+fn game_commands(plies: usize) -> Vec<String> {
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut board = Board::start_position(EndChain);
+    let mut command = "startpos moves".to_string();
+    let mut commands = Vec::new();
+    for _ in 0..plies {
+        let mut buffer = InlineBuffer::<256>::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+        if buffer.len() == 0 {
+            break;
+        }
+        let m = buffer.get(rng.gen_range(0..buffer.len()));
+        board.make_move(m);
+        command.push(' ');
+        command.push_str(&UciMove(m).to_string());
+        commands.push(command.clone());
+    }
+    commands
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let commands = game_commands(300);
+    let mut uci = Uci::new(Idle);
+
+    c.bench_function("position_replay_cached", |b| {
+        b.iter(|| {
+            uci.clear_position();
+            for c in commands.iter() {
+                uci.parse_position(c).unwrap();
+            }
+        })
+    });
+
+    c.bench_function("position_replay_uncached", |b| {
+        b.iter(|| {
+            for c in commands.iter() {
+                uci.clear_position();
+                uci.parse_position(c).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,24 +1,21 @@
-use std::collections::HashMap;
-
 use anyhow::Result;
 use chess_core::{
-    board::EndChain,
-    engine::{Engine, EngineControl, OptionKind},
+    engine::{Engine, EngineControl, Info, OptionKind},
     gen::{gen_type, MoveGenerator},
     Board, Move,
 };
-use chess_uci::Uci;
+use chess_uci::position::Position;
 use rand::Rng;
 
 pub struct Random {
-    board: Board,
+    position: Position,
     gen: MoveGenerator,
 }
 
 impl Random {
     pub fn new() -> Self {
         Random {
-            board: Board::start_position(EndChain),
+            position: Position::start_position(),
             gen: MoveGenerator::new(),
         }
     }
@@ -29,13 +26,17 @@ impl<C: EngineControl> Engine<C> for Random {
 
     fn go(
         &mut self,
-        _: C,
+        control: C,
         _: Option<std::time::Duration>,
         _: chess_core::engine::EngineLimit,
     ) -> Option<Move> {
+        // Draws are claimed by the arbiter, the engine keeps playing but says so.
+        if let Some(rule) = self.position.draw_rule(&self.gen) {
+            control.info(Info::Debug(format!("position is drawn by {}", rule)));
+        }
         let mut moves = Vec::new();
         self.gen
-            .gen_moves::<gen_type::All, _, _>(&self.board, &mut moves);
+            .gen_moves::<gen_type::All, _, _>(self.position.board(), &mut moves);
         if moves.len() == 0 {
             return None;
         }
@@ -43,20 +44,20 @@ impl<C: EngineControl> Engine<C> for Random {
     }
 
     fn set_board(&mut self, board: Board) {
-        self.board = board;
+        self.position.set_board(board);
     }
 
     fn make_move(&mut self, m: Move) {
-        self.board.make_move(m);
+        self.position.make_move(m);
     }
 
-    fn options(&self) -> HashMap<String, OptionKind> {
-        HashMap::new()
+    fn options(&self) -> Vec<(String, OptionKind)> {
+        Vec::new()
     }
 
     fn set_option(&mut self, _: String, _: chess_core::engine::OptionValue) {}
 }
 
 fn main() -> Result<()> {
-    Uci::new(Random::new()).start()
+    chess_uci::run(Random::new)
 }
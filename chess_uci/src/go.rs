@@ -0,0 +1,230 @@
+//! Interpreting the arguments of the `go` command.
+//!
+//! GUIs send all kinds of combinations of arguments, [`GoRequest`] holds them as they were sent
+//! and [`TimeControl::from_go`] turns them into the limits of a single search.
+
+use anyhow::{anyhow, Result};
+use chess_core::{engine::EngineLimit, Player};
+use std::{str::FromStr, time::Duration};
+
+/// The arguments of a `go` command, as sent by the GUI.
+///
+/// Times are in milliseconds and may be negative, some GUIs send negative times when a clock
+/// ran out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoRequest {
+    pub wtime: Option<i64>,
+    pub btime: Option<i64>,
+    pub winc: Option<i64>,
+    pub binc: Option<i64>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<i64>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>,
+    pub infinite: bool,
+    pub ponder: bool,
+    /// The move names following `searchmoves`, not yet checked against the position.
+    pub searchmoves: Vec<String>,
+}
+
+impl GoRequest {
+    /// The arguments of the command.
+    pub const ARGUMENTS: &'static [&'static str] = &[
+        "searchmoves",
+        "ponder",
+        "wtime",
+        "btime",
+        "winc",
+        "binc",
+        "movestogo",
+        "depth",
+        "nodes",
+        "mate",
+        "movetime",
+        "infinite",
+    ];
+
+    /// Parse the arguments following `go`.
+    ///
+    /// Unknown arguments are ignored, a missing or malformed value is an error.
+    pub fn parse(arg: &str) -> Result<Self> {
+        fn value<T: FromStr>(name: &str, value: Option<&str>) -> Result<T> {
+            let value = value.ok_or_else(|| anyhow!("missing value for `{}`", name))?;
+            value
+                .parse()
+                .map_err(|_| anyhow!("invalid value `{}` for `{}`", value, name))
+        }
+
+        let mut res = GoRequest::default();
+        let mut iter = arg.split_whitespace().peekable();
+        while let Some(cmd) = iter.next() {
+            match cmd {
+                "infinite" => res.infinite = true,
+                "ponder" => res.ponder = true,
+                "wtime" => res.wtime = Some(value(cmd, iter.next())?),
+                "btime" => res.btime = Some(value(cmd, iter.next())?),
+                "winc" => res.winc = Some(value(cmd, iter.next())?),
+                "binc" => res.binc = Some(value(cmd, iter.next())?),
+                "movestogo" => res.movestogo = Some(value(cmd, iter.next())?),
+                "movetime" => res.movetime = Some(value(cmd, iter.next())?),
+                "depth" => res.depth = Some(value(cmd, iter.next())?),
+                "nodes" => res.nodes = Some(value(cmd, iter.next())?),
+                "mate" => res.mate = Some(value(cmd, iter.next())?),
+                "searchmoves" => {
+                    // The list of moves runs until the next argument of the command.
+                    while let Some(name) = iter.next_if(|x| !Self::ARGUMENTS.contains(x)) {
+                        res.searchmoves.push(name.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// How long a single search may run, normalized from a [`GoRequest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// Search until told to stop.
+    Infinite,
+    /// Search for exactly this long.
+    MoveTime(Duration),
+    /// Play on the clock of the side to move.
+    Clock {
+        time: Duration,
+        increment: Duration,
+        /// Moves until the next time control, `None` if the rest of the game has to be played
+        /// with the time left.
+        moves_to_go: Option<u32>,
+    },
+    FixedDepth(u32),
+    FixedNodes(u64),
+    /// Search for a mate in this many moves.
+    Mate(u32),
+    /// Stop as soon as any of the limits is reached.
+    Compound(Vec<TimeControl>),
+}
+
+impl TimeControl {
+    /// Moves the time left is divided over when the GUI doesn't say how many moves are left.
+    pub const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+    /// Interpret the arguments of a `go` command for the side to move, returns the time control
+    /// and warnings about the arguments which were ignored or corrected.
+    ///
+    /// - `infinite` overrides every other limit.
+    /// - A move time is preferred over the clock.
+    /// - Negative times are treated as zero.
+    /// - A clock without the time of the side to move is ignored.
+    /// - `movestogo 0` and `mate 0` are ignored.
+    pub fn from_go(go: &GoRequest, side: Player) -> (TimeControl, Vec<String>) {
+        let mut warnings = Vec::new();
+        if go.infinite {
+            return (TimeControl::Infinite, warnings);
+        }
+
+        let mut duration = |name: &str, ms: i64| {
+            if ms < 0 {
+                warnings.push(format!("negative `{}` {}, using 0", name, ms));
+            }
+            Duration::from_millis(ms.max(0) as u64)
+        };
+
+        let mut limits = Vec::new();
+        let (time, increment, time_name) = match side {
+            Player::White => (go.wtime, go.winc, "wtime"),
+            Player::Black => (go.btime, go.binc, "btime"),
+        };
+        let has_clock = go.wtime.is_some() || go.btime.is_some();
+        if let Some(movetime) = go.movetime {
+            limits.push(TimeControl::MoveTime(duration("movetime", movetime)));
+        } else if let Some(time) = time {
+            let time = duration(time_name, time);
+            let increment = duration("increment", increment.unwrap_or(0));
+            let moves_to_go = match go.movestogo {
+                Some(0) => {
+                    warnings.push("ignoring `movestogo 0`".to_string());
+                    None
+                }
+                x => x,
+            };
+            limits.push(TimeControl::Clock {
+                time,
+                increment,
+                moves_to_go,
+            });
+        } else if has_clock {
+            warnings.push(format!(
+                "no `{}` for the side to move, ignoring the clock",
+                time_name
+            ));
+        }
+        if let Some(depth) = go.depth {
+            limits.push(TimeControl::FixedDepth(depth));
+        }
+        if let Some(nodes) = go.nodes {
+            limits.push(TimeControl::FixedNodes(nodes));
+        }
+        match go.mate {
+            Some(0) => warnings.push("ignoring `mate 0`".to_string()),
+            Some(moves) => limits.push(TimeControl::Mate(moves)),
+            None => {}
+        }
+
+        let res = match limits.len() {
+            0 => TimeControl::Infinite,
+            1 => limits.pop().unwrap(),
+            _ => TimeControl::Compound(limits),
+        };
+        (res, warnings)
+    }
+
+    /// Returns the time to spend on the move, `None` if the search is not limited in time.
+    ///
+    /// On the clock the time left is divided over the moves to go, plus most of the increment,
+    /// but never more than half of the time left.
+    pub fn move_time(&self) -> Option<Duration> {
+        match self {
+            TimeControl::MoveTime(x) => Some(*x),
+            TimeControl::Clock {
+                time,
+                increment,
+                moves_to_go,
+            } => {
+                let moves = moves_to_go.unwrap_or(Self::DEFAULT_MOVES_TO_GO).max(1);
+                Some((*time / moves + *increment * 3 / 4).min(*time / 2))
+            }
+            TimeControl::Compound(limits) => limits.iter().filter_map(|x| x.move_time()).min(),
+            TimeControl::Infinite
+            | TimeControl::FixedDepth(_)
+            | TimeControl::FixedNodes(_)
+            | TimeControl::Mate(_) => None,
+        }
+    }
+
+    /// Returns the limits of the search for the engine.
+    pub fn limits(&self) -> EngineLimit {
+        let mut res = EngineLimit {
+            time: self.move_time(),
+            ..EngineLimit::none()
+        };
+        for limit in self.parts() {
+            match limit {
+                TimeControl::FixedDepth(x) => res.depth = Some(*x),
+                TimeControl::FixedNodes(x) => res.nodes = Some(*x),
+                TimeControl::Mate(x) => res.mate = Some(*x),
+                _ => {}
+            }
+        }
+        res
+    }
+
+    fn parts(&self) -> &[TimeControl] {
+        match self {
+            TimeControl::Compound(limits) => limits,
+            x => std::slice::from_ref(x),
+        }
+    }
+}
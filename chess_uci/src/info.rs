@@ -0,0 +1,198 @@
+//! Parsing of the `info` lines an engine sends while searching.
+
+use chess_core::{engine::Score, Player};
+
+/// What an engine reported about its search so far.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct SearchInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub nodes: Option<u64>,
+    /// Score from the perspective of the side to move.
+    pub score: Option<Score>,
+    /// Whether the score is only a bound on the real score.
+    pub bound: bool,
+    /// The principal variation in uci notation.
+    pub pv: Vec<String>,
+}
+
+/// The keywords an info line can contain, used to find the end of a list of moves.
+const KEYWORDS: &[&str] = &[
+    "depth",
+    "seldepth",
+    "time",
+    "nodes",
+    "pv",
+    "multipv",
+    "score",
+    "currmove",
+    "currmovenumber",
+    "hashfull",
+    "nps",
+    "tbhits",
+    "sbhits",
+    "cpuload",
+    "string",
+    "refutation",
+    "currline",
+];
+
+impl SearchInfo {
+    /// Parse an `info` line, returns `None` if the line is not one.
+    ///
+    /// Unknown or malformed fields are skipped.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace().peekable();
+        if parts.next() != Some("info") {
+            return None;
+        }
+
+        let mut res = SearchInfo::default();
+        while let Some(part) = parts.next() {
+            match part {
+                "depth" => res.depth = parts.next().and_then(|x| x.parse().ok()),
+                "seldepth" => res.seldepth = parts.next().and_then(|x| x.parse().ok()),
+                "nodes" => res.nodes = parts.next().and_then(|x| x.parse().ok()),
+                "score" => {
+                    let kind = parts.next();
+                    let value = parts.next().and_then(|x| x.parse().ok());
+                    res.score = match (kind, value) {
+                        (Some("cp"), Some(x)) => Some(Score::Cp(x)),
+                        (Some("mate"), Some(x)) => Some(Score::Mate(x)),
+                        _ => None,
+                    };
+                    if let Some(&x) = parts.peek() {
+                        if x == "lowerbound" || x == "upperbound" {
+                            res.bound = true;
+                            parts.next();
+                        }
+                    }
+                }
+                "pv" => {
+                    res.pv.clear();
+                    while let Some(&x) = parts.peek() {
+                        if KEYWORDS.contains(&x) {
+                            break;
+                        }
+                        res.pv.push(x.to_string());
+                        parts.next();
+                    }
+                }
+                // The rest of the line is free form text.
+                "string" => break,
+                _ => {}
+            }
+        }
+        Some(res)
+    }
+
+    /// Returns the score from the perspective of white, given the side to move in the searched
+    /// position.
+    pub fn white_score(&self, side_to_move: Player) -> Option<Score> {
+        self.score.map(|score| match (side_to_move, score) {
+            (Player::White, x) => x,
+            (Player::Black, Score::Cp(x)) => Score::Cp(-x),
+            (Player::Black, Score::Mate(x)) => Score::Mate(-x),
+        })
+    }
+
+    /// Update with the fields of a newer info line, keeping the fields it doesn't contain.
+    pub fn merge(&mut self, newer: SearchInfo) {
+        self.depth = newer.depth.or(self.depth);
+        self.seldepth = newer.seldepth.or(self.seldepth);
+        self.nodes = newer.nodes.or(self.nodes);
+        if newer.score.is_some() {
+            self.score = newer.score;
+            self.bound = newer.bound;
+        }
+        if !newer.pv.is_empty() {
+            self.pv = newer.pv;
+        }
+    }
+}
+
+/// The score an engine reported for the move it played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvalSample {
+    /// The amount of moves made in the game after the move.
+    pub ply: usize,
+    /// The player who made the move.
+    pub player: Player,
+    /// The score in centipawns from the perspective of white, clamped to
+    /// [`EvalHistory::LIMIT`].
+    pub score: i32,
+}
+
+impl EvalSample {
+    /// Returns the number of the move in the game, counting a move of both players as one.
+    pub fn move_number(&self) -> usize {
+        self.ply.div_ceil(2)
+    }
+}
+
+/// The scores engines reported during a game, for drawing how the evaluation changed.
+#[derive(Clone, Default, Debug)]
+pub struct EvalHistory {
+    samples: Vec<EvalSample>,
+}
+
+impl EvalHistory {
+    /// The highest score in centipawns kept, mate scores are pegged to it.
+    pub const LIMIT: i32 = 1000;
+
+    pub fn new() -> Self {
+        EvalHistory::default()
+    }
+
+    /// Record the score from the perspective of white after the given player made a move.
+    pub fn push(&mut self, ply: usize, player: Player, score: Score) {
+        let score = match score {
+            Score::Cp(x) => x.clamp(-Self::LIMIT, Self::LIMIT),
+            Score::Mate(x) if x > 0 => Self::LIMIT,
+            Score::Mate(_) => -Self::LIMIT,
+        };
+        self.samples.push(EvalSample { ply, player, score });
+    }
+
+    /// Remove the samples of moves after the given ply, for when moves are taken back.
+    pub fn truncate(&mut self, ply: usize) {
+        self.samples.retain(|x| x.ply <= ply);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn samples(&self) -> &[EvalSample] {
+        &self.samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the position of a sample in a chart, with both coordinates between 0 and 1.
+    ///
+    /// The first sample is at the left edge and the last at the right edge, a score of
+    /// [`EvalHistory::LIMIT`] for white at the top and for black at the bottom.
+    pub fn point(&self, sample: &EvalSample) -> (f32, f32) {
+        let first = self.samples.first().map_or(0, |x| x.ply);
+        let last = self.samples.last().map_or(0, |x| x.ply);
+        let x = if last > first {
+            (sample.ply - first) as f32 / (last - first) as f32
+        } else {
+            0.5
+        };
+        let y = 0.5 - sample.score as f32 / (2 * Self::LIMIT) as f32;
+        (x, y)
+    }
+
+    /// Returns the sample closest to the given horizontal position in a chart, between 0 and 1.
+    pub fn nearest(&self, x: f32) -> Option<&EvalSample> {
+        self.samples.iter().min_by(|a, b| {
+            let a = (self.point(a).0 - x).abs();
+            let b = (self.point(b).0 - x).abs();
+            a.total_cmp(&b)
+        })
+    }
+}
@@ -2,45 +2,38 @@
 #![allow(dead_code)]
 
 use std::{
-    collections::HashMap,
     fmt,
-    io::{self, BufRead},
-    time::Duration,
+    io::{self, BufRead, BufReader, Stdout, Write},
+    time::Instant,
 };
 
 use anyhow::{anyhow, bail, ensure, Result};
 use chess_core::{
+    affinity,
     board::{Board, EndChain},
-    engine::{Engine, EngineLimit, EngineThread, Info, OptionKind, Response, ThreadController},
-    gen::{gen_type, InlineBuffer, MoveGenerator},
-    Move, Player, Square,
+    engine::{
+        Engine, EngineLimit, EngineThread, Info, OptionKind, OptionValue, Response,
+        ThreadController,
+    },
+    gen::{gen_type, InlineBuffer, MoveGenerator, MAX_MOVES},
+    Move, Player,
 };
-use crossbeam_channel::select;
+use crossbeam_channel::{select, Receiver};
+use go::{GoRequest, TimeControl};
+
+pub mod go;
+pub mod info;
+pub mod position;
+pub mod profile;
+pub mod review;
+pub mod runner;
 
 #[derive(Clone, Copy)]
 pub struct UciMove(pub Move);
 
 impl fmt::Display for UciMove {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}{}", self.0.from(), self.0.to())?;
-        if self.0.ty() == Move::TYPE_PROMOTION {
-            match self.0.promotion_piece() {
-                Move::PROMOTION_QUEEN => {
-                    write!(f, "q")?;
-                }
-                Move::PROMOTION_ROOK => {
-                    write!(f, "r")?;
-                }
-                Move::PROMOTION_BISHOP => {
-                    write!(f, "b")?;
-                }
-                Move::PROMOTION_KNIGHT => {
-                    write!(f, "n")?;
-                }
-                _ => unreachable!(),
-            }
-        }
-        Ok(())
+        write!(f, "{}", self.0.to_uci())
     }
 }
 
@@ -50,50 +43,89 @@ impl UciMove {
     }
 
     pub fn from_name(name: &str, board: &Board) -> Option<Self> {
-        let gen = MoveGenerator::new();
-        let mut buffer = InlineBuffer::<256>::new();
-        gen.gen_moves::<gen_type::All, _, _>(board, &mut buffer);
-
-        let from = Square::from_name(&name[..2])?;
-        let to = Square::from_name(&name[2..4])?;
-
-        let prom = if let Some(prom) = name.chars().nth(4) {
-            if prom == 'q' {
-                Some(Move::PROMOTION_QUEEN)
-            } else if prom == 'r' {
-                Some(Move::PROMOTION_ROOK)
-            } else if prom == 'b' {
-                Some(Move::PROMOTION_BISHOP)
-            } else if prom == 'k' {
-                Some(Move::PROMOTION_KNIGHT)
-            } else {
-                return None;
-            }
-        } else {
-            None
-        };
+        Move::from_uci_on_board(name, board, &MoveGenerator::new()).map(UciMove)
+    }
 
-        for m in buffer.iter() {
-            if m.to() == to && m.from() == from {
-                if m.ty() == Move::TYPE_PROMOTION && Some(m.promotion_piece()) != prom {
-                    continue;
-                }
-                return Some(UciMove(m));
-            }
-        }
-        None
+    /// Find the move with the given name in a list of already generated legal moves.
+    pub fn from_name_in(name: &str, buffer: &InlineBuffer<MAX_MOVES>) -> Option<Self> {
+        Move::from_uci_in(name, buffer.as_slice()).map(UciMove)
     }
 }
 
+/// Positions searched by the bench command.
+const BENCH_POSITIONS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11",
+    "4rrk1/pp1n3p/3q2pQ/2p1pb2/2PP4/2P3N1/P2B2PP/4RRK1 b - - 7 19",
+    "r3r1k1/2p2ppp/p1p1bn2/8/1q2P3/2NPQN2/PPP3PP/R4RK1 b - - 2 15",
+    "6k1/6p1/6Pp/ppp5/3pn2P/1P3K2/1PP2P2/3N4 b - - 0 1",
+];
+
+/// Depth to which the bench positions are searched.
+const BENCH_DEPTH: u32 = 5;
+
+/// Option handled by the protocol itself which prints principal variations in SAN.
+pub const SHOW_SAN_OPTION: &str = "UCI_ShowSAN";
+
+/// Option handled by the protocol itself which pins the search thread to a list of cpus like
+/// `0-3`, or `all` to let it run anywhere.
+pub const CPU_MASK_OPTION: &str = "CpuMask";
+
+/// Option handled by the protocol itself which sets the nice level of the search thread.
+pub const THREAD_PRIORITY_OPTION: &str = "ThreadPriority";
+
 /// An generic implementation of the UCI protocol.
-pub struct Uci {
+///
+/// Responses are written to `W`, which is stdout unless created with [`Uci::with_output`].
+pub struct Uci<W: Write = Stdout> {
     board: Board,
+    /// The position part of the last position command, without the moves.
+    position: String,
+    /// The moves of the last position command which have been applied to the board.
+    moves: Vec<String>,
+    move_gen: MoveGenerator,
     debug_mode: bool,
+    /// Whether principal variations are printed in SAN instead of uci notation.
+    show_san: bool,
     manager: EngineThread,
-    options: HashMap<String, OptionKind>,
-    name: &'static str,
+    /// Amount of times the engine was recovered after a panic, as last reported.
+    restarts: usize,
+    /// The options of the engine and of the protocol, in the order they are shown.
+    options: Vec<(String, OptionKind)>,
+    /// The name of the engine followed by its version, if it has one.
+    name: String,
     author: &'static str,
     running: bool,
+    out: W,
+}
+
+/// Returns the name the engine identifies itself with, `NAME v1.2.3` if it has a version.
+pub fn engine_id<E: Engine<ThreadController>>() -> String {
+    match E::VERSION {
+        Some(version) => format!("{} {}", E::NAME, version),
+        None => E::NAME.to_string(),
+    }
+}
+
+/// Run an engine created by `f` as a uci engine, reading commands from stdin.
+///
+/// The first command line argument can change what is run instead: `--version` prints the name
+/// and version of the engine and `bench` runs the benchmark.
+pub fn run<E, F>(f: F) -> Result<()>
+where
+    E: Engine<ThreadController> + Send,
+    F: FnMut() -> E + Send + 'static,
+{
+    match std::env::args().nth(1).as_deref() {
+        Some("--version") => {
+            println!("{}", engine_id::<E>());
+            Ok(())
+        }
+        Some("bench") => Uci::from_fn(f).bench().map(|_| ()),
+        Some(x) => bail!("unknown argument `{}`", x),
+        None => Uci::from_fn(f).start(),
+    }
 }
 
 pub fn split_once(s: &str) -> (&str, &str) {
@@ -105,214 +137,622 @@ pub fn split_once(s: &str) -> (&str, &str) {
     }
 }
 
+/// Parse the arguments of a `setoption` command into the name of the option it sets and its
+/// value, checked against the options of the engine.
+///
+/// Option names are compared case insensitively and may contain spaces, or even the word
+/// `value`: the name is the part before the first `value` keyword after which the rest of the
+/// command names an existing option. The value is the rest of the command, spaces included.
+/// Spin values are clamped to the range of the option.
+pub fn parse_setoption_args(
+    options: &[(String, OptionKind)],
+    arg: &str,
+) -> Result<(String, OptionValue)> {
+    parse_setoption_command(options, arg).map(|(name, value, _)| (name, value))
+}
+
+/// Like [`parse_setoption_args`], but also returns a warning when a spin value was clamped.
+pub fn parse_setoption_command(
+    options: &[(String, OptionKind)],
+    arg: &str,
+) -> Result<(String, OptionValue, Option<String>)> {
+    let arg = arg
+        .trim()
+        .strip_prefix("name ")
+        .ok_or_else(|| anyhow!("missing option name"))?;
+    let offsets = word_offsets(arg);
+    let words: Vec<&str> = offsets.iter().map(|(_, x)| *x).collect();
+    let find = |name: &str| {
+        options
+            .iter()
+            .find(|(x, _)| x.eq_ignore_ascii_case(name))
+            .map(|(name, kind)| (name.clone(), kind))
+    };
+
+    // Every `value` word could end the name, with the whole command as the name of an option
+    // without a value as the last candidate.
+    let candidates = words
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| **x == "value")
+        .map(|(i, _)| {
+            let value = offsets.get(i + 1).map_or("", |(at, _)| &arg[*at..]);
+            (words[..i].join(" "), Some(value.to_string()))
+        })
+        .chain(Some((words.join(" "), None)));
+    let mut found = None;
+    for (name, value) in candidates {
+        if let Some((name, kind)) = find(&name) {
+            found = Some((name, kind, value));
+            break;
+        }
+    }
+    let (name, kind, value) = found.ok_or_else(|| {
+        let end = words
+            .iter()
+            .position(|x| *x == "value")
+            .unwrap_or(words.len());
+        anyhow!("unknown option `{}`", words[..end].join(" "))
+    })?;
+
+    let mut warning = None;
+    let value = match (kind, value) {
+        (OptionKind::Button, _) => OptionValue::Button,
+        (_, None) => bail!("missing value for option `{}`", name),
+        (OptionKind::Check { .. }, Some(x)) => {
+            if x.eq_ignore_ascii_case("true") {
+                OptionValue::Check(true)
+            } else if x.eq_ignore_ascii_case("false") {
+                OptionValue::Check(false)
+            } else {
+                bail!("invalid value `{}` for check option `{}`", x, name)
+            }
+        }
+        (OptionKind::Spin { min, max, .. }, Some(x)) => {
+            let value: i64 = x
+                .parse()
+                .map_err(|_| anyhow!("invalid value `{}` for spin option `{}`", x, name))?;
+            let min = min.unwrap_or(i32::MIN) as i64;
+            let max = max.unwrap_or(i32::MAX) as i64;
+            let clamped = value.clamp(min, max);
+            if clamped != value {
+                warning = Some(format!(
+                    "value {} of option `{}` is out of range, using {}",
+                    value, name, clamped
+                ));
+            }
+            OptionValue::Spin(clamped as i32)
+        }
+        (OptionKind::Combo { choices, .. }, Some(x)) => OptionValue::Combo(
+            choices
+                .iter()
+                .position(|var| var.eq_ignore_ascii_case(&x))
+                .ok_or_else(|| anyhow!("invalid value `{}` for option `{}`", x, name))?,
+        ),
+        (OptionKind::String { .. }, Some(x)) => OptionValue::String(x),
+    };
+    Ok((name, value, warning))
+}
+
+/// Returns the words of the string with the byte offset at which each starts.
+fn word_offsets(s: &str) -> Vec<(usize, &str)> {
+    let mut res = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices().chain(Some((s.len(), ' '))) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(at), true) => {
+                res.push((at, &s[at..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    res
+}
+
+/// Returns the line declaring the option in response to the `uci` command, like
+/// `option name Hash type spin default 16 min 1 max 4096`.
+///
+/// Spin options without a range are declared with the range of an `i32`, as the protocol
+/// requires both bounds.
+pub fn format_option(name: &str, kind: &OptionKind) -> String {
+    let mut line = format!("option name {} type ", name);
+    match kind {
+        OptionKind::Check { default } => line.push_str(&format!("check default {}", default)),
+        OptionKind::Spin { default, min, max } => line.push_str(&format!(
+            "spin default {} min {} max {}",
+            default,
+            min.unwrap_or(i32::MIN),
+            max.unwrap_or(i32::MAX)
+        )),
+        OptionKind::Combo { default, choices } => {
+            line.push_str(&format!("combo default {}", default));
+            for choice in choices {
+                line.push_str(&format!(" var {}", choice));
+            }
+        }
+        OptionKind::Button => line.push_str("button"),
+        OptionKind::String { default } if default.is_empty() => {
+            line.push_str("string default <empty>")
+        }
+        OptionKind::String { default } => line.push_str(&format!("string default {}", default)),
+    }
+    line
+}
+
+/// Spawn a thread which sends every line read from the reader over the returned channel.
+///
+/// The channel is disconnected when the reader reaches its end.
+pub fn read_lines<R: BufRead + Send + 'static>(mut reader: R) -> Receiver<String> {
+    let (send, recv) = crossbeam_channel::bounded(8);
+    std::thread::spawn(move || loop {
+        let mut buffer = String::new();
+        match reader.read_line(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                if send.send(buffer).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    recv
+}
+
 impl Uci {
     pub fn new<E: Engine<ThreadController> + Send>(engine: E) -> Self {
+        Self::with_output(engine, io::stdout())
+    }
+
+    /// Create a protocol handler for engines created by `f`.
+    ///
+    /// `f` is called again to replace the engine when it panics.
+    pub fn from_fn<E, F>(f: F) -> Self
+    where
+        E: Engine<ThreadController> + Send,
+        F: FnMut() -> E + Send + 'static,
+    {
+        Self::from_fn_with_output(f, io::stdout())
+    }
+}
+
+impl<W: Write> Uci<W> {
+    /// Create a protocol handler which writes its responses to the given output.
+    pub fn with_output<E: Engine<ThreadController> + Send>(engine: E, out: W) -> Self {
+        let options = engine.options();
+        Self::with_manager::<E>(EngineThread::new(engine), options, out)
+    }
+
+    /// Create a protocol handler for engines created by `f` which writes its responses to the
+    /// given output.
+    pub fn from_fn_with_output<E, F>(mut f: F, out: W) -> Self
+    where
+        E: Engine<ThreadController> + Send,
+        F: FnMut() -> E + Send + 'static,
+    {
+        let engine = f();
         let options = engine.options();
+        Self::with_manager::<E>(EngineThread::with_factory(engine, f), options, out)
+    }
+
+    fn with_manager<E: Engine<ThreadController>>(
+        manager: EngineThread,
+        mut options: Vec<(String, OptionKind)>,
+        out: W,
+    ) -> Self {
+        // The options handled by the protocol are shown after those of the engine.
+        options.retain(|(name, _)| {
+            ![SHOW_SAN_OPTION, CPU_MASK_OPTION, THREAD_PRIORITY_OPTION].contains(&name.as_str())
+        });
+        options.push((
+            SHOW_SAN_OPTION.to_string(),
+            OptionKind::Check { default: false },
+        ));
+        options.push((
+            CPU_MASK_OPTION.to_string(),
+            OptionKind::String {
+                default: "all".to_string(),
+            },
+        ));
+        options.push((
+            THREAD_PRIORITY_OPTION.to_string(),
+            OptionKind::Spin {
+                default: 0,
+                min: Some(affinity::NICE_RANGE.0),
+                max: Some(affinity::NICE_RANGE.1),
+            },
+        ));
         Uci {
             board: Board::start_position(EndChain),
+            position: String::new(),
+            moves: Vec::new(),
+            move_gen: MoveGenerator::new(),
             debug_mode: false,
-            manager: EngineThread::new(engine),
+            show_san: false,
+            manager,
+            restarts: 0,
             options,
-            name: E::NAME,
+            name: engine_id::<E>(),
             author: E::AUTHOR,
             running: true,
+            out,
         }
     }
 
+    /// Run the protocol on stdin.
     pub fn start(&mut self) -> Result<()> {
-        let (io_send, io_recv) = crossbeam_channel::bounded(8);
-
-        std::thread::spawn(move || {
-            let stdin = io::stdin();
-            let mut handle = stdin.lock();
-            loop {
-                let mut buffer = String::new();
-                handle.read_line(&mut buffer).unwrap();
-                io_send.send(buffer).ok();
-            }
-        });
+        self.run(read_lines(BufReader::new(io::stdin())))
+    }
 
-        let line = io_recv.recv()?;
+    /// Run the protocol with commands received from the given channel.
+    ///
+    /// Returns when the `quit` command is received or the channel is disconnected.
+    pub fn run(&mut self, input: Receiver<String>) -> Result<()> {
+        let line = match input.recv() {
+            Ok(x) => x,
+            Err(_) => return Ok(()),
+        };
 
         ensure!(
             line.trim() == "uci",
             "Protocol did not start with 'uci' command"
         );
 
-        print!("id name ");
-        println!("{}", self.name);
-        print!("id author ");
-        println!("{}", self.author);
+        self.send(&format!("id name {}", self.name))?;
+        self.send(&format!("id author {}", self.author))?;
+        self.print_options()?;
 
-        println!("uciok");
+        self.send("uciok")?;
         while self.running {
             select! {
-                recv(io_recv) -> line => self.handle_line(line?)?,
+                recv(input) -> line => match line {
+                    Ok(line) => self.handle_line(line)?,
+                    Err(_) => break,
+                },
                 recv(self.manager.recv()) -> resp => self.handle_response(resp?)?,
             }
         }
         Ok(())
     }
 
+    /// Write a single response line and flush it, so lines are never split up.
+    fn send(&mut self, line: &str) -> Result<()> {
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()?;
+        Ok(())
+    }
+
     fn handle_response(&mut self, resp: Response) -> Result<()> {
+        let restarts = self.manager.restarts();
+        if restarts != self.restarts {
+            self.restarts = restarts;
+            if self.debug_mode {
+                self.send("info string engine recovered from a panic")?;
+            }
+        }
+
         match resp {
             Response::Info(info) => match info {
-                Info::BestMove { value, .. } => println!("info score cp {}", value),
+                Info::BestMove { value, .. } => self.send(&format!("info score cp {}", value))?,
                 Info::Round => {}
-                Info::Depth(x) => println!("info depth {}", x),
-                Info::Nodes(x) => println!("info nodes {}", x),
-                Info::NodesPerSec(x) => println!("info nps {}", x),
-                Info::TransHit(x) => println!("info tbhits {}", x),
+                Info::Progress {
+                    nodes,
+                    nps,
+                    time,
+                    hashfull,
+                    tbhits,
+                } => self.send(&format!(
+                    "info nodes {} nps {} time {} hashfull {} tbhits {}",
+                    nodes,
+                    nps as u64,
+                    time.as_millis(),
+                    hashfull,
+                    tbhits
+                ))?,
+                Info::Stats {
+                    depth,
+                    seldepth,
+                    nodes,
+                    nps,
+                    time,
+                    hashfull,
+                    score,
+                    wdl,
+                    pv,
+                } => {
+                    let mut line = format!(
+                        "info depth {} seldepth {} nodes {} nps {} time {} hashfull {} score {}",
+                        depth,
+                        seldepth,
+                        nodes,
+                        nps as u64,
+                        time.as_millis(),
+                        hashfull,
+                        score
+                    );
+                    if let Some(wdl) = wdl {
+                        line.push_str(&format!(" {}", wdl));
+                    }
+                    if !pv.is_empty() {
+                        line.push_str(" pv ");
+                        line.push_str(&self.format_pv(&pv));
+                    }
+                    self.send(&line)?
+                }
+                Info::Depth(x) => self.send(&format!("info depth {}", x))?,
+                Info::Nodes(x) => self.send(&format!("info nodes {}", x))?,
+                Info::NodesPerSec(x) => self.send(&format!("info nps {}", x))?,
+                Info::TransHit(x) => self.send(&format!("info tbhits {}", x))?,
                 Info::Pv(x) => {
-                    print!("info pv ");
-                    x.iter().for_each(|x| {
-                        let m = UciMove(*x);
-                        print!("{} ", m);
-                    });
-                    println!()
+                    let line = format!("info pv {}", self.format_pv(&x));
+                    self.send(&line)?
                 }
+                Info::String(x) => self.send(&format!("info string {}", x))?,
                 Info::Debug(x) => {
-                    print!("debug ");
-                    println!("{}", x);
+                    if self.debug_mode {
+                        self.send(&format!("info string {}", x))?
+                    }
                 }
             },
-            Response::Done(x) => {
-                if let Some(m) = x {
-                    println!("bestmove {}", UciMove(m))
-                }
-            }
+            Response::Done(x) => match x {
+                Some(m) => self.send(&format!("bestmove {}", UciMove(m)))?,
+                None => self.send("bestmove 0000")?,
+            },
         }
         Ok(())
     }
 
+    /// Returns the moves of a principal variation from the current position, separated by
+    /// spaces.
+    ///
+    /// With [`SHOW_SAN_OPTION`] enabled the moves are written in SAN with move numbers counted
+    /// from the current position, like `1.e4 e5 2.Nf3`. Moves after one which is not legal in
+    /// the position it is played in are written in uci notation.
+    pub fn format_pv(&self, pv: &[Move]) -> String {
+        let mut tokens = Vec::new();
+        if !self.show_san {
+            tokens.extend(pv.iter().map(|m| UciMove(*m).to_string()));
+            return tokens.join(" ");
+        }
+
+        let mut board = self.board.clone();
+        let mut buffer = InlineBuffer::<MAX_MOVES>::new();
+        let black_started = (board.state.player == Player::Black) as usize;
+        for (i, m) in pv.iter().enumerate() {
+            buffer.clear();
+            self.move_gen
+                .gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+            if !buffer.as_slice().contains(m) {
+                tokens.extend(pv[i..].iter().map(|m| UciMove(*m).to_string()));
+                break;
+            }
+            let san = m.to_san(&board, &self.move_gen);
+            tokens.push(match board.state.player {
+                Player::White => format!("{}.{}", (i + black_started) / 2 + 1, san),
+                Player::Black if i == 0 => format!("1...{}", san),
+                Player::Black => san,
+            });
+            board.make_move(*m);
+        }
+        tokens.join(" ")
+    }
+
     fn handle_line(&mut self, line: String) -> Result<()> {
         let (command, rest) = split_once(line.trim());
 
         match command {
-            "isready" => {
-                println!("readyok");
-            }
+            "isready" => self.send("readyok")?,
             "debug" => match rest {
                 "on" => self.debug_mode = true,
                 "off" => self.debug_mode = false,
                 _ => bail!("misformed command"),
             },
             "go" => self.parse_go(rest)?,
+            "setoption" => self.parse_setoption(rest)?,
             "stop" => self.manager.stop(),
-            "ucinewgame" => {}
+            "ucinewgame" => {
+                self.clear_position();
+                self.manager.new_game();
+            }
+            "bench" => {
+                self.bench()?;
+                self.running = false;
+            }
             "position" => self.parse_position(rest)?,
             "quit" => {
                 self.running = false;
             }
             "" => {}
-            _ => {
-                println!("invalid command");
+            _ => self.send("invalid command")?,
+        }
+
+        Ok(())
+    }
+
+    pub fn parse_go(&mut self, arg: &str) -> Result<()> {
+        let (_, limits, warnings) = self.parse_go_command(arg)?;
+        for warning in warnings {
+            self.send(&format!("info string {}", warning))?;
+        }
+        // The time to spend on the move is already part of the limits.
+        self.manager.start(None, limits);
+        Ok(())
+    }
+
+    /// Parse the arguments of a go command into the time control of the search and the limits
+    /// the engine searches with.
+    pub fn parse_go_limits(&self, arg: &str) -> Result<(TimeControl, EngineLimit)> {
+        self.parse_go_command(arg)
+            .map(|(time_control, limits, _)| (time_control, limits))
+    }
+
+    /// Parse the arguments of a go command like [`Uci::parse_go_limits`], also returning
+    /// warnings about arguments which were ignored or corrected.
+    ///
+    /// Moves after `searchmoves` which are not legal in the position are ignored. If none of
+    /// them are legal all moves are searched.
+    pub fn parse_go_command(&self, arg: &str) -> Result<(TimeControl, EngineLimit, Vec<String>)> {
+        let request = GoRequest::parse(arg)?;
+        let (time_control, mut warnings) = TimeControl::from_go(&request, self.board.state.player);
+        let mut limits = time_control.limits();
+        if !request.searchmoves.is_empty() {
+            let mut buffer = InlineBuffer::<MAX_MOVES>::new();
+            self.move_gen
+                .gen_moves::<gen_type::All, _, _>(&self.board, &mut buffer);
+            let mut moves = Vec::new();
+            for name in request.searchmoves.iter() {
+                match UciMove::from_name_in(name, &buffer) {
+                    Some(m) => moves.push(m.0),
+                    None => warnings.push(format!(
+                        "ignoring searchmoves move `{}`, it is not legal",
+                        name
+                    )),
+                }
+            }
+            if moves.is_empty() {
+                warnings.push("no legal searchmoves, searching all moves".to_string());
+            } else {
+                limits.search_moves = Some(moves);
             }
         }
+        Ok((time_control, limits, warnings))
+    }
 
+    /// Print the options of the engine in response to the `uci` command.
+    fn print_options(&mut self) -> Result<()> {
+        let lines: Vec<String> = self
+            .options
+            .iter()
+            .map(|(name, kind)| format_option(name, kind))
+            .collect();
+        for line in lines {
+            self.send(&line)?;
+        }
         Ok(())
     }
 
-    pub fn parse_go(&self, arg: &str) -> Result<()> {
-        let mut iter = arg.split_whitespace();
-        let mut time_limit = None;
-        let mut limits = EngineLimit::none();
-        while let Some(cmd) = iter.next() {
-            match cmd {
-                "wtime" => {
-                    let time = iter
-                        .next()
-                        .ok_or_else(|| anyhow!("missing time number"))?
-                        .parse()?;
-                    if self.board.state.player == Player::White {
-                        time_limit = Some(Duration::from_millis(time));
-                    }
+    /// Handle a setoption command.
+    ///
+    /// Nothing is sent back when the option is set, unknown options and invalid values are
+    /// reported with an `info string` line and otherwise ignored. Spin values outside the range
+    /// of the option are clamped, which is reported as well.
+    pub fn parse_setoption(&mut self, arg: &str) -> Result<()> {
+        match parse_setoption_command(&self.options, arg) {
+            Ok((name, value, warning)) => {
+                if let Some(warning) = warning {
+                    self.send(&format!("info string {}", warning))?;
                 }
-                "btime" => {
-                    let time = iter
-                        .next()
-                        .ok_or_else(|| anyhow!("missing time number"))?
-                        .parse()?;
-                    if self.board.state.player == Player::Black {
-                        time_limit = Some(Duration::from_millis(time));
+                match (name.as_str(), value) {
+                    (SHOW_SAN_OPTION, OptionValue::Check(x)) => self.show_san = x,
+                    (CPU_MASK_OPTION | THREAD_PRIORITY_OPTION, _) if !affinity::SUPPORTED => {
+                        self.send(&format!(
+                            "info string option `{}` is not supported on this platform",
+                            name
+                        ))?
                     }
+                    (CPU_MASK_OPTION, OptionValue::String(x)) => {
+                        let cpus = if x.trim().eq_ignore_ascii_case("all") {
+                            // This thread is never pinned so it can run on every cpu.
+                            affinity::thread_affinity()
+                        } else {
+                            affinity::parse_cpu_mask(&x)
+                        };
+                        match cpus {
+                            Ok(cpus) => self.manager.set_affinity(cpus),
+                            Err(e) => self.send(&format!("info string {}", e))?,
+                        }
+                    }
+                    (THREAD_PRIORITY_OPTION, OptionValue::Spin(x)) => self.manager.set_priority(x),
+                    (_, value) => self.manager.set_option(name, value),
                 }
-                "depth" => {
-                    let depth = iter
-                        .next()
-                        .ok_or_else(|| anyhow!("missing depth number"))?
-                        .parse()?;
-                    limits.depth = Some(depth);
-                }
-                "nodes" => {
-                    let nodes = iter
-                        .next()
-                        .ok_or_else(|| anyhow!("missing nodes number"))?
-                        .parse()?;
-                    limits.nodes = Some(nodes);
-                }
-                "movetime" => {
-                    let time = iter
-                        .next()
-                        .ok_or_else(|| anyhow!("missing nodes number"))?
-                        .parse()?;
-                    limits.time = Some(Duration::from_millis(time));
-                }
-                "infinite" => {
-                    self.manager.start(None, EngineLimit::none());
-                    return Ok(());
-                }
-                _ => {}
             }
+            Err(e) => self.send(&format!("info string {}", e))?,
         }
-        self.manager.start(time_limit, limits);
         Ok(())
     }
 
-    pub fn parse_position(&self, arg: &str) -> Result<()> {
-        let mut board;
-
-        let rem = match arg.split_once(" ") {
-            Some(("startpos", rem)) => {
-                board = Board::start_position(EndChain);
-                self.manager.set_board(board.clone());
-                rem
-            }
-            Some(("fen", rem)) => match rem.find("moves") {
-                Some(x) => {
-                    board = Board::from_fen(&rem[..x], EndChain)?;
-                    self.manager.set_board(board.clone());
-                    &rem[x..]
-                }
-                None => {
-                    board = Board::from_fen(rem, EndChain)?;
-                    self.manager.set_board(board.clone());
-                    return Ok(());
-                }
-            },
-            None => {
-                if arg.starts_with("startpos") {
-                    board = Board::start_position(EndChain);
-                    self.manager.set_board(board.clone());
-                    return Ok(());
-                } else {
-                    println!("invalid command");
-                    return Ok(());
+    /// Search a fixed set of positions to a fixed depth and print the total amount of nodes
+    /// searched and the nodes per second.
+    ///
+    /// The node count only depends on the engine so it can be used as a fingerprint of the
+    /// search.
+    pub fn bench(&mut self) -> Result<u64> {
+        let start = Instant::now();
+        let mut total = 0;
+        for fen in BENCH_POSITIONS {
+            self.manager.set_board(Board::from_fen(fen, EndChain)?);
+            self.manager.start(None, EngineLimit::depth(BENCH_DEPTH));
+            let mut nodes = 0;
+            loop {
+                match self.manager.recv().recv()? {
+                    Response::Info(Info::Nodes(x))
+                    | Response::Info(Info::Stats { nodes: x, .. }) => nodes = x as u64,
+                    Response::Info(_) => {}
+                    Response::Done(_) => break,
                 }
             }
-            _ => bail!("invalid position command"),
+            total += nodes;
+        }
+        let nps = total as f64 / start.elapsed().as_secs_f64();
+        self.send(&format!("{} nodes {} nps", total, nps as u64))?;
+        Ok(total)
+    }
+
+    /// Returns the board of the last position command.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Forget the last position command so that the next one is set up from scratch.
+    pub fn clear_position(&mut self) {
+        self.position.clear();
+        self.moves.clear();
+    }
+
+    /// Handle a position command.
+    ///
+    /// GUI's resend the full game with every position command, so if the command extends the
+    /// previous one only the new moves are applied and forwarded to the engine.
+    ///
+    /// An invalid fen is reported with an `info string` line and the previous position is kept.
+    pub fn parse_position(&mut self, arg: &str) -> Result<()> {
+        let (position, moves) = match arg.find("moves") {
+            Some(x) => (arg[..x].trim(), &arg[x + "moves".len()..]),
+            None => (arg.trim(), ""),
         };
 
-        let mut iterator = rem.split_whitespace();
-        ensure!(iterator.next() == Some("moves"));
+        let extends_previous = position == self.position
+            && moves.split_whitespace().count() >= self.moves.len()
+            && self
+                .moves
+                .iter()
+                .zip(moves.split_whitespace())
+                .all(|(a, b)| a == b);
+
+        if !extends_previous {
+            self.board = match split_once(position) {
+                ("startpos", "") => Board::start_position(EndChain),
+                ("fen", fen) => match Board::from_fen(fen, EndChain) {
+                    Ok(x) => x,
+                    // The previous position is kept so the engine never searches a broken one.
+                    Err(e) => return self.send(&format!("info string invalid fen: {}", e)),
+                },
+                _ => bail!("invalid position command"),
+            };
+            self.manager.set_board(self.board.clone());
+            self.position.clear();
+            self.position.push_str(position);
+            self.moves.clear();
+        }
 
-        for m in iterator {
-            let m = UciMove::from_name(m, &board)
-                .ok_or_else(|| anyhow!("invalid move"))?
-                .0;
+        for name in moves.split_whitespace().skip(self.moves.len()) {
+            let m = Move::from_uci_fast(name, &self.board, &self.move_gen)
+                .ok_or_else(|| anyhow!("invalid move"))?;
 
-            board.make_move(m);
+            self.board.make_move(m);
             self.manager.make_move(m);
+            self.moves.push(name.to_string());
         }
         Ok(())
     }
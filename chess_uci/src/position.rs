@@ -0,0 +1,66 @@
+//! The position of a game together with the history needed to detect its draws.
+//!
+//! Engines only receive the board a game started from and the moves played since, see
+//! [`Engine::set_board`](chess_core::engine::Engine::set_board). An engine which keeps its
+//! position in a [`Position`] knows when a game is drawn by repetition or the fifty-move rule
+//! without tracking any history itself.
+
+use chess_core::{
+    board::EndChain,
+    gen::{MoveGenerator, PositionStatus},
+    repetition::{DrawRule, PositionHistory},
+    Board, Move,
+};
+
+/// The current board of a game and the hashes of every position played since it was set.
+#[derive(Clone, Debug)]
+pub struct Position {
+    board: Board,
+    history: PositionHistory,
+}
+
+impl Position {
+    pub fn new(board: Board) -> Self {
+        let mut history = PositionHistory::new();
+        history.push(&board);
+        Position { board, history }
+    }
+
+    pub fn start_position() -> Self {
+        Self::new(Board::start_position(EndChain))
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn history(&self) -> &PositionHistory {
+        &self.history
+    }
+
+    /// Start a new game from the board, forgetting the history of the previous one.
+    pub fn set_board(&mut self, board: Board) {
+        self.history.clear();
+        self.history.push(&board);
+        self.board = board;
+    }
+
+    pub fn make_move(&mut self, m: Move) {
+        self.board.make_move(m);
+        self.history.push(&self.board);
+    }
+
+    /// Returns the rule by which the game is drawn in the current position.
+    ///
+    /// A checkmate on the move which completes the fifty-move rule ends the game instead.
+    pub fn draw_rule(&self, gen: &MoveGenerator) -> Option<DrawRule> {
+        let rule = self.history.draw_rule(&self.board)?;
+        if rule == DrawRule::FiftyMoves {
+            let info = gen.gen_info(&self.board);
+            if gen.position_status(&self.board, &info) == PositionStatus::Checkmate {
+                return None;
+            }
+        }
+        Some(rule)
+    }
+}
@@ -0,0 +1,173 @@
+//! Option profiles for uci engines.
+//!
+//! A profile is read from a sidecar file next to the engine binary, named after the binary with
+//! `.toml` appended, for example `engines/alpha_beta.toml`:
+//!
+//! ```toml
+//! name = "alpha beta, big hash"
+//!
+//! [options]
+//! Hash = 256
+//! contempt = 50
+//! ```
+//!
+//! The options are sent to the engine as `setoption` commands, in the order of the file, after
+//! the `uci` handshake and before `isready`.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// The configuration an engine is started with.
+#[derive(Clone, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EngineProfile {
+    /// Name to show for the engine instead of the name of its binary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Values of uci options, in the order they are set.
+    #[serde(default, with = "ordered_options")]
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineProfile {
+    /// Returns the path of the profile of the engine at the given path.
+    pub fn path_for(engine: &Path) -> PathBuf {
+        let mut name = engine.file_name().unwrap_or_default().to_os_string();
+        name.push(".toml");
+        engine.with_file_name(name)
+    }
+
+    /// Load the profile of the engine at the given path, returns an empty profile if the engine
+    /// doesn't have one.
+    pub fn load(engine: &Path) -> Result<Self> {
+        let path = Self::path_for(engine);
+        if !path.exists() {
+            return Ok(EngineProfile::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read engine profile `{}`", path.display()))?;
+        text.parse()
+            .with_context(|| format!("failed to parse engine profile `{}`", path.display()))
+    }
+
+    /// Returns the `setoption` commands for the options of the profile which are in the options
+    /// the engine advertised, together with the names of the options which are not.
+    ///
+    /// Option names are compared case insensitively, as the protocol requires.
+    pub fn setoption_commands(&self, advertised: &[String]) -> (Vec<String>, Vec<String>) {
+        let mut commands = Vec::new();
+        let mut unknown = Vec::new();
+        for (name, value) in self.options.iter() {
+            if advertised.iter().any(|x| x.eq_ignore_ascii_case(name)) {
+                commands.push(format!("setoption name {} value {}", name, value));
+            } else {
+                unknown.push(name.clone());
+            }
+        }
+        (commands, unknown)
+    }
+}
+
+impl FromStr for EngineProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+/// Returns the name of the option advertised by an `option` line from an engine.
+pub fn advertised_option(line: &str) -> Option<String> {
+    let mut parts = line.split_whitespace();
+    if parts.next() != Some("option") || parts.next() != Some("name") {
+        return None;
+    }
+    let name: Vec<_> = parts.take_while(|x| *x != "type").collect();
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.join(" "))
+}
+
+/// (De)serialize options as a map while keeping the order of its entries.
+mod ordered_options {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(options: &[(String, String)], s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(options.len()))?;
+        for (name, value) in options {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(String, String)>, D::Error> {
+        d.deserialize_map(OptionsVisitor)
+    }
+
+    struct OptionsVisitor;
+
+    impl<'de> Visitor<'de> for OptionsVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a table of option values")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut res = Vec::new();
+            while let Some((name, OptionValue(value))) = map.next_entry()? {
+                res.push((name, value));
+            }
+            Ok(res)
+        }
+    }
+
+    /// An option value of any scalar type, converted to the text sent to the engine.
+    struct OptionValue(String);
+
+    impl<'de> Deserialize<'de> for OptionValue {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            d.deserialize_any(ValueVisitor)
+        }
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = OptionValue;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a string, number or boolean")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<OptionValue, E> {
+            Ok(OptionValue(v.to_string()))
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<OptionValue, E> {
+            Ok(OptionValue(v.to_string()))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<OptionValue, E> {
+            Ok(OptionValue(v.to_string()))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<OptionValue, E> {
+            Ok(OptionValue(v.to_string()))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<OptionValue, E> {
+            Ok(OptionValue(v.to_string()))
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Reviews of played games, measuring how much worse every move was than the move the engine
+//! prefers.
+//!
+//! Every position of the game is searched once. The score of a played move is the negated
+//! score of the position it leads to, its loss is how much lower that is than the score of the
+//! position before it. Scores are capped to [`MAX_SCORE`] so mates count as a large but finite
+//! advantage: finding a slower mate costs nothing, missing a mate or walking into one costs up
+//! to twice the cap.
+
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::{Board, EndChain},
+    engine::{Engine, EngineLimit, NoControl, OptionValue},
+    gen::{MoveGenerator, PositionStatus},
+    Move, Player,
+};
+use serde::{Serialize, Serializer};
+
+use crate::split_once;
+
+/// Scores are capped to this many centipawns, for either side.
+pub const MAX_SCORE: i32 = 1000;
+
+/// A game to review, a start position and the moves played from it.
+#[derive(Clone, Debug)]
+pub struct Game {
+    pub start: Board,
+    pub moves: Vec<Move>,
+}
+
+impl Game {
+    /// Parse a game written as the argument of a uci `position` command, like
+    /// `startpos moves e2e4 e7e5` or `fen <fen> moves e2e4`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (position, moves) = match text.find("moves") {
+            Some(x) => (text[..x].trim(), &text[x + "moves".len()..]),
+            None => (text, ""),
+        };
+        let start = match split_once(position) {
+            ("startpos", "") => Board::start_position(EndChain),
+            ("fen", fen) => Board::from_fen(fen, EndChain)?,
+            _ => bail!("game should start with `startpos` or `fen`"),
+        };
+
+        let gen = MoveGenerator::new();
+        let mut board = start.clone();
+        let mut res = Vec::new();
+        for name in moves.split_whitespace() {
+            let m = Move::from_uci_on_board(name, &board, &gen)
+                .ok_or_else(|| anyhow!("illegal move `{}` in game", name))?;
+            board.make_move(m);
+            res.push(m);
+        }
+        Ok(Game { start, moves: res })
+    }
+}
+
+/// How bad a move is, by its loss in centipawns.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Judgement {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    pub const INACCURACY: i32 = 50;
+    pub const MISTAKE: i32 = 100;
+    pub const BLUNDER: i32 = 300;
+
+    pub fn from_loss(loss: i32) -> Option<Self> {
+        if loss >= Self::BLUNDER {
+            Some(Judgement::Blunder)
+        } else if loss >= Self::MISTAKE {
+            Some(Judgement::Mistake)
+        } else if loss >= Self::INACCURACY {
+            Some(Judgement::Inaccuracy)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Judgement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Judgement::Inaccuracy => write!(f, "inaccuracy"),
+            Judgement::Mistake => write!(f, "mistake"),
+            Judgement::Blunder => write!(f, "blunder"),
+        }
+    }
+}
+
+/// The review of a single move.
+#[derive(Clone, Debug, Serialize)]
+pub struct MoveReview {
+    /// Number of the move as written in a game score, counted from 1 at the start position.
+    pub number: u32,
+    #[serde(serialize_with = "serialize_player")]
+    pub player: Player,
+    #[serde(serialize_with = "serialize_move")]
+    pub played: Move,
+    /// The move the engine prefers, `None` if the search didn't return one.
+    #[serde(serialize_with = "serialize_best")]
+    pub best: Option<Move>,
+    /// Capped score of the position after the played move, for the player who made it.
+    pub played_score: i32,
+    /// Capped score of the position before the move, for the player who made it.
+    pub best_score: i32,
+    /// How many centipawns the played move is worse than the best move.
+    pub loss: i32,
+    pub judgement: Option<Judgement>,
+}
+
+/// The review of a game.
+#[derive(Clone, Debug, Serialize)]
+pub struct GameReview {
+    pub moves: Vec<MoveReview>,
+    /// Average loss of the moves of each player, zero for a player without moves.
+    pub white_average_loss: f32,
+    pub black_average_loss: f32,
+}
+
+impl GameReview {
+    /// Review the game by searching every position with the alpha beta engine to the given
+    /// limit.
+    pub fn review(game: &Game, limit: EngineLimit) -> Self {
+        let mut engine = AlphaBeta::<NoControl>::new();
+        // A draw is worth the same to both players in a review.
+        engine.set_option("contempt".to_string(), OptionValue::Spin(0));
+        let gen = MoveGenerator::new();
+
+        // The capped score and best move of every position, including the last.
+        let mut board = game.start.clone();
+        let mut positions = Vec::with_capacity(game.moves.len() + 1);
+        for m in game.moves.iter().copied().map(Some).chain(Some(None)) {
+            let info = gen.gen_info(&board);
+            let position = match gen.position_status(&board, &info) {
+                PositionStatus::Checkmate => (-MAX_SCORE, None),
+                PositionStatus::Stalemate => (0, None),
+                _ => {
+                    let analysis = engine.analyze(&board, limit.clone());
+                    let score = analysis.score.clamp(-MAX_SCORE, MAX_SCORE);
+                    (score, analysis.best_move)
+                }
+            };
+            positions.push(position);
+            if let Some(m) = m {
+                board.make_move(m);
+            }
+        }
+
+        let mut moves = Vec::with_capacity(game.moves.len());
+        let mut player = game.start.state.player;
+        // The board doesn't keep the move number, moves are counted from the start position.
+        let mut number = 1;
+        for (i, played) in game.moves.iter().copied().enumerate() {
+            let (best_score, best) = positions[i];
+            let played_score = if best == Some(played) {
+                best_score
+            } else {
+                -positions[i + 1].0
+            };
+            let loss = (best_score - played_score).max(0);
+            moves.push(MoveReview {
+                number,
+                player,
+                played,
+                best,
+                played_score,
+                best_score,
+                loss,
+                judgement: Judgement::from_loss(loss),
+            });
+            if player == Player::Black {
+                number += 1;
+            }
+            player = player.flip();
+        }
+
+        let average = |player: Player| {
+            let losses: Vec<_> = moves
+                .iter()
+                .filter(|x| x.player == player)
+                .map(|x| x.loss)
+                .collect();
+            if losses.is_empty() {
+                0.0
+            } else {
+                losses.iter().sum::<i32>() as f32 / losses.len() as f32
+            }
+        };
+        GameReview {
+            white_average_loss: average(Player::White),
+            black_average_loss: average(Player::Black),
+            moves,
+        }
+    }
+}
+
+/// Formats the review as a table with a row per move.
+impl fmt::Display for GameReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} {:<6} {:<6} {:>7} {:<6} {:>7} {:>5}",
+            "move", "player", "played", "score", "best", "score", "loss"
+        )?;
+        for m in self.moves.iter() {
+            let best = m.best.map(|x| x.to_uci()).unwrap_or_default();
+            write!(
+                f,
+                "{:>5} {:<6} {:<6} {:>7} {:<6} {:>7} {:>5}",
+                m.number,
+                player_name(m.player),
+                m.played.to_uci(),
+                m.played_score,
+                best,
+                m.best_score,
+                m.loss
+            )?;
+            if let Some(judgement) = m.judgement {
+                write!(f, " {}", judgement)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "average loss white: {:.1}", self.white_average_loss)?;
+        write!(f, "average loss black: {:.1}", self.black_average_loss)
+    }
+}
+
+fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::White => "white",
+        Player::Black => "black",
+    }
+}
+
+fn serialize_player<S: Serializer>(player: &Player, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(player_name(*player))
+}
+
+fn serialize_move<S: Serializer>(m: &Move, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&m.to_uci())
+}
+
+fn serialize_best<S: Serializer>(m: &Option<Move>, s: S) -> Result<S::Ok, S::Error> {
+    match m {
+        Some(m) => s.serialize_some(&m.to_uci()),
+        None => s.serialize_none(),
+    }
+}
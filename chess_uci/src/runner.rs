@@ -0,0 +1,570 @@
+//! Playing full games between two players.
+//!
+//! Players can either be engines running in the same process or uci engines running as a
+//! separate process.
+//!
+//! [`GameRunner`] asks the player to move for a move and waits for it, which is how the
+//! tournament and the tests play their games. The ui doesn't play through it: its players move
+//! from input events while the window keeps drawing, and moves can be taken back. It shares the
+//! rules with the runner instead, ending its games with [`game_outcome`] and reporting them as a
+//! [`GameOutcome`] and [`Termination`].
+
+use std::{
+    fmt,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{self, Child, ChildStdin, ChildStdout, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chess_core::{
+    board::{Board, EndChain, MoveChain},
+    engine::{Engine, EngineLimit, NoControl},
+    gen::{gen_type, InlineBuffer, MoveGenerator, PositionStatus, MAX_MOVES},
+    repetition::{DrawRule, PositionHistory},
+    Move, Player as Color,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    profile::{advertised_option, EngineProfile},
+    UciMove,
+};
+
+/// The time each player has for a game.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+/// The clocks of both players at the moment a player is asked for a move.
+#[derive(Clone, Copy, Debug)]
+pub struct Clocks {
+    pub white: Duration,
+    pub black: Duration,
+    pub white_increment: Duration,
+    pub black_increment: Duration,
+}
+
+/// A handicap for one of the players in a game.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Handicap {
+    /// Multiplier applied to the time control of the player.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_scale: Option<f32>,
+    /// Search every move to this depth instead of playing on the clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_depth: Option<u32>,
+    /// Search this many nodes for every move instead of playing on the clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_nodes: Option<u64>,
+}
+
+impl Handicap {
+    /// Returns whether the handicap changes nothing.
+    pub fn is_none(&self) -> bool {
+        *self == Handicap::default()
+    }
+
+    /// Returns the time control of a player with this handicap.
+    pub fn scale(&self, time: TimeControl) -> TimeControl {
+        let scale = self.time_scale.unwrap_or(1.0);
+        TimeControl {
+            initial: time.initial.mul_f32(scale),
+            increment: time.increment.mul_f32(scale),
+        }
+    }
+
+    /// Returns the fixed search limit, if the player doesn't play on the clock.
+    ///
+    /// A fixed depth takes precedence over a fixed amount of nodes.
+    pub fn fixed_limit(&self) -> Option<SearchLimit> {
+        self.fixed_depth
+            .map(SearchLimit::Depth)
+            .or_else(|| self.fixed_nodes.map(SearchLimit::Nodes))
+    }
+}
+
+impl fmt::Display for Handicap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(x) = self.time_scale {
+            parts.push(format!("time x{}", x));
+        }
+        if let Some(x) = self.fixed_depth {
+            parts.push(format!("depth {}", x));
+        }
+        if let Some(x) = self.fixed_nodes {
+            parts.push(format!("nodes {}", x));
+        }
+        if parts.is_empty() {
+            return write!(f, "none");
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// How a player is asked to search for a move.
+#[derive(Clone, Copy, Debug)]
+pub enum SearchLimit {
+    /// Play on the clock.
+    Clock(Clocks),
+    Depth(u32),
+    Nodes(u64),
+}
+
+impl SearchLimit {
+    /// Returns the arguments of the uci `go` command.
+    pub fn go_command(&self) -> String {
+        match self {
+            SearchLimit::Clock(clocks) => format!(
+                "go wtime {} btime {} winc {} binc {}",
+                clocks.white.as_millis(),
+                clocks.black.as_millis(),
+                clocks.white_increment.as_millis(),
+                clocks.black_increment.as_millis(),
+            ),
+            SearchLimit::Depth(x) => format!("go depth {}", x),
+            SearchLimit::Nodes(x) => format!("go nodes {}", x),
+        }
+    }
+}
+
+/// Something which can play moves in a game.
+pub trait Player {
+    /// Returns the uci name of the move to play in the position reached by playing the given
+    /// moves from the start position.
+    ///
+    /// An error means the player has crashed.
+    fn play(&mut self, start: &Board, moves: &[Move], limit: SearchLimit) -> Result<String>;
+}
+
+/// A player running an engine in the current process.
+pub struct EnginePlayer<E>(pub E);
+
+impl<E: Engine<NoControl>> Player for EnginePlayer<E> {
+    fn play(&mut self, start: &Board, moves: &[Move], limit: SearchLimit) -> Result<String> {
+        self.0.set_board(start.clone());
+        let mut board = start.clone();
+        for m in moves {
+            self.0.make_move(*m);
+            board.make_move(*m);
+        }
+        let (time_left, limit) = match limit {
+            SearchLimit::Clock(clocks) => match board.state.player {
+                Color::White => (Some(clocks.white), EngineLimit::none()),
+                Color::Black => (Some(clocks.black), EngineLimit::none()),
+            },
+            SearchLimit::Depth(x) => (None, EngineLimit::depth(x)),
+            SearchLimit::Nodes(x) => (None, EngineLimit::nodes(x)),
+        };
+        Ok(self
+            .0
+            .go(NoControl, time_left, limit)
+            .map(|m| UciMove(m).to_string())
+            .unwrap_or_else(|| "0000".to_string()))
+    }
+}
+
+/// A player running a uci engine as a separate process.
+pub struct UciPlayer {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    child: Child,
+    /// The name the engine identified itself with in the handshake.
+    id: Option<String>,
+}
+
+impl UciPlayer {
+    /// Start the engine at the given path with the options of its profile, if it has one.
+    pub fn from_path(p: &Path) -> Result<Self> {
+        let profile = EngineProfile::load(p)?;
+        Self::with_profile(p, &profile)
+    }
+
+    /// Start the engine at the given path with the options of the given profile.
+    ///
+    /// Options the engine doesn't advertise are skipped with a warning.
+    pub fn with_profile(path: &Path, profile: &EngineProfile) -> Result<Self> {
+        let mut child = process::Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start engine `{}`", path.display()))?;
+
+        let mut p = UciPlayer {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            id: None,
+        };
+
+        writeln!(p.stdin, "uci")?;
+        let mut advertised = Vec::new();
+        let mut id = None;
+        p.read_until("uciok", |line| {
+            if let Some(name) = line.strip_prefix("id name ") {
+                id = Some(name.trim().to_string());
+            }
+            advertised.extend(advertised_option(line))
+        })?;
+        p.id = id;
+
+        let (commands, unknown) = profile.setoption_commands(&advertised);
+        for name in unknown {
+            eprintln!(
+                "warning: engine `{}` has no option `{}`, ignoring it",
+                path.display(),
+                name
+            );
+        }
+        for command in commands {
+            writeln!(p.stdin, "{}", command)?;
+        }
+
+        writeln!(p.stdin, "isready")?;
+        p.read_until("readyok", |_| {})?;
+        Ok(p)
+    }
+
+    /// Returns the name the engine identified itself with, including its version if it reports
+    /// one.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Read lines from the engine until one consisting of the given token.
+    fn read_until(&mut self, token: &str, mut f: impl FnMut(&str)) -> Result<()> {
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if self.stdout.read_line(&mut buffer)? == 0 {
+                bail!("engine closed its output before `{}`", token);
+            }
+            if buffer.trim() == token {
+                return Ok(());
+            }
+            f(buffer.trim());
+        }
+    }
+}
+
+impl Player for UciPlayer {
+    fn play(&mut self, start: &Board, moves: &[Move], limit: SearchLimit) -> Result<String> {
+        write!(self.stdin, "position fen {}", start.to_fen())?;
+        if !moves.is_empty() {
+            write!(self.stdin, " moves")?;
+            for m in moves.iter() {
+                write!(self.stdin, " {}", UciMove(*m))?;
+            }
+        }
+        writeln!(self.stdin)?;
+        writeln!(self.stdin, "{}", limit.go_command())?;
+
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if self.stdout.read_line(&mut buffer)? == 0 {
+                bail!("engine closed its output");
+            }
+            if buffer.starts_with("bestmove") {
+                return buffer
+                    .split_whitespace()
+                    .nth(1)
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("Move missing after `bestmove` command"));
+            }
+        }
+    }
+}
+
+impl Drop for UciPlayer {
+    fn drop(&mut self) {
+        writeln!(self.stdin, "quit").ok();
+        self.child.wait().ok();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameOutcome {
+    WhiteWon,
+    BlackWon,
+    Drawn,
+}
+
+impl GameOutcome {
+    /// The outcome of a game the given player lost.
+    pub fn lost_by(player: Color) -> Self {
+        match player {
+            Color::White => GameOutcome::BlackWon,
+            Color::Black => GameOutcome::WhiteWon,
+        }
+    }
+}
+
+/// Why a game ended.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    InsufficientMaterial,
+    /// A player returned a move which is not legal in the position.
+    IllegalMove,
+    /// A player failed to return a move at all.
+    Crash,
+    /// A player ran out of time.
+    TimeForfeit,
+}
+
+/// Returns how the game ended if it is over in the position on the board, with the positions
+/// played before in the history.
+pub fn game_outcome<C: MoveChain>(
+    move_gen: &MoveGenerator,
+    board: &Board<C>,
+    history: &PositionHistory,
+) -> Option<(GameOutcome, Termination)> {
+    let info = move_gen.gen_info(board);
+    match move_gen.position_status(board, &info) {
+        PositionStatus::Checkmate => {
+            return Some((
+                GameOutcome::lost_by(board.state.player),
+                Termination::Checkmate,
+            ))
+        }
+        PositionStatus::Stalemate => return Some((GameOutcome::Drawn, Termination::Stalemate)),
+        _ => {}
+    }
+    let termination = match history.draw_rule(board) {
+        Some(DrawRule::Repetition) => Termination::Repetition,
+        Some(DrawRule::FiftyMoves) => Termination::FiftyMoves,
+        None if move_gen.drawn(board, &info) => Termination::InsufficientMaterial,
+        None => return None,
+    };
+    Some((GameOutcome::Drawn, termination))
+}
+
+impl GameOutcome {
+    /// Returns the result as written in PGN.
+    pub fn pgn_result(self) -> &'static str {
+        match self {
+            GameOutcome::WhiteWon => "1-0",
+            GameOutcome::BlackWon => "0-1",
+            GameOutcome::Drawn => "1/2-1/2",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PlayedGame {
+    pub start: Board,
+    pub moves: Vec<Move>,
+    pub result: GameOutcome,
+    pub termination_reason: Termination,
+    /// The time control of the game, without handicaps.
+    pub time: TimeControl,
+    /// Time left on the clocks of white and black, after each move.
+    pub clock_history: Vec<(Duration, Duration)>,
+    /// Time between asking a player for a move and receiving it, for each move.
+    pub move_times: Vec<Duration>,
+}
+
+impl PlayedGame {
+    /// Returns the game in PGN with the given names of the players, with the clock of the
+    /// player after every move in a `[%clk h:mm:ss]` comment.
+    pub fn to_pgn(&self, white: &str, black: &str) -> String {
+        let result = self.result.pgn_result();
+        let mut res = String::new();
+        for (tag, value) in [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", white),
+            ("Black", black),
+            ("Result", result),
+        ] {
+            res.push_str(&format!("[{} \"{}\"]\n", tag, value));
+        }
+        let fen = self.start.to_fen();
+        if fen != Board::start_position(EndChain).to_fen() {
+            res.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen));
+        }
+        res.push_str(&format!(
+            "[TimeControl \"{}+{}\"]\n\n",
+            self.time.initial.as_secs_f32(),
+            self.time.increment.as_secs_f32()
+        ));
+
+        let gen = MoveGenerator::new();
+        let mut board = self.start.clone();
+        let mut tokens = Vec::new();
+        let black_started = (self.start.state.player == Color::Black) as usize;
+        for (i, m) in self.moves.iter().enumerate() {
+            let player = board.state.player;
+            if i == 0 && player == Color::Black {
+                tokens.push("1...".to_string());
+            } else if player == Color::White {
+                tokens.push(format!("{}.", (i + black_started) / 2 + 1));
+            }
+            tokens.push(m.to_san(&board, &gen));
+            if let Some((white, black)) = self.clock_history.get(i) {
+                let left = match player {
+                    Color::White => *white,
+                    Color::Black => *black,
+                };
+                tokens.push(format!("{{[%clk {}]}}", format_clock(left)));
+            }
+            board.make_move(*m);
+        }
+        tokens.push(result.to_string());
+
+        // Export format keeps lines below 80 characters.
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() + 1 > 79 {
+                res.push_str(&line);
+                res.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        res.push_str(&line);
+        res.push('\n');
+        res
+    }
+}
+
+/// Format the time on a clock as hours, minutes and seconds.
+pub fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Plays a game between two players from a start position.
+pub struct GameRunner {
+    white: Box<dyn Player>,
+    black: Box<dyn Player>,
+    start: Board,
+    time: TimeControl,
+    white_handicap: Handicap,
+    black_handicap: Handicap,
+    move_gen: MoveGenerator,
+}
+
+impl GameRunner {
+    pub fn new(
+        white: Box<dyn Player>,
+        black: Box<dyn Player>,
+        start: Board,
+        time: TimeControl,
+    ) -> Self {
+        GameRunner {
+            white,
+            black,
+            start,
+            time,
+            white_handicap: Handicap::default(),
+            black_handicap: Handicap::default(),
+            move_gen: MoveGenerator::new(),
+        }
+    }
+
+    /// Play the given player with a handicap.
+    pub fn with_handicap(mut self, player: Color, handicap: Handicap) -> Self {
+        match player {
+            Color::White => self.white_handicap = handicap,
+            Color::Black => self.black_handicap = handicap,
+        }
+        self
+    }
+
+    /// Play the game until it is decided.
+    pub fn play(&mut self) -> PlayedGame {
+        let mut board = self.start.clone();
+        let mut history = PositionHistory::new();
+        history.push(&board);
+
+        let mut moves = Vec::new();
+        let mut clock_history = Vec::new();
+        let mut move_times = Vec::new();
+        let white_control = self.white_handicap.scale(self.time);
+        let black_control = self.black_handicap.scale(self.time);
+        let mut white_time = white_control.initial;
+        let mut black_time = black_control.initial;
+
+        let (result, termination_reason) = loop {
+            if let Some(x) = game_outcome(&self.move_gen, &board, &history) {
+                break x;
+            }
+            let player = board.state.player;
+
+            let clocks = Clocks {
+                white: white_time,
+                black: black_time,
+                white_increment: white_control.increment,
+                black_increment: black_control.increment,
+            };
+            let (engine, time_left, control, handicap) = match player {
+                Color::White => (
+                    &mut self.white,
+                    &mut white_time,
+                    white_control,
+                    self.white_handicap,
+                ),
+                Color::Black => (
+                    &mut self.black,
+                    &mut black_time,
+                    black_control,
+                    self.black_handicap,
+                ),
+            };
+            // A player searching to a fixed limit doesn't play on the clock.
+            let limit = handicap.fixed_limit().unwrap_or(SearchLimit::Clock(clocks));
+
+            let start = Instant::now();
+            let name = match engine.play(&self.start, &moves, limit) {
+                Ok(x) => x,
+                Err(_) => break (GameOutcome::lost_by(player), Termination::Crash),
+            };
+            let elapsed = start.elapsed();
+
+            if let SearchLimit::Clock(_) = limit {
+                if *time_left < elapsed {
+                    break (GameOutcome::lost_by(player), Termination::TimeForfeit);
+                }
+                *time_left = *time_left - elapsed + control.increment;
+            }
+
+            let mut buffer = InlineBuffer::<MAX_MOVES>::new();
+            self.move_gen
+                .gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+            let m = match UciMove::from_name_in(&name, &buffer) {
+                Some(x) => x.0,
+                None => break (GameOutcome::lost_by(player), Termination::IllegalMove),
+            };
+            move_times.push(elapsed);
+            clock_history.push((white_time, black_time));
+            board.make_move(m);
+            moves.push(m);
+            history.push(&board);
+        };
+
+        PlayedGame {
+            start: self.start.clone(),
+            moves,
+            result,
+            termination_reason,
+            time: self.time,
+            clock_history,
+            move_times,
+        }
+    }
+}
@@ -0,0 +1,109 @@
+#![cfg(target_os = "linux")]
+
+use chess_core::{
+    affinity,
+    engine::{Engine, EngineControl, EngineLimit},
+    Board, Move,
+};
+use chess_uci::Uci;
+use std::{
+    fs,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The cpus the search thread could run on and its nice level, as read from `/proc`.
+type ThreadState = Arc<Mutex<Option<(String, i32)>>>;
+
+/// Engine which records the state of the thread it searches on.
+struct Inspector(ThreadState);
+
+impl<C: EngineControl> Engine<C> for Inspector {
+    const NAME: &'static str = "Inspector";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        let status = fs::read_to_string("/proc/thread-self/status").unwrap();
+        let cpus = status
+            .lines()
+            .find_map(|x| x.strip_prefix("Cpus_allowed_list:"))
+            .unwrap()
+            .trim()
+            .to_string();
+        // The name of the thread in parentheses can contain spaces, the nice level is the 17th
+        // field after it.
+        let stat = fs::read_to_string("/proc/thread-self/stat").unwrap();
+        let after_name = &stat[stat.rfind(')').unwrap() + 1..];
+        let nice = after_name
+            .split_whitespace()
+            .nth(16)
+            .unwrap()
+            .parse()
+            .unwrap();
+        *self.0.lock().unwrap() = Some((cpus, nice));
+        None
+    }
+
+    fn set_board(&mut self, _: Board) {}
+
+    fn make_move(&mut self, _: Move) {}
+}
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Search and wait until the engine recorded the state of its thread.
+fn search(uci: &mut Uci<SharedOutput>, state: &ThreadState) -> (String, i32) {
+    state.lock().unwrap().take();
+    uci.parse_go("depth 1").unwrap();
+    let start = Instant::now();
+    loop {
+        if let Some(x) = state.lock().unwrap().take() {
+            return x;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5), "no search");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn search_thread_is_pinned() {
+    let state = ThreadState::default();
+    let output = SharedOutput::default();
+    let mut uci = Uci::with_output(Inspector(state.clone()), output.clone());
+
+    let cpus = affinity::thread_affinity().unwrap();
+    let (all, nice) = search(&mut uci, &state);
+    assert_eq!(all, affinity::format_cpu_mask(&cpus));
+    assert_eq!(nice, 0);
+
+    let pinned = affinity::format_cpu_mask(&cpus[..1]);
+    uci.parse_setoption(&format!("name CpuMask value {}", pinned))
+        .unwrap();
+    uci.parse_setoption("name ThreadPriority value 5").unwrap();
+    assert_eq!(search(&mut uci, &state), (pinned, 5));
+    // Only the search thread is restricted.
+    assert_eq!(affinity::thread_affinity().unwrap(), cpus);
+
+    uci.parse_setoption("name CpuMask value all").unwrap();
+    assert_eq!(search(&mut uci, &state).0, all);
+
+    uci.parse_setoption("name CpuMask value 3-1").unwrap();
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(
+        output,
+        "info string invalid cpu range `3-1` in cpu mask `3-1`\n"
+    );
+}
@@ -0,0 +1,20 @@
+use std::process::Command;
+
+fn bench_output() -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_alpha_beta"))
+        .arg("bench")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+    let line = output.lines().last().unwrap().to_string();
+    line.split(" nodes").next().unwrap().to_string()
+}
+
+#[test]
+fn bench_node_count_is_stable() {
+    let first = bench_output();
+    let second = bench_output();
+    assert!(first.parse::<u64>().unwrap() > 0);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,45 @@
+use chess_core::{engine::Score, Player};
+use chess_uci::info::EvalHistory;
+
+#[test]
+fn scores_are_clamped() {
+    let mut history = EvalHistory::new();
+    history.push(1, Player::White, Score::Cp(35));
+    history.push(2, Player::Black, Score::Cp(-5000));
+    history.push(3, Player::White, Score::Mate(2));
+    history.push(4, Player::Black, Score::Mate(-1));
+
+    let scores: Vec<_> = history.samples().iter().map(|x| x.score).collect();
+    assert_eq!(
+        scores,
+        [
+            35,
+            -EvalHistory::LIMIT,
+            EvalHistory::LIMIT,
+            -EvalHistory::LIMIT
+        ]
+    );
+    let numbers: Vec<_> = history.samples().iter().map(|x| x.move_number()).collect();
+    assert_eq!(numbers, [1, 1, 2, 2]);
+}
+
+#[test]
+fn chart_points() {
+    let mut history = EvalHistory::new();
+    history.push(10, Player::White, Score::Cp(0));
+    assert_eq!(history.point(&history.samples()[0]), (0.5, 0.5));
+
+    history.push(11, Player::Black, Score::Mate(3));
+    history.push(12, Player::White, Score::Mate(-3));
+    let points: Vec<_> = history.samples().iter().map(|x| history.point(x)).collect();
+    assert_eq!(points, [(0.0, 0.5), (0.5, 0.0), (1.0, 1.0)]);
+
+    assert_eq!(history.nearest(0.4).unwrap().ply, 11);
+    assert_eq!(history.nearest(0.9).unwrap().ply, 12);
+
+    history.truncate(11);
+    assert_eq!(history.samples().len(), 2);
+    assert_eq!(history.nearest(1.0).unwrap().ply, 11);
+    history.clear();
+    assert!(history.nearest(0.5).is_none());
+}
@@ -0,0 +1,160 @@
+use chess_core::Player;
+use chess_uci::go::{GoRequest, TimeControl};
+use std::time::Duration;
+
+fn time_control(arg: &str, side: Player) -> (TimeControl, Vec<String>) {
+    TimeControl::from_go(&GoRequest::parse(arg).unwrap(), side)
+}
+
+fn ms(x: u64) -> Duration {
+    Duration::from_millis(x)
+}
+
+#[test]
+fn parse_request() {
+    let go =
+        GoRequest::parse("wtime 1000 btime -50 winc 10 movestogo 5 searchmoves e2e4 d2d4 ponder")
+            .unwrap();
+    assert_eq!(go.wtime, Some(1000));
+    assert_eq!(go.btime, Some(-50));
+    assert_eq!(go.winc, Some(10));
+    assert_eq!(go.binc, None);
+    assert_eq!(go.movestogo, Some(5));
+    assert_eq!(go.searchmoves, ["e2e4", "d2d4"]);
+    assert!(go.ponder);
+    assert!(!go.infinite);
+
+    assert!(GoRequest::parse("wtime").is_err());
+    assert!(GoRequest::parse("depth ten").is_err());
+    assert!(GoRequest::parse("movestogo -1").is_err());
+}
+
+#[test]
+fn clock_of_side_to_move() {
+    let arg = "wtime 60000 btime 30000 winc 1000 binc 500";
+    let (white, warnings) = time_control(arg, Player::White);
+    assert!(warnings.is_empty());
+    assert_eq!(
+        white,
+        TimeControl::Clock {
+            time: ms(60000),
+            increment: ms(1000),
+            moves_to_go: None,
+        }
+    );
+    let (black, _) = time_control(arg, Player::Black);
+    assert_eq!(
+        black,
+        TimeControl::Clock {
+            time: ms(30000),
+            increment: ms(500),
+            moves_to_go: None,
+        }
+    );
+    assert_eq!(black.move_time(), Some(ms(30000 / 30 + 375)));
+
+    let (control, _) = time_control("btime 10000 movestogo 4", Player::Black);
+    assert_eq!(control.move_time(), Some(ms(2500)));
+    // Never more than half of the time left, however large the increment.
+    let (control, _) = time_control("wtime 1000 winc 5000", Player::White);
+    assert_eq!(control.move_time(), Some(ms(500)));
+}
+
+#[test]
+fn wtime_without_btime() {
+    let (control, warnings) = time_control("wtime 60000 winc 1000", Player::Black);
+    assert_eq!(control, TimeControl::Infinite);
+    assert_eq!(warnings.len(), 1);
+    assert!(control.limits().time.is_none());
+
+    // Other limits still apply.
+    let (control, _) = time_control("wtime 60000 depth 5", Player::Black);
+    assert_eq!(control, TimeControl::FixedDepth(5));
+}
+
+#[test]
+fn movestogo_zero() {
+    let (control, warnings) = time_control("wtime 9000 btime 9000 movestogo 0", Player::White);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        control,
+        TimeControl::Clock {
+            time: ms(9000),
+            increment: ms(0),
+            moves_to_go: None,
+        }
+    );
+    assert_eq!(control.move_time(), Some(ms(300)));
+}
+
+#[test]
+fn movetime() {
+    let (control, warnings) = time_control("movetime 0", Player::White);
+    assert!(warnings.is_empty());
+    assert_eq!(control, TimeControl::MoveTime(ms(0)));
+    assert_eq!(control.limits().time, Some(ms(0)));
+
+    // A move time is preferred over the clock.
+    let (control, _) = time_control("wtime 100000 btime 100000 movetime 250", Player::White);
+    assert_eq!(control, TimeControl::MoveTime(ms(250)));
+}
+
+#[test]
+fn negative_times() {
+    let (control, warnings) = time_control("wtime -200 btime 5000 winc -10", Player::White);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        control,
+        TimeControl::Clock {
+            time: ms(0),
+            increment: ms(0),
+            moves_to_go: None,
+        }
+    );
+    assert_eq!(control.move_time(), Some(ms(0)));
+
+    let (control, warnings) = time_control("movetime -1", Player::Black);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(control, TimeControl::MoveTime(ms(0)));
+}
+
+#[test]
+fn compound_limits() {
+    let (control, _) = time_control("movetime 1000 depth 6 nodes 5000", Player::White);
+    assert_eq!(
+        control,
+        TimeControl::Compound(vec![
+            TimeControl::MoveTime(ms(1000)),
+            TimeControl::FixedDepth(6),
+            TimeControl::FixedNodes(5000),
+        ])
+    );
+    let limits = control.limits();
+    assert_eq!(limits.time, Some(ms(1000)));
+    assert_eq!(limits.depth, Some(6));
+    assert_eq!(limits.nodes, Some(5000));
+
+    let (control, _) = time_control("wtime 1000 depth 6 infinite", Player::White);
+    assert_eq!(control, TimeControl::Infinite);
+    let (control, _) = time_control("", Player::White);
+    assert_eq!(control, TimeControl::Infinite);
+}
+
+#[test]
+fn mate() {
+    let (control, warnings) = time_control("mate 3", Player::White);
+    assert!(warnings.is_empty());
+    assert_eq!(control, TimeControl::Mate(3));
+    assert_eq!(control.limits().mate, Some(3));
+    assert_eq!(control.limits().time, None);
+
+    let (control, _) = time_control("mate 2 movetime 500", Player::Black);
+    assert_eq!(
+        control,
+        TimeControl::Compound(vec![TimeControl::MoveTime(ms(500)), TimeControl::Mate(2),])
+    );
+
+    let (control, warnings) = time_control("mate 0", Player::White);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(control, TimeControl::Infinite);
+}
@@ -0,0 +1,54 @@
+use chess_core::{engine::Score, Player};
+use chess_uci::info::SearchInfo;
+
+#[test]
+fn parse_centipawns() {
+    let info = SearchInfo::parse(
+        "info depth 12 seldepth 18 multipv 1 score cp -35 nodes 123456 nps 900000 time 137 pv e7e5 g1f3 b8c6",
+    )
+    .unwrap();
+    assert_eq!(info.depth, Some(12));
+    assert_eq!(info.seldepth, Some(18));
+    assert_eq!(info.nodes, Some(123456));
+    assert_eq!(info.score, Some(Score::Cp(-35)));
+    assert!(!info.bound);
+    assert_eq!(info.pv, ["e7e5", "g1f3", "b8c6"]);
+}
+
+#[test]
+fn parse_mate() {
+    let info =
+        SearchInfo::parse("info depth 7 score mate -3 upperbound pv h7h8 hashfull 10").unwrap();
+    assert_eq!(info.score, Some(Score::Mate(-3)));
+    assert!(info.bound);
+    assert_eq!(info.pv, ["h7h8"]);
+
+    // The format the engines in this repository write.
+    let info = SearchInfo::parse(
+        "info depth 4 seldepth 9 nodes 5000 nps 100000 time 50 hashfull 1 score mate 2 pv a1a7 e7d8 b6b8",
+    )
+    .unwrap();
+    assert_eq!(info.depth, Some(4));
+    assert_eq!(info.score, Some(Score::Mate(2)));
+    assert_eq!(info.pv.len(), 3);
+    assert_eq!(info.white_score(Player::White), Some(Score::Mate(2)));
+    assert_eq!(info.white_score(Player::Black), Some(Score::Mate(-2)));
+}
+
+#[test]
+fn ignore_other_lines() {
+    assert_eq!(SearchInfo::parse("bestmove e2e4"), None);
+    let info = SearchInfo::parse("info string score cp 100 is not a score").unwrap();
+    assert_eq!(info, SearchInfo::default());
+    let info = SearchInfo::parse("info depth x score cp").unwrap();
+    assert_eq!(info, SearchInfo::default());
+}
+
+#[test]
+fn merge_keeps_missing_fields() {
+    let mut info = SearchInfo::parse("info depth 5 score cp 20 pv e2e4").unwrap();
+    info.merge(SearchInfo::parse("info depth 6 currmove d2d4 currmovenumber 2").unwrap());
+    assert_eq!(info.depth, Some(6));
+    assert_eq!(info.score, Some(Score::Cp(20)));
+    assert_eq!(info.pv, ["e2e4"]);
+}
@@ -0,0 +1,184 @@
+use chess_core::{
+    board::EndChain,
+    engine::{Engine, EngineControl, EngineLimit},
+    gen::{gen_type, InlineBuffer, MoveGenerator, MoveList},
+    repetition::DrawRule,
+    Board, Move,
+};
+use chess_uci::{position::Position, Uci, UciMove};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Engine which only keeps track of its board and the amount of times it was set.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<(Option<Board>, usize)>>);
+
+impl Recorder {
+    /// Wait until the engine thread has caught up with the given board.
+    fn wait_for(&self, board: &Board) -> usize {
+        let start = Instant::now();
+        loop {
+            {
+                let (b, set_count) = &*self.0.lock().unwrap();
+                if b.as_ref().map(|b| b.is_equal(board)).unwrap_or(false) {
+                    return *set_count;
+                }
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "engine out of sync"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl<C: EngineControl> Engine<C> for Recorder {
+    const NAME: &'static str = "Recorder";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        None
+    }
+
+    fn set_board(&mut self, board: Board) {
+        let mut lock = self.0.lock().unwrap();
+        lock.0 = Some(board);
+        lock.1 += 1;
+    }
+
+    fn make_move(&mut self, m: Move) {
+        self.0.lock().unwrap().0.as_mut().unwrap().make_move(m);
+    }
+}
+
+/// Returns the position commands a GUI would send while playing a random game.
+fn game_commands(plies: usize, seed: u64) -> (Vec<String>, Board) {
+    let gen = MoveGenerator::new();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board = Board::start_position(EndChain);
+    let mut command = "startpos moves".to_string();
+    let mut commands = Vec::new();
+    for _ in 0..plies {
+        let mut buffer = InlineBuffer::<256>::new();
+        gen.gen_moves::<gen_type::All, _, _>(&board, &mut buffer);
+        if buffer.len() == 0 {
+            return game_commands(plies, seed + 1);
+        }
+        let m = buffer.get(rng.gen_range(0..buffer.len()));
+        board.make_move(m);
+        command.push(' ');
+        command.push_str(&UciMove(m).to_string());
+        commands.push(command.clone());
+    }
+    (commands, board)
+}
+
+#[test]
+fn position_switches_game() {
+    let engine = Recorder::default();
+    let mut uci = Uci::new(engine.clone());
+
+    let (first, _) = game_commands(40, 1);
+    for c in first.iter() {
+        uci.parse_position(c).unwrap();
+    }
+    let (second, board) = game_commands(30, 2);
+    for c in second.iter() {
+        uci.parse_position(c).unwrap();
+    }
+    assert!(uci.board().is_equal(&board));
+    assert_eq!(engine.wait_for(&board), 2);
+
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+    uci.parse_position(&format!("fen {} moves g1f3", fen))
+        .unwrap();
+    let mut board = Board::from_fen(fen, EndChain).unwrap();
+    board.make_move(UciMove::from_name("g1f3", &board).unwrap().0);
+    assert!(uci.board().is_equal(&board));
+    assert_eq!(engine.wait_for(&board), 3);
+}
+
+#[test]
+fn position_replay_cached() {
+    let (commands, board) = game_commands(300, 0);
+
+    let engine = Recorder::default();
+    let mut uci = Uci::new(engine.clone());
+    for c in commands.iter() {
+        uci.parse_position(c).unwrap();
+    }
+    assert!(uci.board().is_equal(&board));
+    assert_eq!(engine.wait_for(&board), 1);
+
+    let engine = Recorder::default();
+    let mut uci = Uci::new(engine.clone());
+    for c in commands.iter() {
+        uci.clear_position();
+        uci.parse_position(c).unwrap();
+    }
+    assert!(uci.board().is_equal(&board));
+    assert_eq!(engine.wait_for(&board), commands.len());
+}
+
+#[test]
+fn replay_matches_generation() {
+    let gen = MoveGenerator::new();
+    for seed in 0..5 {
+        let (commands, board) = game_commands(120, seed * 7);
+        let command = commands.last().unwrap();
+        let mut uci = Uci::new(Recorder::default());
+        uci.parse_position(command).unwrap();
+        assert!(uci.board().is_equal(&board), "{}", command);
+
+        // Every move resolves to the same move as when looking it up in the generated moves.
+        let mut replay = Board::start_position(EndChain);
+        for name in command.split_whitespace().skip(2) {
+            let m = Move::from_uci_on_board(name, &replay, &gen).unwrap();
+            assert_eq!(
+                Move::from_uci_fast(name, &replay, &gen),
+                Some(m),
+                "{}",
+                name
+            );
+            replay.make_move(m);
+        }
+    }
+
+    // Illegal moves are still rejected, the last one moves a pinned pawn.
+    for moves in ["e2e5", "e1e2", "e2e4 e7e5 e1g1", "e2e4 e7e5 d1h5 f7f6"] {
+        let mut uci = Uci::new(Recorder::default());
+        assert!(uci
+            .parse_position(&format!("startpos moves {}", moves))
+            .is_err());
+    }
+}
+
+#[test]
+fn position_history() {
+    let gen = MoveGenerator::new();
+    let mut position = Position::start_position();
+    assert_eq!(position.history().len(), 1);
+
+    // Knights out and back twice repeats the start position three times.
+    let moves = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    for name in moves.iter().chain(moves.iter()) {
+        assert_eq!(position.draw_rule(&gen), None);
+        let m = UciMove::from_name(name, position.board()).unwrap().0;
+        position.make_move(m);
+    }
+    assert_eq!(position.history().len(), 9);
+    assert_eq!(position.draw_rule(&gen), Some(DrawRule::Repetition));
+
+    // Setting a new board starts a new history.
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80", EndChain).unwrap();
+    position.set_board(board);
+    assert_eq!(position.history().len(), 1);
+    assert_eq!(position.draw_rule(&gen), None);
+    let m = UciMove::from_name("a1a2", position.board()).unwrap().0;
+    position.make_move(m);
+    assert_eq!(position.draw_rule(&gen), Some(DrawRule::FiftyMoves));
+}
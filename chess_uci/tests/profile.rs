@@ -0,0 +1,107 @@
+use chess_uci::{
+    profile::{advertised_option, EngineProfile},
+    runner::UciPlayer,
+};
+use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+/// An engine which writes the `setoption` commands it receives to a log file next to it.
+const STUB: &str = r#"#!/bin/sh
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name stub"
+            echo "option name Hash type spin default 16 min 1 max 1024"
+            echo "option name Move Overhead type spin default 10 min 0 max 1000"
+            echo "option name Ponder type check default false"
+            echo "uciok"
+            ;;
+        setoption*) echo "$line" >> "$0.log" ;;
+        isready) echo "readyok" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+const PROFILE: &str = r#"
+name = "stub, big hash"
+
+[options]
+Ponder = true
+Hash = 256
+Threads = 4
+"Move Overhead" = "30"
+"#;
+
+fn stub_engine(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chess_uci_profile_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, STUB).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn parse_profile() {
+    let profile: EngineProfile = PROFILE.parse().unwrap();
+    assert_eq!(profile.name.as_deref(), Some("stub, big hash"));
+    let options: Vec<_> = profile
+        .options
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    assert_eq!(
+        options,
+        ["Ponder=true", "Hash=256", "Threads=4", "Move Overhead=30"]
+    );
+
+    assert_eq!(
+        EngineProfile::path_for("engines/alpha_beta".as_ref()),
+        PathBuf::from("engines/alpha_beta.toml")
+    );
+    assert!("[options]\nHash = [1, 2]".parse::<EngineProfile>().is_err());
+}
+
+#[test]
+fn advertised_options() {
+    assert_eq!(
+        advertised_option("option name Move Overhead type spin default 10").as_deref(),
+        Some("Move Overhead")
+    );
+    assert_eq!(advertised_option("id name stub"), None);
+    assert_eq!(advertised_option("option name type check"), None);
+}
+
+#[test]
+fn profile_options_sent_in_order() {
+    let engine = stub_engine("ordered");
+    fs::write(EngineProfile::path_for(&engine), PROFILE).unwrap();
+    let log = engine.with_file_name("ordered.log");
+    fs::remove_file(&log).ok();
+
+    // The profile next to the engine is picked up, the unknown Threads option is skipped.
+    drop(UciPlayer::from_path(&engine).unwrap());
+
+    let sent = fs::read_to_string(&log).unwrap();
+    let sent: Vec<_> = sent.lines().collect();
+    assert_eq!(
+        sent,
+        [
+            "setoption name Ponder value true",
+            "setoption name Hash value 256",
+            "setoption name Move Overhead value 30",
+        ]
+    );
+}
+
+#[test]
+fn no_profile() {
+    let engine = stub_engine("plain");
+    let log = engine.with_file_name("plain.log");
+    fs::remove_file(&log).ok();
+
+    let player = UciPlayer::from_path(&engine).unwrap();
+    assert_eq!(player.id(), Some("stub"));
+    drop(player);
+    assert!(!log.exists());
+}
@@ -0,0 +1,577 @@
+use anyhow::Result;
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    board::EndChain,
+    engine::{Engine, EngineControl, EngineLimit, Info},
+    gen::{gen_type, MoveGenerator},
+    Board, Move,
+};
+use chess_uci::{go::TimeControl, Uci, UciMove};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Output which sends every complete line over a channel.
+struct LineOutput {
+    send: Sender<String>,
+    buffer: Vec<u8>,
+}
+
+impl Write for LineOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(at) = self.buffer.iter().position(|x| *x == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=at).collect();
+            let line = String::from_utf8_lossy(&line[..at]).into_owned();
+            self.send.send(line).ok();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An engine running the uci protocol in memory.
+struct Harness {
+    input: Sender<String>,
+    output: Receiver<String>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self::with(|out| Uci::with_output(AlphaBeta::new(), out))
+    }
+
+    /// Run the protocol handler created by `f`.
+    fn with<F>(f: F) -> Self
+    where
+        F: FnOnce(LineOutput) -> Uci<LineOutput> + Send + 'static,
+    {
+        let (input, input_recv) = crossbeam_channel::unbounded();
+        let (output_send, output) = crossbeam_channel::unbounded();
+        let handle = thread::spawn(move || {
+            let out = LineOutput {
+                send: output_send,
+                buffer: Vec::new(),
+            };
+            f(out).run(input_recv)
+        });
+        Harness {
+            input,
+            output,
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, line: &str) {
+        self.input.send(format!("{}\n", line)).unwrap();
+    }
+
+    /// Wait for a line starting with the given prefix, returning the skipped lines and the
+    /// matching line.
+    fn expect(&self, prefix: &str) -> (Vec<String>, String) {
+        let start = Instant::now();
+        let mut skipped = Vec::new();
+        loop {
+            let timeout = Duration::from_secs(10).saturating_sub(start.elapsed());
+            match self.output.recv_timeout(timeout) {
+                Ok(line) if line.starts_with(prefix) => return (skipped, line),
+                Ok(line) => skipped.push(line),
+                Err(_) => panic!("no line starting with `{}`, got: {:?}", prefix, skipped),
+            }
+        }
+    }
+
+    fn handshake(&self) -> Vec<String> {
+        self.send("uci");
+        self.expect("uciok").0
+    }
+
+    /// Quit the engine, returning the result of the protocol handler.
+    fn quit(mut self) -> Result<()> {
+        self.send("quit");
+        self.handle.take().unwrap().join().unwrap()
+    }
+}
+
+#[test]
+fn handshake() {
+    let uci = Harness::new();
+    let lines = uci.handshake();
+    assert_eq!(lines[0], "id name AlphaBeta 2 v0.1.0");
+    assert!(lines[1].starts_with("id author"));
+    assert!(lines
+        .iter()
+        .any(|x| x == "option name Hash type spin default 16 min 1 max 4096"));
+
+    uci.send("isready");
+    uci.expect("readyok");
+    uci.quit().unwrap();
+}
+
+/// The full response of the alpha-beta engine to the `uci` command, to catch changes to the
+/// declared options and their format.
+const ALPHA_BETA_HANDSHAKE: &str = "\
+id name AlphaBeta 2 v0.1.0
+id author Mees Delzenne
+option name Hash type spin default 16 min 1 max 4096
+option name contempt type spin default 100 min -100 max 900
+option name Style type combo default normal var solid var normal var aggressive
+option name UCI_AnalyseMode type check default false
+option name EvalTrace type check default false
+option name CheckExtension type check default true
+option name UCI_ShowWDL type check default false
+option name ShowStats type check default false
+option name DumpTree type spin default 0 min 0 max 16
+option name DumpTreeNodes type spin default 100000 min 1 max 10000000
+option name DumpTreeFile type string default alpha_beta.dot
+option name BookFile type string default <empty>
+option name SyzygyPath type string default <empty>
+option name AspirationWindow type spin default 25 min 0 max 975
+option name Move Overhead type spin default 30 min 0 max 5000
+option name UCI_ShowSAN type check default false
+option name CpuMask type string default all
+option name ThreadPriority type spin default 0 min -20 max 19
+";
+
+#[test]
+fn handshake_snapshot() {
+    let uci = Harness::new();
+    let mut lines = uci.handshake();
+    lines.push(String::new());
+    assert_eq!(lines.join("\n"), ALPHA_BETA_HANDSHAKE);
+    uci.quit().unwrap();
+}
+
+#[test]
+fn position_go_bestmove() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+    uci.send(&format!("position fen {} moves g1f3 d7d6", fen));
+    uci.send("go depth 3");
+    let (info, line) = uci.expect("bestmove");
+    assert!(info.iter().any(|x| x.starts_with("info depth 3 ")));
+
+    let mut board = Board::from_fen(fen, EndChain).unwrap();
+    for m in ["g1f3", "d7d6"] {
+        board.make_move(UciMove::from_name(m, &board).unwrap().0);
+    }
+    let name = line.split_whitespace().nth(1).unwrap();
+    assert!(
+        UciMove::from_name(name, &board).is_some(),
+        "illegal move {}",
+        name
+    );
+    uci.quit().unwrap();
+}
+
+#[test]
+fn debug_lines_only_in_debug_mode() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("position startpos");
+    uci.send("go depth 3");
+    let (info, _) = uci.expect("bestmove");
+    assert!(!info.iter().any(|x| x.contains("counter move cutoffs")));
+    assert!(!info.iter().any(|x| x.starts_with("debug")));
+
+    uci.send("debug on");
+    uci.send("go depth 3");
+    let (info, _) = uci.expect("bestmove");
+    assert!(info
+        .iter()
+        .any(|x| x.starts_with("info string counter move cutoffs ")));
+    assert!(info
+        .iter()
+        .any(|x| x.starts_with("info string aspiration re-searches ")));
+    uci.quit().unwrap();
+}
+
+#[test]
+fn forced_move_and_game_over() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    // The king can only step to h7.
+    uci.send("position fen 7k/8/8/8/8/8/8/K5R1 b - - 0 1");
+    uci.send("go depth 4");
+    let (_, line) = uci.expect("bestmove");
+    assert_eq!(line, "bestmove h8h7");
+
+    // Without legal moves the engine answers with the null move.
+    for fen in [
+        "7k/6Q1/6K1/8/8/8/8/8 b - - 0 1",
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+    ] {
+        uci.send(&format!("position fen {}", fen));
+        uci.send("go depth 4");
+        let (_, line) = uci.expect("bestmove");
+        assert_eq!(line, "bestmove 0000", "{}", fen);
+    }
+
+    // The engine keeps working afterwards.
+    uci.send("position startpos");
+    uci.send("go depth 2");
+    let (_, line) = uci.expect("bestmove");
+    assert_ne!(line, "bestmove 0000");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn combined_info_line() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("position startpos moves e2e4");
+    uci.send("go depth 4");
+    let (_, line) = uci.expect("info depth 4");
+    let fields: Vec<_> = line.split_whitespace().collect();
+    let mut last = 0;
+    for name in [
+        "depth", "seldepth", "nodes", "nps", "time", "hashfull", "score", "pv",
+    ] {
+        let at = fields
+            .iter()
+            .position(|x| *x == name)
+            .unwrap_or_else(|| panic!("missing {} in `{}`", name, line));
+        assert!(
+            at > last || name == "depth",
+            "{} out of order in `{}`",
+            name,
+            line
+        );
+        last = at;
+    }
+    let seldepth: u32 = fields[4].parse().unwrap();
+    assert!(seldepth >= 4, "{}", line);
+    let score = fields.iter().position(|x| *x == "score").unwrap();
+    assert_eq!(fields[score + 1], "cp");
+    uci.expect("bestmove");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn setoption() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("setoption name EvalTrace value true");
+    uci.send("position startpos");
+    uci.send("go depth 1");
+    let (_, line) = uci.expect("info string eval total");
+    assert_eq!(line, "info string eval total 0");
+    uci.expect("bestmove");
+
+    uci.send("setoption name ShowStats value true");
+    uci.send("go depth 2");
+    let (skipped, _) = uci.expect("info string stats total");
+    assert!(skipped
+        .iter()
+        .any(|x| x.starts_with("info string stats depth 2 ")));
+    uci.expect("bestmove");
+
+    // Mistakes in options are reported without stopping the engine.
+    uci.send("setoption name Unknown value 1");
+    let (_, line) = uci.expect("info string");
+    assert_eq!(line, "info string unknown option `Unknown`");
+    uci.send("setoption name EvalTrace value maybe");
+    let (_, line) = uci.expect("info string");
+    assert_eq!(
+        line,
+        "info string invalid value `maybe` for check option `EvalTrace`"
+    );
+    uci.send("isready");
+    uci.expect("readyok");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn show_wdl() {
+    let uci = Harness::new();
+    let lines = uci.handshake();
+    assert!(lines
+        .iter()
+        .any(|x| x == "option name UCI_ShowWDL type check default false"));
+
+    uci.send("setoption name UCI_ShowWDL value true");
+    uci.send("position startpos");
+    uci.send("go depth 2");
+    let (_, line) = uci.expect("info depth 2");
+    let fields: Vec<_> = line.split_whitespace().collect();
+    let at = fields
+        .iter()
+        .position(|x| *x == "wdl")
+        .unwrap_or_else(|| panic!("missing wdl in `{}`", line));
+    let chances: u32 = fields[at + 1..at + 4]
+        .iter()
+        .map(|x| x.parse::<u32>().unwrap())
+        .sum();
+    assert_eq!(chances, 1000, "{}", line);
+    uci.expect("bestmove");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn invalid_fen_keeps_position() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("position startpos moves e2e4");
+    uci.send("position fen 4k3/8/8/8/8/8/8/8 w - - 0 1");
+    let (_, line) = uci.expect("info string");
+    assert_eq!(line, "info string invalid fen: White has no king");
+    uci.send("position fen 4k3/8/8/8/8/8/8/3PK3 w - - 0 1");
+    let (_, line) = uci.expect("info string");
+    assert_eq!(line, "info string invalid fen: pawn on back rank d1");
+
+    // The engine still searches the position before the invalid ones.
+    uci.send("go depth 1");
+    let (_, line) = uci.expect("bestmove");
+    let mut board = Board::start_position(EndChain);
+    board.make_move(UciMove::from_name("e2e4", &board).unwrap().0);
+    let name = line.split_whitespace().nth(1).unwrap();
+    assert!(
+        UciMove::from_name(name, &board).is_some(),
+        "illegal move {}",
+        name
+    );
+    uci.quit().unwrap();
+}
+
+#[test]
+fn go_infinite_then_stop() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("position startpos moves e2e4");
+    uci.send("go infinite searchmoves e7e5");
+    thread::sleep(Duration::from_millis(200));
+    uci.send("stop");
+    let (_, line) = uci.expect("bestmove");
+    assert_eq!(line, "bestmove e7e5");
+
+    uci.send("go depth 4");
+    uci.expect("info depth 4");
+    uci.expect("bestmove");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn go_searchmoves() {
+    let uci = Harness::new();
+    uci.handshake();
+
+    uci.send("position startpos");
+    uci.send("go depth 4 searchmoves a2a3");
+    let (_, line) = uci.expect("bestmove");
+    assert_eq!(line, "bestmove a2a3");
+
+    // Illegal moves are reported and left out of the restriction.
+    uci.send("go depth 3 searchmoves e2e5 h2h3");
+    let (info, line) = uci.expect("bestmove");
+    assert!(info
+        .iter()
+        .any(|x| x.starts_with("info string ignoring searchmoves move `e2e5`")));
+    assert_eq!(line, "bestmove h2h3");
+
+    // Without any legal move the restriction is dropped.
+    uci.send("go depth 2 searchmoves e7e5");
+    let (info, line) = uci.expect("bestmove");
+    assert!(info
+        .iter()
+        .any(|x| x.starts_with("info string no legal searchmoves")));
+    assert_ne!(line, "bestmove 0000");
+    uci.quit().unwrap();
+}
+
+#[test]
+fn parse_go_infinite_searchmoves() {
+    let mut uci = Uci::new(AlphaBeta::new());
+    uci.parse_position("startpos").unwrap();
+
+    let (time_control, limits) = uci
+        .parse_go_limits("wtime 1000 infinite searchmoves e2e4 g1f3 depth 3")
+        .unwrap();
+    assert_eq!(time_control, TimeControl::Infinite);
+    assert!(limits.depth.is_none());
+    assert!(limits.time.is_none());
+    let moves: Vec<_> = limits
+        .search_moves
+        .unwrap()
+        .into_iter()
+        .map(|m| UciMove(m).to_string())
+        .collect();
+    assert_eq!(moves, ["e2e4", "g1f3"]);
+}
+
+/// Engine which plays the first legal move and panics on the third search.
+struct Flaky {
+    board: Board,
+    searches: Arc<AtomicUsize>,
+}
+
+impl<C: EngineControl> Engine<C> for Flaky {
+    const NAME: &'static str = "Flaky";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        if self.searches.fetch_add(1, Ordering::SeqCst) == 2 {
+            panic!("third search");
+        }
+        let mut moves = Vec::new();
+        MoveGenerator::new().gen_moves::<gen_type::All, _, _>(&self.board, &mut moves);
+        moves.first().copied()
+    }
+
+    fn set_board(&mut self, board: Board) {
+        self.board = board;
+    }
+
+    fn make_move(&mut self, m: Move) {
+        self.board.make_move(m);
+    }
+}
+
+#[test]
+fn recover_from_panic() {
+    let searches = Arc::new(AtomicUsize::new(0));
+    let created = Arc::new(AtomicUsize::new(0));
+    let uci = {
+        let searches = searches.clone();
+        let created = created.clone();
+        Harness::with(move |out| {
+            Uci::from_fn_with_output(
+                move || {
+                    created.fetch_add(1, Ordering::SeqCst);
+                    Flaky {
+                        board: Board::start_position(EndChain),
+                        searches: searches.clone(),
+                    }
+                },
+                out,
+            )
+        })
+    };
+    uci.handshake();
+    uci.send("debug on");
+
+    uci.send("position startpos moves e2e4");
+    for _ in 0..2 {
+        uci.send("go depth 1");
+        let (_, line) = uci.expect("bestmove");
+        assert_ne!(line, "bestmove 0000");
+    }
+
+    uci.send("go depth 1");
+    let (skipped, line) = uci.expect("bestmove");
+    assert_eq!(line, "bestmove 0000");
+    assert!(skipped
+        .iter()
+        .any(|x| x.contains("engine panicked: third search")));
+    assert!(skipped.iter().any(|x| x.starts_with("info string")));
+    assert_eq!(created.load(Ordering::SeqCst), 2);
+
+    // The fresh engine continues from the last position.
+    for _ in 0..2 {
+        uci.send("go depth 1");
+        let (_, line) = uci.expect("bestmove");
+        let name = line.split_whitespace().nth(1).unwrap();
+        let mut board = Board::start_position(EndChain);
+        board.make_move(UciMove::from_name("e2e4", &board).unwrap().0);
+        assert!(UciMove::from_name(name, &board).is_some(), "{}", line);
+    }
+    assert_eq!(searches.load(Ordering::SeqCst), 5);
+    uci.quit().unwrap();
+}
+
+/// Engine which reports a fixed principal variation, given in uci notation, and plays its first
+/// move.
+struct FixedPv {
+    board: Board,
+    pv: &'static [&'static str],
+}
+
+impl<C: EngineControl> Engine<C> for FixedPv {
+    const NAME: &'static str = "FixedPv";
+
+    fn go(&mut self, control: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        let mut board = self.board.clone();
+        let mut pv = Vec::new();
+        for name in self.pv {
+            let m = UciMove::from_name(name, &board).unwrap().0;
+            board.make_move(m);
+            pv.push(m);
+        }
+        control.info(Info::Pv(pv.clone()));
+        pv.first().copied()
+    }
+
+    fn set_board(&mut self, board: Board) {
+        self.board = board;
+    }
+
+    fn make_move(&mut self, m: Move) {
+        self.board.make_move(m);
+    }
+}
+
+#[test]
+fn show_san() {
+    let uci = Harness::with(|out| {
+        Uci::with_output(
+            FixedPv {
+                board: Board::start_position(EndChain),
+                pv: &["e2e4", "e7e5", "g1f3", "b8c6", "e1e2"],
+            },
+            out,
+        )
+    });
+    let lines = uci.handshake();
+    assert!(lines
+        .iter()
+        .any(|x| x == "option name UCI_ShowSAN type check default false"));
+
+    uci.send("position startpos");
+    uci.send("go depth 1");
+    let (_, line) = uci.expect("info pv");
+    assert_eq!(line, "info pv e2e4 e7e5 g1f3 b8c6 e1e2");
+    uci.expect("bestmove");
+
+    uci.send("setoption name UCI_ShowSAN value true");
+    uci.send("go depth 1");
+    let (_, line) = uci.expect("info pv");
+    assert_eq!(line, "info pv 1.e4 e5 2.Nf3 Nc6 3.Ke2");
+    uci.expect("bestmove");
+    uci.quit().unwrap();
+
+    // Numbering starts with an ellipsis when black moves first.
+    let uci = Harness::with(|out| {
+        Uci::with_output(
+            FixedPv {
+                board: Board::start_position(EndChain),
+                pv: &["e7e5", "g1f3"],
+            },
+            out,
+        )
+    });
+    uci.handshake();
+    uci.send("setoption name UCI_ShowSAN value true");
+    uci.send("position startpos moves e2e4");
+    uci.send("go depth 1");
+    let (_, line) = uci.expect("info pv");
+    assert_eq!(line, "info pv 1...e5 2.Nf3");
+    uci.expect("bestmove");
+    uci.quit().unwrap();
+}
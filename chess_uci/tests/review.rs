@@ -0,0 +1,57 @@
+use chess_core::{engine::EngineLimit, Player, Square};
+use chess_uci::review::{Game, GameReview, Judgement};
+
+#[test]
+fn flags_hung_queen() {
+    // After 2...d5 the bishop on c8 attacks the queen on g4, which white leaves hanging with
+    // 3.Nc3.
+    let game = Game::parse("startpos moves e2e4 e7e5 d1g4 d7d5 b1c3 c8g4").unwrap();
+    let review = GameReview::review(&game, EngineLimit::depth(4));
+    assert_eq!(review.moves.len(), 6);
+
+    let blunders: Vec<_> = review
+        .moves
+        .iter()
+        .filter(|x| x.judgement == Some(Judgement::Blunder))
+        .collect();
+    assert_eq!(blunders.len(), 1, "{}", review);
+    let blunder = blunders[0];
+    assert_eq!(blunder.number, 3);
+    assert_eq!(blunder.player, Player::White);
+    assert_eq!(blunder.played.to_uci(), "b1c3");
+    // Any better move saves the queen.
+    assert_eq!(
+        blunder.best.unwrap().from(),
+        Square::from_name("g4").unwrap()
+    );
+
+    assert!(review.white_average_loss > review.black_average_loss);
+}
+
+#[test]
+fn mate_scores_are_capped() {
+    // Black can mate with Qh4, but plays a waiting move instead.
+    let game = Game::parse("startpos moves f2f3 e7e5 g2g4 a7a6").unwrap();
+    let review = GameReview::review(&game, EngineLimit::depth(3));
+    let missed = &review.moves[3];
+    assert_eq!(missed.best.unwrap().to_uci(), "d8h4");
+    assert_eq!(missed.best_score, chess_uci::review::MAX_SCORE);
+    assert_eq!(missed.judgement, Some(Judgement::Blunder));
+
+    // The losing moves of white walk into the mate.
+    assert_eq!(review.moves[2].judgement, Some(Judgement::Blunder));
+}
+
+#[test]
+fn parse_games() {
+    let game = Game::parse("fen 6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1 moves e1e8").unwrap();
+    assert_eq!(game.moves.len(), 1);
+    assert!(Game::parse("startpos moves e2e5").is_err());
+    assert!(Game::parse("e2e4").is_err());
+
+    // The review of a game ending in mate scores the final position as lost.
+    let review = GameReview::review(&game, EngineLimit::depth(2));
+    assert_eq!(review.moves[0].played_score, chess_uci::review::MAX_SCORE);
+    assert_eq!(review.moves[0].loss, 0);
+    assert!(review.to_string().contains("e1e8"));
+}
@@ -0,0 +1,313 @@
+use chess_core::{
+    board::EndChain,
+    engine::{Engine, EngineControl, EngineLimit},
+    gen::{gen_type, MoveGenerator},
+    Board, Move, Player,
+};
+use chess_uci::{
+    runner::{
+        self, Clocks, EnginePlayer, GameOutcome, GameRunner, Handicap, PlayedGame, SearchLimit,
+        Termination, TimeControl,
+    },
+    UciMove,
+};
+use rand::Rng;
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+struct Random {
+    board: Board,
+    gen: MoveGenerator,
+}
+
+impl<C: EngineControl> Engine<C> for Random {
+    const NAME: &'static str = "Random";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        let mut moves = Vec::new();
+        self.gen
+            .gen_moves::<gen_type::All, _, _>(&self.board, &mut moves);
+        if moves.is_empty() {
+            return None;
+        }
+        Some(moves[rand::thread_rng().gen_range(0..moves.len())])
+    }
+
+    fn set_board(&mut self, board: Board) {
+        self.board = board;
+    }
+
+    fn make_move(&mut self, m: Move) {
+        self.board.make_move(m);
+    }
+}
+
+fn random() -> Box<EnginePlayer<Random>> {
+    Box::new(EnginePlayer(Random {
+        board: Board::start_position(EndChain),
+        gen: MoveGenerator::new(),
+    }))
+}
+
+#[test]
+fn random_vs_random() {
+    let mut runner = GameRunner::new(
+        random(),
+        random(),
+        Board::start_position(EndChain),
+        TimeControl {
+            initial: Duration::from_secs(60),
+            increment: Duration::ZERO,
+        },
+    );
+    let game = runner.play();
+    assert!(!matches!(
+        game.termination_reason,
+        Termination::IllegalMove | Termination::Crash | Termination::TimeForfeit
+    ));
+    assert_eq!(game.moves.len(), game.clock_history.len());
+    assert_eq!(game.moves.len(), game.move_times.len());
+}
+
+/// Plays random moves after thinking for a moment.
+struct Slow(Random);
+
+impl<C: EngineControl> Engine<C> for Slow {
+    const NAME: &'static str = "Slow";
+
+    fn go(&mut self, c: C, time_left: Option<Duration>, limit: EngineLimit) -> Option<Move> {
+        std::thread::sleep(Duration::from_millis(2));
+        self.0.go(c, time_left, limit)
+    }
+
+    fn set_board(&mut self, board: Board) {
+        Engine::<C>::set_board(&mut self.0, board);
+    }
+
+    fn make_move(&mut self, m: Move) {
+        Engine::<C>::make_move(&mut self.0, m);
+    }
+}
+
+/// Returns the times of the clock comments like `{[%clk 0:01:23]}` in seconds.
+fn parse_clocks(pgn: &str) -> Vec<u64> {
+    pgn.split("{[%clk ")
+        .skip(1)
+        .map(|x| {
+            let time = &x[..x.find("]}").unwrap()];
+            time.split(':')
+                .map(|x| x.parse::<u64>().unwrap())
+                .fold(0, |acc, x| acc * 60 + x)
+        })
+        .collect()
+}
+
+#[test]
+fn pgn_clocks() {
+    let slow = || {
+        Box::new(EnginePlayer(Slow(Random {
+            board: Board::start_position(EndChain),
+            gen: MoveGenerator::new(),
+        })))
+    };
+    let time = TimeControl {
+        initial: Duration::from_secs(60),
+        increment: Duration::from_secs(1),
+    };
+    let mut runner = GameRunner::new(slow(), slow(), Board::start_position(EndChain), time);
+    let game = runner.play();
+    assert_eq!(game.moves.len(), game.clock_history.len());
+    assert!(game
+        .move_times
+        .iter()
+        .all(|x| *x >= Duration::from_millis(2)));
+
+    let pgn = game.to_pgn("white", "black");
+    assert!(pgn.contains("[TimeControl \"60+1\"]"), "{}", pgn);
+    assert!(pgn.starts_with("[Event "), "{}", pgn);
+    assert!(pgn.lines().all(|x| x.len() < 80), "{}", pgn);
+    assert!(
+        pgn.trim_end().ends_with(game.result.pgn_result()),
+        "{}",
+        pgn
+    );
+
+    let clocks = parse_clocks(&pgn);
+    assert_eq!(clocks.len(), game.moves.len(), "{}", pgn);
+
+    // The clock after the n-th move of a player is the initial time plus n increments minus
+    // the time spent on those moves, rounded down to seconds.
+    let mut spent = [Duration::ZERO; 2];
+    for (i, clock) in clocks.iter().enumerate() {
+        let player = i % 2;
+        spent[player] += game.move_times[i];
+        let moves = (i / 2 + 1) as u32;
+        let expected = time.initial + time.increment * moves - spent[player];
+        assert_eq!(*clock, expected.as_secs(), "move {}", i);
+        let (white, black) = game.clock_history[i];
+        assert_eq!([white, black][player], expected, "move {}", i);
+    }
+}
+
+/// The time left and limits an engine was asked to search with.
+type Searches = Rc<RefCell<Vec<(Option<Duration>, EngineLimit)>>>;
+
+/// Plays random moves and records how it was asked to search.
+struct Recording {
+    random: Random,
+    searches: Searches,
+}
+
+impl<C: EngineControl> Engine<C> for Recording {
+    const NAME: &'static str = "Recording";
+
+    fn go(&mut self, c: C, time_left: Option<Duration>, limit: EngineLimit) -> Option<Move> {
+        self.searches.borrow_mut().push((time_left, limit.clone()));
+        self.random.go(c, time_left, limit)
+    }
+
+    fn set_board(&mut self, board: Board) {
+        Engine::<C>::set_board(&mut self.random, board);
+    }
+
+    fn make_move(&mut self, m: Move) {
+        Engine::<C>::make_move(&mut self.random, m);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn recording() -> (Box<EnginePlayer<Recording>>, Searches) {
+    let searches = Rc::new(RefCell::new(Vec::new()));
+    let player = Box::new(EnginePlayer(Recording {
+        random: Random {
+            board: Board::start_position(EndChain),
+            gen: MoveGenerator::new(),
+        },
+        searches: searches.clone(),
+    }));
+    (player, searches)
+}
+
+#[test]
+fn handicaps() {
+    let (white, white_searches) = recording();
+    let (black, black_searches) = recording();
+    let mut runner = GameRunner::new(
+        white,
+        black,
+        Board::start_position(EndChain),
+        TimeControl {
+            initial: Duration::from_secs(60),
+            increment: Duration::from_secs(1),
+        },
+    )
+    .with_handicap(
+        Player::White,
+        Handicap {
+            fixed_depth: Some(3),
+            ..Handicap::default()
+        },
+    )
+    .with_handicap(
+        Player::Black,
+        Handicap {
+            time_scale: Some(0.5),
+            ..Handicap::default()
+        },
+    );
+    let game = runner.play();
+    assert_ne!(game.termination_reason, Termination::TimeForfeit);
+
+    let white_searches = white_searches.borrow();
+    assert!(!white_searches.is_empty());
+    for (time_left, limit) in white_searches.iter() {
+        assert_eq!(*time_left, None);
+        assert_eq!(limit.depth, Some(3));
+    }
+    let black_searches = black_searches.borrow();
+    assert_eq!(black_searches[0].0, Some(Duration::from_secs(30)));
+    for (time_left, limit) in black_searches.iter() {
+        assert!(time_left.is_some());
+        assert_eq!(limit.depth, None);
+    }
+}
+
+#[test]
+fn go_commands() {
+    let clocks = Clocks {
+        white: Duration::from_millis(60_000),
+        black: Duration::from_millis(30_000),
+        white_increment: Duration::from_millis(1000),
+        black_increment: Duration::from_millis(500),
+    };
+    assert_eq!(
+        SearchLimit::Clock(clocks).go_command(),
+        "go wtime 60000 btime 30000 winc 1000 binc 500"
+    );
+    assert_eq!(SearchLimit::Depth(6).go_command(), "go depth 6");
+    assert_eq!(SearchLimit::Nodes(10_000).go_command(), "go nodes 10000");
+
+    let handicap = Handicap {
+        fixed_depth: Some(3),
+        fixed_nodes: Some(1000),
+        ..Handicap::default()
+    };
+    assert!(matches!(
+        handicap.fixed_limit(),
+        Some(SearchLimit::Depth(3))
+    ));
+    assert!(Handicap::default().fixed_limit().is_none());
+    assert!(Handicap::default().is_none());
+}
+
+/// Plays the given moves in order.
+struct Scripted(Vec<&'static str>);
+
+impl runner::Player for Scripted {
+    fn play(&mut self, _: &Board, _: &[Move], _: SearchLimit) -> anyhow::Result<String> {
+        Ok(self.0.remove(0).to_string())
+    }
+}
+
+fn scripted_game(white: Vec<&'static str>, black: Vec<&'static str>, start: Board) -> PlayedGame {
+    GameRunner::new(
+        Box::new(Scripted(white)),
+        Box::new(Scripted(black)),
+        start,
+        TimeControl {
+            initial: Duration::from_secs(60),
+            increment: Duration::ZERO,
+        },
+    )
+    .play()
+}
+
+#[test]
+fn scripted_checkmate() {
+    let game = scripted_game(
+        vec!["f2f3", "g2g4"],
+        vec!["e7e5", "d8h4"],
+        Board::start_position(EndChain),
+    );
+    assert_eq!(game.result, GameOutcome::BlackWon);
+    assert_eq!(game.termination_reason, Termination::Checkmate);
+    let moves: Vec<_> = game.moves.iter().map(|m| UciMove(*m).to_string()).collect();
+    assert_eq!(moves, ["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(game.moves.len(), game.move_times.len());
+}
+
+#[test]
+fn scripted_illegal_move() {
+    let game = scripted_game(vec!["e2e5"], vec![], Board::start_position(EndChain));
+    assert_eq!(game.result, GameOutcome::BlackWon);
+    assert_eq!(game.termination_reason, Termination::IllegalMove);
+    assert!(game.moves.is_empty());
+}
+
+#[test]
+fn finished_start_position() {
+    let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", EndChain).unwrap();
+    let game = scripted_game(vec![], vec![], board);
+    assert_eq!(game.result, GameOutcome::Drawn);
+    assert_eq!(game.termination_reason, Termination::Stalemate);
+    assert!(game.moves.is_empty());
+}
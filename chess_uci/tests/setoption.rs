@@ -0,0 +1,255 @@
+use chess_core::{
+    engine::{Engine, EngineControl, EngineLimit, OptionKind, OptionValue},
+    Board, Move,
+};
+use chess_uci::{parse_setoption_args, Uci};
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+fn options() -> Vec<(String, OptionKind)> {
+    [
+        (
+            "Hash",
+            OptionKind::Spin {
+                default: 16,
+                min: Some(1),
+                max: Some(1024),
+            },
+        ),
+        (
+            "Move Overhead",
+            OptionKind::Spin {
+                default: 10,
+                min: None,
+                max: None,
+            },
+        ),
+        ("Ponder", OptionKind::Check { default: false }),
+        ("Clear Hash", OptionKind::Button),
+        (
+            "Style",
+            OptionKind::Combo {
+                default: "Risky".to_string(),
+                choices: vec!["Solid".into(), "Risky".into(), "Very Risky".into()],
+            },
+        ),
+        ("Use value of pieces", OptionKind::string()),
+        (
+            "Book File",
+            OptionKind::String {
+                default: "book.bin".to_string(),
+            },
+        ),
+    ]
+    .into_iter()
+    .map(|(name, kind)| (name.to_string(), kind))
+    .collect()
+}
+
+fn parse(arg: &str) -> Result<(String, OptionValue), String> {
+    parse_setoption_args(&options(), arg).map_err(|e| e.to_string())
+}
+
+#[test]
+fn spins_are_clamped() {
+    let hash = |x: i32| Ok(("Hash".to_string(), OptionValue::Spin(x)));
+    assert_eq!(parse("name Hash value 256"), hash(256));
+    assert_eq!(parse("name Hash value 100000"), hash(1024));
+    assert_eq!(parse("name Hash value 0"), hash(1));
+    assert_eq!(parse("name Hash value -5000000000"), hash(1));
+    assert_eq!(
+        parse("name Hash value lots"),
+        Err("invalid value `lots` for spin option `Hash`".to_string())
+    );
+}
+
+#[test]
+fn names_with_spaces() {
+    assert_eq!(
+        parse("name Move Overhead value 30"),
+        Ok(("Move Overhead".to_string(), OptionValue::Spin(30)))
+    );
+    // Names are compared case insensitively, the engine gets its own spelling.
+    assert_eq!(
+        parse("name move overhead value 30"),
+        Ok(("Move Overhead".to_string(), OptionValue::Spin(30)))
+    );
+    assert_eq!(
+        parse("name Style value risky"),
+        Ok(("Style".to_string(), OptionValue::Combo(1)))
+    );
+    // The word value in an option name isn't mistaken for the start of the value.
+    assert_eq!(
+        parse("name Use value of pieces value classic value"),
+        Ok((
+            "Use value of pieces".to_string(),
+            OptionValue::String("classic value".to_string())
+        ))
+    );
+}
+
+#[test]
+fn values_with_spaces() {
+    // The value is passed on as it was sent, spaces included.
+    assert_eq!(
+        parse("name Book File value C:/Program Files/books/main  book.bin"),
+        Ok((
+            "Book File".to_string(),
+            OptionValue::String("C:/Program Files/books/main  book.bin".to_string())
+        ))
+    );
+    assert_eq!(
+        parse("name Style value very risky"),
+        Ok(("Style".to_string(), OptionValue::Combo(2)))
+    );
+    assert_eq!(
+        parse("name Style value Very  Risky"),
+        Err("invalid value `Very  Risky` for option `Style`".to_string())
+    );
+    assert_eq!(
+        parse("name Book File value"),
+        Ok(("Book File".to_string(), OptionValue::String(String::new())))
+    );
+}
+
+#[test]
+fn checks_and_buttons() {
+    assert_eq!(
+        parse("name Ponder value true"),
+        Ok(("Ponder".to_string(), OptionValue::Check(true)))
+    );
+    assert_eq!(
+        parse("name Ponder value FALSE"),
+        Ok(("Ponder".to_string(), OptionValue::Check(false)))
+    );
+    assert_eq!(
+        parse("name Ponder value 1"),
+        Err("invalid value `1` for check option `Ponder`".to_string())
+    );
+    assert_eq!(
+        parse("name Clear Hash"),
+        Ok(("Clear Hash".to_string(), OptionValue::Button))
+    );
+    assert_eq!(
+        parse("name Ponder"),
+        Err("missing value for option `Ponder`".to_string())
+    );
+}
+
+#[test]
+fn unknown_options() {
+    assert_eq!(
+        parse("name Threads value 4"),
+        Err("unknown option `Threads`".to_string())
+    );
+    assert_eq!(
+        parse("name Contempt"),
+        Err("unknown option `Contempt`".to_string())
+    );
+    assert_eq!(
+        parse("Hash value 4"),
+        Err("missing option name".to_string())
+    );
+}
+
+/// Engine which records the options it is given.
+#[derive(Clone, Default)]
+struct Options(Arc<Mutex<Vec<(String, OptionValue)>>>);
+
+impl<C: EngineControl> Engine<C> for Options {
+    const NAME: &'static str = "Options";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        None
+    }
+
+    fn set_board(&mut self, _: Board) {}
+
+    fn make_move(&mut self, _: Move) {}
+
+    fn options(&self) -> Vec<(String, OptionKind)> {
+        options()
+    }
+
+    fn set_option(&mut self, name: String, value: OptionValue) {
+        self.0.lock().unwrap().push((name, value));
+    }
+}
+
+/// Output which can still be read after it is given to the protocol handler.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn engine_only_sees_valid_values() {
+    let engine = Options::default();
+    let output = Output::default();
+    let mut uci = Uci::with_output(engine.clone(), output.clone());
+    uci.parse_setoption("name Hash value 100000").unwrap();
+    uci.parse_setoption("name Ponder value yes").unwrap();
+    uci.parse_setoption("name Ponder value true").unwrap();
+
+    let start = Instant::now();
+    while engine.0.lock().unwrap().len() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(5), "options not set");
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(
+        *engine.0.lock().unwrap(),
+        [
+            ("Hash".to_string(), OptionValue::Spin(1024)),
+            ("Ponder".to_string(), OptionValue::Check(true)),
+        ]
+    );
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "info string value 100000 of option `Hash` is out of range, using 1024",
+            "info string invalid value `yes` for check option `Ponder`",
+        ]
+    );
+}
+
+#[test]
+fn declared_in_order() {
+    let output = Output::default();
+    let mut uci = Uci::with_output(Options::default(), output.clone());
+    let (input, input_recv) = crossbeam_channel::unbounded();
+    input.send("uci\n".to_string()).unwrap();
+    drop(input);
+    uci.run(input_recv).unwrap();
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<_> = output.lines().filter(|x| x.starts_with("option")).collect();
+    // The options of the engine come first, in the order it declared them.
+    assert_eq!(
+        lines[..7],
+        [
+            "option name Hash type spin default 16 min 1 max 1024",
+            "option name Move Overhead type spin default 10 min -2147483648 max 2147483647",
+            "option name Ponder type check default false",
+            "option name Clear Hash type button",
+            "option name Style type combo default Risky var Solid var Risky var Very Risky",
+            "option name Use value of pieces type string default <empty>",
+            "option name Book File type string default book.bin",
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+use chess_alpha_beta::AlphaBeta;
+use chess_core::{
+    engine::{Engine, EngineControl, EngineLimit, ThreadController, Version},
+    Board, Move,
+};
+use std::{process::Command, time::Duration};
+
+/// An engine without a version.
+struct Unversioned;
+
+impl<C: EngineControl> Engine<C> for Unversioned {
+    const NAME: &'static str = "Unversioned";
+
+    fn go(&mut self, _: C, _: Option<Duration>, _: EngineLimit) -> Option<Move> {
+        None
+    }
+
+    fn set_board(&mut self, _: Board) {}
+
+    fn make_move(&mut self, _: Move) {}
+}
+
+#[test]
+fn version_format() {
+    assert_eq!(Version::new(1, 12, 0).to_string(), "v1.12.0");
+    assert!(Version::new(1, 2, 3) < Version::new(1, 10, 0));
+}
+
+#[test]
+fn engine_id() {
+    assert_eq!(
+        chess_uci::engine_id::<AlphaBeta<ThreadController>>(),
+        "AlphaBeta 2 v0.1.0"
+    );
+    // Engines without a version are identified by their name alone.
+    assert_eq!(chess_uci::engine_id::<Unversioned>(), "Unversioned");
+}
+
+#[test]
+fn version_flag() {
+    let output = Command::new(env!("CARGO_BIN_EXE_alpha_beta"))
+        .arg("--version")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "AlphaBeta 2 v0.1.0\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_alpha_beta"))
+        .arg("--unknown")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
@@ -1,10 +1,14 @@
 use chess_core::{
     bb::BB,
     board::{Board, UnmakeMove},
-    gen::MoveGenerator,
+    clock::Clock,
+    engine::Score,
+    gen::{IllegalReason, MoveGenerator},
     hash::Hasher,
-    Move, Piece, Square,
+    repetition::PositionHistory,
+    Move, Piece, Player, Promotion, Square,
 };
+use chess_uci::info::SearchInfo;
 use ggez::{
     graphics::{self, Canvas, Color, DrawMode, DrawParam, Drawable, Image, Mesh, Rect, Text},
     input,
@@ -12,29 +16,62 @@ use ggez::{
     mint::Vector2,
     Context, GameResult,
 };
+use std::time::{Duration, Instant};
+
+/// How long the reason for an illegal move is shown.
+const MESSAGE_TIME: Duration = Duration::from_secs(3);
+
+/// The most moves of the principal variation shown below the board.
+const MAX_PV_MOVES: usize = 8;
 
 pub struct RenderBoard {
     hasher: Hasher,
+    move_gen: MoveGenerator,
     pub board: Board,
     selected: Option<Square>,
     possible_moves: Vec<Square>,
     dragging: Option<Square>,
     mov: Option<(Square, Square)>,
+    /// Why the last attempted move was illegal and when it was attempted.
+    illegal: Option<(IllegalReason, Instant)>,
+    /// The square a pawn is promoting on while the player picks the piece to promote to.
+    promotion: Option<Square>,
     rect: Rect,
+    /// The player whose side of the board is drawn at the bottom.
+    pub orientation: Player,
     pub made_moves: Vec<UnmakeMove>,
+    /// The positions of the game, for detecting repetitions.
+    pub history: PositionHistory,
+    /// The clocks of the players if the game is played with a time control.
+    pub clock: Option<Clock>,
+    /// How long the player took for every made move.
+    pub move_times: Vec<Duration>,
+    /// The last search info reported by an engine, with the side to move in the position it
+    /// searched.
+    pub analysis: Option<(Player, SearchInfo)>,
 }
 
 impl RenderBoard {
     pub fn new(board: Board, hasher: Hasher) -> Self {
+        let mut history = PositionHistory::new();
+        history.push(&board);
         RenderBoard {
             hasher,
+            move_gen: MoveGenerator::new(),
             board,
             selected: None,
             dragging: None,
             possible_moves: Vec::new(),
             mov: None,
+            illegal: None,
+            promotion: None,
             rect: Rect::zero(),
+            orientation: Player::White,
             made_moves: Vec::new(),
+            history,
+            clock: None,
+            move_times: Vec::new(),
+            analysis: None,
         }
     }
 
@@ -45,10 +82,32 @@ impl RenderBoard {
         within: Rect,
         sprite: &Image,
     ) -> GameResult<()> {
-        let max_size = within.w.min(within.h);
+        // Leave room for the clocks above and below the board.
+        let clock_height = if self.clock.is_some() {
+            within.h / 16.0
+        } else {
+            0.0
+        };
+        // Leave room for the evaluation bar left of the board and the search info below it.
+        let (bar_width, info_height) = if self.analysis.is_some() {
+            (within.w / 32.0, within.h / 24.0)
+        } else {
+            (0.0, 0.0)
+        };
+        let max_size =
+            (within.w - 4.0 * bar_width).min(within.h - 2.0 * clock_height - info_height);
         let offset_x = (within.w - max_size).max(0.0) / 2.0;
-        let offset_y = (within.h - max_size).max(0.0) / 2.0;
+        let offset_y = (within.h - max_size - info_height).max(0.0) / 2.0;
         let square_size = max_size / 8.0;
+        let orientation = self.orientation;
+        // Returns the top left corner of a square on the screen
+        let square_pos = move |s: Square| {
+            let (column, row) = s.to_view(orientation);
+            (
+                offset_x + square_size * column as f32,
+                offset_y + square_size * row as f32,
+            )
+        };
 
         // Draw the board
         for i in 0..8 {
@@ -73,7 +132,7 @@ impl RenderBoard {
 
                 if self
                     .possible_moves
-                    .contains(&Square::from_file_rank(i, 7 - j))
+                    .contains(&Square::from_view(i, j, orientation))
                 {
                     let mut color = Color::from_rgb_u32(0x98971a);
                     color.a = 0.8;
@@ -95,7 +154,8 @@ impl RenderBoard {
         let letter_offset_x = offset_x + square_size - (square_size / 6.0) - (square_size / 32.0);
         let letter_offset_y = offset_y + square_size - (square_size / 6.0) - (square_size / 32.0);
         for i in 0..8 {
-            let mut text = Text::new(('A' as u8 + i) as char);
+            let file = Square::from_view(i, 7, orientation).file();
+            let mut text = Text::new(('A' as u8 + file) as char);
             text.set_scale(square_size / 6.0);
             let x = letter_offset_x + square_size * i as f32;
             let y = letter_offset_y + square_size * 7 as f32;
@@ -110,7 +170,8 @@ impl RenderBoard {
         let letter_offset_x = offset_x + (square_size / 16.0);
         let letter_offset_y = offset_y + (square_size / 16.0);
         for i in 0..8 {
-            let mut text = Text::new(('8' as u8 - i) as char);
+            let rank = Square::from_view(0, i, orientation).rank();
+            let mut text = Text::new(('1' as u8 + rank) as char);
             text.set_scale(square_size / 6.0);
             let x = letter_offset_x;
             let y = letter_offset_y + square_size * i as f32;
@@ -124,8 +185,7 @@ impl RenderBoard {
 
         // Draw previous move
         if let Some((from, to)) = self.mov {
-            let x = offset_x + square_size * from.file() as f32;
-            let y = offset_y + square_size * (7 - from.rank()) as f32;
+            let (x, y) = square_pos(from);
             let color = Color::from_rgb_u32(0x98971a);
 
             let rect = Rect {
@@ -138,8 +198,7 @@ impl RenderBoard {
             let rect = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color)?;
             graphics::draw(canvas, &rect, DrawParam::new());
 
-            let x = offset_x + square_size * to.file() as f32;
-            let y = offset_y + square_size * (7 - to.rank()) as f32;
+            let (x, y) = square_pos(to);
 
             let rect = Rect {
                 x,
@@ -152,9 +211,36 @@ impl RenderBoard {
             graphics::draw(canvas, &rect, DrawParam::new());
         }
 
+        // Draw the rays of pieces checking the king and outline pinned pieces
+        let info = self.move_gen.gen_info(&self.board);
+        let mut color = Color::from_rgb_u32(0xcc241d);
+        color.a = 0.5;
+        for s in self.move_gen.check_rays(&self.board, &info) {
+            let (x, y) = square_pos(s);
+            let rect = Rect {
+                x,
+                y,
+                w: square_size,
+                h: square_size,
+            };
+            let rect = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color)?;
+            canvas.draw(&rect, DrawParam::new());
+        }
+        let color = Color::from_rgb_u32(0x458588);
+        for s in self.move_gen.pinned_pieces(&self.board, &info) {
+            let (x, y) = square_pos(s);
+            let rect = Rect {
+                x,
+                y,
+                w: square_size,
+                h: square_size,
+            };
+            let rect = Mesh::new_rectangle(ctx, DrawMode::stroke(square_size / 16.0), rect, color)?;
+            canvas.draw(&rect, DrawParam::new());
+        }
+
         if let Some(s) = self.selected {
-            let x = offset_x + square_size * s.file() as f32;
-            let y = offset_y + square_size * (7 - s.rank()) as f32;
+            let (x, y) = square_pos(s);
             let color = Color::from_rgb_u32(0xd65d0e);
 
             let rect = Rect {
@@ -168,6 +254,25 @@ impl RenderBoard {
             graphics::draw(canvas, &rect, DrawParam::new());
         }
 
+        if let Some((reason, time)) = self.illegal {
+            if time.elapsed() > MESSAGE_TIME {
+                self.illegal = None;
+            } else if let Some(s) = reason.attacker() {
+                let (x, y) = square_pos(s);
+                let color = Color::from_rgb_u32(0xcc241d);
+
+                let rect = Rect {
+                    x,
+                    y,
+                    w: square_size,
+                    h: square_size,
+                };
+
+                let rect = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color)?;
+                graphics::draw(canvas, &rect, DrawParam::new());
+            }
+        }
+
         // Draw all pieces except one that is dragged
         let exclude = if let Some(x) = self.dragging {
             BB::square(x)
@@ -178,10 +283,7 @@ impl RenderBoard {
         for piece in 0..12 {
             let param = piece_to_param(piece, [square_size, square_size], &sprite, ctx);
             for p in (self.board.pieces[Piece::from_u8(piece)] & !exclude).iter() {
-                let i = p.file();
-                let j = 7 - p.rank();
-                let x = offset_x + square_size * i as f32;
-                let y = offset_y + square_size * j as f32;
+                let (x, y) = square_pos(p);
                 canvas.draw(sprite, param.dest([x, y]));
             }
         }
@@ -199,6 +301,158 @@ impl RenderBoard {
             canvas.draw(sprite, param.dest(pos));
         }
 
+        // Draw the pieces to choose from for a promotion
+        if let Some(to) = self.promotion {
+            for (s, promotion) in Self::promotion_squares(to) {
+                let (x, y) = square_pos(s);
+                let rect = Rect {
+                    x,
+                    y,
+                    w: square_size,
+                    h: square_size,
+                };
+                let rect = Mesh::new_rectangle(
+                    ctx,
+                    DrawMode::fill(),
+                    rect,
+                    Color::from_rgb_u32(0xebdbb2),
+                )?;
+                canvas.draw(&rect, DrawParam::new());
+                let piece = promotion.piece(self.board.state.player) as u8;
+                let param = piece_to_param(piece, [square_size, square_size], &sprite, ctx);
+                canvas.draw(sprite, param.dest([x, y]));
+            }
+        }
+
+        // Draw why the last attempted move was illegal
+        if let Some((reason, _)) = self.illegal {
+            let mut text = Text::new(reason.to_string());
+            text.set_scale(square_size / 4.0);
+            let size = text.measure(ctx)?;
+            let x = offset_x + (max_size - size.x) / 2.0;
+            let y = offset_y + (max_size - size.y) / 2.0;
+            let background = Rect {
+                x: x - square_size / 8.0,
+                y: y - square_size / 8.0,
+                w: size.x + square_size / 4.0,
+                h: size.y + square_size / 4.0,
+            };
+            let background = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                background,
+                Color::from_rgb_u32(0x282828),
+            )?;
+            canvas.draw(&background, DrawParam::new());
+            canvas.draw(
+                &text,
+                DrawParam::new()
+                    .dest([x, y])
+                    .color(Color::from_rgb_u32(0xfb4934)),
+            );
+        }
+
+        // Draw the clocks, the clock of the player at the bottom of the board below it
+        if let Some(clock) = self.clock.as_ref() {
+            let now = Instant::now();
+            let flagged = clock.flagged(now);
+            for (player, y) in [
+                (orientation.flip(), offset_y - clock_height),
+                (orientation, offset_y + max_size),
+            ] {
+                let mut text = Text::new(format_time(clock.remaining(player, now)));
+                text.set_scale(clock_height * 0.8);
+                let size = text.measure(ctx)?;
+                let color = if flagged == Some(player) {
+                    Color::from_rgb_u32(0xfb4934)
+                } else if clock.running() == Some(player) {
+                    Color::from_rgb_u32(0xebdbb2)
+                } else {
+                    Color::from_rgb_u32(0x928374)
+                };
+                let x = offset_x + max_size - size.x;
+                let y = y + (clock_height - size.y) / 2.0;
+                canvas.draw(&text, DrawParam::new().dest([x, y]).color(color));
+
+                // The time the player took for their last move, on the other side of the clock.
+                if let Some(time) = self.last_move_time(player) {
+                    let mut text = Text::new(format!("last move {}", format_time(time)));
+                    text.set_scale(clock_height * 0.5);
+                    let size = text.measure(ctx)?;
+                    let y = y + (clock_height * 0.8 - size.y) / 2.0;
+                    canvas.draw(
+                        &text,
+                        DrawParam::new()
+                            .dest([offset_x, y])
+                            .color(Color::from_rgb_u32(0x928374)),
+                    );
+                }
+            }
+        }
+
+        // Draw the evaluation bar, filled with white from white's side by the expected score of
+        // white, and the depth, score and principal variation of the search
+        if let Some((side_to_move, info)) = self.analysis.as_ref() {
+            let score = info.white_score(*side_to_move);
+            let x = offset_x - 2.0 * bar_width;
+            let background = Rect {
+                x,
+                y: offset_y,
+                w: bar_width,
+                h: max_size,
+            };
+            let background = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                background,
+                Color::from_rgb_u32(0x282828),
+            )?;
+            canvas.draw(&background, DrawParam::new());
+            if let Some(score) = score {
+                let height = max_size * white_fraction(score);
+                if height > 0.0 {
+                    let y = match orientation {
+                        Player::White => offset_y + max_size - height,
+                        Player::Black => offset_y,
+                    };
+                    let bar = Rect {
+                        x,
+                        y,
+                        w: bar_width,
+                        h: height,
+                    };
+                    let bar = Mesh::new_rectangle(
+                        ctx,
+                        DrawMode::fill(),
+                        bar,
+                        Color::from_rgb_u32(0xebdbb2),
+                    )?;
+                    canvas.draw(&bar, DrawParam::new());
+                }
+            }
+
+            let mut line = String::new();
+            if let Some(depth) = info.depth {
+                line.push_str(&format!("depth {} ", depth));
+            }
+            if let Some(score) = score {
+                line.push_str(&format!("{} ", score));
+            }
+            for m in info.pv.iter().take(MAX_PV_MOVES) {
+                line.push_str(&format!("{} ", m));
+            }
+            let mut text = Text::new(line.trim_end());
+            text.set_scale(info_height * 0.6);
+            let size = text.measure(ctx)?;
+            let y = offset_y + max_size + clock_height + (info_height - size.y) / 2.0;
+            canvas.draw(
+                &text,
+                DrawParam::new()
+                    .dest([offset_x, y])
+                    .color(Color::from_rgb_u32(0xebdbb2)),
+            );
+        }
+
         self.rect = Rect {
             x: offset_x,
             y: offset_y,
@@ -208,6 +462,11 @@ impl RenderBoard {
         Ok(())
     }
 
+    /// Turn the board around, showing the side of the other player at the bottom.
+    pub fn flip(&mut self) {
+        self.orientation = self.orientation.flip();
+    }
+
     pub fn set_possible(&mut self, moves: Vec<Square>) {
         self.possible_moves = moves;
     }
@@ -216,11 +475,48 @@ impl RenderBoard {
         let m = self.board.make_move(mov);
 
         self.made_moves.push(m);
+        self.history.push(&self.board);
         self.highlight(mov.from(), mov.to());
         self.possible_moves.clear();
+        self.illegal = None;
         self.clear_drag();
     }
 
+    /// Show why an attempted move is illegal for a short while.
+    pub fn show_illegal(&mut self, reason: IllegalReason) {
+        self.illegal = Some((reason, Instant::now()));
+    }
+
+    /// Show the pieces a pawn moving to the given square can promote to.
+    pub fn show_promotion(&mut self, to: Square) {
+        self.promotion = Some(to);
+    }
+
+    /// Returns the promotion shown at the given mouse position and stops showing the choices.
+    pub fn pick_promotion(&mut self, pos: impl Into<Point2<f32>>) -> Option<Promotion> {
+        let to = self.promotion.take()?;
+        let square = self.square(pos)?;
+        Self::promotion_squares(to)
+            .find(|(s, _)| *s == square)
+            .map(|(_, promotion)| promotion)
+    }
+
+    /// The squares the promotion choices are shown on, from the promotion square towards the
+    /// center of the board.
+    fn promotion_squares(to: Square) -> impl Iterator<Item = (Square, Promotion)> {
+        Promotion::ALL
+            .iter()
+            .enumerate()
+            .map(move |(i, promotion)| {
+                let rank = if to.rank() == 7 {
+                    to.rank() - i as u8
+                } else {
+                    to.rank() + i as u8
+                };
+                (Square::from_file_rank(to.file(), rank), *promotion)
+            })
+    }
+
     /// Returns the square on the board for a specific mouse position
     pub fn square(&mut self, pos: impl Into<Point2<f32>>) -> Option<Square> {
         let pos = pos.into();
@@ -228,11 +524,10 @@ impl RenderBoard {
             return None;
         }
 
-        let file = ((pos.x - self.rect.x) / (self.rect.w / 8.0)) as u8;
-        let rank = ((pos.y - self.rect.y) / (self.rect.h / 8.0)) as u8;
-        let rank = 7 - rank;
+        let column = ((pos.x - self.rect.x) / (self.rect.w / 8.0)) as u8;
+        let row = ((pos.y - self.rect.y) / (self.rect.h / 8.0)) as u8;
 
-        let s = Square::from_file_rank(file, rank);
+        let s = Square::from_view(column.min(7), row.min(7), self.orientation);
         println!("{}", s);
         Some(s)
     }
@@ -243,6 +538,7 @@ impl RenderBoard {
         }
         if let Some(x) = self.made_moves.pop() {
             self.board.unmake_move(x);
+            self.history.pop();
         }
         /*
         if let Some(x) = self.made_moves.pop() {
@@ -291,6 +587,35 @@ impl RenderBoard {
     pub fn clear_drag(&mut self) {
         self.dragging = None;
     }
+
+    /// Returns how long the given player took for their last move.
+    pub fn last_move_time(&self, player: Player) -> Option<Duration> {
+        // The player to move didn't make the last move.
+        let back = if self.board.state.player == player {
+            2
+        } else {
+            1
+        };
+        self.move_times
+            .len()
+            .checked_sub(back)
+            .map(|x| self.move_times[x])
+    }
+}
+
+/// Format the time left on a clock as minutes, seconds and tenths of a second.
+fn format_time(time: Duration) -> String {
+    let tenths = time.as_millis() / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Returns the part of the evaluation bar filled for white, from the score of white.
+fn white_fraction(score: Score) -> f32 {
+    match score {
+        Score::Cp(x) => 1.0 / (1.0 + 10f32.powf(-x as f32 / 400.0)),
+        Score::Mate(x) if x > 0 => 1.0,
+        Score::Mate(_) => 0.0,
+    }
 }
 
 fn color_black() -> Color {
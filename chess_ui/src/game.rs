@@ -1,13 +1,23 @@
-use chess_core::{board::Board, hash::Hasher, Player as PlayerColor};
+use chess_core::{
+    board::Board, clock::Clock, gen::MoveGenerator, hash::Hasher, Player as PlayerColor,
+};
+use chess_uci::{
+    info::EvalHistory,
+    runner::{self, GameOutcome, Termination},
+};
 use ggez::{
     audio::{SoundSource, Source},
     event::{EventHandler, MouseButton},
-    graphics::{self, Canvas, Color, Image, Rect},
-    input::keyboard::KeyInput,
+    graphics::{self, Canvas, Color, DrawMode, DrawParam, Image, Mesh, Rect},
+    input::keyboard::{KeyCode, KeyInput},
     Context, GameResult,
 };
+use std::time::Instant;
 
-use crate::{player::Player, RenderBoard};
+use crate::{
+    player::{Player, PlayerKind},
+    RenderBoard,
+};
 
 #[derive(Eq, PartialEq, Debug)]
 pub enum PlayedMove {
@@ -16,6 +26,20 @@ pub enum PlayedMove {
     Castle,
 }
 
+/// Returns the message printed when a game ended with the given result.
+pub fn result_message(result: GameOutcome, termination: Termination) -> &'static str {
+    match (result, termination) {
+        (GameOutcome::WhiteWon, Termination::TimeForfeit) => "WHITE WON ON TIME",
+        (GameOutcome::BlackWon, Termination::TimeForfeit) => "BLACK WON ON TIME",
+        (GameOutcome::WhiteWon, _) => "WHITE WON",
+        (GameOutcome::BlackWon, _) => "BLACK WON",
+        (GameOutcome::Drawn, Termination::Stalemate) => "DRAWN BY STALEMATE",
+        (GameOutcome::Drawn, Termination::Repetition) => "DRAWN BY THREEFOLD REPETITION",
+        (GameOutcome::Drawn, Termination::FiftyMoves) => "DRAWN BY THE FIFTY-MOVE RULE",
+        (GameOutcome::Drawn, _) => "DRAWN",
+    }
+}
+
 pub struct Chess {
     board: RenderBoard,
     piece_sprite: Image,
@@ -24,7 +48,21 @@ pub struct Chess {
     play_move: PlayedMove,
     white: Box<dyn Player>,
     black: Box<dyn Player>,
+    /// The players which can be swapped in during the game.
+    choices: Vec<PlayerKind>,
+    white_choice: Option<usize>,
+    black_choice: Option<usize>,
+    search_time: f32,
     resized: Option<Rect>,
+    /// How the game ended, if it has.
+    result: Option<(GameOutcome, Termination)>,
+    move_gen: MoveGenerator,
+    /// The scores the engines reported for their moves.
+    evals: EvalHistory,
+    /// Where the chart of the scores was last drawn.
+    eval_chart: Option<Rect>,
+    /// When the player to move was asked for their move.
+    turn_start: Instant,
 }
 
 impl Chess {
@@ -34,8 +72,15 @@ impl Chess {
         hasher: Hasher,
         mut white: Box<dyn Player>,
         mut black: Box<dyn Player>,
+        choices: Vec<PlayerKind>,
+        search_time: f32,
+        clock: Option<Clock>,
     ) -> Chess {
-        let board = RenderBoard::new(board, hasher);
+        let mut board = RenderBoard::new(board, hasher);
+        board.clock = clock;
+        if let Some(clock) = board.clock.as_mut() {
+            clock.start(board.board.state.player, Instant::now());
+        }
         match board.board.state.player {
             PlayerColor::White => white.start_turn(&board),
             PlayerColor::Black => black.start_turn(&board),
@@ -48,13 +93,120 @@ impl Chess {
             white,
             board,
             black,
+            choices,
+            white_choice: None,
+            black_choice: None,
+            search_time,
             resized: None,
+            result: None,
+            move_gen: MoveGenerator::new(),
+            evals: EvalHistory::new(),
+            eval_chart: None,
+            turn_start: Instant::now(),
         }
     }
 
+    /// Called after a player made a move, hands the turn to the other player.
+    fn moved(&mut self) {
+        println!("FEN: {}", self.board.board.to_fen());
+        let mover = self.board.board.state.player.flip();
+        let score = match mover {
+            PlayerColor::White => self.white.last_score(),
+            PlayerColor::Black => self.black.last_score(),
+        };
+        if let Some(score) = score {
+            self.evals.push(self.board.made_moves.len(), mover, score);
+        }
+        let now = Instant::now();
+        let time = now.saturating_duration_since(self.turn_start);
+        self.board.move_times.push(time);
+        self.turn_start = now;
+        if let Some(clock) = self.board.clock.as_mut() {
+            clock.press(now);
+        }
+        if self.check_flag() {
+            return;
+        }
+        if let Some((result, termination)) =
+            runner::game_outcome(&self.move_gen, &self.board.board, &self.board.history)
+        {
+            self.finish(result, termination);
+            return;
+        }
+        if self.white_turn() {
+            self.white.start_turn(&self.board);
+        } else {
+            self.black.start_turn(&self.board);
+        }
+    }
+
+    /// End the game if a player ran out of time, returns whether the game has ended.
+    fn check_flag(&mut self) -> bool {
+        let now = Instant::now();
+        let Some(clock) = self.board.clock.as_mut() else {
+            return false;
+        };
+        let Some(flagged) = clock.flagged(now) else {
+            return false;
+        };
+        self.white.shutdown();
+        self.black.shutdown();
+        self.board.clear_select();
+        self.board.clear_drag();
+        self.finish(GameOutcome::lost_by(flagged), Termination::TimeForfeit);
+        true
+    }
+
+    /// End the game with the given result.
+    fn finish(&mut self, result: GameOutcome, termination: Termination) {
+        println!("{}", result_message(result, termination));
+        if let Some(clock) = self.board.clock.as_mut() {
+            clock.stop(Instant::now());
+        }
+        self.result = Some((result, termination));
+    }
+
     fn white_turn(&self) -> bool {
         self.board.board.state.player == PlayerColor::White
     }
+
+    /// Replace the player of the given color with the next choice, keeping the game played so
+    /// far.
+    fn swap_player(&mut self, color: PlayerColor) {
+        if self.choices.is_empty() {
+            return;
+        }
+        let len = self.choices.len();
+        let choice = match color {
+            PlayerColor::White => &mut self.white_choice,
+            PlayerColor::Black => &mut self.black_choice,
+        };
+        let next = choice.map(|x| (x + 1) % len).unwrap_or(0);
+        *choice = Some(next);
+
+        let kind = &self.choices[next];
+        let new = match kind.build(self.search_time) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("failed to load player {}: {:?}", kind.name(), e);
+                return;
+            }
+        };
+        println!("{:?} is now played by {}", color, kind.name());
+
+        let player = match color {
+            PlayerColor::White => &mut self.white,
+            PlayerColor::Black => &mut self.black,
+        };
+        player.shutdown();
+        *player = new;
+        self.board.clear_select();
+        self.board.clear_drag();
+        if self.board.board.state.player == color && self.result.is_none() {
+            player.start_turn(&self.board);
+            self.turn_start = Instant::now();
+        }
+    }
 }
 
 impl EventHandler for Chess {
@@ -72,6 +224,10 @@ impl EventHandler for Chess {
             }
         }
 
+        if self.result.is_some() || self.check_flag() {
+            return Ok(());
+        }
+
         self.play_move = if self.white_turn() {
             self.white.update(&mut self.board)
         } else {
@@ -79,12 +235,7 @@ impl EventHandler for Chess {
         };
 
         if self.play_move != PlayedMove::Didnt {
-            println!("FEN: {}", self.board.board.to_fen());
-            if self.white_turn() {
-                self.white.start_turn(&self.board);
-            } else {
-                self.black.start_turn(&self.board);
-            }
+            self.moved();
         }
 
         match self.play_move {
@@ -108,10 +259,25 @@ impl EventHandler for Chess {
         if let Some(x) = self.resized.take() {
             canvas.set_screen_coordinates(x);
         }
-        let Some(coords) = canvas.screen_coordinates() else {
+        let Some(mut coords) = canvas.screen_coordinates() else {
             canvas.finish(ctx)?;
             return Ok(());
         };
+        // Leave room for the chart of the scores below the board.
+        self.eval_chart = None;
+        if !self.evals.is_empty() {
+            let height = coords.h / 6.0;
+            coords.h -= height;
+            let margin = height / 8.0;
+            let chart = Rect {
+                x: coords.x + margin,
+                y: coords.y + coords.h + margin,
+                w: coords.w - 2.0 * margin,
+                h: height - 2.0 * margin,
+            };
+            draw_eval_chart(ctx, &mut canvas, chart, &self.evals)?;
+            self.eval_chart = Some(chart);
+        }
         self.board
             .draw(ctx, &mut canvas, coords, &self.piece_sprite)?;
 
@@ -128,11 +294,29 @@ impl EventHandler for Chess {
         let Some(keycode) = _input.keycode else {
             return Ok(());
         };
+        match keycode {
+            KeyCode::F1 => {
+                self.swap_player(PlayerColor::White);
+                return Ok(());
+            }
+            KeyCode::F2 => {
+                self.swap_player(PlayerColor::Black);
+                return Ok(());
+            }
+            KeyCode::F => {
+                self.board.flip();
+                return Ok(());
+            }
+            _ => {}
+        }
         if self.white_turn() {
             self.white.key_down(&mut self.board, keycode);
         } else {
             self.black.key_down(&mut self.board, keycode);
         }
+        // Forget the scores and times of moves which were taken back.
+        self.evals.truncate(self.board.made_moves.len());
+        self.board.move_times.truncate(self.board.made_moves.len());
         Ok(())
     }
 
@@ -143,6 +327,24 @@ impl EventHandler for Chess {
         x: f32,
         y: f32,
     ) -> GameResult<()> {
+        if let Some(chart) = self.eval_chart.filter(|r| r.contains([x, y])) {
+            if let Some(sample) = self.evals.nearest((x - chart.x) / chart.w) {
+                let player = match sample.player {
+                    PlayerColor::White => "white",
+                    PlayerColor::Black => "black",
+                };
+                println!(
+                    "move {} by {}: {:.2}",
+                    sample.move_number(),
+                    player,
+                    sample.score as f32 / 100.0
+                );
+            }
+            return Ok(());
+        }
+        if self.result.is_some() {
+            return Ok(());
+        }
         if self.white_turn() {
             self.white
                 .mouse_button_down_event(button, x, y, &mut self.board);
@@ -160,6 +362,9 @@ impl EventHandler for Chess {
         x: f32,
         y: f32,
     ) -> GameResult<()> {
+        if self.result.is_some() {
+            return Ok(());
+        }
         self.play_move = if self.white_turn() {
             self.white
                 .mouse_button_up_event(button, x, y, &mut self.board)
@@ -169,12 +374,7 @@ impl EventHandler for Chess {
         };
 
         if self.play_move != PlayedMove::Didnt {
-            println!("FEN: {}", self.board.board.to_fen());
-            if self.white_turn() {
-                self.white.start_turn(&self.board);
-            } else {
-                self.black.start_turn(&self.board);
-            }
+            self.moved();
         }
         Ok(())
     }
@@ -206,3 +406,51 @@ impl EventHandler for Chess {
         Ok(())
     }
 }
+
+/// Draw a line chart of the scores within the given rectangle, with a point for every move
+/// colored by the player who made it.
+fn draw_eval_chart(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    within: Rect,
+    evals: &EvalHistory,
+) -> GameResult<()> {
+    let background =
+        Mesh::new_rectangle(ctx, DrawMode::fill(), within, Color::from_rgb_u32(0x282828))?;
+    canvas.draw(&background, DrawParam::new());
+
+    let middle = within.y + within.h / 2.0;
+    let zero = Mesh::new_line(
+        ctx,
+        &[[within.x, middle], [within.x + within.w, middle]],
+        1.0,
+        Color::from_rgb_u32(0x928374),
+    )?;
+    canvas.draw(&zero, DrawParam::new());
+
+    let to_screen = |(x, y): (f32, f32)| [within.x + x * within.w, within.y + y * within.h];
+    let color = |player| match player {
+        PlayerColor::White => Color::from_rgb_u32(0xebdbb2),
+        PlayerColor::Black => Color::from_rgb_u32(0xd65d0e),
+    };
+    let samples = evals.samples();
+    for pair in samples.windows(2) {
+        let from = to_screen(evals.point(&pair[0]));
+        let to = to_screen(evals.point(&pair[1]));
+        let line = Mesh::new_line(ctx, &[from, to], 2.0, color(pair[1].player))?;
+        canvas.draw(&line, DrawParam::new());
+    }
+    let radius = (within.h / 32.0).max(2.0);
+    for sample in samples {
+        let point = Mesh::new_circle(
+            ctx,
+            DrawMode::fill(),
+            to_screen(evals.point(sample)),
+            radius,
+            0.1,
+            color(sample.player),
+        )?;
+        canvas.draw(&point, DrawParam::new());
+    }
+    Ok(())
+}
@@ -3,27 +3,53 @@
 use chess_alpha_beta::AlphaBeta;
 use chess_core::{
     board::{Board, EndChain},
+    clock::Clock,
     gen::MoveGenerator,
     hash::Hasher,
+    repetition::PositionHistory,
 };
 use chess_mcts::Mcts;
+use chess_uci::runner;
 use ggez::{
     conf::{WindowMode, WindowSetup},
     event, graphics, ContextBuilder,
 };
-use std::{env, path};
+use std::{
+    env,
+    path::{self, PathBuf},
+    time::Duration,
+};
 use structopt::StructOpt;
 
 mod board;
 mod game;
 use board::RenderBoard;
 mod player;
-use player::{MousePlayer, Player, RandomPlayer, ThreadedEval};
+use player::{
+    find_engines, ExternalPlayer, LineSource, MousePlayer, Player, PlayerKind, RandomPlayer,
+    ThreadedEval,
+};
 
 #[derive(Debug, StructOpt)]
 pub struct Opt {
     #[structopt(short, long)]
     self_play: bool,
+    /// Directory containing UCI engines which can be swapped in during the game.
+    #[structopt(long, default_value = "engines")]
+    engines: PathBuf,
+    /// Time in seconds engines spend on a move.
+    #[structopt(long, default_value = "2.0")]
+    search_time: f32,
+    /// Time in seconds each player has for the game, without a limit if not given.
+    #[structopt(long)]
+    time: Option<f32>,
+    /// Time in seconds added to the clock of a player after each of their moves.
+    #[structopt(long, default_value = "0.0")]
+    increment: f32,
+    /// White plays the moves in UCI notation read from this file, a named pipe or `-` for
+    /// standard input, for example from the bridge to an electronic board.
+    #[structopt(long)]
+    external: Option<PathBuf>,
     fen: Option<String>,
 }
 
@@ -40,36 +66,63 @@ fn main() {
 
     let hasher = Hasher::new();
 
-    let board = if let Some(x) = args.fen {
-        Board::from_fen(&x, EndChain).unwrap()
+    // Positions typed in by hand are fixed up where possible.
+    let board = if let Some(x) = args.fen.as_deref() {
+        match Board::from_fen_lenient(x, EndChain) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("invalid fen: {}", e);
+                return;
+            }
+        }
     } else {
         Board::start_position(EndChain)
     };
 
-    let mov_gen = MoveGenerator::new();
-    let info = mov_gen.gen_info(&board);
-    if mov_gen.check_mate(&board, &info) {
-        if board.state.player == chess_core::Player::White {
-            println!("BLACK WON");
-            return;
-        } else {
-            println!("WHITE WON");
-            return;
-        }
-    }
-    if mov_gen.drawn(&board, &info) {
-        println!("DRAWN");
+    let mut history = PositionHistory::new();
+    history.push(&board);
+    if let Some((result, termination)) =
+        runner::game_outcome(&MoveGenerator::new(), &board, &history)
+    {
+        println!("{}", game::result_message(result, termination));
         return;
     }
 
     //let white = Box::new(MousePlayer::new());
-    let white = Box::new(ThreadedEval::new(2.0, chess_alpha_beta::AlphaBeta::new()));
+    let white: Box<dyn Player> = if let Some(path) = args.external.as_ref() {
+        match LineSource::open(path) {
+            Ok(x) => Box::new(ExternalPlayer::new(Box::new(x))),
+            Err(e) => {
+                println!("{:?}", e);
+                return;
+            }
+        }
+    } else {
+        Box::new(ThreadedEval::new(
+            args.search_time,
+            chess_alpha_beta::AlphaBeta::new(),
+        ))
+    };
     let black: Box<dyn Player> = if args.self_play {
         Box::new(MousePlayer::new())
     } else {
-        Box::new(ThreadedEval::new(2.0, chess_alpha_beta::AlphaBeta::new()))
+        Box::new(ThreadedEval::new(
+            args.search_time,
+            chess_alpha_beta::AlphaBeta::new(),
+        ))
     };
 
+    // F1 and F2 cycle the white and black player through these.
+    let mut choices = vec![PlayerKind::Mouse, PlayerKind::AlphaBeta];
+    choices.extend(find_engines(&args.engines).into_iter().map(PlayerKind::Uci));
+
+    let clock = args.time.map(|x| {
+        Clock::new(
+            Duration::from_secs_f32(x),
+            Duration::from_secs_f32(args.increment),
+        )
+    });
+
     // Make a Context.
     let (mut ctx, event_loop) = ContextBuilder::new("Chess", "Mees Delzenne")
         .add_resource_path(resource_dir)
@@ -81,7 +134,16 @@ fn main() {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let my_game = game::Chess::new(&mut ctx, board, hasher, white, black);
+    let my_game = game::Chess::new(
+        &mut ctx,
+        board,
+        hasher,
+        white,
+        black,
+        choices,
+        args.search_time,
+        clock,
+    );
 
     // Run!
     event::run(ctx, event_loop, my_game)
@@ -38,6 +38,17 @@ impl ThreadedEval {
                 x.iter().for_each(|x| print!("{} ", x));
                 println!();
             }
+            Info::Stats {
+                depth,
+                nodes,
+                score,
+                pv,
+                ..
+            } => {
+                print!("{}: {} ({} nodes) PV: ", depth, score, nodes);
+                pv.iter().for_each(|x| print!("{} ", x));
+                println!();
+            }
             _ => {}
         }
     }
@@ -90,7 +101,17 @@ impl Player for ThreadedEval {
             self.board.copy_position(&board.board);
             self.manager.set_board(board.board.clone());
         }
+        let time_left = board
+            .clock
+            .as_ref()
+            .map(|x| x.remaining(board.board.state.player, Instant::now()));
         self.manager
-            .start(None, EngineLimit::time(self.search_time));
+            .start(time_left, EngineLimit::time(self.search_time));
+    }
+
+    fn shutdown(&mut self) {
+        if self.time.take().is_some() {
+            self.manager.stop();
+        }
     }
 }
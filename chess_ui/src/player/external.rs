@@ -0,0 +1,176 @@
+use super::{play_move, Player};
+use crate::{board::RenderBoard, game::PlayedMove};
+use anyhow::{Context, Result};
+use chess_core::{
+    board::Board,
+    gen::{gen_type, MoveGenerator},
+    Move,
+};
+use crossbeam_channel::Receiver;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    thread,
+};
+
+/// A source of moves from outside of the program, like an electronic board.
+pub trait MoveSource {
+    /// Returns the move to play in the position if one was made, called every frame while it
+    /// is the turn of the player.
+    fn poll_move(&mut self, board: &Board) -> Option<Move>;
+
+    /// Called with the move of the opponent when the turn of the player starts.
+    fn notify_opponent_move(&mut self, m: Move);
+}
+
+/// A player making the moves of a [`MoveSource`].
+///
+/// Moves which are not legal in the position are logged and ignored, so a source can't make
+/// the game go out of sync.
+pub struct ExternalPlayer {
+    source: Box<dyn MoveSource>,
+    move_gen: MoveGenerator,
+    possible_moves: Vec<Move>,
+}
+
+impl ExternalPlayer {
+    pub fn new(source: Box<dyn MoveSource>) -> Self {
+        ExternalPlayer {
+            source,
+            move_gen: MoveGenerator::new(),
+            possible_moves: Vec::new(),
+        }
+    }
+}
+
+impl Player for ExternalPlayer {
+    fn start_turn(&mut self, board: &RenderBoard) {
+        self.possible_moves.clear();
+        self.move_gen
+            .gen_moves::<gen_type::All, _, _>(&board.board, &mut self.possible_moves);
+        if let Some(x) = board.made_moves.last() {
+            self.source.notify_opponent_move(x.mov);
+        }
+    }
+
+    fn update(&mut self, board: &mut RenderBoard) -> PlayedMove {
+        let Some(m) = self.source.poll_move(&board.board) else {
+            return PlayedMove::Didnt;
+        };
+        if !self.possible_moves.contains(&m) {
+            println!("rejected external move {}, it is not legal", m);
+            return PlayedMove::Didnt;
+        }
+        board.clear_select();
+        play_move(board, m)
+    }
+}
+
+/// Reads moves in UCI notation, one per line, from standard input or a file.
+///
+/// Lines are read on a separate thread. Lines which arrive while it is not the turn of the
+/// player are discarded when its turn starts. A named pipe is opened again whenever its writer
+/// closes it, so a bridge to a board can be restarted during the game.
+pub struct LineSource {
+    lines: Receiver<String>,
+    move_gen: MoveGenerator,
+}
+
+impl LineSource {
+    /// Read from the file at the path, or from standard input if the path is `-`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if path == Path::new("-") {
+            // The lock of standard input can't be sent to the reading thread.
+            return Ok(Self::from_reader(BufReader::new(io::stdin())));
+        }
+
+        // Opening a named pipe blocks until there is a writer, so only check it exists here.
+        path.metadata()
+            .with_context(|| format!("failed to open `{}`", path.display()))?;
+        let (send, recv) = crossbeam_channel::unbounded();
+        let path = path.to_path_buf();
+        thread::spawn(move || loop {
+            let file = match File::open(&path) {
+                Ok(x) => x,
+                Err(e) => {
+                    println!("failed to open `{}`: {}", path.display(), e);
+                    return;
+                }
+            };
+            for line in BufReader::new(file).lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                if send.send(line).is_err() {
+                    return;
+                }
+            }
+            if !is_fifo(&path) {
+                return;
+            }
+        });
+        Ok(Self::new(recv))
+    }
+
+    /// Read the lines of the reader.
+    pub fn from_reader<R: BufRead + Send + 'static>(reader: R) -> Self {
+        let (send, recv) = crossbeam_channel::unbounded();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                if send.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+        Self::new(recv)
+    }
+
+    fn new(lines: Receiver<String>) -> Self {
+        LineSource {
+            lines,
+            move_gen: MoveGenerator::new(),
+        }
+    }
+}
+
+impl MoveSource for LineSource {
+    fn poll_move(&mut self, board: &Board) -> Option<Move> {
+        loop {
+            let line = self.lines.try_recv().ok()?;
+            let name = line.trim();
+            if name.is_empty() {
+                continue;
+            }
+            match Move::from_uci_on_board(name, board, &self.move_gen) {
+                Some(m) => return Some(m),
+                None => println!("rejected external move `{}`, it is not legal", name),
+            }
+        }
+    }
+
+    fn notify_opponent_move(&mut self, _: Move) {
+        for line in self.lines.try_iter() {
+            let name = line.trim();
+            if !name.is_empty() {
+                println!("rejected external move `{}`, it was not its turn", name);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    path.metadata()
+        .map(|x| x.file_type().is_fifo())
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_: &Path) -> bool {
+    false
+}
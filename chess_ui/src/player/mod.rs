@@ -1,15 +1,22 @@
 use crate::{board::RenderBoard, game::PlayedMove};
+use anyhow::Result;
 use chess_core::{
     board::Board,
+    engine::Score,
     gen::{gen_type, MoveGenerator},
-    Move, Square,
+    Move, Promotion, Square,
 };
 use ggez::{event::MouseButton, input::keyboard::KeyCode};
+use std::path::PathBuf;
 
 mod eval;
 pub use eval::ThreadedEval;
+mod external;
+pub use external::{ExternalPlayer, LineSource};
 mod random;
 pub use random::RandomPlayer;
+mod uci;
+pub use uci::{find_engines, UciPlayer};
 
 pub trait Player {
     fn update(&mut self, _board: &mut RenderBoard) -> PlayedMove {
@@ -48,6 +55,44 @@ pub trait Player {
     ) -> PlayedMove {
         PlayedMove::Didnt
     }
+
+    /// The score from the perspective of white the player reported for the last move it made.
+    fn last_score(&self) -> Option<Score> {
+        None
+    }
+
+    /// Called before the player is replaced, a search in progress should be stopped and its
+    /// move discarded.
+    fn shutdown(&mut self) {}
+}
+
+/// The kinds of players which can be selected during a game.
+#[derive(Clone, Debug)]
+pub enum PlayerKind {
+    Mouse,
+    AlphaBeta,
+    Uci(PathBuf),
+}
+
+impl PlayerKind {
+    pub fn name(&self) -> String {
+        match self {
+            PlayerKind::Mouse => "mouse".to_string(),
+            PlayerKind::AlphaBeta => "alpha beta".to_string(),
+            PlayerKind::Uci(path) => path.display().to_string(),
+        }
+    }
+
+    pub fn build(&self, search_time: f32) -> Result<Box<dyn Player>> {
+        Ok(match self {
+            PlayerKind::Mouse => Box::new(MousePlayer::new()),
+            PlayerKind::AlphaBeta => Box::new(ThreadedEval::new(
+                search_time,
+                chess_alpha_beta::AlphaBeta::new(),
+            )),
+            PlayerKind::Uci(path) => Box::new(UciPlayer::new(path, search_time)?),
+        })
+    }
 }
 
 pub struct NullPlayer;
@@ -59,6 +104,8 @@ pub struct MousePlayer {
     possible_moves: Vec<Move>,
     holding: Option<Square>,
     dragging: bool,
+    /// The squares of a promotion waiting for the piece to promote to.
+    promoting: Option<(Square, Square)>,
 }
 
 impl MousePlayer {
@@ -68,10 +115,22 @@ impl MousePlayer {
             possible_moves: Vec::new(),
             holding: None,
             dragging: false,
+            promoting: None,
         }
     }
 }
 
+fn play_move(board: &mut RenderBoard, m: Move) -> PlayedMove {
+    board.highlight(m.from(), m.to());
+    board.make_move(m);
+    assert!(board.board.is_valid(), "{:?}", board.board);
+    if m.ty() == Move::TYPE_CASTLE {
+        PlayedMove::Castle
+    } else {
+        PlayedMove::Move
+    }
+}
+
 impl Player for MousePlayer {
     fn start_turn(&mut self, board: &RenderBoard) {
         self.possible_moves.clear();
@@ -114,21 +173,20 @@ impl Player for MousePlayer {
         y: f32,
         board: &mut RenderBoard,
     ) {
+        if self.promoting.is_some() {
+            return;
+        }
         if button == MouseButton::Left {
             if let Some(x) = board.square([x, y]) {
                 if board
-                    .on(x)
-                    .map(|x| x.player() == board.board.state.player)
+                    .board
+                    .piece_at(x)
+                    .map(|(_, player)| player == board.board.state.player)
                     .unwrap_or(false)
                 {
                     board.select(x);
-                    let mut moves = Vec::new();
-                    for m in self.possible_moves.iter() {
-                        if m.from() == x {
-                            moves.push(m.to())
-                        }
-                    }
-                    board.set_possible(moves);
+                    let moves = self.move_gen.legal_destinations(&board.board, x);
+                    board.set_possible(moves.iter().collect());
                     self.holding = Some(x);
                 }
             }
@@ -148,20 +206,33 @@ impl Player for MousePlayer {
             return PlayedMove::Didnt;
         }
 
+        if let Some((from, to)) = self.promoting.take() {
+            // Clicking outside of the choices cancels the promotion.
+            let promotion = board.pick_promotion([x, y]);
+            if let Some(m) = Move::from_squares_in(from, to, promotion, &self.possible_moves) {
+                return play_move(board, m);
+            }
+            board.clear_select();
+            return PlayedMove::Didnt;
+        }
+
         if self.dragging {
             board.clear_drag();
             if let Some(from) = self.holding.take() {
                 if let Some(to) = board.square([x, y]) {
-                    for m in self.possible_moves.iter().copied() {
-                        if m.from() == from && m.to() == to {
-                            board.highlight(from, to);
-                            board.make_move(m);
-                            assert!(board.board.is_valid(), "{:?}", board.board);
-                            if m.ty() == Move::TYPE_CASTLE {
-                                return PlayedMove::Castle;
-                            } else {
-                                return PlayedMove::Move;
-                            }
+                    if let Some(m) = Move::from_squares_in(from, to, None, &self.possible_moves) {
+                        return play_move(board, m);
+                    }
+                    let promotion = Some(Promotion::Queen);
+                    if Move::from_squares_in(from, to, promotion, &self.possible_moves).is_some() {
+                        self.promoting = Some((from, to));
+                        board.show_promotion(to);
+                        return PlayedMove::Didnt;
+                    }
+                    if from != to {
+                        if let Some(reason) = self.move_gen.explain_illegal(&board.board, from, to)
+                        {
+                            board.show_illegal(reason);
                         }
                     }
                 }
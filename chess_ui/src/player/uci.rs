@@ -0,0 +1,260 @@
+use super::Player;
+use crate::{board::RenderBoard, game::PlayedMove};
+use anyhow::{bail, Context, Result};
+use chess_core::{engine::Score, gen::MoveGenerator, Move, Player as Color};
+use chess_uci::info::SearchInfo;
+use crossbeam_channel::{Receiver, RecvTimeoutError, TryRecvError};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A player controlled by an external engine speaking the UCI protocol.
+pub struct UciPlayer {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    gen: MoveGenerator,
+    search_time: Duration,
+    /// Whether the engine is searching and has not yet returned a move.
+    thinking: bool,
+    /// What the engine reported about the search in progress.
+    info: SearchInfo,
+    /// The score from the perspective of white of the last search which returned a move.
+    last_score: Option<Score>,
+    exited: bool,
+}
+
+impl UciPlayer {
+    /// How long to wait on the engine during startup and shutdown.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn new(path: &Path, search_time: f32) -> Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start engine `{}`", path.display()))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (send, lines) = crossbeam_channel::unbounded();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if send.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut player = UciPlayer {
+            name: path
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            child,
+            stdin,
+            lines,
+            gen: MoveGenerator::new(),
+            search_time: Duration::from_secs_f32(search_time),
+            thinking: false,
+            info: SearchInfo::default(),
+            last_score: None,
+            exited: false,
+        };
+        player.send("uci")?;
+        player.wait_for("uciok")?;
+        player.send("isready")?;
+        player.wait_for("readyok")?;
+        Ok(player)
+    }
+
+    fn send(&mut self, line: &str) -> Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    /// Wait for a line starting with the given token, returning the line.
+    fn wait_for(&mut self, token: &str) -> Result<String> {
+        let deadline = Instant::now() + Self::TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(timeout) {
+                Ok(line) => {
+                    if line.split_whitespace().next() == Some(token) {
+                        return Ok(line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    bail!("engine `{}` did not respond with `{}`", self.name, token)
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    bail!("engine `{}` exited", self.name)
+                }
+            }
+        }
+    }
+
+    /// Returns the `position` command for the game played on the board.
+    fn position(board: &RenderBoard) -> String {
+        let mut start = board.board.clone();
+        for m in board.made_moves.iter().rev() {
+            start.unmake_move(*m);
+        }
+        let mut res = format!("position fen {}", start.to_fen());
+        if !board.made_moves.is_empty() {
+            res.push_str(" moves");
+            for m in board.made_moves.iter() {
+                write!(res, " {}", m.mov.to_uci()).unwrap();
+            }
+        }
+        res
+    }
+
+    fn start_search(&mut self, board: &RenderBoard) -> Result<()> {
+        self.send(&Self::position(board))?;
+        match board.clock.as_ref() {
+            Some(clock) => {
+                let now = Instant::now();
+                self.send(&format!(
+                    "go wtime {} btime {} winc {} binc {}",
+                    clock.remaining(Color::White, now).as_millis(),
+                    clock.remaining(Color::Black, now).as_millis(),
+                    clock.increment().as_millis(),
+                    clock.increment().as_millis(),
+                ))?;
+            }
+            None => self.send(&format!("go movetime {}", self.search_time.as_millis()))?,
+        }
+        self.thinking = true;
+        self.info = SearchInfo::default();
+        Ok(())
+    }
+}
+
+impl Player for UciPlayer {
+    fn update(&mut self, board: &mut RenderBoard) -> PlayedMove {
+        loop {
+            let line = match self.lines.try_recv() {
+                Ok(x) => x,
+                Err(TryRecvError::Empty) => return PlayedMove::Didnt,
+                Err(TryRecvError::Disconnected) => {
+                    if self.thinking {
+                        println!("engine `{}` exited", self.name);
+                        self.thinking = false;
+                    }
+                    return PlayedMove::Didnt;
+                }
+            };
+
+            if !self.thinking {
+                continue;
+            }
+            if let Some(info) = SearchInfo::parse(&line) {
+                self.info.merge(info);
+                board.analysis = Some((board.board.state.player, self.info.clone()));
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some("bestmove") {
+                continue;
+            }
+            self.thinking = false;
+
+            let name = parts.next().unwrap_or_default();
+            let Some(m) = Move::from_uci_on_board(name, &board.board, &self.gen) else {
+                println!("engine `{}` played illegal move `{}`", self.name, name);
+                return PlayedMove::Didnt;
+            };
+            self.last_score = self.info.white_score(board.board.state.player);
+            board.highlight(m.from(), m.to());
+            board.make_move(m);
+            if m.ty() == Move::TYPE_CASTLE {
+                return PlayedMove::Castle;
+            } else {
+                return PlayedMove::Move;
+            }
+        }
+    }
+
+    fn start_turn(&mut self, board: &RenderBoard) {
+        if let Err(e) = self.start_search(board) {
+            println!("failed to start search: {:?}", e);
+        }
+    }
+
+    fn last_score(&self) -> Option<Score> {
+        self.last_score
+    }
+
+    fn shutdown(&mut self) {
+        if self.exited {
+            return;
+        }
+        self.exited = true;
+
+        // The move of a search still in progress is discarded.
+        if self.thinking {
+            self.thinking = false;
+            if self.send("stop").is_ok() {
+                self.wait_for("bestmove").ok();
+            }
+        }
+        self.send("quit").ok();
+
+        let deadline = Instant::now() + Self::TIMEOUT;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+impl Drop for UciPlayer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|x| x.is_file() && x.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() && path.extension().map(|x| x == "exe").unwrap_or(false)
+}
+
+/// Returns the executables in the given directory sorted by name.
+pub fn find_engines(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut res: Vec<_> = entries
+        .filter_map(|x| x.ok())
+        .map(|x| x.path())
+        .filter(|x| is_executable(x))
+        .collect();
+    res.sort();
+    res
+}